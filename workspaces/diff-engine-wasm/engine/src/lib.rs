@@ -4,7 +4,7 @@ use chrono::Utc;
 use nanoid::nanoid;
 use optic_diff_engine::{
   analyze_undocumented_bodies, Aggregate, Body, BodyAnalysisResult, CommandContext,
-  EndpointQueries, HttpInteraction, InteractionDiffResult, JsonTrail,
+  EndpointQueries, HttpInteraction, InteractionDiffResult, JsonTrail, json_schema_from_shape,
   LearnedShapeDiffAffordancesProjection, LearnedUndocumentedBodiesProjection, SpecCommand,
   SpecEvent, SpecIdGenerator, SpecProjection, TaggedInput, TrailObservationsResult, TrailValues,
   ResponseBodyDescriptor, ResponseId
@@ -373,6 +373,18 @@ pub fn spec_resolve_response(
     .map_err(|err| JsValue::from(format!("responses could not be serialized: {:?}", err)))
 }
 
+#[wasm_bindgen]
+pub fn spec_shape_json_schema(
+  spec: &WasmSpecProjection,
+  shape_id: String,
+) -> Result<String, JsValue> {
+  let json_schema = json_schema_from_shape(spec.projection.shape(), &shape_id)
+    .ok_or_else(|| JsValue::from(format!("shape '{}' could not be found", shape_id)))?;
+
+  serde_json::to_string(&json_schema)
+    .map_err(|err| JsValue::from(format!("json schema could not be serialized: {:?}", err)))
+}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////
 #[wasm_bindgen]
 pub fn next_nano_id(prefix: String) -> String {
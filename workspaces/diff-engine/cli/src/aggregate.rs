@@ -0,0 +1,151 @@
+use optic_diff_engine::{DiffSeverity, InteractionDiffResult};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+pub const DEFAULT_SAMPLE_SIZE: usize = 10;
+
+/// Diff results grouped by fingerprint, so a capture yielding the same diff for many interactions
+/// is summarized by a single record, in the order the distinct diffs were first found.
+#[derive(Debug)]
+pub struct DiffAggregation {
+  sample_size: usize,
+  diffs: Vec<AggregatedDiff>,
+  indexes_by_fingerprint: HashMap<String, usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AggregatedDiff {
+  pub fingerprint: String,
+  pub diff: InteractionDiffResult,
+  pub severity: DiffSeverity,
+  pub occurrences: usize,
+  // bounded samples of the interactions the diff was found for, to keep memory constant no
+  // matter how many interactions are diffed
+  pub requests_sample: Vec<String>,
+  pub tags_sample: Vec<String>,
+}
+
+impl DiffAggregation {
+  pub fn with_sample_size(sample_size: usize) -> Self {
+    Self {
+      sample_size,
+      diffs: vec![],
+      indexes_by_fingerprint: HashMap::new(),
+    }
+  }
+
+  pub fn push(
+    &mut self,
+    result: InteractionDiffResult,
+    tags: &[String],
+    fingerprint: String,
+    severity: DiffSeverity,
+    request: &str,
+  ) {
+    let index = match self.indexes_by_fingerprint.get(&fingerprint) {
+      Some(index) => *index,
+      None => {
+        let index = self.diffs.len();
+        self
+          .indexes_by_fingerprint
+          .insert(fingerprint.clone(), index);
+        self.diffs.push(AggregatedDiff {
+          fingerprint,
+          diff: result,
+          severity,
+          occurrences: 0,
+          requests_sample: vec![],
+          tags_sample: vec![],
+        });
+        index
+      }
+    };
+
+    let sample_size = self.sample_size;
+    let aggregated = &mut self.diffs[index];
+    aggregated.occurrences += 1;
+    add_to_sample(&mut aggregated.requests_sample, request, sample_size);
+    for tag in tags {
+      add_to_sample(&mut aggregated.tags_sample, tag, sample_size);
+    }
+  }
+
+  pub fn into_diffs(self) -> impl Iterator<Item = AggregatedDiff> {
+    self.diffs.into_iter()
+  }
+}
+
+impl fmt::Display for AggregatedDiff {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "[{}] {}: {} ({} occurrences)",
+      self.severity,
+      self.requests_sample.join(", "),
+      self.diff,
+      self.occurrences
+    )
+  }
+}
+
+fn add_to_sample(sample: &mut Vec<String>, value: &str, sample_size: usize) {
+  if sample.len() < sample_size && !sample.iter().any(|sampled| sampled == value) {
+    sample.push(String::from(value));
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use serde_json::json;
+
+  // a request without the documented header of the given name
+  fn missing_request_header(name: &str, parameter_id: &str) -> InteractionDiffResult {
+    serde_json::from_value(json!({
+      "MissingHeader": {
+        "interactionTrail": { "path": [{ "RequestHeader": { "name": name } }] },
+        "requestsTrail": { "SpecRequestParameter": { "parameterId": parameter_id } }
+      }
+    }))
+    .expect("missing header should be a valid diff result")
+  }
+
+  #[test]
+  fn aggregates_diffs_by_fingerprint() {
+    let missing_token = || missing_request_header("x-token", "parameter_1");
+    let missing_tenant = || missing_request_header("x-tenant", "parameter_2");
+
+    let mut aggregation = DiffAggregation::with_sample_size(2);
+    for interaction in 0..5 {
+      let tags = vec![format!("interaction_{}", interaction)];
+      aggregation.push(
+        missing_token(),
+        &tags,
+        missing_token().fingerprint(),
+        DiffSeverity::Breaking,
+        "GET /todos",
+      );
+    }
+    aggregation.push(
+      missing_tenant(),
+      &[String::from("interaction_5")],
+      missing_tenant().fingerprint(),
+      DiffSeverity::Breaking,
+      "GET /users",
+    );
+
+    let diffs = aggregation.into_diffs().collect::<Vec<_>>();
+    assert_eq!(diffs.len(), 2, "diffs are aggregated by fingerprint");
+    assert_eq!(diffs[0].fingerprint, missing_token().fingerprint());
+    assert_eq!(diffs[0].occurrences, 5);
+    assert_eq!(diffs[0].tags_sample, vec!["interaction_0", "interaction_1"]);
+    assert_eq!(diffs[0].requests_sample, vec!["GET /todos"]);
+    assert_eq!(diffs[1].occurrences, 1);
+    assert_eq!(
+      diffs[0].to_string(),
+      "[breaking] GET /todos: missing required request header `x-token` (5 occurrences)"
+    );
+  }
+}
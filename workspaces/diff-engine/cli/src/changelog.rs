@@ -0,0 +1,173 @@
+use super::events_from_chunks;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use optic_diff_engine::{
+  ChangedEndpoint, ChangedShape, ChangelogChanges, ChangelogEntry, ChangelogProjection,
+  SpecChunkEvent,
+};
+use tokio::io::{stdout, AsyncWriteExt};
+
+pub const SUBCOMMAND_NAME: &str = "changelog";
+
+pub fn create_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name(SUBCOMMAND_NAME)
+    .about(
+      "Lists the endpoints and shapes added, updated and removed by every commit to the API spec",
+    )
+    .arg(
+      Arg::with_name("format")
+        .long("format")
+        .takes_value(true)
+        .possible_values(&["json", "markdown"])
+        .default_value("json")
+        .help("Writes the changelog as json or as a markdown document"),
+    )
+}
+
+pub async fn main<'a>(command_matches: &'a ArgMatches<'a>, spec_chunks: Vec<SpecChunkEvent>) {
+  let changelog = ChangelogProjection::from(events_from_chunks(spec_chunks).await);
+
+  let output = match command_matches.value_of("format") {
+    Some("markdown") => to_markdown(changelog.entries()),
+    _ => {
+      let mut json = serde_json::to_string_pretty(changelog.entries())
+        .expect("changelog should serialize to json");
+      json.push('\n');
+      json
+    }
+  };
+
+  let mut stdout = stdout();
+  stdout
+    .write_all(output.as_bytes())
+    .await
+    .unwrap_or_else(|err| panic!("could not write changelog to stdout: {}", err));
+  stdout
+    .flush()
+    .await
+    .unwrap_or_else(|err| panic!("could not write changelog to stdout: {}", err));
+}
+
+// Most recent commits first, like changelogs are usually read
+fn to_markdown(entries: &[ChangelogEntry]) -> String {
+  let mut markdown = String::from("# Changelog\n");
+
+  for entry in entries.iter().rev() {
+    let title = entry.commit_message.lines().next().unwrap_or_default();
+    let title = if title.trim().is_empty() {
+      "Untitled commit"
+    } else {
+      title.trim()
+    };
+    markdown.push_str(&format!("\n## {}\n\n", title));
+
+    let mut details = vec![format!("Batch `{}`", entry.batch_id)];
+    if let Some(event_context) = &entry.event_context {
+      details.push(format!("committed at {}", event_context.created_at));
+      details.push(format!("by client `{}`", event_context.client_id));
+    }
+    markdown.push_str(&format!("{}\n", details.join(", ")));
+
+    if entry.endpoints.is_empty() && entry.shapes.is_empty() {
+      markdown.push_str("\nNo changes to endpoints or shapes.\n");
+      continue;
+    }
+    if !entry.endpoints.is_empty() {
+      markdown.push_str("\n### Endpoints\n\n");
+      push_changes(&mut markdown, &entry.endpoints, endpoint_label);
+    }
+    if !entry.shapes.is_empty() {
+      markdown.push_str("\n### Shapes\n\n");
+      push_changes(&mut markdown, &entry.shapes, shape_label);
+    }
+  }
+
+  markdown
+}
+
+fn push_changes<T: PartialEq>(
+  markdown: &mut String,
+  changes: &ChangelogChanges<T>,
+  label: impl Fn(&T) -> String,
+) {
+  let groups = vec![
+    ("Added", &changes.added),
+    ("Updated", &changes.updated),
+    ("Removed", &changes.removed),
+  ];
+  for (change, items) in groups {
+    for item in items {
+      markdown.push_str(&format!("- {} {}\n", change, label(item)));
+    }
+  }
+}
+
+fn endpoint_label(endpoint: &ChangedEndpoint) -> String {
+  format!("`{} {}`", endpoint.method, endpoint.path)
+}
+
+fn shape_label(shape: &ChangedShape) -> String {
+  let kind = shape.base_shape_id.trim_start_matches('$');
+  match &shape.name {
+    Some(name) => format!("`{}` ({}, `{}`)", name, kind, shape.shape_id),
+    None => format!("`{}` ({})", shape.shape_id, kind),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use optic_diff_engine::SpecEvent;
+  use serde_json::json;
+
+  #[test]
+  fn renders_changelog_as_markdown() {
+    let events: Vec<SpecEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded":{"pathId":"path_1","parentPathId":"root","name":"todos"}},
+      {"BatchCommitStarted":{"batchId":"batch_1","commitMessage":"Document todos\n\nFrom captured traffic","eventContext":{"clientId":"ci","clientSessionId":"session_1","clientCommandBatchId":"batch_1","createdAt":"2021-01-01T10:00:00Z"}}},
+      {"RequestAdded":{"requestId":"request_1","pathId":"path_1","httpMethod":"GET"}},
+      {"ShapeAdded":{"shapeId":"shape_1","baseShapeId":"$object","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":"Todo"}},
+      {"ShapeAdded":{"shapeId":"shape_2","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"BatchCommitEnded":{"batchId":"batch_1"}},
+      {"BatchCommitStarted":{"batchId":"batch_2","commitMessage":""}},
+      {"ShapeRemoved":{"shapeId":"shape_2"}},
+      {"BatchCommitEnded":{"batchId":"batch_2"}},
+      {"BatchCommitStarted":{"batchId":"batch_3","commitMessage":"Nothing"}},
+      {"BatchCommitEnded":{"batchId":"batch_3"}},
+    ]))
+    .expect("should be able to deserialize events");
+
+    let changelog = ChangelogProjection::from(events);
+
+    assert_eq!(
+      to_markdown(changelog.entries()),
+      "# Changelog\n\
+       \n\
+       ## Nothing\n\
+       \n\
+       Batch `batch_3`\n\
+       \n\
+       No changes to endpoints or shapes.\n\
+       \n\
+       ## Untitled commit\n\
+       \n\
+       Batch `batch_2`\n\
+       \n\
+       ### Shapes\n\
+       \n\
+       - Removed `shape_2` (string)\n\
+       \n\
+       ## Document todos\n\
+       \n\
+       Batch `batch_1`, committed at 2021-01-01T10:00:00Z, by client `ci`\n\
+       \n\
+       ### Endpoints\n\
+       \n\
+       - Added `GET /todos`\n\
+       \n\
+       ### Shapes\n\
+       \n\
+       - Added `Todo` (object, `shape_1`)\n\
+       - Added `shape_2` (string)\n"
+    );
+  }
+}
@@ -127,8 +127,20 @@ async fn commit(
       .expect("command could not be applied");
   }
 
-  let mut new_events = batch.commit();
+  let new_events = batch.commit();
 
+  write_batch(spec_events, new_events, batch_id, append_to_root, spec_dir_path).await;
+}
+
+/// Writes a committed batch to the api dir as a new spec change file (or appended to
+/// the root spec file) and the new events to stdout
+pub async fn write_batch(
+  spec_events: Vec<SpecEvent>,
+  mut new_events: Vec<SpecEvent>,
+  batch_id: String,
+  append_to_root: bool,
+  spec_dir_path: impl AsRef<Path>,
+) {
   let spec_chunk_event = if append_to_root {
     let mut all_events = spec_events;
     all_events.append(&mut new_events);
@@ -0,0 +1,43 @@
+use super::events_from_chunks;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use optic_diff_engine::streams;
+use optic_diff_engine::{compact_spec, CompactionConfig, SpecChunkEvent};
+use std::process;
+use tokio::io::stdout;
+
+pub const SUBCOMMAND_NAME: &str = "compact";
+
+pub fn create_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name(SUBCOMMAND_NAME)
+    .about("Writes the fewest events that describe the same endpoints and shapes as the API spec")
+    .arg(
+      Arg::with_name("keep-batch-commits")
+        .long("keep-batch-commits")
+        .takes_value(false)
+        .help("Keeps batch commits and event contexts, so the history of the spec stays intact"),
+    )
+}
+
+pub async fn main<'a>(command_matches: &'a ArgMatches<'a>, spec_chunks: Vec<SpecChunkEvent>) {
+  let mut config = CompactionConfig::default();
+  if command_matches.is_present("keep-batch-commits") {
+    config = config.with_batch_commits();
+  }
+
+  let events = events_from_chunks(spec_chunks).await;
+  let events_count = events.len();
+  let compacted_events = compact_spec(events, &config).unwrap_or_else(|err| {
+    eprintln!("Could not compact specification: {}", err);
+    process::exit(1);
+  });
+
+  streams::spec_events::write_to_json_array(stdout(), &compacted_events)
+    .await
+    .unwrap_or_else(|err| panic!("could not write compacted events to stdout: {}", err));
+
+  eprintln!(
+    "compacted {} events into {}",
+    events_count,
+    compacted_events.len()
+  );
+}
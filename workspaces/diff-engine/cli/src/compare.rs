@@ -0,0 +1,194 @@
+use super::{events_at_commit, events_from_chunks};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use optic_diff_engine::streams;
+use optic_diff_engine::{compare_specs, SpecChunkEvent, SpecEvent, SpecProjection};
+use std::process;
+use tokio::fs;
+use tokio::io::{stdout, AsyncWriteExt};
+
+pub const SUBCOMMAND_NAME: &str = "compare";
+
+pub fn create_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name(SUBCOMMAND_NAME)
+    .about("Detects changes between the API spec (as the old version) and a new version of it")
+    .arg(
+      Arg::with_name("new-spec")
+        .value_name("NEW_SPEC_PATH")
+        .required_unless_one(&["from-commit", "to-commit"])
+        .help("The new version of the API spec, either a single file or a directory [default: the API spec itself, when comparing commits of it]"),
+    )
+    .arg(
+      Arg::with_name("from-commit")
+        .long("from-commit")
+        .value_name("BATCH_ID")
+        .takes_value(true)
+        .help("Uses the old version of the API spec as it was right after the batch commit with this id"),
+    )
+    .arg(
+      Arg::with_name("to-commit")
+        .long("to-commit")
+        .value_name("BATCH_ID")
+        .takes_value(true)
+        .help("Uses the new version of the API spec as it was right after the batch commit with this id"),
+    )
+    .arg(
+      Arg::with_name("format")
+        .long("format")
+        .takes_value(true)
+        .possible_values(&["json", "text"])
+        .default_value("json")
+        .help("Writes changes as json lines or as human-readable messages"),
+    )
+    .arg(
+      Arg::with_name("fail-on-breaking")
+        .long("fail-on-breaking")
+        .takes_value(false)
+        .help("Exits with a non-zero code when any of the changes could break clients"),
+    )
+}
+
+pub async fn main<'a>(command_matches: &'a ArgMatches<'a>, spec_chunks: Vec<SpecChunkEvent>) {
+  let new_spec_chunks = match command_matches.value_of("new-spec") {
+    Some(new_spec_path) => Some(load_spec_chunks(new_spec_path).await),
+    None => None,
+  };
+
+  let old_events = events_from_chunks(spec_chunks).await;
+  let new_events = match new_spec_chunks {
+    Some(new_spec_chunks) => Some(events_from_chunks(new_spec_chunks).await),
+    None => None,
+  };
+  let (old_spec, new_spec) = spec_versions(
+    old_events,
+    new_events,
+    command_matches.value_of("from-commit"),
+    command_matches.value_of("to-commit"),
+  );
+  let changes = compare_specs(&old_spec, &new_spec);
+
+  let mut output = String::new();
+  for change in &changes {
+    if command_matches.value_of("format") == Some("text") {
+      let severity = if change.breaking {
+        "breaking"
+      } else {
+        "non-breaking"
+      };
+      output.push_str(&format!("[{}] {}\n", severity, change));
+    } else {
+      let change_json = serde_json::to_string(change).expect("changes should serialize to json");
+      output.push_str(&change_json);
+      output.push('\n');
+    }
+  }
+
+  let mut stdout = stdout();
+  stdout
+    .write_all(output.as_bytes())
+    .await
+    .unwrap_or_else(|err| panic!("could not write changes to stdout: {}", err));
+  stdout
+    .flush()
+    .await
+    .unwrap_or_else(|err| panic!("could not write changes to stdout: {}", err));
+
+  let breaking_count = changes.iter().filter(|change| change.breaking).count();
+  eprintln!(
+    "found {} changes, of which {} breaking",
+    changes.len(),
+    breaking_count
+  );
+  if command_matches.is_present("fail-on-breaking") && breaking_count > 0 {
+    process::exit(1);
+  }
+}
+
+async fn load_spec_chunks(spec_path: &str) -> Vec<SpecChunkEvent> {
+  let is_dir = fs::metadata(spec_path)
+    .await
+    .map(|metadata| metadata.is_dir())
+    .unwrap_or_else(|err| {
+      eprintln!("Could not read new specification: {}", err);
+      process::exit(1);
+    });
+  if is_dir {
+    streams::spec_chunks::from_api_dir(spec_path).await
+  } else {
+    streams::spec_chunks::from_root_api_file(spec_path).await
+  }
+  .unwrap_or_else(|err| {
+    eprintln!("Could not load new specification: {:?}", err);
+    process::exit(1);
+  })
+}
+
+// the old version of the spec as of `--from-commit` and the new version as of `--to-commit`, where
+// the new version is the spec itself when no new spec is given
+fn spec_versions(
+  old_events: Vec<SpecEvent>,
+  new_events: Option<Vec<SpecEvent>>,
+  from_commit: Option<&str>,
+  to_commit: Option<&str>,
+) -> (SpecProjection, SpecProjection) {
+  let new_events = new_events.unwrap_or_else(|| old_events.clone());
+  let old_spec = SpecProjection::from(events_at_commit(old_events, from_commit));
+  let new_spec = SpecProjection::from(events_at_commit(new_events, to_commit));
+  (old_spec, new_spec)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use serde_json::json;
+
+  fn todos_spec_events() -> Vec<SpecEvent> {
+    serde_json::from_value(json!([
+      {"BatchCommitStarted": {"batchId": "batch_1", "commitMessage": "Document todos", "eventContext": {"clientId": "anonymous", "clientSessionId": "session", "clientCommandBatchId": "batch_1", "createdAt": "2021-01-01T10:00:00Z"}}},
+      {"PathComponentAdded": {"pathId": "path_1", "parentPathId": "root", "name": "todos"}},
+      {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "GET"}},
+      {"BatchCommitEnded": {"batchId": "batch_1", "eventContext": {"clientId": "anonymous", "clientSessionId": "session", "clientCommandBatchId": "batch_1", "createdAt": "2021-01-01T10:00:00Z"}}},
+      {"BatchCommitStarted": {"batchId": "batch_2", "commitMessage": "Document creating todos", "eventContext": {"clientId": "anonymous", "clientSessionId": "session", "clientCommandBatchId": "batch_2", "createdAt": "2021-01-01T10:00:00Z"}}},
+      {"RequestAdded": {"requestId": "request_2", "pathId": "path_1", "httpMethod": "POST"}},
+      {"BatchCommitEnded": {"batchId": "batch_2", "eventContext": {"clientId": "anonymous", "clientSessionId": "session", "clientCommandBatchId": "batch_2", "createdAt": "2021-01-01T10:00:00Z"}}},
+      {"BatchCommitStarted": {"batchId": "batch_3", "commitMessage": "Stop listing todos", "eventContext": {"clientId": "anonymous", "clientSessionId": "session", "clientCommandBatchId": "batch_3", "createdAt": "2021-01-01T10:00:00Z"}}},
+      {"RequestRemoved": {"requestId": "request_1"}},
+      {"BatchCommitEnded": {"batchId": "batch_3", "eventContext": {"clientId": "anonymous", "clientSessionId": "session", "clientCommandBatchId": "batch_3", "createdAt": "2021-01-01T10:00:00Z"}}},
+    ]))
+    .expect("todos spec events should be valid spec events")
+  }
+
+  #[test]
+  fn can_compare_commits_of_a_spec() {
+    let (old_spec, new_spec) =
+      spec_versions(todos_spec_events(), None, Some("batch_1"), Some("batch_2"));
+    let changes = compare_specs(&old_spec, &new_spec);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(
+      (changes[0].method.as_str(), changes[0].path.as_str()),
+      ("POST", "/todos")
+    );
+    assert!(!changes[0].breaking);
+
+    let (old_spec, new_spec) = spec_versions(todos_spec_events(), None, Some("batch_2"), None);
+    let changes = compare_specs(&old_spec, &new_spec);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(
+      (changes[0].method.as_str(), changes[0].path.as_str()),
+      ("GET", "/todos")
+    );
+    assert!(changes[0].breaking, "removing an endpoint is breaking");
+  }
+
+  #[test]
+  fn can_compare_a_commit_of_a_spec_with_a_new_spec() {
+    let new_events = todos_spec_events();
+    let (old_spec, new_spec) =
+      spec_versions(todos_spec_events(), Some(new_events), Some("batch_1"), None);
+    let changes = compare_specs(&old_spec, &new_spec);
+    assert_eq!(
+      changes.len(),
+      2,
+      "endpoints added and removed since the first commit"
+    );
+  }
+}
@@ -0,0 +1,47 @@
+use super::events_from_chunks;
+use clap::{App, Arg, ArgGroup, ArgMatches, SubCommand};
+use optic_diff_engine::export_openapi;
+use optic_diff_engine::{SpecChunkEvent, SpecProjection};
+use tokio::io::{stdout, AsyncWriteExt};
+
+pub const SUBCOMMAND_NAME: &str = "export";
+
+pub fn create_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name(SUBCOMMAND_NAME)
+    .about("Exports the API spec in a different format")
+    .arg(
+      Arg::with_name("openapi")
+        .long("openapi")
+        .takes_value(false)
+        .help("Export the API spec as an OpenAPI 3.0 document"),
+    )
+    .group(
+      ArgGroup::with_name("format")
+        .args(&["openapi"])
+        .multiple(false)
+        .required(true),
+    )
+}
+
+pub async fn main<'a>(command_matches: &'a ArgMatches<'a>, spec_chunks: Vec<SpecChunkEvent>) {
+  let spec_projection = SpecProjection::from(events_from_chunks(spec_chunks).await);
+
+  let document = if command_matches.is_present("openapi") {
+    export_openapi(&spec_projection)
+  } else {
+    unreachable!("format is required");
+  };
+
+  let document_json =
+    serde_json::to_vec_pretty(&document).expect("exported document should serialize to json");
+
+  let mut stdout = stdout();
+  stdout
+    .write_all(&document_json)
+    .await
+    .unwrap_or_else(|err| panic!("could not write exported document to stdout: {}", err));
+  stdout
+    .flush()
+    .await
+    .unwrap_or_else(|err| panic!("could not write exported document to stdout: {}", err));
+}
@@ -0,0 +1,111 @@
+use super::commit::write_batch;
+use super::events_from_chunks;
+use super::learn::IdGenerator;
+use chrono::Utc;
+use clap::{App, Arg, ArgGroup, ArgMatches, SubCommand};
+use optic_diff_engine::import_openapi;
+use optic_diff_engine::CommandContext;
+use optic_diff_engine::{SpecChunkEvent, SpecProjection};
+use std::path::Path;
+use std::process;
+use uuid::Uuid;
+
+pub const SUBCOMMAND_NAME: &str = "import";
+
+pub fn create_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name(SUBCOMMAND_NAME)
+    .about("Imports an API description in a different format as a new batch commit")
+    .arg(
+      Arg::with_name("openapi")
+        .long("openapi")
+        .value_name("OPENAPI_FILE")
+        .takes_value(true)
+        .help("Import an OpenAPI 3.x document (JSON)"),
+    )
+    .group(
+      ArgGroup::with_name("format")
+        .args(&["openapi"])
+        .multiple(false)
+        .required(true),
+    )
+    .arg(
+      Arg::with_name("commit-message")
+        .short("m")
+        .value_name("COMMIT_MESSAGE")
+        .takes_value(true)
+        .default_value("Imported OpenAPI document")
+        .help("The commit message describing the imported batch"),
+    )
+    .arg(
+      Arg::with_name("append-to-root")
+        .long("append-to-root")
+        .required(false)
+        .takes_value(false)
+        .help("Append new batch commit to the root spec file, instead of a new spec change file"),
+    )
+}
+
+pub async fn main<'a>(
+  command_matches: &'a ArgMatches<'a>,
+  spec_chunks: Vec<SpecChunkEvent>,
+  spec_path: impl AsRef<Path>,
+) {
+  let openapi_path = command_matches
+    .value_of("openapi")
+    .expect("format is required");
+
+  let commit_message = command_matches
+    .value_of("commit-message")
+    .expect("commit-message has a default value");
+
+  let append_to_root = command_matches.is_present("append-to-root");
+
+  if append_to_root
+    && !spec_chunks
+      .iter()
+      .all(|chunk| matches!(chunk, SpecChunkEvent::Root(_)))
+  {
+    eprintln!("Commits cannot be appended to the root when non-root chunks exist");
+    process::exit(1);
+  }
+
+  let document_json = tokio::fs::read(openapi_path).await.unwrap_or_else(|err| {
+    eprintln!("Could not read OpenAPI file: {}", err);
+    process::exit(1);
+  });
+  let document: serde_json::Value = serde_json::from_slice(&document_json).unwrap_or_else(|err| {
+    eprintln!("OpenAPI JSON file could not be parsed: {}", err);
+    process::exit(1);
+  });
+
+  let spec_events = events_from_chunks(spec_chunks).await;
+  let spec_projection = SpecProjection::from(spec_events.clone());
+
+  let mut id_generator = IdGenerator;
+  let import = import_openapi(&spec_projection, &document, &mut id_generator).unwrap_or_else(|err| {
+    eprintln!("OpenAPI document could not be imported: {}", err);
+    process::exit(1);
+  });
+
+  for unsupported in &import.unsupported {
+    eprintln!("{}: {}", unsupported.location, unsupported.description);
+  }
+
+  let batch_id = Uuid::new_v4().to_hyphenated().to_string();
+  let batch_command_context = CommandContext::new(
+    batch_id.clone(),
+    String::from("anonymous"),
+    String::from("unknown-session"),
+    Utc::now(),
+  );
+
+  let new_events = import
+    .commit(
+      spec_projection,
+      String::from(commit_message),
+      batch_command_context,
+    )
+    .expect("imported commands should be valid");
+
+  write_batch(spec_events, new_events, batch_id, append_to_root, spec_path).await;
+}
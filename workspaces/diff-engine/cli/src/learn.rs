@@ -12,8 +12,10 @@ use tokio_stream::wrappers::ReceiverStream;
 use optic_diff_engine::streams;
 use optic_diff_engine::Aggregate;
 use optic_diff_engine::{
-  analyze_documented_bodies, analyze_undocumented_bodies, InteractionDiffResult,
-  LearnedShapeDiffAffordancesProjection, LearnedUndocumentedBodiesProjection,
+  analyze_documented_bodies, analyze_undocumented_bodies, analyze_undocumented_parameters,
+  InteractionDiffResult, LearnedShapeDiffAffordancesProjection,
+  LearnedUndocumentedBodiesProjection, LearnedUndocumentedParametersProjection,
+  ShapeLearningConfig,
 };
 use optic_diff_engine::{
   HttpInteraction, SpecChunkEvent, SpecEvent, SpecIdGenerator, SpecProjection, TaggedInput,
@@ -30,6 +32,19 @@ pub fn create_subcommand<'a, 'b>() -> App<'a, 'b> {
         .takes_value(false)
         .help("Learn shapes of undocumented bodies from interactions piped to stdin"),
     )
+    .arg(
+      Arg::with_name("enums")
+        .long("enums")
+        .takes_value(false)
+        .requires("undocumented-bodies")
+        .help("Learn strings with a few distinct, repeatedly observed values as enums"),
+    )
+    .arg(
+      Arg::with_name("undocumented-parameters")
+        .long("undocumented-parameters")
+        .takes_value(false)
+        .help("Learn query parameters and headers of documented endpoints from interactions piped to stdin"),
+    )
     .arg(
       Arg::with_name("shape-diffs-affordances")
         .long("shape-diffs-affordances")
@@ -45,7 +60,11 @@ pub fn create_subcommand<'a, 'b>() -> App<'a, 'b> {
     )
     .group(
       ArgGroup::with_name("subject")
-        .args(&["undocumented-bodies", "shape-diffs-affordances"])
+        .args(&[
+          "undocumented-bodies",
+          "undocumented-parameters",
+          "shape-diffs-affordances",
+        ])
         .multiple(false)
         .required(true),
     )
@@ -63,7 +82,25 @@ pub async fn main<'a>(
     let interaction_lines = streams::http_interaction::json_lines(stdin);
     let sink = stdout();
 
-    learn_undocumented_bodies(spec_events, input_queue_size, interaction_lines, sink).await;
+    let mut learning_config = ShapeLearningConfig::default();
+    if command_matches.is_present("enums") {
+      learning_config = learning_config.with_enums();
+    }
+
+    learn_undocumented_bodies(
+      spec_events,
+      input_queue_size,
+      interaction_lines,
+      sink,
+      learning_config,
+    )
+    .await;
+  } else if command_matches.is_present("undocumented-parameters") {
+    let stdin = stdin();
+    let interaction_lines = streams::http_interaction::json_lines(stdin);
+    let sink = stdout();
+
+    learn_undocumented_parameters(spec_events, input_queue_size, interaction_lines, sink).await;
   } else if command_matches.is_present("shape-diffs-affordances") {
     let diffs_path = command_matches
       .value_of("tagged-diff-results")
@@ -97,6 +134,7 @@ async fn learn_undocumented_bodies<S: 'static + AsyncWrite + Unpin + Send>(
   input_queue_size: usize,
   interaction_lines: impl Stream<Item = Result<String, std::io::Error>>,
   sink: S,
+  learning_config: ShapeLearningConfig,
 ) {
   let spec_projection = Arc::new(SpecProjection::from(spec_events));
 
@@ -154,7 +192,7 @@ async fn learn_undocumented_bodies<S: 'static + AsyncWrite + Unpin + Send>(
     }
 
     let endpoint_bodies = learned_undocumented_bodies
-      .into_endpoint_bodies(&mut id_generator)
+      .into_endpoint_bodies_with_config(&mut id_generator, &learning_config)
       .collect::<Vec<_>>();
 
     streams::write_to_json_lines(sink, endpoint_bodies.iter())
@@ -165,6 +203,79 @@ async fn learn_undocumented_bodies<S: 'static + AsyncWrite + Unpin + Send>(
   try_join!(analyzing_bodies, aggregating_results).expect("essential worker task panicked");
 }
 
+async fn learn_undocumented_parameters<S: 'static + AsyncWrite + Unpin + Send>(
+  spec_events: Vec<SpecEvent>,
+  input_queue_size: usize,
+  interaction_lines: impl Stream<Item = Result<String, std::io::Error>>,
+  sink: S,
+) {
+  let spec_projection = Arc::new(SpecProjection::from(spec_events));
+
+  let (analysis_sender, analysis_receiver) = mpsc::channel(32);
+
+  let analyzing_parameters = async move {
+    let analyze_results = interaction_lines
+      .map(Ok)
+      .try_for_each_concurrent(input_queue_size, |interaction_json_result| {
+        let projection = spec_projection.clone();
+        let analysis_sender = analysis_sender.clone();
+
+        let analyze_task = tokio::spawn(async move {
+          let analyze_comp = tokio::task::spawn_blocking(move || {
+            let interaction_json =
+              interaction_json_result.expect("can read interaction json line from stdin");
+
+            let interaction: HttpInteraction =
+              serde_json::from_str(&interaction_json).expect("could not parse interaction json");
+
+            analyze_undocumented_parameters(&projection, interaction).collect::<Vec<_>>()
+          });
+
+          match analyze_comp.await {
+            Ok(results) => {
+              for result in results {
+                analysis_sender
+                  .send(result)
+                  .await
+                  .expect("could not send analysis result to aggregation channel")
+              }
+            }
+            Err(err) => {
+              // ignore a single interaction not being able to deserialize
+              eprintln!("interaction ignored: {}", err);
+            }
+          }
+        });
+
+        analyze_task
+      })
+      .await;
+
+    analyze_results
+  };
+
+  let aggregating_results = tokio::spawn(async move {
+    let mut analyses = ReceiverStream::new(analysis_receiver);
+    let mut id_generator = IdGenerator;
+
+    let mut learned_undocumented_parameters = LearnedUndocumentedParametersProjection::default();
+
+    while let Some(analysis) = analyses.next().await {
+      learned_undocumented_parameters.apply(analysis);
+    }
+
+    let endpoint_parameters = learned_undocumented_parameters
+      .into_endpoint_parameters(&mut id_generator)
+      .collect::<Vec<_>>();
+
+    streams::write_to_json_lines(sink, endpoint_parameters.iter())
+      .await
+      .expect("could not write endpoint parameters to stdout");
+  });
+
+  try_join!(analyzing_parameters, aggregating_results).expect("essential worker task panicked");
+}
+
 async fn learn_shape_diff_affordances<S: 'static + AsyncWrite + Unpin + Send>(
   spec_events: Vec<SpecEvent>,
   diffs: impl Iterator<Item = InteractionDiffResult>,
@@ -248,7 +359,7 @@ async fn learn_shape_diff_affordances<S: 'static + AsyncWrite + Unpin + Send>(
 }
 
 #[derive(Debug, Default)]
-struct IdGenerator;
+pub struct IdGenerator;
 
 impl SpecIdGenerator for IdGenerator {
   fn generate_id(&mut self, prefix: &str) -> String {
@@ -267,6 +378,7 @@ mod test {
   use serde_json::json;
   use std::path::Path;
   use tokio::fs;
+  use tokio::io::AsyncReadExt;
 
   #[tokio::main]
   #[test]
@@ -280,7 +392,67 @@ mod test {
     let interaction_lines = streams::http_interaction::json_lines(tokio::io::empty());
     let sink = tokio::io::sink();
 
-    learn_undocumented_bodies(spec_events, 1, interaction_lines, sink).await;
+    learn_undocumented_bodies(
+      spec_events,
+      1,
+      interaction_lines,
+      sink,
+      ShapeLearningConfig::default(),
+    )
+    .await;
+  }
+
+  #[tokio::main]
+  #[test]
+  async fn can_learn_endpoint_parameters_from_interactions() {
+    let spec_events: Vec<SpecEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded":{"pathId":"path_id_1","parentPathId":"root","name":"todos"}},
+      {"RequestAdded":{"requestId":"request_id_1","pathId":"path_id_1","httpMethod":"GET"}}
+    ]))
+    .expect("initial spec events should be valid events");
+
+    let interaction = json!({
+      "uuid": "1",
+      "request": {
+        "host": "localhost",
+        "method": "GET",
+        "path": "/todos",
+        "query": { "asJsonString": null, "asText": "limit=10", "shapeHashV1Base64": null },
+        "headers": { "asJsonString": null, "asText": "X-Api-Key: secret", "shapeHashV1Base64": null },
+        "body": {
+          "contentType": null,
+          "value": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null }
+        }
+      },
+      "response": {
+        "statusCode": 200,
+        "headers": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null },
+        "body": {
+          "contentType": null,
+          "value": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null }
+        }
+      },
+      "tags": []
+    });
+    let interactions = format!("{}\n", interaction);
+
+    let interaction_lines = streams::http_interaction::json_lines(interactions.as_bytes());
+    let (sink, mut output) = tokio::io::duplex(64 * 1024);
+
+    learn_undocumented_parameters(spec_events, 1, interaction_lines, sink).await;
+
+    let mut output_json = String::new();
+    output.read_to_string(&mut output_json).await.unwrap();
+    let endpoint_parameters: Vec<serde_json::Value> = output_json
+      .lines()
+      .map(|line| serde_json::from_str(line).unwrap())
+      .collect();
+    assert_eq!(endpoint_parameters.len(), 1);
+    assert_eq!(
+      endpoint_parameters[0]["queryParameters"][0]["name"],
+      "limit"
+    );
+    assert_eq!(endpoint_parameters[0]["headers"][0]["name"], "x-api-key");
   }
 
   #[tokio::main]
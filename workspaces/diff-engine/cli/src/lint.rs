@@ -0,0 +1,56 @@
+use super::events_from_chunks;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use optic_diff_engine::{lint_spec, SpecChunkEvent};
+use std::process;
+use tokio::io::{stdout, AsyncWriteExt};
+
+pub const SUBCOMMAND_NAME: &str = "lint";
+
+pub fn create_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name(SUBCOMMAND_NAME)
+    .about("Checks the events of the API spec for inconsistencies")
+    .arg(
+      Arg::with_name("format")
+        .long("format")
+        .takes_value(true)
+        .possible_values(&["json", "text"])
+        .default_value("json")
+        .help("Writes findings as json lines or as human-readable messages"),
+    )
+    .arg(
+      Arg::with_name("fail-on-findings")
+        .long("fail-on-findings")
+        .takes_value(false)
+        .help("Exits with a non-zero code when the spec has any findings"),
+    )
+}
+
+pub async fn main<'a>(command_matches: &'a ArgMatches<'a>, spec_chunks: Vec<SpecChunkEvent>) {
+  let findings = lint_spec(events_from_chunks(spec_chunks).await);
+
+  let mut output = String::new();
+  for finding in &findings {
+    if command_matches.value_of("format") == Some("text") {
+      output.push_str(&format!("[{:?}] {}\n", finding.rule, finding));
+    } else {
+      let finding_json = serde_json::to_string(finding).expect("findings should serialize to json");
+      output.push_str(&finding_json);
+      output.push('\n');
+    }
+  }
+
+  let mut stdout = stdout();
+  stdout
+    .write_all(output.as_bytes())
+    .await
+    .unwrap_or_else(|err| panic!("could not write findings to stdout: {}", err));
+  stdout
+    .flush()
+    .await
+    .unwrap_or_else(|err| panic!("could not write findings to stdout: {}", err));
+
+  eprintln!("found {} findings", findings.len());
+  if command_matches.is_present("fail-on-findings") && !findings.is_empty() {
+    process::exit(1);
+  }
+}
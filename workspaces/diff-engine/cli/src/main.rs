@@ -1,23 +1,37 @@
+use aggregate::DiffAggregation;
 use clap::{crate_version, App, Arg, ArgGroup, SubCommand};
 use futures::try_join;
 use futures::SinkExt;
 use futures::{StreamExt, TryStreamExt};
 use num_cpus;
-use optic_diff_engine::diff_interaction;
+use optic_diff_engine::diff_interaction_with_config;
 use optic_diff_engine::errors;
 use optic_diff_engine::streams;
 use optic_diff_engine::HttpInteraction;
 use optic_diff_engine::InteractionDiffResult;
 use optic_diff_engine::SpecProjection;
+use optic_diff_engine::{spec_events_until, SpecHistoryPoint};
+use optic_diff_engine::{DiffSeverity, DiffSeverityConfig, InteractionDiffConfig};
 use optic_diff_engine::{SpecChunkEvent, SpecEvent};
+use report::{DiffReport, ReportFormat};
 use std::cmp;
 use std::process;
 use std::sync::Arc;
-use tokio::io::{stdin, stdout};
+use tokio::fs;
+use tokio::io::{stdin, stdout, AsyncWriteExt};
 use tokio::sync::mpsc;
 
+mod aggregate;
+mod changelog;
 mod commit;
+mod compact;
+mod compare;
+mod export;
+mod import;
 mod learn;
+mod lint;
+mod orphans;
+mod report;
 
 fn main() {
   let cli = App::new("Optic Engine CLI")
@@ -61,13 +75,80 @@ fn main() {
     )
     .subcommand(
       SubCommand::with_name("assemble")
-        .about("Assembles a directory of API spec files into a single events stream"),
+        .about("Assembles a directory of API spec files into a single events stream")
+        .arg(at_commit_arg()),
     )
+    .subcommand(changelog::create_subcommand())
     .subcommand(commit::create_subcommand())
+    .subcommand(compact::create_subcommand())
+    .subcommand(compare::create_subcommand())
+    .subcommand(export::create_subcommand())
+    .subcommand(import::create_subcommand())
     .subcommand(learn::create_subcommand())
+    .subcommand(lint::create_subcommand())
+    .subcommand(orphans::create_subcommand())
     .subcommand(
       SubCommand::with_name("diff")
-        .about("Detects differences between API spec and captured interactions (default)"),
+        .about("Detects differences between API spec and captured interactions (default)")
+        .arg(at_commit_arg())
+        .arg(
+          Arg::with_name("format")
+            .long("format")
+            .takes_value(true)
+            .possible_values(&["json", "text"])
+            .default_value("json")
+            .help("Writes diff results as tagged json lines or as human-readable messages"),
+        )
+        .arg(
+          Arg::with_name("report")
+            .long("report")
+            .takes_value(true)
+            .possible_values(&["junit", "sarif"])
+            .help("Writes a report of the distinct diffs per endpoint, for CI systems to render. Exits with a non-zero code when breaking diffs are found, unless told otherwise with --fail-on"),
+        )
+        .arg(
+          Arg::with_name("report-path")
+            .long("report-path")
+            .takes_value(true)
+            .requires("report")
+            .help("Where to write the report [default: optic-diff-report.xml or optic-diff-report.sarif]"),
+        )
+        .arg(
+          Arg::with_name("aggregate")
+            .long("aggregate")
+            .takes_value(false)
+            .help("Writes a single summary per distinct diff once all interactions are diffed, with the amount of occurrences and a sample of interaction tags"),
+        )
+        .arg(
+          Arg::with_name("sample-size")
+            .long("sample-size")
+            .takes_value(true)
+            .requires("aggregate")
+            .help("The maximum amount of interaction tags sampled per distinct diff [default: 10]"),
+        )
+        .arg(
+          Arg::with_name("fail-on")
+            .long("fail-on")
+            .takes_value(true)
+            .possible_values(&["breaking", "additive"])
+            .help("Exits with a non-zero code when diffs of at least this severity are found [default: breaking when writing a report]"),
+        )
+        .arg(
+          Arg::with_name("unmatched-url-severity")
+            .long("unmatched-url-severity")
+            .takes_value(true)
+            .possible_values(&["breaking", "additive"])
+            .default_value("additive")
+            .help("The severity of requests to urls the spec doesn't document"),
+        )
+        .arg(
+          Arg::with_name("ignore-header")
+            .long("ignore-header")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .help("A header to ignore when diffing requests and responses, on top of the standard transport headers. Can be given more than once"),
+        ),
     );
 
   let matches = cli.get_matches();
@@ -76,7 +157,7 @@ fn main() {
     .value_of("specification")
     .expect("SPEC_PATH should be required");
   let spec_path_type = match matches.subcommand_name() {
-    Some("assemble") | Some("commit") => SpecPathType::DIR,
+    Some("assemble") | Some("commit") | Some("import") => SpecPathType::DIR,
     _ => {
       if matches.is_present("use-spec-dir") {
         SpecPathType::DIR
@@ -133,44 +214,192 @@ fn main() {
     };
 
     match matches.subcommand() {
-      ("assemble", Some(_)) => {
+      ("assemble", Some(subcommand_matches)) => {
         // eprintln!("assembling spec folder into spec");
-        assemble(spec_chunks).await;
+        assemble(spec_chunks, subcommand_matches.value_of("at-commit")).await;
+      }
+      (changelog::SUBCOMMAND_NAME, Some(subcommand_matches)) => {
+        changelog::main(subcommand_matches, spec_chunks).await
       }
       (commit::SUBCOMMAND_NAME, Some(subcommand_matches)) => {
         commit::main(subcommand_matches, spec_chunks, spec_path).await
       }
+      (compact::SUBCOMMAND_NAME, Some(subcommand_matches)) => {
+        compact::main(subcommand_matches, spec_chunks).await
+      }
+      (compare::SUBCOMMAND_NAME, Some(subcommand_matches)) => {
+        compare::main(subcommand_matches, spec_chunks).await
+      }
+      (export::SUBCOMMAND_NAME, Some(subcommand_matches)) => {
+        export::main(subcommand_matches, spec_chunks).await
+      }
+      (import::SUBCOMMAND_NAME, Some(subcommand_matches)) => {
+        import::main(subcommand_matches, spec_chunks, spec_path).await
+      }
       (learn::SUBCOMMAND_NAME, Some(subcommand_matches)) => {
         learn::main(subcommand_matches, spec_chunks, input_queue_size).await
       }
+      (lint::SUBCOMMAND_NAME, Some(subcommand_matches)) => {
+        lint::main(subcommand_matches, spec_chunks).await
+      }
+      (orphans::SUBCOMMAND_NAME, Some(subcommand_matches)) => {
+        orphans::main(subcommand_matches, spec_chunks).await
+      }
       _ => {
         eprintln!("diffing interations against a spec");
         eprintln!("using input queue size {}", input_queue_size);
 
-        diff(events_from_chunks(spec_chunks).await, input_queue_size).await;
+        let diff_matches = matches.subcommand_matches("diff");
+        let output_format = match diff_matches.and_then(|matches| matches.value_of("format")) {
+          Some("text") => DiffOutputFormat::Text,
+          _ => DiffOutputFormat::Json,
+        };
+        let report_options = diff_matches
+          .and_then(|matches| matches.value_of("report"))
+          .and_then(ReportFormat::from_name)
+          .map(|report_format| {
+            let report_path = diff_matches
+              .and_then(|matches| matches.value_of("report-path"))
+              .unwrap_or_else(|| report_format.default_path());
+            (report_format, String::from(report_path))
+          });
+
+        let aggregate_sample_size = match diff_matches {
+          Some(matches) if matches.is_present("aggregate") => {
+            match clap::value_t!(matches.value_of("sample-size"), usize) {
+              Ok(sample_size) => Some(sample_size),
+              Err(e) => match e.kind {
+                clap::ErrorKind::ArgumentNotFound => Some(aggregate::DEFAULT_SAMPLE_SIZE),
+                _ => e.exit(),
+              },
+            }
+          }
+          _ => None,
+        };
+        let severity_config = DiffSeverityConfig::default().with_unmatched_request_url_severity(
+          diff_matches
+            .and_then(|matches| matches.value_of("unmatched-url-severity"))
+            .and_then(DiffSeverity::from_name)
+            .unwrap_or(DiffSeverity::Additive),
+        );
+        let fail_on = fail_on_severity(
+          diff_matches.and_then(|matches| matches.value_of("fail-on")),
+          report_options.is_some(),
+        );
+
+        let diff_config = diff_matches
+          .and_then(|matches| matches.values_of("ignore-header"))
+          .into_iter()
+          .flatten()
+          .fold(InteractionDiffConfig::default(), |config, name| {
+            config.with_ignored_header(name)
+          });
+
+        let spec_events = events_at_commit(
+          events_from_chunks(spec_chunks).await,
+          diff_matches.and_then(|matches| matches.value_of("at-commit")),
+        );
+
+        diff(
+          spec_events,
+          input_queue_size,
+          DiffOptions {
+            output_format,
+            report_options,
+            aggregate_sample_size,
+            severity_config,
+            fail_on,
+            diff_config,
+          },
+        )
+        .await;
       }
     };
   });
 }
 
-async fn diff(events: Vec<SpecEvent>, diff_queue_size: usize) {
+async fn diff(events: Vec<SpecEvent>, diff_queue_size: usize, options: DiffOptions) {
+  let DiffOptions {
+    output_format,
+    report_options,
+    aggregate_sample_size,
+    severity_config,
+    fail_on,
+    diff_config,
+  } = options;
   let spec_projection = Arc::new(SpecProjection::from(events));
+  let diff_config = Arc::new(diff_config);
 
   let stdin = stdin(); // TODO: deal with std in never having been attached
 
   let interaction_lines = streams::http_interaction::json_lines(stdin);
 
-  let (results_sender, mut results_receiver) = mpsc::channel(32); // buffer 32 results
+  let (results_sender, mut results_receiver) =
+    mpsc::channel::<(ResultContainer<InteractionDiffResult>, String)>(32); // buffer 32 results
 
+  let report_spec_projection = spec_projection.clone();
   let results_manager = tokio::spawn(async move {
-    let stdout = stdout();
-    let mut results_sink = streams::diff::into_json_lines(stdout);
+    let mut report = report_options.as_ref().map(|_| DiffReport::default());
+    let mut aggregation = aggregate_sample_size.map(DiffAggregation::with_sample_size);
+    let mut results_sink = streams::diff::into_json_lines(stdout());
+    let mut text_output = stdout();
+    let mut most_severe: Option<DiffSeverity> = None;
+
+    while let Some((result_container, request)) = results_receiver.recv().await {
+      let ResultContainer(result, _, fingerprint) = &result_container;
+      let severity = result.severity(&severity_config);
+      most_severe = most_severe.max(Some(severity));
+      if let Some(report) = &mut report {
+        report.push(&report_spec_projection, result, fingerprint, severity, &request);
+      }
+      if let Some(aggregation) = &mut aggregation {
+        let ResultContainer(result, tags, fingerprint) = result_container;
+        aggregation.push(result, &tags, fingerprint, severity, &request);
+        continue;
+      }
 
-    while let Some(result) = results_receiver.recv().await {
-      if let Err(_) = results_sink.send(result).await {
-        panic!("could not write diff result to stdout"); // TODO: Find way to actually write error info
+      match output_format {
+        DiffOutputFormat::Json => {
+          if results_sink.send(result_container).await.is_err() {
+            panic!("could not write diff result to stdout"); // TODO: Find way to actually write error info
+          }
+        }
+        DiffOutputFormat::Text => {
+          let line = format!("[{}] {}: {}\n", severity, request, result_container.0);
+          text_output
+            .write_all(line.as_bytes())
+            .await
+            .unwrap_or_else(|err| panic!("could not write diff result to stdout: {}", err));
+        }
       }
     }
+
+    if let Some(aggregation) = aggregation {
+      let mut summaries_sink = streams::diff::into_json_lines(stdout());
+      for aggregated_diff in aggregation.into_diffs() {
+        match output_format {
+          DiffOutputFormat::Json => {
+            if summaries_sink.send(aggregated_diff).await.is_err() {
+              panic!("could not write diff summary to stdout");
+            }
+          }
+          DiffOutputFormat::Text => {
+            let line = format!("{}\n", aggregated_diff);
+            text_output
+              .write_all(line.as_bytes())
+              .await
+              .unwrap_or_else(|err| panic!("could not write diff summary to stdout: {}", err));
+          }
+        }
+      }
+    }
+
+    text_output
+      .flush()
+      .await
+      .unwrap_or_else(|err| panic!("could not write diff result to stdout: {}", err));
+
+    (report_options.zip(report), most_severe)
   });
 
   tokio::pin!(results_manager);
@@ -182,12 +411,13 @@ async fn diff(events: Vec<SpecEvent>, diff_queue_size: usize) {
       .map(Ok)
       .try_for_each_concurrent(diff_queue_size, |interaction_json_result| {
         let projection = spec_projection.clone();
+        let diff_config = diff_config.clone();
         let results_sender = results_sender.clone();
 
         let diff_task = tokio::spawn(async move {
           let diff_comp = tokio::task::spawn_blocking::<
             _,
-            Option<(Vec<InteractionDiffResult>, Tags)>,
+            Option<(Vec<InteractionDiffResult>, Tags, String)>,
           >(move || {
             let interaction_json =
               interaction_json_result.expect("can read interaction json line from stdin");
@@ -200,7 +430,13 @@ async fn diff(events: Vec<SpecEvent>, diff_queue_size: usize) {
                 }
               };
 
-            Some((diff_interaction(&projection, interaction), tags))
+            // how the interaction is referred to in text output
+            let request = format!(
+              "{} {}",
+              interaction.request.method, interaction.request.path
+            );
+            let results = diff_interaction_with_config(&projection, interaction, &diff_config);
+            Some((results, tags, request))
           });
           //dbg!("waiting for results");
           let results = diff_comp
@@ -208,11 +444,11 @@ async fn diff(events: Vec<SpecEvent>, diff_queue_size: usize) {
             .expect("diffing of interaction should be successful");
           //dbg!("got results");
 
-          if let Some((results, tags)) = results {
+          if let Some((results, tags, request)) = results {
             for result in results {
               //dbg!(&result);
               if let Err(_) = results_sender
-                .send(ResultContainer::from((result, &tags)))
+                .send((ResultContainer::from((result, &tags)), request.clone()))
                 .await
               {
                 panic!("could not write diff result to results channel");
@@ -232,11 +468,33 @@ async fn diff(events: Vec<SpecEvent>, diff_queue_size: usize) {
     diff_results
   };
 
-  try_join!(diffing_interactions, results_manager).expect("essential worker task panicked");
+  let (_, (report, most_severe)) =
+    try_join!(diffing_interactions, results_manager).expect("essential worker task panicked");
+
+  if let Some(((report_format, report_path), report)) = report {
+    fs::write(&report_path, report.render(report_format))
+      .await
+      .unwrap_or_else(|err| panic!("could not write diff report to {}: {}", report_path, err));
+    if !report.is_empty() {
+      eprintln!(
+        "found {} distinct diffs, reported to {}",
+        report.diffs_count(),
+        report_path
+      );
+    }
+  }
+
+  match (fail_on, most_severe) {
+    (Some(fail_on), Some(most_severe)) if most_severe >= fail_on => {
+      eprintln!("found {} diffs", most_severe);
+      process::exit(1);
+    }
+    _ => {}
+  }
 }
 
-async fn assemble(spec_chunks: Vec<SpecChunkEvent>) {
-  let spec_events = events_from_chunks(spec_chunks).await;
+async fn assemble(spec_chunks: Vec<SpecChunkEvent>, at_commit: Option<&str>) {
+  let spec_events = events_at_commit(events_from_chunks(spec_chunks).await, at_commit);
 
   let stdout = stdout();
 
@@ -250,6 +508,22 @@ enum SpecPathType {
   DIR,
 }
 
+enum DiffOutputFormat {
+  Json,
+  Text,
+}
+
+struct DiffOptions {
+  output_format: DiffOutputFormat,
+  report_options: Option<(ReportFormat, String)>,
+  // summarize results per fingerprint, sampling at most this many tags per distinct diff
+  aggregate_sample_size: Option<usize>,
+  severity_config: DiffSeverityConfig,
+  // exit with a non-zero code when diffs of at least this severity are found
+  fail_on: Option<DiffSeverity>,
+  diff_config: InteractionDiffConfig,
+}
+
 #[derive(Debug, serde::Deserialize, serde::Serialize)]
 struct TaggedInput<T>(T, Tags);
 #[derive(Debug, serde::Deserialize, serde::Serialize)]
@@ -263,6 +537,38 @@ impl From<(InteractionDiffResult, &Tags)> for ResultContainer<InteractionDiffRes
   }
 }
 
+fn at_commit_arg<'a, 'b>() -> Arg<'a, 'b> {
+  Arg::with_name("at-commit")
+    .long("at-commit")
+    .value_name("BATCH_ID")
+    .takes_value(true)
+    .help("Uses the API spec as it was right after the batch commit with this id")
+}
+
+// the events of the spec as of the batch commit given with `--at-commit`, if any
+fn events_at_commit(events: Vec<SpecEvent>, at_commit: Option<&str>) -> Vec<SpecEvent> {
+  match at_commit {
+    Some(batch_id) => {
+      let history_point = SpecHistoryPoint::BatchCommit(String::from(batch_id));
+      spec_events_until(events, &history_point).unwrap_or_else(|err| {
+        eprintln!("Could not use the spec at commit {}: {}", batch_id, err);
+        process::exit(1);
+      })
+    }
+    None => events,
+  }
+}
+
+// reports are for gating CI, so breaking diffs fail unless told otherwise, while additive ones
+// only fail when asked for
+fn fail_on_severity(fail_on: Option<&str>, is_reporting: bool) -> Option<DiffSeverity> {
+  match fail_on {
+    Some(severity) => DiffSeverity::from_name(severity),
+    None if is_reporting => Some(DiffSeverity::Breaking),
+    None => None,
+  }
+}
+
 async fn events_from_chunks(chunks: Vec<SpecChunkEvent>) -> Vec<SpecEvent> {
   streams::spec_events::from_spec_chunks(chunks)
     .await
@@ -271,8 +577,24 @@ async fn events_from_chunks(chunks: Vec<SpecChunkEvent>) -> Vec<SpecEvent> {
 
 #[cfg(test)]
 mod test {
+  use super::*;
+
   #[test]
   pub fn do_a_diff() {
     assert_eq!(true, true, "wouldn't you know");
   }
+
+  #[test]
+  fn reports_fail_on_breaking_diffs_by_default() {
+    assert_eq!(fail_on_severity(None, true), Some(DiffSeverity::Breaking));
+    assert_eq!(fail_on_severity(None, false), None);
+    assert_eq!(
+      fail_on_severity(Some("additive"), true),
+      Some(DiffSeverity::Additive)
+    );
+    assert_eq!(
+      fail_on_severity(Some("breaking"), false),
+      Some(DiffSeverity::Breaking)
+    );
+  }
 }
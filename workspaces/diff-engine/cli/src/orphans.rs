@@ -0,0 +1,75 @@
+use super::events_from_chunks;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use optic_diff_engine::{orphaned_shapes, SpecChunkEvent, SpecProjection};
+use tokio::io::{stdout, AsyncWriteExt};
+
+pub const SUBCOMMAND_NAME: &str = "orphans";
+
+pub fn create_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name(SUBCOMMAND_NAME)
+    .about("Lists the shapes of the API spec that aren't used by any endpoint")
+    .arg(
+      Arg::with_name("format")
+        .long("format")
+        .takes_value(true)
+        .possible_values(&["json", "text"])
+        .default_value("json")
+        .help("Writes orphaned shapes as json lines or as human-readable messages"),
+    )
+    .arg(
+      Arg::with_name("commands")
+        .long("commands")
+        .takes_value(false)
+        .conflicts_with("format")
+        .help(
+          "Writes commands removing the orphaned shapes as json lines, to be committed, leaving out shapes kept in use by orphaned shapes using each other",
+        ),
+    )
+}
+
+pub async fn main<'a>(command_matches: &'a ArgMatches<'a>, spec_chunks: Vec<SpecChunkEvent>) {
+  let spec_projection = SpecProjection::from(events_from_chunks(spec_chunks).await);
+  let orphans = orphaned_shapes(&spec_projection);
+  let orphans_count = orphans.len();
+
+  let mut output = String::new();
+  for orphan in orphans {
+    if command_matches.is_present("commands") {
+      let shape_id = orphan.shape_id.clone();
+      match orphan.into_command() {
+        Some(command) => {
+          let command_json =
+            serde_json::to_string(&command).expect("commands should serialize to json");
+          output.push_str(&command_json);
+          output.push('\n');
+        }
+        None => eprintln!(
+          "left out shape {}, as it is used by orphaned shapes that use each other",
+          shape_id
+        ),
+      }
+    } else if command_matches.value_of("format") == Some("text") {
+      match &orphan.name {
+        Some(name) => output.push_str(&format!("{} ({})\n", orphan.shape_id, name)),
+        None => output.push_str(&format!("{}\n", orphan.shape_id)),
+      }
+    } else {
+      let orphan_json =
+        serde_json::to_string(&orphan).expect("orphaned shapes should serialize to json");
+      output.push_str(&orphan_json);
+      output.push('\n');
+    }
+  }
+
+  let mut stdout = stdout();
+  stdout
+    .write_all(output.as_bytes())
+    .await
+    .unwrap_or_else(|err| panic!("could not write orphaned shapes to stdout: {}", err));
+  stdout
+    .flush()
+    .await
+    .unwrap_or_else(|err| panic!("could not write orphaned shapes to stdout: {}", err));
+
+  eprintln!("found {} orphaned shapes", orphans_count);
+}
@@ -0,0 +1,424 @@
+use clap::crate_version;
+use optic_diff_engine::{
+  DiffSeverity, EndpointProjection, InteractionDiffResult, RequestSpecTrail, SpecProjection,
+};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReportFormat {
+  Junit,
+  Sarif,
+}
+
+impl ReportFormat {
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "junit" => Some(ReportFormat::Junit),
+      "sarif" => Some(ReportFormat::Sarif),
+      _ => None,
+    }
+  }
+
+  pub fn default_path(&self) -> &'static str {
+    match self {
+      ReportFormat::Junit => "optic-diff-report.xml",
+      ReportFormat::Sarif => "optic-diff-report.sarif",
+    }
+  }
+}
+
+/// Diff results grouped by the endpoint of the spec they were found for, like `GET /todos`, and
+/// deduplicated by fingerprint, so each distinct diff is reported once per endpoint.
+#[derive(Debug, Default)]
+pub struct DiffReport {
+  groups: BTreeMap<String, BTreeMap<String, ReportedDiff>>,
+}
+
+#[derive(Debug)]
+struct ReportedDiff {
+  diff_type: &'static str,
+  severity: DiffSeverity,
+  message: String,
+  // the observed requests the diff was found for, like `GET /todos`
+  requests: Vec<String>,
+  occurrences: usize,
+}
+
+impl DiffReport {
+  pub fn push(
+    &mut self,
+    spec_projection: &SpecProjection,
+    result: &InteractionDiffResult,
+    fingerprint: &str,
+    severity: DiffSeverity,
+    request: &str,
+  ) {
+    let group_name = requests_trail_name(spec_projection.endpoint(), result.requests_trail());
+    let group = self.groups.entry(group_name).or_default();
+    let reported = group
+      .entry(String::from(fingerprint))
+      .or_insert_with(|| ReportedDiff {
+        diff_type: result.diff_type(),
+        severity,
+        message: result.to_string(),
+        requests: vec![],
+        occurrences: 0,
+      });
+
+    reported.occurrences += 1;
+    if !reported.requests.iter().any(|seen| seen == request) {
+      reported.requests.push(String::from(request));
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.groups.is_empty()
+  }
+
+  pub fn diffs_count(&self) -> usize {
+    self.groups.values().map(|group| group.len()).sum()
+  }
+
+  pub fn render(&self, format: ReportFormat) -> String {
+    match format {
+      ReportFormat::Junit => self.to_junit(),
+      ReportFormat::Sarif => self.to_sarif(),
+    }
+  }
+
+  // Every distinct diff is a failed test case, in a test suite per endpoint
+  fn to_junit(&self) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let diffs_count = self.diffs_count();
+    xml.push_str(&format!(
+      "<testsuites name=\"optic_diff\" tests=\"{}\" failures=\"{}\">\n",
+      diffs_count, diffs_count
+    ));
+
+    for (group_name, diffs) in &self.groups {
+      xml.push_str(&format!(
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">\n",
+        escape_xml(group_name),
+        diffs.len(),
+        diffs.len()
+      ));
+      for (fingerprint, diff) in diffs {
+        let name = format!("{}: {}", diff.requests.join(", "), diff.message);
+        let details = format!(
+          "severity: {}\nfingerprint: {}\noccurrences: {}",
+          diff.severity, fingerprint, diff.occurrences
+        );
+        xml.push_str(&format!(
+          "    <testcase name=\"{}\" classname=\"{}\">\n",
+          escape_xml(&name),
+          escape_xml(group_name)
+        ));
+        xml.push_str(&format!(
+          "      <failure type=\"{}\" message=\"{}\">{}</failure>\n",
+          diff.diff_type,
+          escape_xml(&diff.message),
+          escape_xml(&details)
+        ));
+        xml.push_str("    </testcase>\n");
+      }
+      xml.push_str("  </testsuite>\n");
+    }
+
+    xml.push_str("</testsuites>\n");
+    xml
+  }
+
+  // Every distinct diff is a result, with the kind of diff as its rule and the endpoint as its
+  // (logical) location. Breaking diffs are errors, additive ones warnings.
+  fn to_sarif(&self) -> String {
+    let mut diff_types = self
+      .groups
+      .values()
+      .flat_map(|diffs| diffs.values().map(|diff| diff.diff_type))
+      .collect::<Vec<_>>();
+    diff_types.sort_unstable();
+    diff_types.dedup();
+
+    let rules = diff_types
+      .iter()
+      .map(|diff_type| json!({ "id": diff_type, "name": diff_type }))
+      .collect::<Vec<_>>();
+    let results = self
+      .groups
+      .iter()
+      .flat_map(|(group_name, diffs)| {
+        diffs.iter().map(move |(fingerprint, diff)| {
+          json!({
+            "ruleId": diff.diff_type,
+            "level": match diff.severity {
+              DiffSeverity::Breaking => "error",
+              DiffSeverity::Additive => "warning",
+            },
+            "message": {
+              "text": format!("{}: {}", diff.requests.join(", "), diff.message)
+            },
+            "locations": [{
+              "logicalLocations": [{ "fullyQualifiedName": group_name, "kind": "resource" }]
+            }],
+            "partialFingerprints": { "opticDiffFingerprint/v1": fingerprint },
+            "properties": {
+              "occurrences": diff.occurrences,
+              "requests": diff.requests,
+              "severity": diff.severity,
+            },
+          })
+        })
+      })
+      .collect::<Vec<_>>();
+
+    let sarif = json!({
+      "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+      "version": "2.1.0",
+      "runs": [{
+        "tool": {
+          "driver": {
+            "name": "optic_diff",
+            "version": crate_version!(),
+            "rules": rules,
+          }
+        },
+        "results": results,
+      }]
+    });
+
+    serde_json::to_string_pretty(&sarif).expect("sarif report should serialize to json")
+  }
+}
+
+// Diffs are grouped by the method and path of the endpoint they were found for. Unmatched urls
+// aren't for any endpoint, and ids the spec can't resolve are named as they are.
+fn requests_trail_name(
+  endpoint_projection: &EndpointProjection,
+  requests_trail: &RequestSpecTrail,
+) -> String {
+  let (path_and_method, unresolved_name) = match requests_trail {
+    RequestSpecTrail::SpecRoot(_) => return String::from("spec"),
+    RequestSpecTrail::SpecPath(trail) => {
+      return endpoint_projection
+        .get_absolute_path(&trail.path_id)
+        .unwrap_or_else(|| format!("path {}", trail.path_id))
+    }
+    RequestSpecTrail::SpecRequestRoot(trail) => (
+      endpoint_projection.get_request_path_and_method(&trail.request_id),
+      format!("request {}", trail.request_id),
+    ),
+    RequestSpecTrail::SpecRequestBody(trail) => (
+      endpoint_projection.get_request_path_and_method(&trail.request_id),
+      format!("request {}", trail.request_id),
+    ),
+    RequestSpecTrail::SpecResponseRoot(trail) => (
+      endpoint_projection.get_response_path_and_method(&trail.response_id),
+      format!("response {}", trail.response_id),
+    ),
+    RequestSpecTrail::SpecResponseBody(trail) => (
+      endpoint_projection.get_response_path_and_method(&trail.response_id),
+      format!("response {}", trail.response_id),
+    ),
+    RequestSpecTrail::SpecRequestParameter(trail) => (
+      endpoint_projection
+        .request_parameters
+        .get(&trail.parameter_id)
+        .map(|parameter| (parameter.path_id.clone(), parameter.http_method.clone())),
+      format!("request parameter {}", trail.parameter_id),
+    ),
+  };
+
+  path_and_method
+    .and_then(|(path_id, method)| {
+      let path = endpoint_projection.get_absolute_path(&path_id)?;
+      Some(format!("{} {}", method, path))
+    })
+    .unwrap_or(unresolved_name)
+}
+
+fn escape_xml(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+    .replace('\n', "&#10;")
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use optic_diff_engine::SpecEvent;
+
+  fn todos_spec() -> SpecProjection {
+    let events: Vec<SpecEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1", "parentPathId": "root", "name": "todos"}},
+      {"PathParameterAdded": {"pathId": "path_2", "parentPathId": "path_1", "name": "todoId"}},
+      {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "GET"}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_1", "pathId": "path_1", "httpMethod": "GET", "httpStatusCode": 200}},
+      {"RequestParameterAddedByPathAndMethod": {"parameterId": "parameter_1", "pathId": "path_1", "httpMethod": "GET", "parameterLocation": "header", "name": "x-token"}},
+      {"RequestAdded": {"requestId": "request_2", "pathId": "path_2", "httpMethod": "PUT"}},
+    ]))
+    .expect("todos spec events should be valid spec events");
+    SpecProjection::from(events)
+  }
+
+  // a request to GET /todos without the documented x-token header
+  fn missing_token_header() -> InteractionDiffResult {
+    serde_json::from_value(json!({
+      "MissingHeader": {
+        "interactionTrail": { "path": [{ "RequestHeader": { "name": "x-token" } }] },
+        "requestsTrail": { "SpecRequestParameter": { "parameterId": "parameter_1" } }
+      }
+    }))
+    .expect("missing header should be a valid diff result")
+  }
+
+  // a response to GET /todos with a list of todos, where an object was documented
+  fn unmatched_todos_response() -> InteractionDiffResult {
+    serde_json::from_value(json!({
+      "UnmatchedResponseBodyShape": {
+        "interactionTrail": { "path": [{ "ResponseBody": { "contentType": "application/json", "statusCode": 200 } }] },
+        "requestsTrail": { "SpecResponseBody": { "responseId": "response_1" } },
+        "shapeDiffResult": {
+          "UnmatchedShape": {
+            "jsonTrail": { "path": [] },
+            "shapeTrail": { "rootShapeId": "todos_shape", "path": [] },
+            "expectedKinds": ["ObjectKind"],
+            "observedKind": "Array"
+          }
+        }
+      }
+    }))
+    .expect("unmatched response body shape should be a valid diff result")
+  }
+
+  // a request to PUT /todos/{todoId} with a body, where none was documented
+  fn undocumented_todo_body() -> InteractionDiffResult {
+    serde_json::from_value(json!({
+      "UnmatchedRequestBodyContentType": {
+        "interactionTrail": { "path": [{ "RequestBody": { "contentType": "application/json" } }] },
+        "requestsTrail": { "SpecRequestRoot": { "requestId": "request_2" } }
+      }
+    }))
+    .expect("unmatched request body content type should be a valid diff result")
+  }
+
+  #[test]
+  fn reports_distinct_diffs_per_endpoint() {
+    let spec = todos_spec();
+    let missing_header = missing_token_header();
+    let unmatched_response = unmatched_todos_response();
+
+    let mut report = DiffReport::default();
+    assert!(report.is_empty());
+    let additive = DiffSeverity::Additive;
+    let breaking = DiffSeverity::Breaking;
+    report.push(
+      &spec,
+      &missing_header,
+      &missing_header.fingerprint(),
+      breaking,
+      "GET /todos",
+    );
+    report.push(
+      &spec,
+      &missing_header,
+      &missing_header.fingerprint(),
+      breaking,
+      "GET /todos?page=2",
+    );
+    report.push(
+      &spec,
+      &unmatched_response,
+      &unmatched_response.fingerprint(),
+      additive,
+      "GET /todos",
+    );
+    assert_eq!(
+      report.diffs_count(),
+      2,
+      "diffs are deduplicated by fingerprint"
+    );
+
+    let junit = report.render(ReportFormat::Junit);
+    assert!(junit.contains("<testsuites name=\"optic_diff\" tests=\"2\" failures=\"2\">"));
+    assert!(junit.contains("<testsuite name=\"GET /todos\" tests=\"2\" failures=\"2\">"));
+    assert!(junit.contains(
+      "<testcase name=\"GET /todos, GET /todos?page=2: missing required request header `x-token`\""
+    ));
+    assert!(junit.contains("occurrences: 2"));
+    assert!(junit.contains("severity: breaking"));
+
+    let sarif: serde_json::Value = serde_json::from_str(&report.render(ReportFormat::Sarif))
+      .expect("sarif report should be valid json");
+    let results = sarif["runs"][0]["results"]
+      .as_array()
+      .expect("sarif report should have results");
+    assert_eq!(results.len(), 2);
+    let levels = results
+      .iter()
+      .map(|result| {
+        (
+          result["ruleId"].as_str().unwrap(),
+          result["level"].as_str().unwrap(),
+        )
+      })
+      .collect::<BTreeMap<_, _>>();
+    assert_eq!(levels["MissingHeader"], "error");
+    assert_eq!(levels["UnmatchedResponseBodyShape"], "warning");
+    assert_eq!(
+      results[0]["locations"][0]["logicalLocations"][0]["fullyQualifiedName"],
+      "GET /todos"
+    );
+    assert_eq!(
+      sarif["runs"][0]["tool"]["driver"]["rules"]
+        .as_array()
+        .unwrap()
+        .len(),
+      2
+    );
+  }
+
+  #[test]
+  fn names_endpoints_by_method_and_path() {
+    let spec = todos_spec();
+    let undocumented_body = undocumented_todo_body();
+    let unmatched_url: InteractionDiffResult = serde_json::from_value(json!({
+      "UnmatchedRequestUrl": {
+        "interactionTrail": { "path": [{ "Url": { "path": "/users" } }, { "Method": { "method": "GET" } }] },
+        "requestsTrail": { "SpecRoot": {} }
+      }
+    }))
+    .expect("unmatched request url should be a valid diff result");
+    let unknown_response: InteractionDiffResult = serde_json::from_value(json!({
+      "UnmatchedResponseBodyContentType": {
+        "interactionTrail": { "path": [{ "ResponseStatusCode": { "statusCode": 404 } }] },
+        "requestsTrail": { "SpecResponseRoot": { "responseId": "response_9" } }
+      }
+    }))
+    .expect("unmatched response body content type should be a valid diff result");
+
+    let mut report = DiffReport::default();
+    for (result, request) in [
+      (&undocumented_body, "PUT /todos/1"),
+      (&unmatched_url, "GET /users"),
+      (&unknown_response, "GET /todos"),
+    ] {
+      report.push(
+        &spec,
+        result,
+        &result.fingerprint(),
+        DiffSeverity::Additive,
+        request,
+      );
+    }
+
+    let group_names = report.groups.keys().collect::<Vec<_>>();
+    assert_eq!(
+      group_names,
+      vec!["PUT /todos/{todoId}", "response response_9", "spec"]
+    );
+  }
+}
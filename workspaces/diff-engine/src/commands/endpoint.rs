@@ -1,8 +1,10 @@
-use super::{CommandContext, SpecCommand, SpecCommandError};
+use super::{CommandContext, ShapeCommand, SpecCommand, SpecCommandError};
 use serde::{Deserialize, Serialize};
 
-use crate::events::EndpointEvent;
-use crate::projections::endpoint::ROOT_PATH_ID;
+use crate::events::{EndpointEvent, ShapeEvent};
+use crate::projections::endpoint::{
+  Node, HEADER_PARAMETER_LOCATION, QUERY_PARAMETER_LOCATION, ROOT_PATH_ID,
+};
 use crate::projections::EndpointProjection;
 use crate::state::endpoint::{
   PathComponentId, RequestId, RequestParameterId, ResponseId, ShapedBodyDescriptor,
@@ -11,6 +13,7 @@ use crate::state::endpoint::{
 use crate::state::shape::ShapeId;
 use crate::{events::endpoint as endpoint_events, state::body};
 use cqrs_core::AggregateCommand;
+use petgraph::graph::NodeIndex;
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
 pub enum EndpointCommand {
@@ -41,6 +44,10 @@ pub enum EndpointCommand {
   UnsetResponseBodyShape(UnsetResponseBodyShape),
   RemoveResponse(RemoveResponse),
 
+  // Query parameters
+  AddQueryParameter(AddQueryParameter),
+  SetQueryParameterShape(SetQueryParameterShape),
+
   // Headers
   AddHeaderParameter(AddHeaderParameter),
   SetHeaderParameterShape(SetHeaderParameterShape),
@@ -50,6 +57,33 @@ pub enum EndpointCommand {
 }
 
 impl EndpointCommand {
+  // Path components
+  // ---------------
+
+  pub fn add_path_component(
+    path_id: PathComponentId,
+    parent_path_id: PathComponentId,
+    name: String,
+  ) -> EndpointCommand {
+    EndpointCommand::AddPathComponent(AddPathComponent {
+      path_id,
+      parent_path_id,
+      name,
+    })
+  }
+
+  pub fn add_path_parameter(
+    path_id: PathComponentId,
+    parent_path_id: PathComponentId,
+    name: String,
+  ) -> EndpointCommand {
+    EndpointCommand::AddPathParameter(AddPathParameter {
+      path_id,
+      parent_path_id,
+      name,
+    })
+  }
+
   pub fn set_path_parameter_shape(path_id: PathComponentId, shape_id: ShapeId) -> EndpointCommand {
     EndpointCommand::SetPathParameterShape(SetPathParameterShape {
       path_id,
@@ -123,6 +157,62 @@ impl EndpointCommand {
       },
     })
   }
+
+  // Query parameters
+  // ----------------
+
+  pub fn add_query_parameter(
+    parameter_id: RequestParameterId,
+    request_id: RequestId,
+    name: String,
+  ) -> EndpointCommand {
+    EndpointCommand::AddQueryParameter(AddQueryParameter {
+      parameter_id,
+      request_id,
+      name,
+    })
+  }
+
+  pub fn set_query_parameter_shape(
+    parameter_id: RequestParameterId,
+    shape_id: ShapeId,
+  ) -> EndpointCommand {
+    EndpointCommand::SetQueryParameterShape(SetQueryParameterShape {
+      parameter_id,
+      parameter_descriptor: ShapedRequestParameterShapeDescriptor {
+        shape_id,
+        is_removed: false,
+      },
+    })
+  }
+
+  // Headers
+  // -------
+
+  pub fn add_header_parameter(
+    parameter_id: RequestParameterId,
+    request_id: RequestId,
+    name: String,
+  ) -> EndpointCommand {
+    EndpointCommand::AddHeaderParameter(AddHeaderParameter {
+      parameter_id,
+      request_id,
+      name,
+    })
+  }
+
+  pub fn set_header_parameter_shape(
+    parameter_id: RequestParameterId,
+    shape_id: ShapeId,
+  ) -> EndpointCommand {
+    EndpointCommand::SetHeaderParameterShape(SetHeaderParameterShape {
+      parameter_id,
+      parameter_descriptor: ShapedRequestParameterShapeDescriptor {
+        shape_id,
+        is_removed: false,
+      },
+    })
+  }
 }
 
 // Path components
@@ -195,8 +285,8 @@ pub struct AddRequest {
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SetRequestContentType {
-  request_id: RequestId,
-  http_content_type: String,
+  pub request_id: RequestId,
+  pub http_content_type: String,
 }
 
 //@GOTCHA #leftovers-from-designer-ui @TODO we should probably not support this command's ability to change the content type anymore, or enforce uniqueness of content types across multiple requests
@@ -211,13 +301,13 @@ pub struct SetRequestBodyShape {
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UnsetRequestBodyShape {
-  request_id: RequestId,
+  pub request_id: RequestId,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RemoveRequest {
-  request_id: RequestId,
+  pub request_id: RequestId,
 }
 
 // Responses
@@ -226,9 +316,9 @@ pub struct RemoveRequest {
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AddResponse {
-  response_id: ResponseId,
-  request_id: RequestId,
-  http_status_code: u16,
+  pub response_id: ResponseId,
+  pub request_id: RequestId,
+  pub http_status_code: u16,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
@@ -244,16 +334,16 @@ pub struct AddResponseByPathAndMethod {
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SetResponseContentType {
-  response_id: ResponseId,
-  http_content_type: String,
+  pub response_id: ResponseId,
+  pub http_content_type: String,
 }
 
 //@GOTCHA #leftovers-from-designer-ui @TODO we should probably not support this command anymore
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SetResponseStatusCode {
-  response_id: ResponseId,
-  http_status_code: u16,
+  pub response_id: ResponseId,
+  pub http_status_code: u16,
 }
 
 //@GOTCHA #leftovers-from-designer-ui @TODO we should probably not support this command's ability to change the content type anymore, or enforce uniqueness of content types across multiple responses
@@ -268,13 +358,31 @@ pub struct SetResponseBodyShape {
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UnsetResponseBodyShape {
-  response_id: ResponseId,
+  pub response_id: ResponseId,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RemoveResponse {
-  response_id: ResponseId,
+  pub response_id: ResponseId,
+}
+
+// Query parameters
+// ----------------
+
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddQueryParameter {
+  pub parameter_id: RequestParameterId,
+  pub request_id: RequestId,
+  pub name: String,
+}
+
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetQueryParameterShape {
+  pub parameter_id: RequestParameterId,
+  pub parameter_descriptor: ShapedRequestParameterShapeDescriptor,
 }
 
 // Headers
@@ -283,35 +391,35 @@ pub struct RemoveResponse {
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AddHeaderParameter {
-  parameter_id: RequestParameterId,
-  request_id: RequestId,
-  name: String,
+  pub parameter_id: RequestParameterId,
+  pub request_id: RequestId,
+  pub name: String,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SetHeaderParameterShape {
-  parameter_id: RequestParameterId,
-  parameter_descriptor: ShapedRequestParameterShapeDescriptor,
+  pub parameter_id: RequestParameterId,
+  pub parameter_descriptor: ShapedRequestParameterShapeDescriptor,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RenameHeaderParameter {
-  parameter_id: RequestParameterId,
-  name: String,
+  pub parameter_id: RequestParameterId,
+  pub name: String,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UnsetHeaderParameterShape {
-  parameter_id: RequestParameterId,
+  pub parameter_id: RequestParameterId,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RemoveHeaderParameter {
-  parameter_id: RequestParameterId,
+  pub parameter_id: RequestParameterId,
 }
 
 // Command handling
@@ -465,8 +573,66 @@ impl AggregateCommand<EndpointProjection> for EndpointCommand {
         ))]
       }
 
+      EndpointCommand::SetRequestContentType(command) => {
+        validation.require(
+          validation.request_exists(&command.request_id),
+          "request must exist to set request content type",
+        )?;
+
+        vec![EndpointEvent::from(
+          endpoint_events::RequestContentTypeSet::from(command),
+        )]
+      }
+
+      EndpointCommand::UnsetRequestBodyShape(command) => {
+        validation.require(
+          validation.request_exists(&command.request_id),
+          "request must exist to unset request body shape",
+        )?;
+
+        vec![EndpointEvent::from(
+          endpoint_events::RequestBodyUnset::from(command),
+        )]
+      }
+
+      EndpointCommand::RemoveRequest(command) => {
+        validation.require(
+          validation.request_exists(&command.request_id),
+          "request must exist to remove request",
+        )?;
+
+        vec![EndpointEvent::from(endpoint_events::RequestRemoved::from(
+          command,
+        ))]
+      }
+
       // Responses
       // ---------
+      EndpointCommand::AddResponse(command) => {
+        validation.require(
+          !validation.response_exists(&command.response_id),
+          "response id must be assignable to add response",
+        )?;
+        validation.require(
+          validation.request_exists(&command.request_id),
+          "request must exist to add response",
+        )?;
+        // responses are described by path and method, shared by all requests of an operation
+        let (path_id, http_method) = projection
+          .get_request_path_and_method(&command.request_id)
+          .expect("requests should always belong to a path and method");
+
+        vec![EndpointEvent::from(
+          endpoint_events::ResponseAddedByPathAndMethod {
+            response_id: command.response_id,
+            path_id,
+            http_method,
+            http_status_code: command.http_status_code,
+            event_context: None,
+          },
+        )]
+      }
+
       EndpointCommand::AddResponseByPathAndMethod(command) => {
         validation.require(
           !validation.response_exists(&command.response_id),
@@ -493,9 +659,187 @@ impl AggregateCommand<EndpointProjection> for EndpointCommand {
         ))]
       }
 
+      EndpointCommand::SetResponseContentType(command) => {
+        validation.require(
+          validation.response_exists(&command.response_id),
+          "response must exist to set response content type",
+        )?;
+
+        vec![EndpointEvent::from(
+          endpoint_events::ResponseContentTypeSet::from(command),
+        )]
+      }
+
+      EndpointCommand::SetResponseStatusCode(command) => {
+        validation.require(
+          validation.response_exists(&command.response_id),
+          "response must exist to set response status code",
+        )?;
+
+        vec![EndpointEvent::from(
+          endpoint_events::ResponseStatusCodeSet::from(command),
+        )]
+      }
+
+      EndpointCommand::UnsetResponseBodyShape(command) => {
+        validation.require(
+          validation.response_exists(&command.response_id),
+          "response must exist to unset response body shape",
+        )?;
+
+        vec![EndpointEvent::from(
+          endpoint_events::ResponseBodyUnset::from(command),
+        )]
+      }
+
+      EndpointCommand::RemoveResponse(command) => {
+        validation.require(
+          validation.response_exists(&command.response_id),
+          "response must exist to remove response",
+        )?;
+
+        vec![EndpointEvent::from(endpoint_events::ResponseRemoved::from(
+          command,
+        ))]
+      }
+
+      // Query parameters
+      // ----------------
+      EndpointCommand::AddQueryParameter(command) => {
+        validation.require(
+          !validation.request_parameter_exists(&command.parameter_id),
+          "parameter id must be assignable to add query parameter",
+        )?;
+        validation.require(
+          validation.request_exists(&command.request_id),
+          "request must exist to add query parameter",
+        )?;
+        // parameters are described by path and method, shared by all requests of an operation
+        let (path_id, http_method) = projection
+          .get_request_path_and_method(&command.request_id)
+          .expect("requests should always belong to a path and method");
+
+        vec![EndpointEvent::from(
+          endpoint_events::RequestParameterAddedByPathAndMethod {
+            parameter_id: command.parameter_id,
+            path_id,
+            http_method,
+            parameter_location: String::from(QUERY_PARAMETER_LOCATION),
+            name: command.name,
+            event_context: None,
+          },
+        )]
+      }
+
+      EndpointCommand::SetQueryParameterShape(command) => {
+        validation.require(
+          validation.query_parameter_exists(&command.parameter_id),
+          "query parameter must exist to set query parameter shape",
+        )?;
+
+        vec![EndpointEvent::from(
+          endpoint_events::RequestParameterShapeSet::from(command),
+        )]
+      }
+
+      // Headers
+      // -------
+      EndpointCommand::AddHeaderParameter(command) => {
+        validation.require(
+          !validation.request_parameter_exists(&command.parameter_id),
+          "parameter id must be assignable to add header parameter",
+        )?;
+        validation.require(
+          validation.request_exists(&command.request_id),
+          "request must exist to add header parameter",
+        )?;
+        // parameters are described by path and method, shared by all requests of an operation
+        let (path_id, http_method) = projection
+          .get_request_path_and_method(&command.request_id)
+          .expect("requests should always belong to a path and method");
+
+        vec![EndpointEvent::from(
+          endpoint_events::RequestParameterAddedByPathAndMethod {
+            parameter_id: command.parameter_id,
+            path_id,
+            http_method,
+            parameter_location: String::from(HEADER_PARAMETER_LOCATION),
+            name: command.name,
+            event_context: None,
+          },
+        )]
+      }
+
+      EndpointCommand::SetHeaderParameterShape(command) => {
+        validation.require(
+          validation.header_parameter_exists(&command.parameter_id),
+          "header parameter must exist to set header parameter shape",
+        )?;
+
+        vec![EndpointEvent::from(
+          endpoint_events::RequestParameterShapeSet::from(command),
+        )]
+      }
+
+      EndpointCommand::RenameHeaderParameter(command) => {
+        validation.require(
+          validation.header_parameter_exists(&command.parameter_id),
+          "header parameter must exist to rename header parameter",
+        )?;
+
+        vec![EndpointEvent::from(
+          endpoint_events::RequestParameterRenamed::from(command),
+        )]
+      }
+
+      EndpointCommand::UnsetHeaderParameterShape(command) => {
+        validation.require(
+          validation.header_parameter_exists(&command.parameter_id),
+          "header parameter must exist to unset header parameter shape",
+        )?;
+
+        vec![EndpointEvent::from(
+          endpoint_events::RequestParameterShapeUnset::from(command),
+        )]
+      }
+
+      EndpointCommand::RemoveHeaderParameter(command) => {
+        validation.require(
+          validation.header_parameter_exists(&command.parameter_id),
+          "header parameter must exist to remove header parameter",
+        )?;
+
+        vec![EndpointEvent::from(
+          endpoint_events::RequestParameterRemoved::from(command),
+        )]
+      }
+    };
+
+    Ok(events)
+  }
+}
+
+impl AggregateCommand<EndpointProjection> for ShapeCommand {
+  type Error = SpecCommandError;
+  type Event = EndpointEvent;
+  type Events = Vec<EndpointEvent>;
+
+  fn execute_on(self, projection: &EndpointProjection) -> Result<Self::Events, Self::Error> {
+    let validation = CommandValidationQueries::from((projection, &self));
+
+    let events = match self {
+      ShapeCommand::RemoveShape(command) => {
+        validation.require(
+          !validation.shape_is_referenced(&command.shape_id),
+          "shape must not be used by bodies or parameters to remove shape",
+        )?;
+
+        vec![] // validation only
+      }
+
       _ => Err(SpecCommandError::Unimplemented(
-        "endpoint command not implemented for endpoint projection",
-        SpecCommand::EndpointCommand(self),
+        "shape command not implemented for endpoint projection",
+        SpecCommand::ShapeCommand(self),
       ))?,
     };
 
@@ -503,7 +847,7 @@ impl AggregateCommand<EndpointProjection> for EndpointCommand {
   }
 }
 
-struct CommandValidationQueries<'a> {
+pub(crate) struct CommandValidationQueries<'a> {
   command_description: String,
   endpoint_projection: &'a EndpointProjection,
 }
@@ -544,6 +888,114 @@ impl<'a> CommandValidationQueries<'a> {
       .get_response_node_index(response_id)
       .is_some()
   }
+
+  pub fn request_parameter_exists(&self, parameter_id: &RequestParameterId) -> bool {
+    self
+      .endpoint_projection
+      .request_parameters
+      .contains_key(parameter_id)
+  }
+
+  pub fn query_parameter_exists(&self, parameter_id: &RequestParameterId) -> bool {
+    self.request_parameter_exists_in(parameter_id, QUERY_PARAMETER_LOCATION)
+  }
+
+  pub fn header_parameter_exists(&self, parameter_id: &RequestParameterId) -> bool {
+    self.request_parameter_exists_in(parameter_id, HEADER_PARAMETER_LOCATION)
+  }
+
+  // whether another request of the same operation already has a body of the content type
+  pub fn request_body_content_type_exists(
+    &self,
+    request_id: &RequestId,
+    http_content_type: &str,
+  ) -> bool {
+    match self.endpoint_projection.get_request_node_index(request_id) {
+      Some(request_node_index) => {
+        self.sibling_body_content_type_exists(*request_node_index, http_content_type)
+      }
+      None => false,
+    }
+  }
+
+  // whether another response of the same operation and status code already has a body of the
+  // content type
+  pub fn response_body_content_type_exists(
+    &self,
+    response_id: &ResponseId,
+    http_content_type: &str,
+  ) -> bool {
+    match self
+      .endpoint_projection
+      .get_response_node_index(response_id)
+    {
+      Some(response_node_index) => {
+        self.sibling_body_content_type_exists(*response_node_index, http_content_type)
+      }
+      None => false,
+    }
+  }
+
+  pub fn shape_is_referenced(&self, shape_id: &ShapeId) -> bool {
+    let projection = self.endpoint_projection;
+    let referenced_by_body = projection.graph.raw_nodes().iter().any(|node| {
+      let body = match &node.weight {
+        Node::Request(_, descriptor) => &descriptor.body,
+        Node::Response(_, descriptor) => &descriptor.body,
+        _ => return false,
+      };
+      matches!(body, Some(body) if body.root_shape_id == *shape_id)
+    });
+
+    referenced_by_body
+      || projection
+        .path_parameter_shapes
+        .values()
+        .any(|id| id == shape_id)
+      || projection
+        .request_parameters
+        .values()
+        .any(|parameter| parameter.shape_id.as_ref() == Some(shape_id))
+  }
+
+  // requests and responses are children of the method and status code nodes respectively
+  fn sibling_body_content_type_exists(
+    &self,
+    node_index: NodeIndex,
+    http_content_type: &str,
+  ) -> bool {
+    let graph = &self.endpoint_projection.graph;
+    let parent_node_index = match graph
+      .neighbors_directed(node_index, petgraph::Direction::Outgoing)
+      .next()
+    {
+      Some(parent_node_index) => parent_node_index,
+      None => return false,
+    };
+
+    graph
+      .neighbors_directed(parent_node_index, petgraph::Direction::Incoming)
+      .filter(|sibling_index| *sibling_index != node_index)
+      .any(|sibling_index| {
+        let body = match graph.node_weight(sibling_index) {
+          Some(Node::Request(_, descriptor)) => &descriptor.body,
+          Some(Node::Response(_, descriptor)) => &descriptor.body,
+          _ => return false,
+        };
+        matches!(body, Some(body) if body.http_content_type == http_content_type)
+      })
+  }
+
+  fn request_parameter_exists_in(&self, parameter_id: &RequestParameterId, location: &str) -> bool {
+    match self
+      .endpoint_projection
+      .request_parameters
+      .get(parameter_id)
+    {
+      Some(descriptor) => descriptor.location == location,
+      None => false,
+    }
+  }
 }
 
 impl<'a> From<(&'a EndpointProjection, &EndpointCommand)> for CommandValidationQueries<'a> {
@@ -557,17 +1009,44 @@ impl<'a> From<(&'a EndpointProjection, &EndpointCommand)> for CommandValidationQ
   }
 }
 
-#[cfg(test)]
-mod test {
-  use super::*;
-  use crate::events::SpecEvent;
-  use cqrs_core::Aggregate;
-  use insta::assert_debug_snapshot;
-  use serde_json::json;
+impl<'a> From<(&'a EndpointProjection, &ShapeCommand)> for CommandValidationQueries<'a> {
+  fn from((endpoint_projection, shape_command): (&'a EndpointProjection, &ShapeCommand)) -> Self {
+    Self {
+      command_description: format!("{:?}", shape_command),
+      endpoint_projection,
+    }
+  }
+}
 
-  #[test]
-  pub fn can_handle_add_path_component_command() {
-    let initial_events: Vec<SpecEvent> = serde_json::from_value(json!([
+impl<'a> From<(&'a EndpointProjection, &EndpointEvent)> for CommandValidationQueries<'a> {
+  fn from((endpoint_projection, endpoint_event): (&'a EndpointProjection, &EndpointEvent)) -> Self {
+    Self {
+      command_description: format!("{:?}", endpoint_event),
+      endpoint_projection,
+    }
+  }
+}
+
+impl<'a> From<(&'a EndpointProjection, &ShapeEvent)> for CommandValidationQueries<'a> {
+  fn from((endpoint_projection, shape_event): (&'a EndpointProjection, &ShapeEvent)) -> Self {
+    Self {
+      command_description: format!("{:?}", shape_event),
+      endpoint_projection,
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::events::SpecEvent;
+  use cqrs_core::Aggregate;
+  use insta::assert_debug_snapshot;
+  use serde_json::json;
+
+  #[test]
+  pub fn can_handle_add_path_component_command() {
+    let initial_events: Vec<SpecEvent> = serde_json::from_value(json!([
       {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
     ]))
     .expect("initial events should be valid spec events");
@@ -1091,4 +1570,466 @@ mod test {
       projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
     }
   }
+
+  #[test]
+  pub fn can_handle_set_request_content_type_command() {
+    let initial_events: Vec<EndpointEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "POST"}},
+      {"RequestBodySet": {"requestId": "request_1", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "shape_1", "isRemoved": false }}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_1", "pathId": "path_1", "httpMethod": "POST", "httpStatusCode": 200}},
+      {"ResponseBodySet": {"responseId": "response_1", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "shape_2", "isRemoved": false }}}
+    ]))
+    .expect("initial events should be valid endpoint events");
+
+    let mut projection = EndpointProjection::from(initial_events);
+
+    let valid_command: EndpointCommand = serde_json::from_value(json!(
+      {"SetRequestContentType": {"requestId": "request_1", "httpContentType": "application/xml"}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!(
+      "can_handle_set_request_content_type_command__new_events",
+      new_events
+    );
+
+    let unexisting_request: EndpointCommand = serde_json::from_value(json!(
+      {"SetRequestContentType": {"requestId": "not-a-request", "httpContentType": "application/xml"}}
+    ))
+    .unwrap();
+    let unexisting_request_result = projection.execute(unexisting_request);
+    assert!(unexisting_request_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_set_request_content_type_command__unexisting_request_result",
+      unexisting_request_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+  }
+
+  #[test]
+  pub fn can_handle_unset_request_body_shape_command() {
+    let initial_events: Vec<EndpointEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "POST"}},
+      {"RequestBodySet": {"requestId": "request_1", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "shape_1", "isRemoved": false }}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_1", "pathId": "path_1", "httpMethod": "POST", "httpStatusCode": 200}},
+      {"ResponseBodySet": {"responseId": "response_1", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "shape_2", "isRemoved": false }}}
+    ]))
+    .expect("initial events should be valid endpoint events");
+
+    let mut projection = EndpointProjection::from(initial_events);
+
+    let valid_command: EndpointCommand = serde_json::from_value(json!(
+      {"UnsetRequestBodyShape": {"requestId": "request_1"}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!(
+      "can_handle_unset_request_body_shape_command__new_events",
+      new_events
+    );
+
+    let unexisting_request: EndpointCommand = serde_json::from_value(json!(
+      {"UnsetRequestBodyShape": {"requestId": "not-a-request"}}
+    ))
+    .unwrap();
+    let unexisting_request_result = projection.execute(unexisting_request);
+    assert!(unexisting_request_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_unset_request_body_shape_command__unexisting_request_result",
+      unexisting_request_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+  }
+
+  #[test]
+  pub fn can_handle_remove_request_command() {
+    let initial_events: Vec<EndpointEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "POST"}},
+      {"RequestBodySet": {"requestId": "request_1", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "shape_1", "isRemoved": false }}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_1", "pathId": "path_1", "httpMethod": "POST", "httpStatusCode": 200}},
+      {"ResponseBodySet": {"responseId": "response_1", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "shape_2", "isRemoved": false }}}
+    ]))
+    .expect("initial events should be valid endpoint events");
+
+    let mut projection = EndpointProjection::from(initial_events);
+
+    let valid_command: EndpointCommand = serde_json::from_value(json!(
+      {"RemoveRequest": {"requestId": "request_1"}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!("can_handle_remove_request_command__new_events", new_events);
+
+    let unexisting_request: EndpointCommand = serde_json::from_value(json!(
+      {"RemoveRequest": {"requestId": "not-a-request"}}
+    ))
+    .unwrap();
+    let unexisting_request_result = projection.execute(unexisting_request);
+    assert!(unexisting_request_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_remove_request_command__unexisting_request_result",
+      unexisting_request_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+    assert!(projection
+      .get_request_node_index(&String::from("request_1"))
+      .is_none());
+  }
+
+  #[test]
+  pub fn can_handle_add_response_command() {
+    let initial_events: Vec<EndpointEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "POST"}},
+      {"RequestBodySet": {"requestId": "request_1", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "shape_1", "isRemoved": false }}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_1", "pathId": "path_1", "httpMethod": "POST", "httpStatusCode": 200}},
+      {"ResponseBodySet": {"responseId": "response_1", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "shape_2", "isRemoved": false }}}
+    ]))
+    .expect("initial events should be valid endpoint events");
+
+    let mut projection = EndpointProjection::from(initial_events);
+
+    let valid_command: EndpointCommand = serde_json::from_value(json!(
+      {"AddResponse": {"responseId": "response_2", "requestId": "request_1", "httpStatusCode": 404}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!("can_handle_add_response_command__new_events", new_events);
+
+    let unexisting_request: EndpointCommand = serde_json::from_value(json!(
+      {"AddResponse": {"responseId": "response_2", "requestId": "not-a-request", "httpStatusCode": 404}}
+    ))
+    .unwrap();
+    let unexisting_request_result = projection.execute(unexisting_request);
+    assert!(unexisting_request_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_add_response_command__unexisting_request_result",
+      unexisting_request_result.unwrap_err()
+    );
+
+    let unassignable_response: EndpointCommand = serde_json::from_value(json!(
+      {"AddResponse": {"responseId": "response_1", "requestId": "request_1", "httpStatusCode": 404}}
+    ))
+    .unwrap();
+    let unassignable_response_result = projection.execute(unassignable_response);
+    assert!(unassignable_response_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_add_response_command__unassignable_response_result",
+      unassignable_response_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+  }
+
+  #[test]
+  pub fn can_handle_set_response_content_type_command() {
+    let initial_events: Vec<EndpointEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "POST"}},
+      {"RequestBodySet": {"requestId": "request_1", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "shape_1", "isRemoved": false }}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_1", "pathId": "path_1", "httpMethod": "POST", "httpStatusCode": 200}},
+      {"ResponseBodySet": {"responseId": "response_1", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "shape_2", "isRemoved": false }}}
+    ]))
+    .expect("initial events should be valid endpoint events");
+
+    let mut projection = EndpointProjection::from(initial_events);
+
+    let valid_command: EndpointCommand = serde_json::from_value(json!(
+      {"SetResponseContentType": {"responseId": "response_1", "httpContentType": "application/xml"}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!(
+      "can_handle_set_response_content_type_command__new_events",
+      new_events
+    );
+
+    let unexisting_response: EndpointCommand = serde_json::from_value(json!(
+      {"SetResponseContentType": {"responseId": "not-a-response", "httpContentType": "application/xml"}}
+    ))
+    .unwrap();
+    let unexisting_response_result = projection.execute(unexisting_response);
+    assert!(unexisting_response_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_set_response_content_type_command__unexisting_response_result",
+      unexisting_response_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+  }
+
+  #[test]
+  pub fn can_handle_set_response_status_code_command() {
+    let initial_events: Vec<EndpointEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "POST"}},
+      {"RequestBodySet": {"requestId": "request_1", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "shape_1", "isRemoved": false }}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_1", "pathId": "path_1", "httpMethod": "POST", "httpStatusCode": 200}},
+      {"ResponseBodySet": {"responseId": "response_1", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "shape_2", "isRemoved": false }}}
+    ]))
+    .expect("initial events should be valid endpoint events");
+
+    let mut projection = EndpointProjection::from(initial_events);
+
+    let valid_command: EndpointCommand = serde_json::from_value(json!(
+      {"SetResponseStatusCode": {"responseId": "response_1", "httpStatusCode": 201}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!(
+      "can_handle_set_response_status_code_command__new_events",
+      new_events
+    );
+
+    let unexisting_response: EndpointCommand = serde_json::from_value(json!(
+      {"SetResponseStatusCode": {"responseId": "not-a-response", "httpStatusCode": 201}}
+    ))
+    .unwrap();
+    let unexisting_response_result = projection.execute(unexisting_response);
+    assert!(unexisting_response_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_set_response_status_code_command__unexisting_response_result",
+      unexisting_response_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+  }
+
+  #[test]
+  pub fn can_handle_unset_response_body_shape_command() {
+    let initial_events: Vec<EndpointEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "POST"}},
+      {"RequestBodySet": {"requestId": "request_1", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "shape_1", "isRemoved": false }}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_1", "pathId": "path_1", "httpMethod": "POST", "httpStatusCode": 200}},
+      {"ResponseBodySet": {"responseId": "response_1", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "shape_2", "isRemoved": false }}}
+    ]))
+    .expect("initial events should be valid endpoint events");
+
+    let mut projection = EndpointProjection::from(initial_events);
+
+    let valid_command: EndpointCommand = serde_json::from_value(json!(
+      {"UnsetResponseBodyShape": {"responseId": "response_1"}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!(
+      "can_handle_unset_response_body_shape_command__new_events",
+      new_events
+    );
+
+    let unexisting_response: EndpointCommand = serde_json::from_value(json!(
+      {"UnsetResponseBodyShape": {"responseId": "not-a-response"}}
+    ))
+    .unwrap();
+    let unexisting_response_result = projection.execute(unexisting_response);
+    assert!(unexisting_response_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_unset_response_body_shape_command__unexisting_response_result",
+      unexisting_response_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+  }
+
+  #[test]
+  pub fn can_handle_remove_response_command() {
+    let initial_events: Vec<EndpointEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "POST"}},
+      {"RequestBodySet": {"requestId": "request_1", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "shape_1", "isRemoved": false }}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_1", "pathId": "path_1", "httpMethod": "POST", "httpStatusCode": 200}},
+      {"ResponseBodySet": {"responseId": "response_1", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "shape_2", "isRemoved": false }}}
+    ]))
+    .expect("initial events should be valid endpoint events");
+
+    let mut projection = EndpointProjection::from(initial_events);
+
+    let valid_command: EndpointCommand = serde_json::from_value(json!(
+      {"RemoveResponse": {"responseId": "response_1"}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!("can_handle_remove_response_command__new_events", new_events);
+
+    let unexisting_response: EndpointCommand = serde_json::from_value(json!(
+      {"RemoveResponse": {"responseId": "not-a-response"}}
+    ))
+    .unwrap();
+    let unexisting_response_result = projection.execute(unexisting_response);
+    assert!(unexisting_response_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_remove_response_command__unexisting_response_result",
+      unexisting_response_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+    assert!(projection
+      .get_response_node_index(&String::from("response_1"))
+      .is_none());
+  }
+
+  #[test]
+  pub fn can_handle_add_header_parameter_command() {
+    let initial_events: Vec<EndpointEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "POST"}},
+      {"RequestParameterAddedByPathAndMethod": {"parameterId": "query_1", "pathId": "path_1", "httpMethod": "POST", "parameterLocation": "query", "name": "limit"}}
+    ]))
+    .expect("initial events should be valid endpoint events");
+
+    let mut projection = EndpointProjection::from(initial_events);
+
+    let valid_command: EndpointCommand = serde_json::from_value(json!(
+      {"AddHeaderParameter": {"parameterId": "header_1", "requestId": "request_1", "name": "X-Request-Id"}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!(
+      "can_handle_add_header_parameter_command__new_events",
+      new_events
+    );
+
+    let unexisting_request: EndpointCommand = serde_json::from_value(json!(
+      {"AddHeaderParameter": {"parameterId": "header_2", "requestId": "not-a-request", "name": "X-Request-Id"}}
+    ))
+    .unwrap();
+    let unexisting_request_result = projection.execute(unexisting_request);
+    assert!(unexisting_request_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_add_header_parameter_command__unexisting_request_result",
+      unexisting_request_result.unwrap_err()
+    );
+
+    let unassignable_parameter_id: EndpointCommand = serde_json::from_value(json!(
+      {"AddHeaderParameter": {"parameterId": "query_1", "requestId": "request_1", "name": "X-Request-Id"}}
+    ))
+    .unwrap();
+    let unassignable_parameter_id_result = projection.execute(unassignable_parameter_id);
+    assert!(unassignable_parameter_id_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_add_header_parameter_command__unassignable_parameter_id_result",
+      unassignable_parameter_id_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+  }
+
+  #[test]
+  pub fn can_handle_set_header_parameter_shape_command() {
+    let initial_events: Vec<EndpointEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "POST"}},
+      {"RequestParameterAddedByPathAndMethod": {"parameterId": "header_1", "pathId": "path_1", "httpMethod": "POST", "parameterLocation": "header", "name": "x-request-id"}},
+      {"RequestParameterAddedByPathAndMethod": {"parameterId": "query_1", "pathId": "path_1", "httpMethod": "POST", "parameterLocation": "query", "name": "limit"}}
+    ]))
+    .expect("initial events should be valid endpoint events");
+
+    let mut projection = EndpointProjection::from(initial_events);
+
+    let valid_command: EndpointCommand = serde_json::from_value(json!(
+      {"SetHeaderParameterShape": {"parameterId": "header_1", "parameterDescriptor": { "shapeId": "shape_1", "isRemoved": false }}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!(
+      "can_handle_set_header_parameter_shape_command__new_events",
+      new_events
+    );
+
+    let query_parameter: EndpointCommand = serde_json::from_value(json!(
+      {"SetHeaderParameterShape": {"parameterId": "query_1", "parameterDescriptor": { "shapeId": "shape_1", "isRemoved": false }}}
+    ))
+    .unwrap();
+    let query_parameter_result = projection.execute(query_parameter);
+    assert!(query_parameter_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_set_header_parameter_shape_command__query_parameter_result",
+      query_parameter_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+    assert_eq!(
+      projection.request_parameters["header_1"].shape_id,
+      Some(String::from("shape_1"))
+    );
+
+    let remove_command: EndpointCommand = serde_json::from_value(json!(
+      {"RemoveHeaderParameter": {"parameterId": "header_1"}}
+    ))
+    .unwrap();
+    let remove_events = projection
+      .execute(remove_command)
+      .expect("removing an existing header parameter should yield new events");
+    for event in remove_events {
+      projection.apply(event);
+    }
+    assert!(!projection.request_parameters.contains_key("header_1"));
+  }
 }
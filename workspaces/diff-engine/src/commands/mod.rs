@@ -268,6 +268,20 @@ impl AggregateCommand<SpecProjection> for SpecCommand {
         .map(|rfc_event| SpecEvent::from(rfc_event))
         .collect::<Vec<_>>(),
 
+      SpecCommand::ShapeCommand(ShapeCommand::RemoveShape(command)) => {
+        spec_projection
+          .endpoint()
+          .execute(ShapeCommand::RemoveShape(command.clone()))?; // validate shape isn't in use
+        let shape_events = spec_projection
+          .shape()
+          .execute(ShapeCommand::RemoveShape(command))?;
+
+        shape_events
+          .into_iter()
+          .map(SpecEvent::from)
+          .collect::<Vec<_>>()
+      }
+
       SpecCommand::ShapeCommand(shape_command) => spec_projection
         .shape()
         .execute(shape_command)?
@@ -473,6 +487,45 @@ mod test {
     }
   }
 
+  #[test]
+  pub fn can_handle_remove_shape_command() {
+    let initial_events: Vec<SpecEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"RequestAdded": {"requestId": "request_1", "pathId": "path_1", "httpMethod": "POST"}},
+      {"ShapeAdded":{"shapeId":"string_shape_1","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":"",}},
+      {"ShapeAdded":{"shapeId":"string_shape_2","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":"",}},
+      {"RequestBodySet": {"requestId": "request_1", "bodyDescriptor": { "httpContentType": "application/json", "shapeId": "string_shape_1", "isRemoved": false }}}
+    ]))
+    .expect("initial events should be valid spec events");
+
+    let mut projection = SpecProjection::from(initial_events);
+
+    let valid_command: SpecCommand = serde_json::from_value(json!(
+      {"RemoveShape": {"shapeId": "string_shape_2"}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+
+    let body_shape: SpecCommand = serde_json::from_value(json!(
+      {"RemoveShape": {"shapeId": "string_shape_1"}}
+    ))
+    .unwrap();
+    let body_shape_result = projection.execute(body_shape);
+    assert!(body_shape_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_remove_shape_command__body_shape_result",
+      body_shape_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+  }
+
   #[test]
   pub fn spec_handler_provides_event_context_from_capture_context() {
     let initial_events: Vec<SpecEvent> = serde_json::from_value(json!([
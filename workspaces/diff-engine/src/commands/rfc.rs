@@ -57,14 +57,14 @@ pub struct AddContribution {
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SetAPIName {
-  new_name: String,
+  pub new_name: String,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SetGitState {
-  commit_id: String,
-  branch_name: String,
+  pub commit_id: String,
+  pub branch_name: String,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
@@ -135,6 +135,24 @@ impl AggregateCommand<HistoryProjection> for RfcCommand {
         vec![RfcEvent::from(rfc_events::ContributionAdded::from(command))]
       }
 
+      RfcCommand::SetAPIName(command) => {
+        validation.require(
+          !command.new_name.trim().is_empty(),
+          "name can not be empty to set api name",
+        )?;
+
+        vec![RfcEvent::from(rfc_events::APINamed::from(command))]
+      }
+
+      RfcCommand::SetGitState(command) => {
+        validation.require(
+          !command.commit_id.is_empty(),
+          "commit id can not be empty to set git state",
+        )?;
+
+        vec![RfcEvent::from(rfc_events::GitStateSet::from(command))]
+      }
+
       _ => Err(SpecCommandError::Unimplemented(
         "rfc command not implemented for rfc projection",
         SpecCommand::RfcCommand(self),
@@ -145,7 +163,7 @@ impl AggregateCommand<HistoryProjection> for RfcCommand {
   }
 }
 
-struct CommandValidationQueries<'a> {
+pub(crate) struct CommandValidationQueries<'a> {
   command_description: String,
   history_projection: &'a HistoryProjection,
 }
@@ -186,6 +204,15 @@ impl<'a> From<(&'a HistoryProjection, &RfcCommand)> for CommandValidationQueries
   }
 }
 
+impl<'a> From<(&'a HistoryProjection, &RfcEvent)> for CommandValidationQueries<'a> {
+  fn from((history_projection, rfc_event): (&'a HistoryProjection, &RfcEvent)) -> Self {
+    Self {
+      command_description: format!("{:?}", rfc_event),
+      history_projection,
+    }
+  }
+}
+
 #[cfg(test)]
 mod test {
   use super::*;
@@ -293,4 +320,72 @@ mod test {
       new_events
     );
   }
+
+  #[test]
+  pub fn can_handle_set_api_name_command() {
+    let initial_events: Vec<SpecEvent> =
+      serde_json::from_value(json!([])).expect("initial events should be valid spec events");
+
+    let mut projection = HistoryProjection::from(initial_events);
+
+    let command: RfcCommand = serde_json::from_value(json!(
+      {"SetAPIName": {"newName": "Todo API"}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(command)
+      .expect("new api name should be applicable to projection");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!("can_handle_set_api_name_command__new_events", new_events);
+
+    let empty_name: RfcCommand = serde_json::from_value(json!(
+      {"SetAPIName": {"newName": "  "}}
+    ))
+    .unwrap();
+    let empty_name_result = projection.execute(empty_name);
+    assert!(empty_name_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_set_api_name_command__empty_name_result",
+      empty_name_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event);
+    }
+  }
+
+  #[test]
+  pub fn can_handle_set_git_state_command() {
+    let initial_events: Vec<SpecEvent> =
+      serde_json::from_value(json!([])).expect("initial events should be valid spec events");
+
+    let mut projection = HistoryProjection::from(initial_events);
+
+    let command: RfcCommand = serde_json::from_value(json!(
+      {"SetGitState": {"commitId": "0a1b2c3d", "branchName": "main"}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(command)
+      .expect("new git state should be applicable to projection");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!("can_handle_set_git_state_command__new_events", new_events);
+
+    let empty_commit_id: RfcCommand = serde_json::from_value(json!(
+      {"SetGitState": {"commitId": "", "branchName": "main"}}
+    ))
+    .unwrap();
+    let empty_commit_id_result = projection.execute(empty_commit_id);
+    assert!(empty_commit_id_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_set_git_state_command__empty_commit_id_result",
+      empty_commit_id_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event);
+    }
+  }
 }
@@ -1,12 +1,15 @@
 use super::{EndpointCommand, SpecCommand, SpecCommandError};
 use crate::{events::shape as shape_events, shapehash::ShapeDescriptor, state::shape::ShapeProvider};
-use crate::events::ShapeEvent;
+use crate::events::{EndpointEvent, ShapeEvent};
+use crate::projections::shape::{Edge, Node};
 use crate::projections::ShapeProjection;
 use crate::state::shape::{
   FieldId, FieldShapeDescriptor, FieldShapeFromShape, ParameterShapeDescriptor, ProviderDescriptor, ProviderInShape, ShapeId, ShapeKind,
-  ShapeParameterId, ShapeParametersDescriptor,
+  ShapeParameterId, ShapeParametersDescriptor, StringFormat,
 };
 use cqrs_core::AggregateCommand;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
@@ -27,6 +30,10 @@ pub enum ShapeCommand {
   RenameField(RenameField),
   RemoveField(RemoveField),
   SetFieldShape(SetFieldShape),
+
+  // Formats
+  SetStringFormat(SetStringFormat),
+  SetStringEnum(SetStringEnum),
 }
 
 impl ShapeCommand {
@@ -34,6 +41,10 @@ impl ShapeCommand {
     Self::AddShape(AddShape::new(shape_id, String::from(shape_kind.get_descriptor().base_shape_id), name))
   }
 
+  pub fn remove_shape(shape_id: ShapeId) -> Self {
+    Self::RemoveShape(RemoveShape { shape_id })
+  }
+
   pub fn add_shape_parameter(shape_parameter_id: ShapeParameterId, shape_id: ShapeId, name: String) -> Self {
     Self::AddShapeParameter(AddShapeParameter { shape_parameter_id, shape_id, name })
   }
@@ -59,6 +70,14 @@ impl ShapeCommand {
       })
     })
   }
+
+  pub fn set_string_format(shape_id: ShapeId, format: StringFormat) -> Self {
+    Self::SetStringFormat(SetStringFormat { shape_id, format: Some(format) })
+  }
+
+  pub fn set_string_enum(shape_id: ShapeId, values: Vec<String>) -> Self {
+    Self::SetStringEnum(SetStringEnum { shape_id, values: Some(values) })
+  }
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
@@ -89,14 +108,14 @@ pub struct SetBaseShape {
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RenameShape {
-  shape_id: ShapeId,
-  name: String,
+  pub shape_id: ShapeId,
+  pub name: String,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RemoveShape {
-  shape_id: ShapeId,
+  pub shape_id: ShapeId,
 }
 
 // Shape parameters
@@ -113,14 +132,14 @@ pub struct AddShapeParameter {
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RemoveShapeParameter {
-  shape_parameter_id: ShapeParameterId,
+  pub shape_parameter_id: ShapeParameterId,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RenameShapeParameter {
-  shape_parameter_id: ShapeParameterId,
-  name: String,
+  pub shape_parameter_id: ShapeParameterId,
+  pub name: String,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
@@ -144,14 +163,14 @@ pub struct AddField {
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RenameField {
-  field_id: FieldId,
-  name: String,
+  pub field_id: FieldId,
+  pub name: String,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RemoveField {
-  field_id: FieldId,
+  pub field_id: FieldId,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
@@ -160,6 +179,23 @@ pub struct SetFieldShape {
   pub shape_descriptor: FieldShapeDescriptor,
 }
 
+// Formats
+// -------
+
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetStringFormat {
+  pub shape_id: ShapeId,
+  pub format: Option<StringFormat>,
+}
+
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetStringEnum {
+  pub shape_id: ShapeId,
+  pub values: Option<Vec<String>>,
+}
+
 // Command handling
 // ----------------
 
@@ -250,6 +286,36 @@ impl AggregateCommand<ShapeProjection> for ShapeCommand {
         vec![ShapeEvent::from(shape_events::BaseShapeSet::from(command))]
       }
 
+      ShapeCommand::RenameShape(command) => {
+        validation.require(
+          validation.shape_id_exists(&command.shape_id),
+          "shape must exist to rename shape",
+        )?;
+        validation.require(
+          !validation.base_shape_id_exists(&command.shape_id),
+          "shape must not be base shape to rename shape",
+        )?;
+
+        vec![ShapeEvent::from(shape_events::ShapeRenamed::from(command))]
+      }
+
+      ShapeCommand::RemoveShape(command) => {
+        validation.require(
+          validation.shape_id_exists(&command.shape_id),
+          "shape must exist to remove shape",
+        )?;
+        validation.require(
+          !validation.base_shape_id_exists(&command.shape_id),
+          "shape must not be base shape to remove shape",
+        )?;
+        validation.require(
+          !validation.shape_is_referenced(&command.shape_id),
+          "shape must not be used by fields or other shapes to remove shape",
+        )?;
+
+        vec![ShapeEvent::from(shape_events::ShapeRemoved::from(command))]
+      }
+
       // Fields
       // ------
       ShapeCommand::AddField(command) => {
@@ -306,6 +372,24 @@ impl AggregateCommand<ShapeProjection> for ShapeCommand {
         }
       },
 
+      ShapeCommand::RenameField(command) => {
+        validation.require(
+          validation.field_id_exists(&command.field_id),
+          "field must exist to rename field",
+        )?;
+
+        vec![ShapeEvent::from(shape_events::FieldRenamed::from(command))]
+      }
+
+      ShapeCommand::RemoveField(command) => {
+        validation.require(
+          validation.field_id_exists(&command.field_id),
+          "field must exist to remove field",
+        )?;
+
+        vec![ShapeEvent::from(shape_events::FieldRemoved::from(command))]
+      }
+
       // Parameters
       // ----------
       ShapeCommand::AddShapeParameter(command) => {
@@ -323,6 +407,28 @@ impl AggregateCommand<ShapeProjection> for ShapeCommand {
         ))]
       }
 
+      ShapeCommand::RenameShapeParameter(command) => {
+        validation.require(
+          validation.shape_parameter_id_exists(&command.shape_parameter_id),
+          "shape parameter must exist to rename shape parameter",
+        )?;
+
+        vec![ShapeEvent::from(shape_events::ShapeParameterRenamed::from(
+          command,
+        ))]
+      }
+
+      ShapeCommand::RemoveShapeParameter(command) => {
+        validation.require(
+          validation.shape_parameter_id_exists(&command.shape_parameter_id),
+          "shape parameter must exist to remove shape parameter",
+        )?;
+
+        vec![ShapeEvent::from(shape_events::ShapeParameterRemoved::from(
+          command,
+        ))]
+      }
+
       ShapeCommand::SetParameterShape(command) => match &command.shape_descriptor {
         ParameterShapeDescriptor::ProviderInShape(descriptor) => {
           validation.require(
@@ -359,17 +465,46 @@ impl AggregateCommand<ShapeProjection> for ShapeCommand {
         }
       },
 
-      _ => Err(SpecCommandError::Unimplemented(
-        "shape command not implemented for shape projection",
-        SpecCommand::ShapeCommand(self),
-      ))?,
+      // Formats
+      // -------
+      ShapeCommand::SetStringFormat(command) => {
+        validation.require(
+          validation.shape_id_exists(&command.shape_id),
+          "shape must exist to set string format",
+        )?;
+        validation.require(
+          validation.shape_is_string(&command.shape_id),
+          "shape must be a string to set string format",
+        )?;
+
+        vec![ShapeEvent::from(shape_events::StringFormatSet::from(
+          command,
+        ))]
+      }
+
+      ShapeCommand::SetStringEnum(command) => {
+        validation.require(
+          validation.shape_id_exists(&command.shape_id),
+          "shape must exist to set string enum",
+        )?;
+        validation.require(
+          validation.shape_is_string(&command.shape_id),
+          "shape must be a string to set string enum",
+        )?;
+        validation.require(
+          !matches!(&command.values, Some(values) if values.is_empty()),
+          "enum must have at least one value to set string enum",
+        )?;
+
+        vec![ShapeEvent::from(shape_events::StringEnumSet::from(command))]
+      }
     };
 
     Ok(events)
   }
 }
 
-struct CommandValidationQueries<'a> {
+pub(crate) struct CommandValidationQueries<'a> {
   command_description: String,
   shape_projection: &'a ShapeProjection,
 }
@@ -386,35 +521,37 @@ impl<'a> CommandValidationQueries<'a> {
     }
   }
 
-  fn shape_id_exists(&self, shape_id: &ShapeId) -> bool {
+  // removed shapes are kept around for what still refers to them, but don't exist to commands
+  pub fn shape_id_exists(&self, shape_id: &ShapeId) -> bool {
     self
       .shape_projection
       .get_shape_node_index(shape_id)
       .is_some()
+      && !self.shape_projection.is_removed_shape(shape_id)
   }
 
-  fn base_shape_id_exists(&self, shape_id: &ShapeId) -> bool {
+  pub fn base_shape_id_exists(&self, shape_id: &ShapeId) -> bool {
     self
       .shape_projection
       .get_core_shape_node_index(shape_id)
       .is_some()
   }
 
-  fn shape_parameter_id_exists(&self, shape_param_id: &ShapeParameterId) -> bool {
+  pub fn shape_parameter_id_exists(&self, shape_param_id: &ShapeParameterId) -> bool {
     self
       .shape_projection
       .get_shape_parameter_node_index(shape_param_id)
       .is_some()
   }
 
-  fn field_id_exists(&self, field_id: &FieldId) -> bool {
+  pub fn field_id_exists(&self, field_id: &FieldId) -> bool {
     self
       .shape_projection
       .get_field_node_index(field_id)
       .is_some()
   }
 
-  fn shape_can_have_fields(&self, shape_id: &ShapeId) -> bool {
+  pub fn shape_can_have_fields(&self, shape_id: &ShapeId) -> bool {
     let shape_node_index = self.shape_projection.get_shape_node_index(shape_id);
 
     if let None = shape_node_index {
@@ -427,6 +564,55 @@ impl<'a> CommandValidationQueries<'a> {
         .any(|shape_kind| matches!(shape_kind, ShapeKind::ObjectKind))
     }
   }
+
+  pub fn shape_is_string(&self, shape_id: &ShapeId) -> bool {
+    match self.shape_projection.get_shape_node_index(shape_id) {
+      Some(shape_node_index) => self
+        .shape_projection
+        .get_core_shape_kinds(shape_node_index)
+        .expect("shape node exists as we just resolved it, so we get iterator")
+        .any(|shape_kind| matches!(shape_kind, ShapeKind::StringKind)),
+      None => false,
+    }
+  }
+
+  // whether fields, shape parameters or other shapes (through their base shape) refer to the shape.
+  // The shape's own fields and parameters don't count, as they go when the shape goes, and neither
+  // do those of removed shapes.
+  pub fn shape_is_referenced(&self, shape_id: &ShapeId) -> bool {
+    let graph = &self.shape_projection.graph;
+    let shape_node_index = match self.shape_projection.get_shape_node_index(shape_id) {
+      Some(shape_node_index) => *shape_node_index,
+      None => return false,
+    };
+    let is_other_shape = |node_index: NodeIndex| match graph.node_weight(node_index) {
+      Some(Node::Shape(shape_node)) => {
+        shape_node.shape_id != *shape_id
+          && !self.shape_projection.is_removed_shape(&shape_node.shape_id)
+      }
+      _ => false,
+    };
+
+    let referenced_by_field = graph
+      .edges_directed(shape_node_index, petgraph::Direction::Outgoing)
+      .filter(|edge| matches!(edge.weight(), Edge::BelongsTo))
+      .any(|edge| {
+        graph
+          .edges_directed(edge.target(), petgraph::Direction::Outgoing)
+          .filter(|field_edge| matches!(field_edge.weight(), Edge::IsFieldOf))
+          .any(|field_edge| is_other_shape(field_edge.target()))
+      });
+    let referenced_by_shape = graph
+      .edges_directed(shape_node_index, petgraph::Direction::Incoming)
+      .filter(|edge| matches!(edge.weight(), Edge::IsDescendantOf))
+      .any(|edge| is_other_shape(edge.source()));
+    let referenced_by_binding = graph.edge_references().any(|edge| {
+      matches!(edge.weight(), Edge::HasBinding(binding) if binding.shape_id == *shape_id)
+        && is_other_shape(edge.source())
+    });
+
+    referenced_by_field || referenced_by_shape || referenced_by_binding
+  }
 }
 
 impl<'a> From<(&'a ShapeProjection, &EndpointCommand)> for CommandValidationQueries<'a> {
@@ -447,6 +633,24 @@ impl<'a> From<(&'a ShapeProjection, &ShapeCommand)> for CommandValidationQueries
   }
 }
 
+impl<'a> From<(&'a ShapeProjection, &EndpointEvent)> for CommandValidationQueries<'a> {
+  fn from((shape_projection, endpoint_event): (&'a ShapeProjection, &EndpointEvent)) -> Self {
+    Self {
+      command_description: format!("{:?}", endpoint_event),
+      shape_projection,
+    }
+  }
+}
+
+impl<'a> From<(&'a ShapeProjection, &ShapeEvent)> for CommandValidationQueries<'a> {
+  fn from((shape_projection, shape_event): (&'a ShapeProjection, &ShapeEvent)) -> Self {
+    Self {
+      command_description: format!("{:?}", shape_event),
+      shape_projection,
+    }
+  }
+}
+
 #[cfg(test)]
 mod test {
   use super::*;
@@ -792,4 +996,406 @@ mod test {
       projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
     }
   }
+
+  #[test]
+  pub fn can_handle_set_string_format_command() {
+    let initial_events: Vec<ShapeEvent> = serde_json::from_value(json!([
+      {"ShapeAdded":{"shapeId":"string_shape_1","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeAdded":{"shapeId":"number_shape_1","baseShapeId":"$number","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}}
+    ]))
+    .expect("initial events should be valid shape events");
+
+    let mut projection = ShapeProjection::from(initial_events);
+
+    let valid_command: ShapeCommand = serde_json::from_value(json!(
+      {"SetStringFormat":{"shapeId":"string_shape_1","format":"date-time"}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!(
+      "can_handle_set_string_format_command__new_events",
+      new_events
+    );
+
+    let unexisting_shape: ShapeCommand = serde_json::from_value(json!(
+      {"SetStringFormat":{"shapeId":"not-a-shape","format":"date-time"}}
+    ))
+    .unwrap();
+    let unexisting_shape_result = projection.execute(unexisting_shape);
+    assert!(unexisting_shape_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_set_string_format_command__unexisting_shape_result",
+      unexisting_shape_result.unwrap_err()
+    );
+
+    let non_string_shape: ShapeCommand = serde_json::from_value(json!(
+      {"SetStringFormat":{"shapeId":"number_shape_1","format":"uuid"}}
+    ))
+    .unwrap();
+    let non_string_shape_result = projection.execute(non_string_shape);
+    assert!(non_string_shape_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_set_string_format_command__non_string_shape_result",
+      non_string_shape_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+  }
+
+  #[test]
+  pub fn can_handle_set_string_enum_command() {
+    let initial_events: Vec<ShapeEvent> = serde_json::from_value(json!([
+      {"ShapeAdded":{"shapeId":"string_shape_1","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeAdded":{"shapeId":"number_shape_1","baseShapeId":"$number","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}}
+    ]))
+    .expect("initial events should be valid shape events");
+
+    let mut projection = ShapeProjection::from(initial_events);
+
+    let valid_command: ShapeCommand = serde_json::from_value(json!(
+      {"SetStringEnum":{"shapeId":"string_shape_1","values":["active","suspended"]}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!("can_handle_set_string_enum_command__new_events", new_events);
+
+    let non_string_shape: ShapeCommand = serde_json::from_value(json!(
+      {"SetStringEnum":{"shapeId":"number_shape_1","values":["active"]}}
+    ))
+    .unwrap();
+    let non_string_shape_result = projection.execute(non_string_shape);
+    assert!(non_string_shape_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_set_string_enum_command__non_string_shape_result",
+      non_string_shape_result.unwrap_err()
+    );
+
+    let no_values: ShapeCommand = serde_json::from_value(json!(
+      {"SetStringEnum":{"shapeId":"string_shape_1","values":[]}}
+    ))
+    .unwrap();
+    let no_values_result = projection.execute(no_values);
+    assert!(no_values_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_set_string_enum_command__no_values_result",
+      no_values_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+  }
+
+  #[test]
+  pub fn can_handle_rename_shape_command() {
+    let initial_events: Vec<ShapeEvent> = serde_json::from_value(json!([
+      {"ShapeAdded":{"shapeId":"string_shape_1","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeAdded":{"shapeId":"object_shape_1","baseShapeId":"$object","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":"Todo"}},
+      {"FieldAdded":{"fieldId":"field_1","shapeId":"object_shape_1","name":"title","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_1","shapeId":"string_shape_1"}}}},
+      {"ShapeAdded":{"shapeId":"one_off_shape_1","baseShapeId":"$oneOf","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeParameterAdded": {"shapeParameterId": "shape_parameter_1", "shapeId": "one_off_shape_1","name": "","shapeDescriptor": {"ProviderInShape": {"shapeId": "one_off_shape_1","providerDescriptor": {"NoProvider": {}},"consumingParameterId": "shape_parameter_1"}}}},
+    ]))
+    .expect("initial events should be valid shape events");
+
+    let mut projection = ShapeProjection::from(initial_events);
+
+    let valid_command: ShapeCommand = serde_json::from_value(json!(
+      {"RenameShape":{"shapeId":"object_shape_1","name":"TodoItem"}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!("can_handle_rename_shape_command__new_events", new_events);
+
+    let unexisting_shape: ShapeCommand = serde_json::from_value(json!(
+      {"RenameShape":{"shapeId":"not-a-shape","name":"TodoItem"}}
+    ))
+    .unwrap();
+    let unexisting_shape_result = projection.execute(unexisting_shape);
+    assert!(unexisting_shape_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_rename_shape_command__unexisting_shape_result",
+      unexisting_shape_result.unwrap_err()
+    );
+
+    let base_shape: ShapeCommand = serde_json::from_value(json!(
+      {"RenameShape":{"shapeId":"$string","name":"Text"}}
+    ))
+    .unwrap();
+    let base_shape_result = projection.execute(base_shape);
+    assert!(base_shape_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_rename_shape_command__base_shape_result",
+      base_shape_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+    assert_eq!(
+      projection.shape_names.get("object_shape_1"),
+      Some(&String::from("TodoItem"))
+    );
+  }
+
+  #[test]
+  pub fn can_handle_remove_shape_command() {
+    let initial_events: Vec<ShapeEvent> = serde_json::from_value(json!([
+      {"ShapeAdded":{"shapeId":"string_shape_1","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeAdded":{"shapeId":"object_shape_1","baseShapeId":"$object","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":"Todo"}},
+      {"FieldAdded":{"fieldId":"field_1","shapeId":"object_shape_1","name":"title","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_1","shapeId":"string_shape_1"}}}},
+      {"ShapeAdded":{"shapeId":"one_off_shape_1","baseShapeId":"$oneOf","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeParameterAdded": {"shapeParameterId": "shape_parameter_1", "shapeId": "one_off_shape_1","name": "","shapeDescriptor": {"ProviderInShape": {"shapeId": "one_off_shape_1","providerDescriptor": {"NoProvider": {}},"consumingParameterId": "shape_parameter_1"}}}},
+    ]))
+    .expect("initial events should be valid shape events");
+
+    let mut projection = ShapeProjection::from(initial_events);
+
+    let field_shape: ShapeCommand = serde_json::from_value(json!(
+      {"RemoveShape":{"shapeId":"string_shape_1"}}
+    ))
+    .unwrap();
+    let field_shape_result = projection.execute(field_shape.clone());
+    assert!(field_shape_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_remove_shape_command__field_shape_result",
+      field_shape_result.unwrap_err()
+    );
+
+    let valid_command: ShapeCommand = serde_json::from_value(json!(
+      {"RemoveShape":{"shapeId":"object_shape_1"}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!("can_handle_remove_shape_command__new_events", new_events);
+
+    let unexisting_shape: ShapeCommand = serde_json::from_value(json!(
+      {"RemoveShape":{"shapeId":"not-a-shape"}}
+    ))
+    .unwrap();
+    let unexisting_shape_result = projection.execute(unexisting_shape);
+    assert!(unexisting_shape_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_remove_shape_command__unexisting_shape_result",
+      unexisting_shape_result.unwrap_err()
+    );
+
+    let base_shape: ShapeCommand = serde_json::from_value(json!(
+      {"RemoveShape":{"shapeId":"$string"}}
+    ))
+    .unwrap();
+    let base_shape_result = projection.execute(base_shape);
+    assert!(base_shape_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_remove_shape_command__base_shape_result",
+      base_shape_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+    assert!(projection.is_removed_shape(&String::from("object_shape_1")));
+
+    let removed_shape: ShapeCommand = serde_json::from_value(json!(
+      {"RemoveShape":{"shapeId":"object_shape_1"}}
+    ))
+    .unwrap();
+    assert!(
+      projection.execute(removed_shape).is_err(),
+      "removed shapes can't be removed again"
+    );
+    assert!(
+      projection.execute(field_shape).is_ok(),
+      "fields of removed shapes don't keep their shapes in use"
+    );
+  }
+
+  #[test]
+  pub fn can_handle_rename_shape_parameter_command() {
+    let initial_events: Vec<ShapeEvent> = serde_json::from_value(json!([
+      {"ShapeAdded":{"shapeId":"string_shape_1","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeAdded":{"shapeId":"object_shape_1","baseShapeId":"$object","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":"Todo"}},
+      {"FieldAdded":{"fieldId":"field_1","shapeId":"object_shape_1","name":"title","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_1","shapeId":"string_shape_1"}}}},
+      {"ShapeAdded":{"shapeId":"one_off_shape_1","baseShapeId":"$oneOf","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeParameterAdded": {"shapeParameterId": "shape_parameter_1", "shapeId": "one_off_shape_1","name": "","shapeDescriptor": {"ProviderInShape": {"shapeId": "one_off_shape_1","providerDescriptor": {"NoProvider": {}},"consumingParameterId": "shape_parameter_1"}}}},
+    ]))
+    .expect("initial events should be valid shape events");
+
+    let mut projection = ShapeProjection::from(initial_events);
+
+    let valid_command: ShapeCommand = serde_json::from_value(json!(
+      {"RenameShapeParameter":{"shapeParameterId":"shape_parameter_1","name":"option"}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!(
+      "can_handle_rename_shape_parameter_command__new_events",
+      new_events
+    );
+
+    let unexisting_shape_parameter: ShapeCommand = serde_json::from_value(json!(
+      {"RenameShapeParameter":{"shapeParameterId":"not-a-shape-parameter","name":"option"}}
+    ))
+    .unwrap();
+    let unexisting_shape_parameter_result = projection.execute(unexisting_shape_parameter);
+    assert!(unexisting_shape_parameter_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_rename_shape_parameter_command__unexisting_shape_parameter_result",
+      unexisting_shape_parameter_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+  }
+
+  #[test]
+  pub fn can_handle_remove_shape_parameter_command() {
+    let initial_events: Vec<ShapeEvent> = serde_json::from_value(json!([
+      {"ShapeAdded":{"shapeId":"string_shape_1","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeAdded":{"shapeId":"object_shape_1","baseShapeId":"$object","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":"Todo"}},
+      {"FieldAdded":{"fieldId":"field_1","shapeId":"object_shape_1","name":"title","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_1","shapeId":"string_shape_1"}}}},
+      {"ShapeAdded":{"shapeId":"one_off_shape_1","baseShapeId":"$oneOf","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeParameterAdded": {"shapeParameterId": "shape_parameter_1", "shapeId": "one_off_shape_1","name": "","shapeDescriptor": {"ProviderInShape": {"shapeId": "one_off_shape_1","providerDescriptor": {"NoProvider": {}},"consumingParameterId": "shape_parameter_1"}}}},
+    ]))
+    .expect("initial events should be valid shape events");
+
+    let mut projection = ShapeProjection::from(initial_events);
+
+    let valid_command: ShapeCommand = serde_json::from_value(json!(
+      {"RemoveShapeParameter":{"shapeParameterId":"shape_parameter_1"}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!(
+      "can_handle_remove_shape_parameter_command__new_events",
+      new_events
+    );
+
+    let unexisting_shape_parameter: ShapeCommand = serde_json::from_value(json!(
+      {"RemoveShapeParameter":{"shapeParameterId":"not-a-shape-parameter"}}
+    ))
+    .unwrap();
+    let unexisting_shape_parameter_result = projection.execute(unexisting_shape_parameter);
+    assert!(unexisting_shape_parameter_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_remove_shape_parameter_command__unexisting_shape_parameter_result",
+      unexisting_shape_parameter_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+    assert!(projection
+      .get_shape_parameter_node_index(&String::from("shape_parameter_1"))
+      .is_none());
+  }
+
+  #[test]
+  pub fn can_handle_rename_field_command() {
+    let initial_events: Vec<ShapeEvent> = serde_json::from_value(json!([
+      {"ShapeAdded":{"shapeId":"string_shape_1","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeAdded":{"shapeId":"object_shape_1","baseShapeId":"$object","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":"Todo"}},
+      {"FieldAdded":{"fieldId":"field_1","shapeId":"object_shape_1","name":"title","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_1","shapeId":"string_shape_1"}}}},
+      {"ShapeAdded":{"shapeId":"one_off_shape_1","baseShapeId":"$oneOf","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeParameterAdded": {"shapeParameterId": "shape_parameter_1", "shapeId": "one_off_shape_1","name": "","shapeDescriptor": {"ProviderInShape": {"shapeId": "one_off_shape_1","providerDescriptor": {"NoProvider": {}},"consumingParameterId": "shape_parameter_1"}}}},
+    ]))
+    .expect("initial events should be valid shape events");
+
+    let mut projection = ShapeProjection::from(initial_events);
+
+    let valid_command: ShapeCommand = serde_json::from_value(json!(
+      {"RenameField":{"fieldId":"field_1","name":"name"}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!("can_handle_rename_field_command__new_events", new_events);
+
+    let unexisting_field: ShapeCommand = serde_json::from_value(json!(
+      {"RenameField":{"fieldId":"not-a-field","name":"name"}}
+    ))
+    .unwrap();
+    let unexisting_field_result = projection.execute(unexisting_field);
+    assert!(unexisting_field_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_rename_field_command__unexisting_field_result",
+      unexisting_field_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+  }
+
+  #[test]
+  pub fn can_handle_remove_field_command() {
+    let initial_events: Vec<ShapeEvent> = serde_json::from_value(json!([
+      {"ShapeAdded":{"shapeId":"string_shape_1","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeAdded":{"shapeId":"object_shape_1","baseShapeId":"$object","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":"Todo"}},
+      {"FieldAdded":{"fieldId":"field_1","shapeId":"object_shape_1","name":"title","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_1","shapeId":"string_shape_1"}}}},
+      {"ShapeAdded":{"shapeId":"one_off_shape_1","baseShapeId":"$oneOf","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeParameterAdded": {"shapeParameterId": "shape_parameter_1", "shapeId": "one_off_shape_1","name": "","shapeDescriptor": {"ProviderInShape": {"shapeId": "one_off_shape_1","providerDescriptor": {"NoProvider": {}},"consumingParameterId": "shape_parameter_1"}}}},
+    ]))
+    .expect("initial events should be valid shape events");
+
+    let mut projection = ShapeProjection::from(initial_events);
+
+    let valid_command: ShapeCommand = serde_json::from_value(json!(
+      {"RemoveField":{"fieldId":"field_1"}}
+    ))
+    .expect("example command should be a valid command");
+
+    let new_events = projection
+      .execute(valid_command)
+      .expect("valid command should yield new events");
+    assert_eq!(new_events.len(), 1);
+    assert_debug_snapshot!("can_handle_remove_field_command__new_events", new_events);
+
+    let unexisting_field: ShapeCommand = serde_json::from_value(json!(
+      {"RemoveField":{"fieldId":"not-a-field"}}
+    ))
+    .unwrap();
+    let unexisting_field_result = projection.execute(unexisting_field);
+    assert!(unexisting_field_result.is_err());
+    assert_debug_snapshot!(
+      "can_handle_remove_field_command__unexisting_field_result",
+      unexisting_field_result.unwrap_err()
+    );
+
+    for event in new_events {
+      projection.apply(event); // verify this doesn't panic goes a long way to verifying the events
+    }
+    assert!(projection
+      .get_field_node_index(&String::from("field_1"))
+      .is_none());
+  }
 }
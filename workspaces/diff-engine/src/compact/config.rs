@@ -0,0 +1,18 @@
+/// Options for compacting the events of a spec
+#[derive(Debug, Clone, Default)]
+pub struct CompactionConfig {
+  batch_commits: bool,
+}
+
+impl CompactionConfig {
+  /// Keep batch commits and the event contexts referring to them, so the history of the spec stays
+  /// intact. Without them only what the spec describes is kept, not how it came to be.
+  pub fn with_batch_commits(mut self) -> Self {
+    self.batch_commits = true;
+    self
+  }
+
+  pub fn keeps_batch_commits(&self) -> bool {
+    self.batch_commits
+  }
+}
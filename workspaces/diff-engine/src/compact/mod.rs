@@ -0,0 +1,706 @@
+use crate::events::{EndpointEvent, RfcEvent, ShapeEvent, SpecEvent, WithEventContext};
+use crate::projections::endpoint::{EndpointProjection, Node as EndpointNode};
+use crate::projections::shape::{Edge as ShapeEdge, Node as ShapeNode, ShapeProjection};
+use crate::projections::SpecProjection;
+use crate::state::shape::{FieldShapeDescriptor, ParameterShapeDescriptor, ProviderDescriptor};
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+
+mod config;
+
+pub use config::CompactionConfig;
+
+/// Compact the events of a spec into an event stream that builds the same endpoints and shapes.
+///
+/// Events overridden by later events about the same thing, like all but the last rename of a field,
+/// are dropped, as are the events of paths, requests, responses, parameters, shapes and fields that
+/// end up removed, unless events that are kept still refer to them. Everything else keeps its ids
+/// and order. As a check, the endpoints and shapes built from the compacted events are compared to
+/// the ones built from the original events, apart from their history.
+pub fn compact<I>(events: I, config: &CompactionConfig) -> Result<Vec<SpecEvent>, CompactionError>
+where
+  I: IntoIterator<Item = SpecEvent>,
+{
+  let events = events.into_iter().collect::<Vec<_>>();
+  let kept_events = EventLiveness::from(&events[..]).kept_events(config);
+
+  let compacted_events = events
+    .iter()
+    .zip(kept_events)
+    .filter(|(_, is_kept)| *is_kept)
+    .map(|(event, _)| {
+      let mut event = event.clone();
+      if !config.keeps_batch_commits() {
+        event.without_event_context();
+      }
+      event
+    })
+    .collect::<Vec<_>>();
+
+  let original_spec = SpecProjection::from(events);
+  let compacted_spec = SpecProjection::from(compacted_events.clone());
+  if !endpoints_match(original_spec.endpoint(), compacted_spec.endpoint()) {
+    return Err(CompactionError::EndpointsDiffer);
+  }
+  if !shapes_match(original_spec.shape(), compacted_spec.shape()) {
+    return Err(CompactionError::ShapesDiffer);
+  }
+
+  Ok(compacted_events)
+}
+
+#[derive(Debug)]
+pub enum CompactionError {
+  EndpointsDiffer,
+  ShapesDiffer,
+}
+
+impl fmt::Display for CompactionError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let msg = match self {
+      CompactionError::EndpointsDiffer => {
+        "compacted events describe different endpoints than the original events"
+      }
+      CompactionError::ShapesDiffer => {
+        "compacted events describe different shapes than the original events"
+      }
+    };
+    write!(f, "CompactionError: {}", msg)
+  }
+}
+
+impl Error for CompactionError {}
+
+// Liveness
+// --------
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum EntityKind {
+  Path,
+  RequestParameter,
+  Request,
+  Response,
+  Shape,
+  ShapeParameter,
+  Field,
+}
+
+type Entity<'a> = (EntityKind, &'a str);
+
+// What an event does to the entity it is about
+enum Subject<'a> {
+  Added {
+    entity: Entity<'a>,
+    // entity it is removed along with
+    parent: Option<Entity<'a>>,
+  },
+  Updated(Entity<'a>),
+  Removed(Entity<'a>),
+}
+
+#[derive(Default)]
+struct EventFacts<'a> {
+  subject: Option<Subject<'a>>,
+  // other entities that have to exist for the event to apply
+  references: Vec<Entity<'a>>,
+  // events with the same key about the same entity update the same thing
+  key: Option<String>,
+  // whether the event replaces what earlier events with the same key did
+  overrides: bool,
+}
+
+impl<'a> EventFacts<'a> {
+  fn added(entity: Entity<'a>, parent: Option<Entity<'a>>) -> Self {
+    Self {
+      subject: Some(Subject::Added { entity, parent }),
+      references: parent.into_iter().collect(),
+      ..Self::default()
+    }
+  }
+
+  fn updated(entity: Entity<'a>) -> Self {
+    Self {
+      subject: Some(Subject::Updated(entity)),
+      ..Self::default()
+    }
+  }
+
+  fn removed(entity: Entity<'a>) -> Self {
+    Self {
+      subject: Some(Subject::Removed(entity)),
+      ..Self::default()
+    }
+  }
+
+  fn referencing(mut self, entity: Entity<'a>) -> Self {
+    self.references.push(entity);
+    self
+  }
+
+  fn setting(mut self, key: impl Into<String>) -> Self {
+    self.key = Some(key.into());
+    self.overrides = true;
+    self
+  }
+
+  fn modifying(mut self, key: impl Into<String>) -> Self {
+    self.key = Some(key.into());
+    self
+  }
+}
+
+impl<'a> From<&'a SpecEvent> for EventFacts<'a> {
+  fn from(event: &'a SpecEvent) -> Self {
+    use EntityKind::*;
+
+    match event {
+      SpecEvent::EndpointEvent(event) => match event {
+        EndpointEvent::PathComponentAdded(e) => {
+          Self::added((Path, &e.path_id), Some((Path, &e.parent_path_id)))
+        }
+        EndpointEvent::PathParameterAdded(e) => {
+          Self::added((Path, &e.path_id), Some((Path, &e.parent_path_id)))
+        }
+        EndpointEvent::PathComponentRenamed(e) => Self::updated((Path, &e.path_id)).setting("name"),
+        EndpointEvent::PathParameterRenamed(e) => Self::updated((Path, &e.path_id)).setting("name"),
+        EndpointEvent::PathParameterShapeSet(e) => Self::updated((Path, &e.path_id))
+          .referencing((Shape, &e.shape_descriptor.shape_id))
+          .setting("shape"),
+        EndpointEvent::PathComponentRemoved(e) => Self::removed((Path, &e.path_id)),
+        EndpointEvent::PathParameterRemoved(e) => Self::removed((Path, &e.path_id)),
+
+        EndpointEvent::RequestParameterAddedByPathAndMethod(e) => Self::added(
+          (RequestParameter, &e.parameter_id),
+          Some((Path, &e.path_id)),
+        ),
+        EndpointEvent::RequestParameterRenamed(e) => {
+          Self::updated((RequestParameter, &e.parameter_id)).setting("name")
+        }
+        EndpointEvent::RequestParameterShapeSet(e) => {
+          Self::updated((RequestParameter, &e.parameter_id))
+            .referencing((Shape, &e.parameter_descriptor.shape_id))
+            .setting("shape")
+        }
+        EndpointEvent::RequestParameterShapeUnset(e) => {
+          Self::updated((RequestParameter, &e.parameter_id)).setting("shape")
+        }
+        EndpointEvent::RequestParameterRemoved(e) => {
+          Self::removed((RequestParameter, &e.parameter_id))
+        }
+
+        EndpointEvent::RequestAdded(e) => {
+          Self::added((Request, &e.request_id), Some((Path, &e.path_id)))
+        }
+        EndpointEvent::RequestContentTypeSet(e) => {
+          Self::updated((Request, &e.request_id)).modifying("body")
+        }
+        EndpointEvent::RequestBodySet(e) => Self::updated((Request, &e.request_id))
+          .referencing((Shape, &e.body_descriptor.shape_id))
+          .setting("body"),
+        EndpointEvent::RequestBodyUnset(e) => {
+          Self::updated((Request, &e.request_id)).setting("body")
+        }
+        EndpointEvent::RequestRemoved(e) => Self::removed((Request, &e.request_id)),
+
+        EndpointEvent::ResponseAddedByPathAndMethod(e) => {
+          Self::added((Response, &e.response_id), Some((Path, &e.path_id)))
+        }
+        EndpointEvent::ResponseStatusCodeSet(e) => {
+          Self::updated((Response, &e.response_id)).setting("status code")
+        }
+        EndpointEvent::ResponseContentTypeSet(e) => {
+          Self::updated((Response, &e.response_id)).modifying("body")
+        }
+        EndpointEvent::ResponseBodySet(e) => Self::updated((Response, &e.response_id))
+          .referencing((Shape, &e.body_descriptor.shape_id))
+          .setting("body"),
+        EndpointEvent::ResponseBodyUnset(e) => {
+          Self::updated((Response, &e.response_id)).setting("body")
+        }
+        EndpointEvent::ResponseRemoved(e) => Self::removed((Response, &e.response_id)),
+      },
+
+      SpecEvent::ShapeEvent(event) => match event {
+        ShapeEvent::ShapeAdded(e) => {
+          Self::added((Shape, &e.shape_id), None).referencing((Shape, &e.base_shape_id))
+        }
+        ShapeEvent::BaseShapeSet(e) => Self::updated((Shape, &e.shape_id))
+          .referencing((Shape, &e.base_shape_id))
+          .setting("base shape"),
+        ShapeEvent::ShapeRenamed(e) => Self::updated((Shape, &e.shape_id)).setting("name"),
+        ShapeEvent::StringFormatSet(e) => Self::updated((Shape, &e.shape_id)).setting("format"),
+        ShapeEvent::StringEnumSet(e) => Self::updated((Shape, &e.shape_id)).setting("enum"),
+        ShapeEvent::ShapeRemoved(e) => Self::removed((Shape, &e.shape_id)),
+
+        ShapeEvent::ShapeParameterAdded(e) => Self::added(
+          (ShapeParameter, &e.shape_parameter_id),
+          Some((Shape, &e.shape_id)),
+        ),
+        ShapeEvent::ShapeParameterShapeSet(e) => match &e.shape_descriptor {
+          ParameterShapeDescriptor::ProviderInShape(descriptor) => {
+            let facts = Self::updated((Shape, &descriptor.shape_id))
+              .referencing((ShapeParameter, &descriptor.consuming_parameter_id))
+              .setting(format!("binding {}", descriptor.consuming_parameter_id));
+            match &descriptor.provider_descriptor {
+              ProviderDescriptor::ShapeProvider(provider) => {
+                facts.referencing((Shape, &provider.shape_id))
+              }
+              _ => facts,
+            }
+          }
+          ParameterShapeDescriptor::ProviderInField(_) => Self::default(),
+        },
+        ShapeEvent::ShapeParameterRenamed(e) => {
+          Self::updated((ShapeParameter, &e.shape_parameter_id)).setting("name")
+        }
+        ShapeEvent::ShapeParameterRemoved(e) => {
+          Self::removed((ShapeParameter, &e.shape_parameter_id))
+        }
+
+        ShapeEvent::FieldAdded(e) => {
+          let facts = Self::added((Field, &e.field_id), Some((Shape, &e.shape_id)));
+          match &e.shape_descriptor {
+            FieldShapeDescriptor::FieldShapeFromShape(descriptor) => {
+              facts.referencing((Shape, &descriptor.shape_id))
+            }
+            FieldShapeDescriptor::FieldShapeFromParameter(_) => facts,
+          }
+        }
+        ShapeEvent::FieldShapeSet(e) => match &e.shape_descriptor {
+          FieldShapeDescriptor::FieldShapeFromShape(descriptor) => {
+            Self::updated((Field, &descriptor.field_id))
+              .referencing((Shape, &descriptor.shape_id))
+              .setting("shape")
+          }
+          FieldShapeDescriptor::FieldShapeFromParameter(_) => Self::default(),
+        },
+        ShapeEvent::FieldRenamed(e) => Self::updated((Field, &e.field_id)).setting("name"),
+        ShapeEvent::FieldRemoved(e) => Self::removed((Field, &e.field_id)),
+      },
+
+      SpecEvent::RfcEvent(event) => match event {
+        RfcEvent::APINamed(_) => Self::default().setting("api name"),
+        RfcEvent::GitStateSet(_) => Self::default().setting("git state"),
+        RfcEvent::ContributionAdded(e) => {
+          Self::default().setting(format!("contribution {} {}", e.id, e.key))
+        }
+        RfcEvent::BatchCommitStarted(_) | RfcEvent::BatchCommitEnded(_) => Self::default(),
+      },
+    }
+  }
+}
+
+// The events about an entity between adding and removing it. Ids can be reused after removal, which
+// starts a new lifetime.
+#[derive(Default)]
+struct Lifetime {
+  removed: bool,
+  // lifetimes of the entities removed along with this one
+  children: Vec<usize>,
+  events: Vec<usize>,
+}
+
+#[derive(Default)]
+struct EventLiveness {
+  lifetimes: Vec<Lifetime>,
+  // for every event: the lifetime it is part of, the lifetimes it refers to, whether a later event
+  // overrides it and whether it's a batch commit boundary
+  event_lifetimes: Vec<Option<usize>>,
+  event_references: Vec<Vec<usize>>,
+  overridden: Vec<bool>,
+  batch_commit_events: Vec<bool>,
+}
+
+impl EventLiveness {
+  fn kept_events(&self, config: &CompactionConfig) -> Vec<bool> {
+    let mut kept_events = (0..self.overridden.len())
+      .map(|event_index| {
+        if self.batch_commit_events[event_index] {
+          return config.keeps_batch_commits();
+        }
+        !self.overridden[event_index]
+          && match self.event_lifetimes[event_index] {
+            Some(lifetime) => !self.lifetimes[lifetime].removed,
+            None => true,
+          }
+      })
+      .collect::<Vec<_>>();
+
+    // kept events referring to a removed entity need it to exist at that point, so keep all of its
+    // events after all, including the ones the events of the entity refer to in turn
+    let mut revived = vec![false; self.lifetimes.len()];
+    let mut pending = (0..kept_events.len())
+      .filter(|event_index| kept_events[*event_index])
+      .collect::<Vec<_>>();
+    while let Some(event_index) = pending.pop() {
+      for lifetime in &self.event_references[event_index] {
+        if !self.lifetimes[*lifetime].removed || revived[*lifetime] {
+          continue;
+        }
+        revived[*lifetime] = true;
+        for event_index in &self.lifetimes[*lifetime].events {
+          if !self.overridden[*event_index] && !kept_events[*event_index] {
+            kept_events[*event_index] = true;
+            pending.push(*event_index);
+          }
+        }
+      }
+    }
+
+    kept_events
+  }
+
+  fn remove_lifetime<'a>(
+    &mut self,
+    lifetime: usize,
+    current_lifetimes: &mut HashMap<Entity<'a>, usize>,
+  ) {
+    let mut pending = vec![lifetime];
+    while let Some(lifetime) = pending.pop() {
+      if self.lifetimes[lifetime].removed {
+        continue;
+      }
+      self.lifetimes[lifetime].removed = true;
+      pending.extend(&self.lifetimes[lifetime].children);
+    }
+    current_lifetimes.retain(|_, current_lifetime| !self.lifetimes[*current_lifetime].removed);
+  }
+}
+
+impl<'a> From<&'a [SpecEvent]> for EventLiveness {
+  fn from(events: &'a [SpecEvent]) -> Self {
+    let mut liveness = EventLiveness::default();
+    let mut current_lifetimes: HashMap<Entity<'a>, usize> = HashMap::new();
+    // events with a key that haven't been overridden yet, per lifetime and key
+    let mut pending_keys: HashMap<(Option<usize>, String), Vec<usize>> = HashMap::new();
+
+    for (event_index, event) in events.iter().enumerate() {
+      let facts = EventFacts::from(event);
+      let references = facts
+        .references
+        .iter()
+        .filter_map(|entity| current_lifetimes.get(entity).copied())
+        .collect::<Vec<_>>();
+
+      let lifetime = match facts.subject {
+        Some(Subject::Added { entity, parent }) => {
+          let lifetime = liveness.lifetimes.len();
+          liveness.lifetimes.push(Lifetime::default());
+          if let Some(parent_lifetime) = parent.and_then(|parent| current_lifetimes.get(&parent)) {
+            liveness.lifetimes[*parent_lifetime].children.push(lifetime);
+          }
+          current_lifetimes.insert(entity, lifetime);
+          Some(lifetime)
+        }
+        Some(Subject::Updated(entity)) => current_lifetimes.get(&entity).copied(),
+        Some(Subject::Removed(entity)) => {
+          let lifetime = current_lifetimes.get(&entity).copied();
+          if let Some(lifetime) = lifetime {
+            liveness.remove_lifetime(lifetime, &mut current_lifetimes);
+          }
+          lifetime
+        }
+        None => None,
+      };
+      if let Some(lifetime) = lifetime {
+        liveness.lifetimes[lifetime].events.push(event_index);
+      }
+
+      liveness.overridden.push(false);
+      // events about entities that don't exist can't be compared with others
+      let has_subject = facts.subject.is_some();
+      if let (Some(key), true) = (facts.key, lifetime.is_some() || !has_subject) {
+        let pending_events = pending_keys.entry((lifetime, key)).or_default();
+        if facts.overrides {
+          for overridden_index in pending_events.drain(..) {
+            liveness.overridden[overridden_index] = true;
+          }
+        }
+        pending_events.push(event_index);
+      }
+
+      liveness.event_lifetimes.push(lifetime);
+      liveness.event_references.push(references);
+      liveness.batch_commit_events.push(matches!(
+        event,
+        SpecEvent::RfcEvent(RfcEvent::BatchCommitStarted(_))
+          | SpecEvent::RfcEvent(RfcEvent::BatchCommitEnded(_))
+      ));
+    }
+
+    liveness
+  }
+}
+
+// Comparing projections
+// ---------------------
+//
+// Node indices depend on the order nodes were added and removed in, so nodes are compared by id
+
+fn endpoints_match(original: &EndpointProjection, compacted: &EndpointProjection) -> bool {
+  endpoint_nodes(original) == endpoint_nodes(compacted)
+    && original.request_parameters == compacted.request_parameters
+    && original.path_parameter_shapes == compacted.path_parameter_shapes
+}
+
+// nodes with the label of their parent. Methods and status codes have no id, so are labeled by
+// their parent's label and their value
+fn endpoint_nodes(
+  projection: &EndpointProjection,
+) -> BTreeMap<String, (&EndpointNode, Option<String>)> {
+  projection
+    .graph
+    .node_indices()
+    .map(|node_index| {
+      let parent_label = endpoint_parent_node_index(projection, node_index)
+        .map(|parent_index| endpoint_node_label(projection, parent_index));
+      (
+        endpoint_node_label(projection, node_index),
+        (&projection.graph[node_index], parent_label),
+      )
+    })
+    .collect()
+}
+
+fn endpoint_node_label(projection: &EndpointProjection, node_index: NodeIndex) -> String {
+  let node = &projection.graph[node_index];
+  if let Some(node_id) = node.node_id() {
+    return String::from(node_id);
+  }
+
+  let parent_label = endpoint_parent_node_index(projection, node_index)
+    .map(|parent_index| endpoint_node_label(projection, parent_index))
+    .unwrap_or_default();
+  match node {
+    EndpointNode::HttpMethod(http_method) => format!("{} {}", parent_label, http_method),
+    EndpointNode::HttpStatusCode(http_status_code) => {
+      format!("{} {}", parent_label, http_status_code)
+    }
+    _ => unreachable!("nodes without id should be http methods or status codes"),
+  }
+}
+
+fn endpoint_parent_node_index(
+  projection: &EndpointProjection,
+  node_index: NodeIndex,
+) -> Option<NodeIndex> {
+  projection
+    .graph
+    .neighbors_directed(node_index, petgraph::Direction::Outgoing)
+    .next()
+}
+
+fn shapes_match(original: &ShapeProjection, compacted: &ShapeProjection) -> bool {
+  shape_nodes(original) == shape_nodes(compacted)
+    && live_descriptors(original, &original.shape_names)
+      == live_descriptors(compacted, &compacted.shape_names)
+    && live_descriptors(original, &original.shape_parameter_names)
+      == live_descriptors(compacted, &compacted.shape_parameter_names)
+    && live_descriptors(original, &original.string_formats)
+      == live_descriptors(compacted, &compacted.string_formats)
+    && live_descriptors(original, &original.string_enums)
+      == live_descriptors(compacted, &compacted.string_enums)
+}
+
+#[derive(PartialEq)]
+struct ComparedShapeNode<'a> {
+  node: &'a ShapeNode,
+  // outgoing edges with the id of the node they point to, and for fields the shape they are of
+  edges: Vec<(&'a str, &'a ShapeEdge)>,
+  // fields of objects, in order
+  field_ids: Vec<&'a str>,
+}
+
+// Batch commits, and when shapes were created and updated in them, are history rather than shapes.
+// Removed shapes stay in the projection, but compaction only keeps them when they're still used, so
+// only what refers to them is compared.
+fn shape_nodes(projection: &ShapeProjection) -> BTreeMap<&str, ComparedShapeNode<'_>> {
+  let graph = &projection.graph;
+  graph
+    .node_indices()
+    .filter(|node_index| {
+      !matches!(graph[*node_index], ShapeNode::BatchCommit(_))
+        && !is_removed(projection, graph[*node_index].node_id())
+    })
+    .map(|node_index| {
+      let field_shape_edges = graph
+        .edges_directed(node_index, petgraph::Direction::Incoming)
+        .filter(|edge| matches!(edge.weight(), ShapeEdge::BelongsTo));
+      let mut edges = graph
+        .edges_directed(node_index, petgraph::Direction::Outgoing)
+        .filter(|edge| !matches!(edge.weight(), ShapeEdge::CreatedIn | ShapeEdge::UpdatedIn))
+        .map(|edge| (graph[edge.target()].node_id(), edge.weight()))
+        .chain(field_shape_edges.map(|edge| (graph[edge.source()].node_id(), edge.weight())))
+        .collect::<Vec<_>>();
+      edges.sort_by_key(|(target_id, _)| *target_id);
+
+      let field_ids = graph
+        .neighbors_directed(node_index, petgraph::Direction::Incoming)
+        .filter_map(|neighbour_index| match &graph[neighbour_index] {
+          ShapeNode::Field(field_node) => Some(field_node.field_id.as_str()),
+          _ => None,
+        })
+        .collect::<Vec<_>>();
+
+      let node = &graph[node_index];
+      let compared_node = ComparedShapeNode {
+        node,
+        edges,
+        field_ids,
+      };
+      (node.node_id(), compared_node)
+    })
+    .collect()
+}
+
+// descriptors of shapes and shape parameters, leaving out those of removed shapes
+fn live_descriptors<'a, T>(
+  projection: &ShapeProjection,
+  descriptors: &'a HashMap<String, T>,
+) -> BTreeMap<&'a str, &'a T> {
+  descriptors
+    .iter()
+    .filter(|(node_id, _)| !is_removed(projection, node_id))
+    .map(|(node_id, descriptor)| (node_id.as_str(), descriptor))
+    .collect()
+}
+
+// whether a node is a removed shape, or a field or shape parameter of one
+fn is_removed(projection: &ShapeProjection, node_id: &str) -> bool {
+  let node_id = String::from(node_id);
+  let shape_id = projection.get_owner_shape_id(&node_id).unwrap_or(node_id);
+  projection.is_removed_shape(&shape_id)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use insta::assert_json_snapshot;
+  use serde_json::json;
+
+  fn events_from(events: serde_json::Value) -> Vec<SpecEvent> {
+    serde_json::from_value(events).expect("initial events should be valid spec events")
+  }
+
+  fn event_types(events: &[SpecEvent]) -> Vec<&'static str> {
+    use cqrs_core::Event;
+    events.iter().map(|event| event.event_type()).collect()
+  }
+
+  #[test]
+  fn can_compact_overridden_and_removed_events() {
+    let events = events_from(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todo"}},
+      {"PathComponentRenamed": {"pathId": "path_1","name": "todos"}},
+      {"PathComponentAdded": {"pathId": "path_2","parentPathId": "path_1","name": "archive"}},
+      {"ShapeAdded": {"shapeId": "todo_shape","baseShapeId": "$object","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeRenamed": {"shapeId": "todo_shape","name": "Todo"}},
+      {"ShapeAdded": {"shapeId": "title_shape","baseShapeId": "$string","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeAdded": {"shapeId": "other_title_shape","baseShapeId": "$number","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"FieldAdded": {"fieldId": "title_field","shapeId": "todo_shape","name": "title","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "title_field","shapeId": "title_shape"}}}},
+      {"FieldShapeSet": {"shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "title_field","shapeId": "other_title_shape"}}}},
+      {"FieldShapeSet": {"shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "title_field","shapeId": "title_shape"}}}},
+      {"ShapeAdded": {"shapeId": "done_shape","baseShapeId": "$boolean","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"FieldAdded": {"fieldId": "done_field","shapeId": "todo_shape","name": "done","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "done_field","shapeId": "done_shape"}}}},
+      {"FieldRemoved": {"fieldId": "done_field"}},
+      {"ShapeRemoved": {"shapeId": "done_shape"}},
+      {"RequestAdded": {"requestId": "request_1","pathId": "path_1","httpMethod": "GET"}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_1","pathId": "path_1","httpMethod": "GET","httpStatusCode": 200}},
+      {"ResponseStatusCodeSet": {"responseId": "response_1","httpStatusCode": 201}},
+      {"ResponseStatusCodeSet": {"responseId": "response_1","httpStatusCode": 200}},
+      {"ResponseBodySet": {"responseId": "response_1","bodyDescriptor": {"httpContentType": "application/json","shapeId": "title_shape","isRemoved": false}}},
+      {"ResponseContentTypeSet": {"responseId": "response_1","httpContentType": "text/plain"}},
+      {"ResponseBodySet": {"responseId": "response_1","bodyDescriptor": {"httpContentType": "application/json","shapeId": "todo_shape","isRemoved": false}}},
+      {"RequestAdded": {"requestId": "request_2","pathId": "path_2","httpMethod": "GET"}},
+      {"RequestParameterAddedByPathAndMethod": {"parameterId": "parameter_1","pathId": "path_2","httpMethod": "GET","parameterLocation": "query","name": "limit"}},
+      {"PathComponentRemoved": {"pathId": "path_2"}},
+    ]));
+
+    let compacted_events = compact(events, &CompactionConfig::default())
+      .expect("compacted events should describe the same spec");
+
+    assert_json_snapshot!(
+      "can_compact_overridden_and_removed_events__compacted_events",
+      compacted_events
+    );
+  }
+
+  #[test]
+  fn keeps_removed_entities_that_kept_events_refer_to() {
+    let events = events_from(json!([
+      {"ShapeAdded": {"shapeId": "todo_shape","baseShapeId": "$object","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeAdded": {"shapeId": "title_shape","baseShapeId": "$string","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"FieldAdded": {"fieldId": "title_field","shapeId": "todo_shape","name": "title","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "title_field","shapeId": "title_shape"}}}},
+      {"ShapeRenamed": {"shapeId": "title_shape","name": "Title"}},
+      {"ShapeRenamed": {"shapeId": "title_shape","name": "TodoTitle"}},
+      {"ShapeAdded": {"shapeId": "other_title_shape","baseShapeId": "$string","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"FieldShapeSet": {"shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "title_field","shapeId": "other_title_shape"}}}},
+      {"ShapeRemoved": {"shapeId": "title_shape"}},
+    ]));
+
+    let compacted_events = compact(events, &CompactionConfig::default())
+      .expect("compacted events should describe the same spec");
+
+    assert_eq!(
+      event_types(&compacted_events),
+      vec![
+        "ShapeAdded",
+        "ShapeAdded",
+        "FieldAdded",
+        "ShapeRenamed",
+        "ShapeAdded",
+        "FieldShapeSet",
+        "ShapeRemoved"
+      ]
+    );
+  }
+
+  #[test]
+  fn keeps_reused_ids_apart() {
+    let events = events_from(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"PathComponentRemoved": {"pathId": "path_1"}},
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "tasks"}},
+      {"PathComponentRenamed": {"pathId": "path_1","name": "jobs"}},
+    ]));
+
+    let compacted_events = compact(events, &CompactionConfig::default())
+      .expect("compacted events should describe the same spec");
+
+    assert_eq!(
+      event_types(&compacted_events),
+      vec!["PathComponentAdded", "PathComponentRenamed"]
+    );
+  }
+
+  #[test]
+  fn can_keep_batch_commits() {
+    let context = json!({"clientId": "anonymous","clientSessionId": "session_1","clientCommandBatchId": "batch_1","createdAt": "2021-03-01T00:00:00.000Z"});
+    let events = events_from(json!([
+      {"BatchCommitStarted": {"batchId": "batch_1","commitMessage": "","eventContext": context}},
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos","eventContext": context}},
+      {"PathComponentRenamed": {"pathId": "path_1","name": "tasks","eventContext": context}},
+      {"BatchCommitEnded": {"batchId": "batch_1","eventContext": context}},
+    ]));
+
+    let compacted_events = compact(events.clone(), &CompactionConfig::default())
+      .expect("compacted events should describe the same spec");
+    assert_eq!(
+      serde_json::to_value(&compacted_events).unwrap(),
+      json!([
+        {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos","eventContext": null}},
+        {"PathComponentRenamed": {"pathId": "path_1","name": "tasks","eventContext": null}},
+      ])
+    );
+
+    let compacted_events = compact(
+      events.clone(),
+      &CompactionConfig::default().with_batch_commits(),
+    )
+    .expect("compacted events should describe the same spec");
+    assert_eq!(compacted_events, events);
+  }
+}
@@ -0,0 +1,668 @@
+use crate::projections::endpoint::{
+  EndpointProjection, Node, RequestBodyDescriptor, ResponseBodyDescriptor, ROOT_PATH_ID,
+};
+use crate::projections::endpoint::{
+  HEADER_PARAMETER_LOCATION, QUERY_PARAMETER_LOCATION, RESPONSE_HEADER_PARAMETER_LOCATION,
+};
+use crate::projections::SpecProjection;
+use crate::queries::shape::ShapeQueries;
+use petgraph::graph::NodeIndex;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+mod shapes;
+
+use shapes::ShapeSummary;
+
+/// Compute the semantic differences between two versions of a spec: added and removed endpoints,
+/// responses, bodies, parameters and fields, and changes to the types, optionality and
+/// nullability of what's documented. Every change is classified as breaking or not for clients
+/// of the API.
+///
+/// Specs are compared by what they describe rather than by the ids they describe it with, so
+/// specs that were written independently from each other can be compared as well.
+pub fn compare(old_spec: &SpecProjection, new_spec: &SpecProjection) -> Vec<SpecChange> {
+  let old_endpoints = EndpointSummary::collect(old_spec);
+  let mut new_endpoints = EndpointSummary::collect(new_spec);
+  let mut changes = vec![];
+
+  for (key, old_endpoint) in old_endpoints {
+    match new_endpoints.remove(&key) {
+      Some(new_endpoint) => old_endpoint.compare(&new_endpoint, &mut changes),
+      None => {
+        changes.push(old_endpoint.change(ChangeLocation::Endpoint, SpecChangeKind::Removed, true))
+      }
+    }
+  }
+  for (_, new_endpoint) in new_endpoints {
+    changes.push(new_endpoint.change(ChangeLocation::Endpoint, SpecChangeKind::Added, false));
+  }
+
+  changes
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecChange {
+  pub method: String,
+  pub path: String,
+  pub location: ChangeLocation,
+  pub kind: SpecChangeKind,
+  // whether clients written against the old spec could break when using the new one
+  pub breaking: bool,
+}
+
+/// Where in an endpoint a change was made. Json trails are empty for changes to bodies as a
+/// whole, and otherwise point at fields like `items[].price`, with `[]` for the items of lists
+/// and `{}` for the values of maps.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub enum ChangeLocation {
+  Endpoint,
+  #[serde(rename_all = "camelCase")]
+  Response {
+    status_code: u16,
+  },
+  #[serde(rename_all = "camelCase")]
+  RequestBody {
+    content_type: String,
+    json_trail: String,
+  },
+  #[serde(rename_all = "camelCase")]
+  ResponseBody {
+    status_code: u16,
+    content_type: String,
+    json_trail: String,
+  },
+  #[serde(rename_all = "camelCase")]
+  Parameter {
+    location: String,
+    name: String,
+  },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub enum SpecChangeKind {
+  Added,
+  Removed,
+  #[serde(rename_all = "camelCase")]
+  KindChanged {
+    old_kinds: Vec<String>,
+    new_kinds: Vec<String>,
+  },
+  MadeOptional,
+  MadeRequired,
+  MadeNullable,
+  MadeNonNullable,
+}
+
+impl fmt::Display for SpecChange {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{} {}: {} {}",
+      self.method, self.path, self.location, self.kind
+    )
+  }
+}
+
+impl fmt::Display for ChangeLocation {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let (body, json_trail) = match self {
+      ChangeLocation::Endpoint => return write!(f, "endpoint"),
+      ChangeLocation::Response { status_code } => return write!(f, "response {}", status_code),
+      ChangeLocation::Parameter { location, name } => {
+        return write!(f, "{} `{}`", parameter_location_name(location), name)
+      }
+      ChangeLocation::RequestBody {
+        content_type,
+        json_trail,
+      } => (format!("request body ({})", content_type), json_trail),
+      ChangeLocation::ResponseBody {
+        status_code,
+        content_type,
+        json_trail,
+      } => (
+        format!("response {} body ({})", status_code, content_type),
+        json_trail,
+      ),
+    };
+
+    if json_trail.is_empty() {
+      write!(f, "{}", body)
+    } else {
+      write!(f, "`{}` in {}", json_trail, body)
+    }
+  }
+}
+
+impl fmt::Display for SpecChangeKind {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      SpecChangeKind::Added => write!(f, "added"),
+      SpecChangeKind::Removed => write!(f, "removed"),
+      SpecChangeKind::KindChanged {
+        old_kinds,
+        new_kinds,
+      } => write!(
+        f,
+        "changed from {} to {}",
+        old_kinds.join(" or "),
+        new_kinds.join(" or ")
+      ),
+      SpecChangeKind::MadeOptional => write!(f, "made optional"),
+      SpecChangeKind::MadeRequired => write!(f, "made required"),
+      SpecChangeKind::MadeNullable => write!(f, "made nullable"),
+      SpecChangeKind::MadeNonNullable => write!(f, "made non-nullable"),
+    }
+  }
+}
+
+fn parameter_location_name(location: &str) -> &str {
+  match location {
+    QUERY_PARAMETER_LOCATION => "query parameter",
+    HEADER_PARAMETER_LOCATION => "request header",
+    RESPONSE_HEADER_PARAMETER_LOCATION => "response header",
+    location => location,
+  }
+}
+
+// Whether documented values are sent by clients or received by them, which decides whether
+// loosening or tightening what's documented breaks them
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Direction {
+  Request,
+  Response,
+}
+
+impl Direction {
+  fn of_parameter_location(location: &str) -> Self {
+    match location {
+      RESPONSE_HEADER_PARAMETER_LOCATION => Direction::Response,
+      _ => Direction::Request,
+    }
+  }
+}
+
+// Endpoints are identified by their method and path, with path parameters matching regardless of
+// their names
+type EndpointKey = (String, String);
+
+#[derive(Debug)]
+struct EndpointSummary {
+  method: String,
+  path: String,
+  request_bodies: BTreeMap<String, ShapeSummary>,
+  responses: BTreeMap<u16, BTreeMap<String, ShapeSummary>>,
+  parameters: BTreeMap<(String, String), ShapeSummary>,
+}
+
+impl EndpointSummary {
+  fn collect(spec_projection: &SpecProjection) -> BTreeMap<EndpointKey, Self> {
+    let endpoint_projection = spec_projection.endpoint();
+    let shape_queries = ShapeQueries::new(spec_projection.shape());
+    let graph = &endpoint_projection.graph;
+    let root_node_index = *endpoint_projection
+      .node_id_to_index
+      .get(ROOT_PATH_ID)
+      .expect("root path component should always exist");
+
+    let mut endpoints = BTreeMap::new();
+    let mut keys_by_path_id_and_method = HashMap::new();
+    let mut pending = vec![(
+      root_node_index,
+      String::from(ROOT_PATH_ID),
+      String::new(),
+      String::new(),
+    )];
+
+    while let Some((path_node_index, path_id, path, path_key)) = pending.pop() {
+      for child_node_index in children(endpoint_projection, path_node_index) {
+        match graph.node_weight(child_node_index).unwrap() {
+          Node::PathComponent(child_path_id, descriptor) if descriptor.is_parameter => pending
+            .push((
+              child_node_index,
+              child_path_id.clone(),
+              format!("{}/{{{}}}", path, descriptor.name),
+              format!("{}/{{}}", path_key),
+            )),
+          Node::PathComponent(child_path_id, descriptor) => pending.push((
+            child_node_index,
+            child_path_id.clone(),
+            format!("{}/{}", path, descriptor.name),
+            format!("{}/{}", path_key, descriptor.name),
+          )),
+          Node::HttpMethod(http_method) => {
+            let path = if path.is_empty() { "/" } else { &path };
+            let endpoint = EndpointSummary::from_method_node(
+              endpoint_projection,
+              &shape_queries,
+              child_node_index,
+              http_method,
+              path,
+            );
+            let path_key = if path_key.is_empty() { "/" } else { &path_key };
+            let key = (String::from(path_key), http_method.clone());
+            keys_by_path_id_and_method.insert((path_id.clone(), http_method.clone()), key.clone());
+            endpoints.insert(key, endpoint);
+          }
+          _ => {}
+        }
+      }
+    }
+
+    for descriptor in endpoint_projection.request_parameters.values() {
+      let endpoint = keys_by_path_id_and_method
+        .get(&(descriptor.path_id.clone(), descriptor.http_method.clone()))
+        .and_then(|key| endpoints.get_mut(key));
+      if let Some(endpoint) = endpoint {
+        let shape = match &descriptor.shape_id {
+          Some(shape_id) => ShapeSummary::from_shape(&shape_queries, shape_id),
+          None => ShapeSummary::default(),
+        };
+        endpoint.parameters.insert(
+          (descriptor.location.clone(), descriptor.name.clone()),
+          shape,
+        );
+      }
+    }
+
+    endpoints
+  }
+
+  fn from_method_node(
+    endpoint_projection: &EndpointProjection,
+    shape_queries: &ShapeQueries,
+    method_node_index: NodeIndex,
+    method: &str,
+    path: &str,
+  ) -> Self {
+    let graph = &endpoint_projection.graph;
+    let mut request_bodies = BTreeMap::new();
+    let mut responses = BTreeMap::new();
+
+    for child_node_index in children(endpoint_projection, method_node_index) {
+      match graph.node_weight(child_node_index).unwrap() {
+        Node::Request(_, RequestBodyDescriptor { body: Some(body) }) => {
+          let shape = ShapeSummary::from_shape(shape_queries, &body.root_shape_id);
+          request_bodies.insert(body.http_content_type.clone(), shape);
+        }
+        Node::HttpStatusCode(status_code) => {
+          let response_bodies: &mut BTreeMap<_, _> = responses.entry(*status_code).or_default();
+          for response_node_index in children(endpoint_projection, child_node_index) {
+            if let Some(Node::Response(_, ResponseBodyDescriptor { body: Some(body) })) =
+              graph.node_weight(response_node_index)
+            {
+              let shape = ShapeSummary::from_shape(shape_queries, &body.root_shape_id);
+              response_bodies.insert(body.http_content_type.clone(), shape);
+            }
+          }
+        }
+        _ => {}
+      }
+    }
+
+    EndpointSummary {
+      method: String::from(method),
+      path: String::from(path),
+      request_bodies,
+      responses,
+      parameters: BTreeMap::new(),
+    }
+  }
+
+  fn change(&self, location: ChangeLocation, kind: SpecChangeKind, breaking: bool) -> SpecChange {
+    SpecChange {
+      method: self.method.clone(),
+      path: self.path.clone(),
+      location,
+      kind,
+      breaking,
+    }
+  }
+
+  // changes are reported against the new version of the endpoint, so renamed path parameters
+  // show up with their new names
+  fn compare(&self, new_endpoint: &Self, changes: &mut Vec<SpecChange>) {
+    for (content_type, old_body) in &self.request_bodies {
+      let location = |json_trail: String| ChangeLocation::RequestBody {
+        content_type: content_type.clone(),
+        json_trail,
+      };
+      match new_endpoint.request_bodies.get(content_type) {
+        Some(new_body) => {
+          for (json_trail, kind, breaking) in compare_shapes(old_body, new_body, Direction::Request)
+          {
+            changes.push(new_endpoint.change(location(json_trail), kind, breaking));
+          }
+        }
+        None => {
+          changes.push(new_endpoint.change(location(String::new()), SpecChangeKind::Removed, true))
+        }
+      }
+    }
+    for content_type in new_endpoint.request_bodies.keys() {
+      if !self.request_bodies.contains_key(content_type) {
+        let location = ChangeLocation::RequestBody {
+          content_type: content_type.clone(),
+          json_trail: String::new(),
+        };
+        changes.push(new_endpoint.change(location, SpecChangeKind::Added, false));
+      }
+    }
+
+    for (status_code, old_bodies) in &self.responses {
+      let new_bodies = match new_endpoint.responses.get(status_code) {
+        Some(new_bodies) => new_bodies,
+        None => {
+          let location = ChangeLocation::Response {
+            status_code: *status_code,
+          };
+          changes.push(new_endpoint.change(location, SpecChangeKind::Removed, true));
+          continue;
+        }
+      };
+
+      for (content_type, old_body) in old_bodies {
+        let location = |json_trail: String| ChangeLocation::ResponseBody {
+          status_code: *status_code,
+          content_type: content_type.clone(),
+          json_trail,
+        };
+        match new_bodies.get(content_type) {
+          Some(new_body) => {
+            for (json_trail, kind, breaking) in
+              compare_shapes(old_body, new_body, Direction::Response)
+            {
+              changes.push(new_endpoint.change(location(json_trail), kind, breaking));
+            }
+          }
+          None => changes.push(new_endpoint.change(
+            location(String::new()),
+            SpecChangeKind::Removed,
+            true,
+          )),
+        }
+      }
+      for content_type in new_bodies.keys() {
+        if !old_bodies.contains_key(content_type) {
+          let location = ChangeLocation::ResponseBody {
+            status_code: *status_code,
+            content_type: content_type.clone(),
+            json_trail: String::new(),
+          };
+          changes.push(new_endpoint.change(location, SpecChangeKind::Added, false));
+        }
+      }
+    }
+    for status_code in new_endpoint.responses.keys() {
+      if !self.responses.contains_key(status_code) {
+        let location = ChangeLocation::Response {
+          status_code: *status_code,
+        };
+        changes.push(new_endpoint.change(location, SpecChangeKind::Added, false));
+      }
+    }
+
+    for ((location, name), old_shape) in &self.parameters {
+      let direction = Direction::of_parameter_location(location);
+      let change_location = || ChangeLocation::Parameter {
+        location: location.clone(),
+        name: name.clone(),
+      };
+      match new_endpoint
+        .parameters
+        .get(&(location.clone(), name.clone()))
+      {
+        Some(new_shape) => {
+          for (_, kind, breaking) in compare_shapes(old_shape, new_shape, direction) {
+            changes.push(new_endpoint.change(change_location(), kind, breaking));
+          }
+        }
+        None => {
+          // clients relying on a required response header break, sending an extra one doesn't
+          let breaking = direction == Direction::Response && !old_shape.optional;
+          changes.push(new_endpoint.change(change_location(), SpecChangeKind::Removed, breaking));
+        }
+      }
+    }
+    for ((location, name), new_shape) in &new_endpoint.parameters {
+      if !self
+        .parameters
+        .contains_key(&(location.clone(), name.clone()))
+      {
+        let direction = Direction::of_parameter_location(location);
+        let change_location = ChangeLocation::Parameter {
+          location: location.clone(),
+          name: name.clone(),
+        };
+        let breaking = direction == Direction::Request && !new_shape.optional;
+        changes.push(new_endpoint.change(change_location, SpecChangeKind::Added, breaking));
+      }
+    }
+  }
+}
+
+// Compare the summaries of two shapes, yielding the json trail of every change. Clients sending
+// values break when less is accepted, while clients receiving values break when more can be
+// received.
+fn compare_shapes(
+  old_shape: &ShapeSummary,
+  new_shape: &ShapeSummary,
+  direction: Direction,
+) -> Vec<(String, SpecChangeKind, bool)> {
+  let mut changes = vec![];
+  compare_shapes_at(old_shape, new_shape, direction, "", &mut changes);
+  changes
+}
+
+fn compare_shapes_at(
+  old_shape: &ShapeSummary,
+  new_shape: &ShapeSummary,
+  direction: Direction,
+  json_trail: &str,
+  changes: &mut Vec<(String, SpecChangeKind, bool)>,
+) {
+  let is_request = direction == Direction::Request;
+
+  if old_shape.kinds != new_shape.kinds {
+    let breaking = if is_request {
+      !new_shape.kinds.is_superset(&old_shape.kinds)
+    } else {
+      !new_shape.kinds.is_subset(&old_shape.kinds)
+    };
+    let kind = SpecChangeKind::KindChanged {
+      old_kinds: old_shape.kinds.iter().cloned().collect(),
+      new_kinds: new_shape.kinds.iter().cloned().collect(),
+    };
+    changes.push((String::from(json_trail), kind, breaking));
+  }
+  if old_shape.optional != new_shape.optional {
+    let (kind, breaking) = if new_shape.optional {
+      (SpecChangeKind::MadeOptional, !is_request)
+    } else {
+      (SpecChangeKind::MadeRequired, is_request)
+    };
+    changes.push((String::from(json_trail), kind, breaking));
+  }
+  if old_shape.nullable != new_shape.nullable {
+    let (kind, breaking) = if new_shape.nullable {
+      (SpecChangeKind::MadeNullable, !is_request)
+    } else {
+      (SpecChangeKind::MadeNonNullable, is_request)
+    };
+    changes.push((String::from(json_trail), kind, breaking));
+  }
+
+  let field_trail = |field_name: &str| {
+    if json_trail.is_empty() {
+      String::from(field_name)
+    } else {
+      format!("{}.{}", json_trail, field_name)
+    }
+  };
+  for (field_name, old_field) in &old_shape.fields {
+    match new_shape.fields.get(field_name) {
+      Some(new_field) => compare_shapes_at(
+        old_field,
+        new_field,
+        direction,
+        &field_trail(field_name),
+        changes,
+      ),
+      None => {
+        let breaking = !is_request && !old_field.optional;
+        changes.push((field_trail(field_name), SpecChangeKind::Removed, breaking));
+      }
+    }
+  }
+  for (field_name, new_field) in &new_shape.fields {
+    if !old_shape.fields.contains_key(field_name) {
+      let breaking = is_request && !new_field.optional;
+      changes.push((field_trail(field_name), SpecChangeKind::Added, breaking));
+    }
+  }
+
+  if let (Some(old_items), Some(new_items)) = (&old_shape.items, &new_shape.items) {
+    let items_trail = format!("{}[]", json_trail);
+    compare_shapes_at(old_items, new_items, direction, &items_trail, changes);
+  }
+  if let (Some(old_values), Some(new_values)) = (&old_shape.values, &new_shape.values) {
+    let values_trail = format!("{}{{}}", json_trail);
+    compare_shapes_at(old_values, new_values, direction, &values_trail, changes);
+  }
+}
+
+fn children(
+  endpoint_projection: &EndpointProjection,
+  node_index: NodeIndex,
+) -> impl Iterator<Item = NodeIndex> + '_ {
+  endpoint_projection
+    .graph
+    .neighbors_directed(node_index, petgraph::Direction::Incoming)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::events::SpecEvent;
+  use insta::assert_json_snapshot;
+  use serde_json::json;
+
+  fn spec_from_events(events: serde_json::Value) -> SpecProjection {
+    let events: Vec<SpecEvent> =
+      serde_json::from_value(events).expect("initial events should be valid spec events");
+    SpecProjection::from(events)
+  }
+
+  fn old_spec() -> SpecProjection {
+    spec_from_events(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"PathParameterAdded": {"pathId": "path_2","parentPathId": "path_1","name": "todoId"}},
+      {"ShapeAdded": {"shapeId": "todo_shape","baseShapeId": "$object","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": "Todo"}},
+      {"ShapeAdded": {"shapeId": "title_shape","baseShapeId": "$string","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"FieldAdded": {"fieldId": "title_field","shapeId": "todo_shape","name": "title","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "title_field","shapeId": "title_shape"}}}},
+      {"ShapeAdded": {"shapeId": "done_shape","baseShapeId": "$boolean","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"FieldAdded": {"fieldId": "done_field","shapeId": "todo_shape","name": "done","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "done_field","shapeId": "done_shape"}}}},
+      {"ShapeAdded": {"shapeId": "todos_shape","baseShapeId": "$list","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "todos_shape","providerDescriptor": {"ShapeProvider": {"shapeId": "todo_shape"}},"consumingParameterId": "$listItem"}}}},
+      {"ShapeAdded": {"shapeId": "limit_inner_shape","baseShapeId": "$number","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeAdded": {"shapeId": "limit_shape","baseShapeId": "$optional","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "limit_shape","providerDescriptor": {"ShapeProvider": {"shapeId": "limit_inner_shape"}},"consumingParameterId": "$optionalInner"}}}},
+      {"RequestAdded": {"requestId": "request_1","pathId": "path_1","httpMethod": "GET"}},
+      {"RequestParameterAddedByPathAndMethod": {"parameterId": "parameter_1","pathId": "path_1","httpMethod": "GET","parameterLocation": "query","name": "limit"}},
+      {"RequestParameterShapeSet": {"parameterId": "parameter_1","parameterDescriptor": {"shapeId": "limit_shape","isRemoved": false}}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_1","pathId": "path_1","httpMethod": "GET","httpStatusCode": 200}},
+      {"ResponseBodySet": {"responseId": "response_1","bodyDescriptor": {"httpContentType": "application/json","shapeId": "todos_shape","isRemoved": false}}},
+      {"RequestAdded": {"requestId": "request_2","pathId": "path_2","httpMethod": "PUT"}},
+      {"RequestBodySet": {"requestId": "request_2","bodyDescriptor": {"httpContentType": "application/json","shapeId": "todo_shape","isRemoved": false}}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_2","pathId": "path_2","httpMethod": "PUT","httpStatusCode": 204}},
+      {"RequestAdded": {"requestId": "request_3","pathId": "path_2","httpMethod": "DELETE"}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_3","pathId": "path_2","httpMethod": "DELETE","httpStatusCode": 204}},
+    ]))
+  }
+
+  // the same API, evolved and written down with different ids
+  fn new_spec() -> SpecProjection {
+    spec_from_events(json!([
+      {"PathComponentAdded": {"pathId": "path_a","parentPathId": "root","name": "todos"}},
+      {"PathParameterAdded": {"pathId": "path_b","parentPathId": "path_a","name": "id"}},
+      {"ShapeAdded": {"shapeId": "todo","baseShapeId": "$object","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": "Todo"}},
+      {"ShapeAdded": {"shapeId": "title","baseShapeId": "$string","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"FieldAdded": {"fieldId": "title_field","shapeId": "todo","name": "title","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "title_field","shapeId": "title"}}}},
+      {"ShapeAdded": {"shapeId": "done_inner","baseShapeId": "$boolean","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeAdded": {"shapeId": "done","baseShapeId": "$optional","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "done","providerDescriptor": {"ShapeProvider": {"shapeId": "done_inner"}},"consumingParameterId": "$optionalInner"}}}},
+      {"FieldAdded": {"fieldId": "done_field","shapeId": "todo","name": "done","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "done_field","shapeId": "done"}}}},
+      {"ShapeAdded": {"shapeId": "priority","baseShapeId": "$number","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"FieldAdded": {"fieldId": "priority_field","shapeId": "todo","name": "priority","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "priority_field","shapeId": "priority"}}}},
+      {"ShapeAdded": {"shapeId": "todos","baseShapeId": "$list","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "todos","providerDescriptor": {"ShapeProvider": {"shapeId": "todo"}},"consumingParameterId": "$listItem"}}}},
+      {"ShapeAdded": {"shapeId": "limit","baseShapeId": "$string","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"RequestAdded": {"requestId": "get_todos","pathId": "path_a","httpMethod": "GET"}},
+      {"RequestParameterAddedByPathAndMethod": {"parameterId": "limit_parameter","pathId": "path_a","httpMethod": "GET","parameterLocation": "query","name": "limit"}},
+      {"RequestParameterShapeSet": {"parameterId": "limit_parameter","parameterDescriptor": {"shapeId": "limit","isRemoved": false}}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "get_todos_200","pathId": "path_a","httpMethod": "GET","httpStatusCode": 200}},
+      {"ResponseBodySet": {"responseId": "get_todos_200","bodyDescriptor": {"httpContentType": "application/json","shapeId": "todos","isRemoved": false}}},
+      {"RequestAdded": {"requestId": "post_todo","pathId": "path_a","httpMethod": "POST"}},
+      {"RequestBodySet": {"requestId": "post_todo","bodyDescriptor": {"httpContentType": "application/json","shapeId": "todo","isRemoved": false}}},
+      {"RequestAdded": {"requestId": "put_todo","pathId": "path_b","httpMethod": "PUT"}},
+      {"RequestBodySet": {"requestId": "put_todo","bodyDescriptor": {"httpContentType": "application/json","shapeId": "todo","isRemoved": false}}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "put_todo_204","pathId": "path_b","httpMethod": "PUT","httpStatusCode": 204}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "put_todo_404","pathId": "path_b","httpMethod": "PUT","httpStatusCode": 404}},
+    ]))
+  }
+
+  #[test]
+  fn can_compare_spec_versions() {
+    let changes = compare(&old_spec(), &new_spec());
+
+    assert_json_snapshot!("can_compare_spec_versions__changes", changes);
+  }
+
+  #[test]
+  fn can_classify_breaking_changes() {
+    let changes = compare(&old_spec(), &new_spec());
+    let explanations = changes
+      .iter()
+      .map(|change| (change.to_string(), change.breaking))
+      .collect::<Vec<_>>();
+
+    assert!(explanations.contains(&(
+      String::from("DELETE /todos/{todoId}: endpoint removed"),
+      true
+    )));
+    assert!(explanations.contains(&(String::from("POST /todos: endpoint added"), false)));
+    assert!(explanations.contains(&(
+      String::from("GET /todos: query parameter `limit` changed from number to string"),
+      true
+    )));
+    assert!(explanations.contains(&(
+      String::from("GET /todos: query parameter `limit` made required"),
+      true
+    )));
+    assert!(explanations.contains(&(
+      String::from("GET /todos: `[].done` in response 200 body (application/json) made optional"),
+      true
+    )));
+    assert!(explanations.contains(&(
+      String::from("GET /todos: `[].priority` in response 200 body (application/json) added"),
+      false
+    )));
+    assert!(explanations.contains(&(
+      String::from("PUT /todos/{id}: `priority` in request body (application/json) added"),
+      true
+    )));
+    assert!(explanations.contains(&(
+      String::from("PUT /todos/{id}: `done` in request body (application/json) made optional"),
+      false
+    )));
+    assert!(explanations.contains(&(String::from("PUT /todos/{id}: response 404 added"), false)));
+  }
+
+  #[test]
+  fn identical_specs_have_no_changes() {
+    assert!(compare(&old_spec(), &old_spec()).is_empty());
+    assert!(compare(&new_spec(), &new_spec()).is_empty());
+  }
+}
@@ -0,0 +1,118 @@
+use crate::queries::shape::ShapeQueries;
+use crate::shapes::shape_kind_name;
+use crate::state::shape::{ShapeId, ShapeKind};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+/// What a shape promises about the values it describes, independent of the ids of the shapes
+/// describing them, so the shapes of different specs can be compared with each other.
+#[derive(Debug, Default, PartialEq)]
+pub struct ShapeSummary {
+  pub kinds: BTreeSet<String>,
+  pub optional: bool,
+  pub nullable: bool,
+  pub fields: BTreeMap<String, ShapeSummary>,
+  pub items: Option<Box<ShapeSummary>>,
+  pub values: Option<Box<ShapeSummary>>,
+}
+
+impl ShapeSummary {
+  pub fn from_shape(shape_queries: &ShapeQueries, shape_id: &ShapeId) -> Self {
+    let mut summary = ShapeSummary::default();
+    summary.describe(shape_queries, shape_id, &mut HashSet::new());
+    summary
+  }
+
+  // one-of shapes are summarized as the union of their branches, so describing a shape can add
+  // to an existing summary
+  fn describe(
+    &mut self,
+    shape_queries: &ShapeQueries,
+    shape_id: &ShapeId,
+    visiting: &mut HashSet<ShapeId>,
+  ) {
+    let (shape_id, shape_kind) = resolve_shape_kind(shape_queries, shape_id);
+    if !visiting.insert(shape_id.clone()) {
+      // shapes referring back to themselves are only compared up to where they recur
+      self.kinds.insert(shape_kind_name(&shape_kind));
+      return;
+    }
+
+    match shape_kind {
+      ShapeKind::OptionalKind | ShapeKind::NullableKind => {
+        if matches!(shape_kind, ShapeKind::OptionalKind) {
+          self.optional = true;
+        } else {
+          self.nullable = true;
+        }
+        if let Some(inner_shape_id) = parameter_shape(shape_queries, &shape_id, &shape_kind) {
+          self.describe(shape_queries, &inner_shape_id, visiting);
+        }
+      }
+      ShapeKind::OneOfKind => {
+        for (_, branch_shape_id) in shape_queries.resolve_parameters_to_shapes(&shape_id) {
+          self.describe(shape_queries, &branch_shape_id, visiting);
+        }
+      }
+      ShapeKind::ObjectKind => {
+        self.kinds.insert(shape_kind_name(&shape_kind));
+        let fields = shape_queries
+          .resolve_shape_field_id_and_names(&shape_id)
+          .filter_map(|(field_id, field_name)| {
+            let field_shape_id = shape_queries.resolve_field_shape_node(field_id)?;
+            Some((field_name.clone(), field_shape_id))
+          })
+          .collect::<Vec<_>>();
+        for (field_name, field_shape_id) in fields {
+          let field = self.fields.entry(field_name).or_default();
+          field.describe(shape_queries, &field_shape_id, visiting);
+        }
+      }
+      ShapeKind::ListKind | ShapeKind::MapKind => {
+        self.kinds.insert(shape_kind_name(&shape_kind));
+        let nested = match shape_kind {
+          ShapeKind::ListKind => &mut self.items,
+          _ => &mut self.values,
+        };
+        let nested = nested.get_or_insert_with(Default::default);
+        if let Some(nested_shape_id) = parameter_shape(shape_queries, &shape_id, &shape_kind) {
+          nested.describe(shape_queries, &nested_shape_id, visiting);
+        }
+      }
+      shape_kind => {
+        self.kinds.insert(shape_kind_name(&shape_kind));
+      }
+    }
+
+    visiting.remove(&shape_id);
+  }
+}
+
+fn resolve_shape_kind(shape_queries: &ShapeQueries, shape_id: &ShapeId) -> (ShapeId, ShapeKind) {
+  let mut aliased_shape_id = shape_id;
+  let mut seen = HashSet::new();
+  while let Some(base_shape_id) = shape_queries.resolve_base_shape_id(aliased_shape_id) {
+    if !seen.insert(base_shape_id) {
+      break;
+    }
+    aliased_shape_id = base_shape_id;
+  }
+
+  let shape_kind = shape_queries
+    .resolve_to_core_shape(aliased_shape_id)
+    .clone();
+  (aliased_shape_id.clone(), shape_kind)
+}
+
+fn parameter_shape(
+  shape_queries: &ShapeQueries,
+  shape_id: &ShapeId,
+  shape_kind: &ShapeKind,
+) -> Option<ShapeId> {
+  let shape_parameter_id = shape_kind.get_parameter_descriptor()?.shape_parameter_id;
+
+  shape_queries
+    .resolve_parameters_to_shapes(shape_id)
+    .into_iter()
+    .find(|(parameter_id, _)| parameter_id == shape_parameter_id)
+    .map(|(_, parameter_shape_id)| parameter_shape_id)
+}
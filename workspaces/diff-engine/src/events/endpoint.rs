@@ -35,6 +35,7 @@ pub enum EndpointEvent {
   RequestContentTypeSet(RequestContentTypeSet),
   RequestBodySet(RequestBodySet),
   RequestBodyUnset(RequestBodyUnset),
+  RequestRemoved(RequestRemoved),
 
   // Response events
   ResponseAddedByPathAndMethod(ResponseAddedByPathAndMethod),
@@ -255,6 +256,7 @@ impl Event for EndpointEvent {
       EndpointEvent::RequestContentTypeSet(evt) => evt.event_type(),
       EndpointEvent::RequestBodySet(evt) => evt.event_type(),
       EndpointEvent::RequestBodyUnset(evt) => evt.event_type(),
+      EndpointEvent::RequestRemoved(evt) => evt.event_type(),
 
       // Response events
       EndpointEvent::ResponseAddedByPathAndMethod(evt) => evt.event_type(),
@@ -294,6 +296,7 @@ impl WithEventContext for EndpointEvent {
       EndpointEvent::RequestContentTypeSet(evt) => evt.event_context.replace(event_context),
       EndpointEvent::RequestBodySet(evt) => evt.event_context.replace(event_context),
       EndpointEvent::RequestBodyUnset(evt) => evt.event_context.replace(event_context),
+      EndpointEvent::RequestRemoved(evt) => evt.event_context.replace(event_context),
 
       // Response events
       EndpointEvent::ResponseAddedByPathAndMethod(evt) => evt.event_context.replace(event_context),
@@ -304,6 +307,42 @@ impl WithEventContext for EndpointEvent {
       EndpointEvent::ResponseRemoved(evt) => evt.event_context.replace(event_context),
     };
   }
+
+  fn without_event_context(&mut self) {
+    match self {
+      EndpointEvent::PathComponentAdded(evt) => evt.event_context.take(),
+      EndpointEvent::PathComponentRenamed(evt) => evt.event_context.take(),
+      EndpointEvent::PathComponentRemoved(evt) => evt.event_context.take(),
+
+      // path parameters
+      EndpointEvent::PathParameterAdded(evt) => evt.event_context.take(),
+      EndpointEvent::PathParameterShapeSet(evt) => evt.event_context.take(),
+      EndpointEvent::PathParameterRenamed(evt) => evt.event_context.take(),
+      EndpointEvent::PathParameterRemoved(evt) => evt.event_context.take(),
+
+      // request parameters
+      EndpointEvent::RequestParameterAddedByPathAndMethod(evt) => evt.event_context.take(),
+      EndpointEvent::RequestParameterRenamed(evt) => evt.event_context.take(),
+      EndpointEvent::RequestParameterShapeSet(evt) => evt.event_context.take(),
+      EndpointEvent::RequestParameterShapeUnset(evt) => evt.event_context.take(),
+      EndpointEvent::RequestParameterRemoved(evt) => evt.event_context.take(),
+
+      // Request events
+      EndpointEvent::RequestAdded(evt) => evt.event_context.take(),
+      EndpointEvent::RequestContentTypeSet(evt) => evt.event_context.take(),
+      EndpointEvent::RequestBodySet(evt) => evt.event_context.take(),
+      EndpointEvent::RequestBodyUnset(evt) => evt.event_context.take(),
+      EndpointEvent::RequestRemoved(evt) => evt.event_context.take(),
+
+      // Response events
+      EndpointEvent::ResponseAddedByPathAndMethod(evt) => evt.event_context.take(),
+      EndpointEvent::ResponseStatusCodeSet(evt) => evt.event_context.take(),
+      EndpointEvent::ResponseContentTypeSet(evt) => evt.event_context.take(),
+      EndpointEvent::ResponseBodySet(evt) => evt.event_context.take(),
+      EndpointEvent::ResponseBodyUnset(evt) => evt.event_context.take(),
+      EndpointEvent::ResponseRemoved(evt) => evt.event_context.take(),
+    };
+  }
 }
 
 impl Event for PathComponentAdded {
@@ -492,24 +531,96 @@ impl From<RequestAdded> for EndpointEvent {
   }
 }
 
+impl From<RequestContentTypeSet> for EndpointEvent {
+  fn from(event: RequestContentTypeSet) -> Self {
+    Self::RequestContentTypeSet(event)
+  }
+}
+
 impl From<RequestBodySet> for EndpointEvent {
   fn from(event: RequestBodySet) -> Self {
     Self::RequestBodySet(event)
   }
 }
 
+impl From<RequestBodyUnset> for EndpointEvent {
+  fn from(event: RequestBodyUnset) -> Self {
+    Self::RequestBodyUnset(event)
+  }
+}
+
+impl From<RequestRemoved> for EndpointEvent {
+  fn from(event: RequestRemoved) -> Self {
+    Self::RequestRemoved(event)
+  }
+}
+
 impl From<ResponseAddedByPathAndMethod> for EndpointEvent {
   fn from(event: ResponseAddedByPathAndMethod) -> Self {
     Self::ResponseAddedByPathAndMethod(event)
   }
 }
 
+impl From<ResponseStatusCodeSet> for EndpointEvent {
+  fn from(event: ResponseStatusCodeSet) -> Self {
+    Self::ResponseStatusCodeSet(event)
+  }
+}
+
+impl From<ResponseContentTypeSet> for EndpointEvent {
+  fn from(event: ResponseContentTypeSet) -> Self {
+    Self::ResponseContentTypeSet(event)
+  }
+}
+
 impl From<ResponseBodySet> for EndpointEvent {
   fn from(event: ResponseBodySet) -> Self {
     Self::ResponseBodySet(event)
   }
 }
 
+impl From<ResponseBodyUnset> for EndpointEvent {
+  fn from(event: ResponseBodyUnset) -> Self {
+    Self::ResponseBodyUnset(event)
+  }
+}
+
+impl From<ResponseRemoved> for EndpointEvent {
+  fn from(event: ResponseRemoved) -> Self {
+    Self::ResponseRemoved(event)
+  }
+}
+
+impl From<RequestParameterAddedByPathAndMethod> for EndpointEvent {
+  fn from(event: RequestParameterAddedByPathAndMethod) -> Self {
+    Self::RequestParameterAddedByPathAndMethod(event)
+  }
+}
+
+impl From<RequestParameterRenamed> for EndpointEvent {
+  fn from(event: RequestParameterRenamed) -> Self {
+    Self::RequestParameterRenamed(event)
+  }
+}
+
+impl From<RequestParameterShapeSet> for EndpointEvent {
+  fn from(event: RequestParameterShapeSet) -> Self {
+    Self::RequestParameterShapeSet(event)
+  }
+}
+
+impl From<RequestParameterShapeUnset> for EndpointEvent {
+  fn from(event: RequestParameterShapeUnset) -> Self {
+    Self::RequestParameterShapeUnset(event)
+  }
+}
+
+impl From<RequestParameterRemoved> for EndpointEvent {
+  fn from(event: RequestParameterRemoved) -> Self {
+    Self::RequestParameterRemoved(event)
+  }
+}
+
 // Conversion from commands
 // ------------------------
 
@@ -538,15 +649,49 @@ impl From<EndpointCommand> for EndpointEvent {
         EndpointEvent::from(PathParameterRemoved::from(command))
       }
       EndpointCommand::AddRequest(command) => EndpointEvent::from(RequestAdded::from(command)),
+      EndpointCommand::SetRequestContentType(command) => {
+        EndpointEvent::from(RequestContentTypeSet::from(command))
+      }
       EndpointCommand::SetRequestBodyShape(command) => {
         EndpointEvent::from(RequestBodySet::from(command))
       }
+      EndpointCommand::UnsetRequestBodyShape(command) => {
+        EndpointEvent::from(RequestBodyUnset::from(command))
+      }
+      EndpointCommand::RemoveRequest(command) => EndpointEvent::from(RequestRemoved::from(command)),
       EndpointCommand::AddResponseByPathAndMethod(command) => {
         EndpointEvent::from(ResponseAddedByPathAndMethod::from(command))
       }
+      EndpointCommand::SetResponseContentType(command) => {
+        EndpointEvent::from(ResponseContentTypeSet::from(command))
+      }
+      EndpointCommand::SetResponseStatusCode(command) => {
+        EndpointEvent::from(ResponseStatusCodeSet::from(command))
+      }
       EndpointCommand::SetResponseBodyShape(command) => {
         EndpointEvent::from(ResponseBodySet::from(command))
       }
+      EndpointCommand::UnsetResponseBodyShape(command) => {
+        EndpointEvent::from(ResponseBodyUnset::from(command))
+      }
+      EndpointCommand::RemoveResponse(command) => {
+        EndpointEvent::from(ResponseRemoved::from(command))
+      }
+      EndpointCommand::SetQueryParameterShape(command) => {
+        EndpointEvent::from(RequestParameterShapeSet::from(command))
+      }
+      EndpointCommand::SetHeaderParameterShape(command) => {
+        EndpointEvent::from(RequestParameterShapeSet::from(command))
+      }
+      EndpointCommand::RenameHeaderParameter(command) => {
+        EndpointEvent::from(RequestParameterRenamed::from(command))
+      }
+      EndpointCommand::UnsetHeaderParameterShape(command) => {
+        EndpointEvent::from(RequestParameterShapeUnset::from(command))
+      }
+      EndpointCommand::RemoveHeaderParameter(command) => {
+        EndpointEvent::from(RequestParameterRemoved::from(command))
+      }
       _ => unimplemented!(
         "conversion from endpoint command to endpoint event not implemented for variant: {:?}",
         endpoint_command
@@ -647,6 +792,34 @@ impl From<endpoint_commands::SetRequestBodyShape> for RequestBodySet {
   }
 }
 
+impl From<endpoint_commands::SetRequestContentType> for RequestContentTypeSet {
+  fn from(command: endpoint_commands::SetRequestContentType) -> Self {
+    Self {
+      request_id: command.request_id,
+      http_content_type: command.http_content_type,
+      event_context: None,
+    }
+  }
+}
+
+impl From<endpoint_commands::UnsetRequestBodyShape> for RequestBodyUnset {
+  fn from(command: endpoint_commands::UnsetRequestBodyShape) -> Self {
+    Self {
+      request_id: command.request_id,
+      event_context: None,
+    }
+  }
+}
+
+impl From<endpoint_commands::RemoveRequest> for RequestRemoved {
+  fn from(command: endpoint_commands::RemoveRequest) -> Self {
+    Self {
+      request_id: command.request_id,
+      event_context: None,
+    }
+  }
+}
+
 impl From<endpoint_commands::AddResponseByPathAndMethod> for ResponseAddedByPathAndMethod {
   fn from(command: endpoint_commands::AddResponseByPathAndMethod) -> Self {
     Self {
@@ -659,6 +832,26 @@ impl From<endpoint_commands::AddResponseByPathAndMethod> for ResponseAddedByPath
   }
 }
 
+impl From<endpoint_commands::SetResponseContentType> for ResponseContentTypeSet {
+  fn from(command: endpoint_commands::SetResponseContentType) -> Self {
+    Self {
+      response_id: command.response_id,
+      http_content_type: command.http_content_type,
+      event_context: None,
+    }
+  }
+}
+
+impl From<endpoint_commands::SetResponseStatusCode> for ResponseStatusCodeSet {
+  fn from(command: endpoint_commands::SetResponseStatusCode) -> Self {
+    Self {
+      response_id: command.response_id,
+      http_status_code: command.http_status_code,
+      event_context: None,
+    }
+  }
+}
+
 impl From<endpoint_commands::SetResponseBodyShape> for ResponseBodySet {
   fn from(command: endpoint_commands::SetResponseBodyShape) -> Self {
     Self {
@@ -668,3 +861,69 @@ impl From<endpoint_commands::SetResponseBodyShape> for ResponseBodySet {
     }
   }
 }
+
+impl From<endpoint_commands::UnsetResponseBodyShape> for ResponseBodyUnset {
+  fn from(command: endpoint_commands::UnsetResponseBodyShape) -> Self {
+    Self {
+      response_id: command.response_id,
+      event_context: None,
+    }
+  }
+}
+
+impl From<endpoint_commands::RemoveResponse> for ResponseRemoved {
+  fn from(command: endpoint_commands::RemoveResponse) -> Self {
+    Self {
+      response_id: command.response_id,
+      event_context: None,
+    }
+  }
+}
+
+impl From<endpoint_commands::SetQueryParameterShape> for RequestParameterShapeSet {
+  fn from(command: endpoint_commands::SetQueryParameterShape) -> Self {
+    Self {
+      parameter_id: command.parameter_id,
+      parameter_descriptor: command.parameter_descriptor,
+      event_context: None,
+    }
+  }
+}
+
+impl From<endpoint_commands::SetHeaderParameterShape> for RequestParameterShapeSet {
+  fn from(command: endpoint_commands::SetHeaderParameterShape) -> Self {
+    Self {
+      parameter_id: command.parameter_id,
+      parameter_descriptor: command.parameter_descriptor,
+      event_context: None,
+    }
+  }
+}
+
+impl From<endpoint_commands::RenameHeaderParameter> for RequestParameterRenamed {
+  fn from(command: endpoint_commands::RenameHeaderParameter) -> Self {
+    Self {
+      parameter_id: command.parameter_id,
+      name: command.name,
+      event_context: None,
+    }
+  }
+}
+
+impl From<endpoint_commands::UnsetHeaderParameterShape> for RequestParameterShapeUnset {
+  fn from(command: endpoint_commands::UnsetHeaderParameterShape) -> Self {
+    Self {
+      parameter_id: command.parameter_id,
+      event_context: None,
+    }
+  }
+}
+
+impl From<endpoint_commands::RemoveHeaderParameter> for RequestParameterRemoved {
+  fn from(command: endpoint_commands::RemoveHeaderParameter) -> Self {
+    Self {
+      parameter_id: command.parameter_id,
+      event_context: None,
+    }
+  }
+}
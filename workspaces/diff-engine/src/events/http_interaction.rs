@@ -1,12 +1,15 @@
 use super::EventLoadingError;
 use crate::shapehash;
-use crate::state::body::BodyDescriptor;
+use crate::state::body::{BodyDescriptor, ItemsDescriptor, ObjectDescriptor, StringDescriptor};
+use crate::state::media_type::MediaType;
+use crate::state::xml::XmlElement;
 use avro_rs;
 use base64;
 use cqrs_core::Event;
 use protobuf::Message;
 use serde::{Deserialize, Serialize};
 use serde_json;
+use std::collections::BTreeMap;
 use std::io;
 use std::iter::FromIterator;
 
@@ -37,6 +40,137 @@ pub struct Request {
   pub body: Body,
 }
 
+impl Request {
+  /// Observed query parameters by name, with a value for every time the name was used. Captures
+  /// either hold the query as a JSON object of names to values (or lists of values), or as the
+  /// raw query string.
+  pub fn query_parameters(&self) -> BTreeMap<String, Vec<String>> {
+    let mut parameters: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    if let Some(json_string) = &self.query.as_json_string {
+      let json: serde_json::Value = serde_json::from_str(json_string)
+        .expect("as_json_string of ArbitraryData should always be valid json");
+      if let serde_json::Value::Object(fields) = json {
+        for (name, value) in fields {
+          let values = match value {
+            serde_json::Value::Array(items) => items.into_iter().map(parameter_value).collect(),
+            value => vec![parameter_value(value)],
+          };
+          parameters.entry(name).or_default().extend(values);
+        }
+      }
+    } else if let Some(text) = &self.query.as_text {
+      let query_string = text.strip_prefix('?').unwrap_or(text);
+      for pair in query_string.split('&').filter(|pair| !pair.is_empty()) {
+        let mut pair = pair.splitn(2, '=');
+        let name = decode_query_component(pair.next().unwrap_or(""));
+        let value = decode_query_component(pair.next().unwrap_or(""));
+        parameters.entry(name).or_default().push(value);
+      }
+    }
+
+    parameters
+  }
+
+  /// Observed headers by lowercased name, with a value for every time the header was sent
+  pub fn header_parameters(&self) -> BTreeMap<String, Vec<String>> {
+    header_parameters(&self.headers)
+  }
+}
+
+impl Response {
+  /// Observed headers by lowercased name, with a value for every time the header was sent
+  pub fn header_parameters(&self) -> BTreeMap<String, Vec<String>> {
+    header_parameters(&self.headers)
+  }
+}
+
+fn header_parameters(headers: &ArbitraryData) -> BTreeMap<String, Vec<String>> {
+  let mut parameters: BTreeMap<String, Vec<String>> = BTreeMap::new();
+  // header names are case-insensitive, so they're normalized to lowercase
+  let mut push = |name: &str, value: String| {
+    parameters
+      .entry(name.trim().to_lowercase())
+      .or_default()
+      .push(value)
+  };
+
+  if let Some(json_string) = &headers.as_json_string {
+    let json: serde_json::Value = serde_json::from_str(json_string)
+      .expect("as_json_string of ArbitraryData should always be valid json");
+    match json {
+      serde_json::Value::Array(headers) => {
+        for header in headers {
+          if let (Some(serde_json::Value::String(name)), Some(value)) =
+            (header.get("name"), header.get("value"))
+          {
+            push(name, parameter_value(value.clone()));
+          }
+        }
+      }
+      serde_json::Value::Object(fields) => {
+        for (name, value) in fields {
+          match value {
+            serde_json::Value::Array(values) => {
+              for value in values {
+                push(&name, parameter_value(value));
+              }
+            }
+            value => push(&name, parameter_value(value)),
+          }
+        }
+      }
+      _ => {}
+    }
+  } else if let Some(text) = &headers.as_text {
+    for line in text.lines() {
+      let mut line = line.splitn(2, ':');
+      if let (Some(name), Some(value)) = (line.next(), line.next()) {
+        push(name, String::from(value.trim()));
+      }
+    }
+  }
+
+  parameters
+}
+
+fn parameter_value(value: serde_json::Value) -> String {
+  match value {
+    serde_json::Value::String(value) => value,
+    serde_json::Value::Null => String::from(""),
+    value => value.to_string(),
+  }
+}
+
+fn decode_query_component(component: &str) -> String {
+  let bytes = component.as_bytes();
+  let mut decoded = Vec::with_capacity(bytes.len());
+  let mut i = 0;
+  while i < bytes.len() {
+    match bytes[i] {
+      b'+' => decoded.push(b' '),
+      b'%' => {
+        // only two hex digits make an escape, anything else is kept as it is
+        let hex_digit = |offset: usize| {
+          bytes
+            .get(i + offset)
+            .and_then(|byte| char::from(*byte).to_digit(16))
+        };
+        match (hex_digit(1), hex_digit(2)) {
+          (Some(high), Some(low)) => {
+            decoded.push((high * 16 + low) as u8);
+            i += 2;
+          }
+          _ => decoded.push(b'%'),
+        }
+      }
+      byte => decoded.push(byte),
+    }
+    i += 1;
+  }
+  String::from_utf8_lossy(&decoded).into_owned()
+}
+
 #[derive(Clone, Deserialize, Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Response {
@@ -104,6 +238,158 @@ impl From<&ArbitraryData> for Option<BodyDescriptor> {
   }
 }
 
+impl From<&Body> for Option<BodyDescriptor> {
+  /// Bodies captured as text are decoded according to their content type when it's a known
+  /// structured format, so they can be described like JSON bodies.
+  fn from(body: &Body) -> Self {
+    let value = &body.value;
+    let media_type = body.content_type.as_deref().and_then(MediaType::parse);
+    let decoded = match (&value.as_text, media_type) {
+      (Some(text), Some(media_type))
+        if value.shape_hash_v1_base64.is_none() && value.as_json_string.is_none() =>
+      {
+        decode_text_body(text, &media_type)
+      }
+      _ => None,
+    };
+
+    decoded.or_else(|| value.into())
+  }
+}
+
+fn decode_text_body(text: &str, media_type: &MediaType) -> Option<BodyDescriptor> {
+  match media_type.essence().as_str() {
+    "application/x-www-form-urlencoded" => Some(form_urlencoded_body(text)),
+    "multipart/form-data" => Some(multipart_body(text, media_type.parameter("boundary")?)),
+    "application/xml" | "text/xml" => xml_body(text),
+    _ if media_type.suffix() == Some("xml") => xml_body(text),
+    _ => None,
+  }
+}
+
+// Form fields as an object of string values, where fields that are sent more than once (or are
+// named like `tags[]`) are lists of values
+fn form_fields_body(fields: Vec<(String, BodyDescriptor)>) -> BodyDescriptor {
+  let mut values_by_name: BTreeMap<String, (bool, Vec<BodyDescriptor>)> = BTreeMap::new();
+  for (name, value) in fields {
+    let (name, is_list) = match name.strip_suffix("[]") {
+      Some(list_name) => (String::from(list_name), true),
+      None => (name, false),
+    };
+    let (name_is_list, values) = values_by_name.entry(name).or_default();
+    *name_is_list = *name_is_list || is_list;
+    values.push(value);
+  }
+
+  let fields = values_by_name
+    .into_iter()
+    .map(|(name, (is_list, mut values))| {
+      let value = if is_list || values.len() > 1 {
+        BodyDescriptor::Array(ItemsDescriptor::from(values.into_iter()))
+      } else {
+        values.pop().unwrap()
+      };
+      (name, value)
+    });
+  BodyDescriptor::Object(ObjectDescriptor::from(fields))
+}
+
+// The document as an object with its root element as the only field, so a different root element
+// is a different shape
+fn xml_body(text: &str) -> Option<BodyDescriptor> {
+  let root = XmlElement::parse(text)?;
+  let fields = vec![(root.name.clone(), BodyDescriptor::from(root))];
+  Some(BodyDescriptor::Object(ObjectDescriptor::from(
+    fields.into_iter(),
+  )))
+}
+
+fn form_urlencoded_body(text: &str) -> BodyDescriptor {
+  let fields = text
+    .split('&')
+    .filter(|pair| !pair.is_empty())
+    .map(|pair| {
+      let mut pair = pair.splitn(2, '=');
+      let name = decode_query_component(pair.next().unwrap_or(""));
+      let value = decode_query_component(pair.next().unwrap_or(""));
+      let value = BodyDescriptor::String(Some(StringDescriptor::from(value)));
+      (name, value)
+    })
+    .collect();
+  form_fields_body(fields)
+}
+
+fn multipart_body(text: &str, boundary: &str) -> BodyDescriptor {
+  let delimiter = format!("--{}", boundary);
+  let fields = text
+    .split(delimiter.as_str())
+    .skip(1) // preamble
+    .take_while(|part| !part.starts_with("--")) // epilogue, after the closing delimiter
+    .filter_map(multipart_field)
+    .collect();
+  form_fields_body(fields)
+}
+
+// Parts of a multipart body are described by their content: JSON parts as JSON, files as strings
+// of which the (binary) value isn't observed, and other fields as string values
+fn multipart_field(part: &str) -> Option<(String, BodyDescriptor)> {
+  let part = strip_line_break(part, str::strip_prefix)?;
+  let (headers, content) = match part.find("\r\n\r\n") {
+    Some(separator) => (&part[..separator], &part[separator + 4..]),
+    None => {
+      let separator = part.find("\n\n")?;
+      (&part[..separator], &part[separator + 2..])
+    }
+  };
+  let content = strip_line_break(content, str::strip_suffix).unwrap_or(content);
+
+  let mut name = None;
+  let mut is_file = false;
+  let mut media_type = None;
+  for header in headers.lines() {
+    let mut header = header.splitn(2, ':');
+    let (header_name, header_value) = match (header.next(), header.next()) {
+      (Some(header_name), Some(header_value)) => (header_name.trim(), header_value.trim()),
+      _ => continue,
+    };
+    if header_name.eq_ignore_ascii_case("content-disposition") {
+      for parameter in header_value.split(';').skip(1) {
+        let mut parameter = parameter.splitn(2, '=');
+        let parameter_name = parameter.next().unwrap_or("").trim().to_lowercase();
+        let parameter_value = parameter.next().unwrap_or("").trim().trim_matches('"');
+        match parameter_name.as_str() {
+          "name" => name = Some(String::from(parameter_value)),
+          "filename" => is_file = true,
+          _ => {}
+        }
+      }
+    } else if header_name.eq_ignore_ascii_case("content-type") {
+      media_type = MediaType::parse(header_value);
+    }
+  }
+
+  let json_value = match media_type {
+    Some(media_type) if media_type.is_json() && !is_file => {
+      serde_json::from_str::<serde_json::Value>(content).ok()
+    }
+    _ => None,
+  };
+  let value = match json_value {
+    Some(json_value) => BodyDescriptor::from(json_value),
+    None if is_file => BodyDescriptor::String(None),
+    None => BodyDescriptor::String(Some(StringDescriptor::from(content))),
+  };
+
+  Some((name?, value))
+}
+
+fn strip_line_break<'a>(
+  text: &'a str,
+  strip: impl Fn(&'a str, &'static str) -> Option<&'a str>,
+) -> Option<&'a str> {
+  strip(text, "\r\n").or_else(|| strip(text, "\n"))
+}
+
 impl From<shapehash::ShapeDescriptor> for serde_json::value::Value {
   fn from(mut shape_descriptor: shapehash::ShapeDescriptor) -> serde_json::value::Value {
     use serde_json::map::Map;
@@ -444,4 +730,193 @@ mod test {
     let interaction = HttpInteraction::from_json_str(&json);
     interaction.expect("Valid JSON should be able to deserialize into an HttpInteraction");
   }
+
+  #[test]
+  fn can_read_query_parameters() {
+    let request_with_query = |query: serde_json::Value| -> Request {
+      serde_json::from_value(serde_json::json!({
+        "host": "localhost",
+        "method": "GET",
+        "path": "/todos",
+        "query": query,
+        "headers": {},
+        "body": { "contentType": null, "value": {} }
+      }))
+      .expect("should be a valid request")
+    };
+
+    let from_text = request_with_query(serde_json::json!({
+      "asText": "?status=open&tag=a&tag=b%20c&q=two+words&flag"
+    }));
+    let from_json = request_with_query(serde_json::json!({
+      "asJsonString": "{\"status\":\"open\",\"tag\":[\"a\",\"b c\"],\"q\":\"two words\",\"flag\":\"\"}"
+    }));
+
+    let expected: BTreeMap<String, Vec<String>> = vec![
+      ("flag", vec![""]),
+      ("q", vec!["two words"]),
+      ("status", vec!["open"]),
+      ("tag", vec!["a", "b c"]),
+    ]
+    .into_iter()
+    .map(|(name, values)| {
+      (
+        String::from(name),
+        values.into_iter().map(String::from).collect(),
+      )
+    })
+    .collect();
+
+    assert_eq!(from_text.query_parameters(), expected);
+    assert_eq!(from_json.query_parameters(), expected);
+    assert!(request_with_query(serde_json::json!({}))
+      .query_parameters()
+      .is_empty());
+  }
+
+  #[test]
+  fn keeps_malformed_query_escapes() {
+    assert_eq!(decode_query_component("a%20b%2fc%2F"), "a b/c/");
+    assert_eq!(decode_query_component("%+5"), "% 5");
+    assert_eq!(decode_query_component("%-5"), "%-5");
+    assert_eq!(decode_query_component("%zz"), "%zz");
+    assert_eq!(decode_query_component("100%"), "100%");
+    assert_eq!(decode_query_component("%4"), "%4");
+  }
+
+  #[test]
+  fn can_read_header_parameters() {
+    let response_with_headers = |headers: serde_json::Value| -> Response {
+      serde_json::from_value(serde_json::json!({
+        "statusCode": 200,
+        "headers": headers,
+        "body": { "contentType": null, "value": {} }
+      }))
+      .expect("should be a valid response")
+    };
+
+    let from_pairs = response_with_headers(serde_json::json!({
+      "asJsonString": "[{\"name\":\"Content-Type\",\"value\":\"text/plain\"},{\"name\":\"Set-Cookie\",\"value\":\"a=1\"},{\"name\":\"set-cookie\",\"value\":\"b=2\"}]"
+    }));
+    let from_object = response_with_headers(serde_json::json!({
+      "asJsonString": "{\"content-type\":\"text/plain\",\"SET-COOKIE\":[\"a=1\",\"b=2\"]}"
+    }));
+    let from_text = response_with_headers(serde_json::json!({
+      "asText": "Content-Type: text/plain\r\nSet-Cookie: a=1\r\nSet-Cookie: b=2"
+    }));
+
+    let expected: BTreeMap<String, Vec<String>> = vec![
+      ("content-type", vec!["text/plain"]),
+      ("set-cookie", vec!["a=1", "b=2"]),
+    ]
+    .into_iter()
+    .map(|(name, values)| {
+      (
+        String::from(name),
+        values.into_iter().map(String::from).collect(),
+      )
+    })
+    .collect();
+
+    assert_eq!(from_pairs.header_parameters(), expected);
+    assert_eq!(from_object.header_parameters(), expected);
+    assert_eq!(from_text.header_parameters(), expected);
+  }
+
+  #[test]
+  fn can_decode_form_bodies() {
+    let body = |content_type: &str, text: &str| -> Option<BodyDescriptor> {
+      let body: Body = serde_json::from_value(serde_json::json!({
+        "contentType": content_type,
+        "value": { "asText": text }
+      }))
+      .expect("should be a valid body");
+      Option::from(&body)
+    };
+
+    let form_body = body(
+      "application/x-www-form-urlencoded; charset=utf-8",
+      "name=Homer+Simpson&email=homer%40example.com&tags[]=dad&age=39",
+    );
+    assert_eq!(
+      form_body,
+      Some(BodyDescriptor::from(serde_json::json!({
+        "age": "39",
+        "email": "homer@example.com",
+        "name": "Homer Simpson",
+        "tags": ["dad"],
+      })))
+    );
+
+    let multipart_body = body(
+      "multipart/form-data; boundary=XyZ",
+      "preamble\r\n--XyZ\r\nContent-Disposition: form-data; name=\"name\"\r\n\r\nHomer\r\n--XyZ\r\nContent-Disposition: form-data; name=\"address\"\r\nContent-Type: application/json\r\n\r\n{\"street\":\"Evergreen Terrace\",\"number\":742}\r\n--XyZ\r\nContent-Disposition: form-data; name=\"avatar\"; filename=\"homer.png\"\r\nContent-Type: image/png\r\n\r\n\u{89}PNG\r\n--XyZ--\r\nepilogue",
+    );
+    let expected_fields = vec![
+      (
+        "address",
+        BodyDescriptor::from(serde_json::json!({ "street": "Evergreen Terrace", "number": 742 })),
+      ),
+      ("avatar", BodyDescriptor::String(None)),
+      ("name", BodyDescriptor::from(serde_json::json!("Homer"))),
+    ];
+    assert_eq!(
+      multipart_body,
+      Some(BodyDescriptor::Object(ObjectDescriptor::from(
+        expected_fields
+          .into_iter()
+          .map(|(name, value)| (String::from(name), value))
+      ))),
+      "files should be described without their contents"
+    );
+
+    assert_eq!(
+      body("text/plain", "name=Homer"),
+      Some(BodyDescriptor::String(None)),
+      "bodies of other content types should remain opaque text"
+    );
+  }
+
+  #[test]
+  fn can_decode_xml_bodies() {
+    let body = |content_type: &str, text: &str| -> Option<BodyDescriptor> {
+      let body: Body = serde_json::from_value(serde_json::json!({
+        "contentType": content_type,
+        "value": { "asText": text }
+      }))
+      .expect("should be a valid body");
+      Option::from(&body)
+    };
+
+    let document = r#"<?xml version="1.0"?>
+      <order xmlns="urn:example:orders" id="42">
+        <customer>Homer</customer>
+        <item sku="donut">Pink glazed</item>
+        <item sku="duff"/>
+        <shipping><express>true</express></shipping>
+      </order>"#;
+    let expected_body = Some(BodyDescriptor::from(serde_json::json!({
+      "order": {
+        "@id": "42",
+        "customer": "Homer",
+        "item": [
+          { "@sku": "donut", "#text": "Pink glazed" },
+          { "@sku": "duff" },
+        ],
+        "shipping": { "express": "true" },
+      }
+    })));
+    assert_eq!(
+      body("application/xml; charset=utf-8", document),
+      expected_body
+    );
+    assert_eq!(body("text/xml", document), expected_body);
+    assert_eq!(body("application/atom+xml", document), expected_body);
+
+    assert_eq!(
+      body("application/xml", "<order><item></order>"),
+      Some(BodyDescriptor::String(None)),
+      "malformed documents should remain opaque text"
+    );
+  }
 }
@@ -48,6 +48,14 @@ impl WithEventContext for SpecEvent {
       SpecEvent::ShapeEvent(evt) => evt.with_event_context(event_context),
     };
   }
+
+  fn without_event_context(&mut self) {
+    match self {
+      SpecEvent::EndpointEvent(evt) => evt.without_event_context(),
+      SpecEvent::RfcEvent(evt) => evt.without_event_context(),
+      SpecEvent::ShapeEvent(evt) => evt.without_event_context(),
+    };
+  }
 }
 
 impl SpecEvent {
@@ -146,4 +154,5 @@ impl From<CommandContext> for EventContext {
 
 pub trait WithEventContext {
   fn with_event_context(&mut self, event_context: EventContext);
+  fn without_event_context(&mut self);
 }
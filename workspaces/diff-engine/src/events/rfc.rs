@@ -55,7 +55,7 @@ pub struct BatchCommitStarted {
 #[serde(rename_all = "camelCase")]
 pub struct BatchCommitEnded {
   pub batch_id: String,
-  event_context: Option<EventContext>,
+  pub(crate) event_context: Option<EventContext>,
 }
 
 impl Event for RfcEvent {
@@ -80,6 +80,16 @@ impl WithEventContext for RfcEvent {
       RfcEvent::BatchCommitEnded(evt) => evt.event_context.replace(event_context),
     };
   }
+
+  fn without_event_context(&mut self) {
+    match self {
+      RfcEvent::ContributionAdded(evt) => evt.event_context.take(),
+      RfcEvent::APINamed(evt) => evt.event_context.take(),
+      RfcEvent::GitStateSet(evt) => evt.event_context.take(),
+      RfcEvent::BatchCommitStarted(evt) => evt.event_context.take(),
+      RfcEvent::BatchCommitEnded(evt) => evt.event_context.take(),
+    };
+  }
 }
 
 impl Event for ContributionAdded {
@@ -130,6 +140,18 @@ impl From<ContributionAdded> for RfcEvent {
   }
 }
 
+impl From<APINamed> for RfcEvent {
+  fn from(event: APINamed) -> Self {
+    Self::APINamed(event)
+  }
+}
+
+impl From<GitStateSet> for RfcEvent {
+  fn from(event: GitStateSet) -> Self {
+    Self::GitStateSet(event)
+  }
+}
+
 // Conversion from commands
 // ------------------------
 
@@ -139,6 +161,8 @@ impl From<RfcCommand> for RfcEvent {
       RfcCommand::StartBatchCommit(command) => RfcEvent::from(BatchCommitStarted::from(command)),
       RfcCommand::EndBatchCommit(command) => RfcEvent::from(BatchCommitEnded::from(command)),
       RfcCommand::AddContribution(command) => RfcEvent::from(ContributionAdded::from(command)),
+      RfcCommand::SetAPIName(command) => RfcEvent::from(APINamed::from(command)),
+      RfcCommand::SetGitState(command) => RfcEvent::from(GitStateSet::from(command)),
       _ => unimplemented!(
         "conversion from rfc command to rfc event not implemented for variant: {:?}",
         rfc_command
@@ -177,3 +201,22 @@ impl From<rfc_commands::AddContribution> for ContributionAdded {
     }
   }
 }
+
+impl From<rfc_commands::SetAPIName> for APINamed {
+  fn from(command: rfc_commands::SetAPIName) -> Self {
+    Self {
+      name: command.new_name,
+      event_context: None,
+    }
+  }
+}
+
+impl From<rfc_commands::SetGitState> for GitStateSet {
+  fn from(command: rfc_commands::SetGitState) -> Self {
+    Self {
+      branch_name: command.branch_name,
+      commit_id: command.commit_id,
+      event_context: None,
+    }
+  }
+}
@@ -1,6 +1,6 @@
 use super::{EventContext, WithEventContext};
 use crate::state::shape::{
-  FieldShapeDescriptor, ParameterShapeDescriptor, ShapeParametersDescriptor,
+  FieldShapeDescriptor, ParameterShapeDescriptor, ShapeParametersDescriptor, StringFormat,
 };
 use crate::{
   commands::shape as shape_commands,
@@ -29,6 +29,9 @@ pub enum ShapeEvent {
   FieldShapeSet(FieldShapeSet),
   FieldRenamed(FieldRenamed),
   FieldRemoved(FieldRemoved),
+
+  StringFormatSet(StringFormatSet),
+  StringEnumSet(StringEnumSet),
 }
 
 #[derive(Deserialize, Debug, PartialEq, Serialize, Clone)]
@@ -128,6 +131,22 @@ pub struct FieldRemoved {
   pub event_context: Option<EventContext>,
 }
 
+#[derive(Deserialize, Debug, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StringFormatSet {
+  pub shape_id: ShapeId,
+  pub format: Option<StringFormat>,
+  pub event_context: Option<EventContext>,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StringEnumSet {
+  pub shape_id: ShapeId,
+  pub values: Option<Vec<String>>,
+  pub event_context: Option<EventContext>,
+}
+
 impl Event for ShapeEvent {
   fn event_type(&self) -> &'static str {
     match self {
@@ -144,6 +163,9 @@ impl Event for ShapeEvent {
       ShapeEvent::FieldShapeSet(evt) => evt.event_type(),
       ShapeEvent::FieldRenamed(evt) => evt.event_type(),
       ShapeEvent::FieldRemoved(evt) => evt.event_type(),
+
+      ShapeEvent::StringFormatSet(evt) => evt.event_type(),
+      ShapeEvent::StringEnumSet(evt) => evt.event_type(),
     }
   }
 }
@@ -164,6 +186,30 @@ impl WithEventContext for ShapeEvent {
       ShapeEvent::FieldShapeSet(evt) => evt.event_context.replace(event_context),
       ShapeEvent::FieldRenamed(evt) => evt.event_context.replace(event_context),
       ShapeEvent::FieldRemoved(evt) => evt.event_context.replace(event_context),
+
+      ShapeEvent::StringFormatSet(evt) => evt.event_context.replace(event_context),
+      ShapeEvent::StringEnumSet(evt) => evt.event_context.replace(event_context),
+    };
+  }
+
+  fn without_event_context(&mut self) {
+    match self {
+      ShapeEvent::ShapeAdded(evt) => evt.event_context.take(),
+      ShapeEvent::BaseShapeSet(evt) => evt.event_context.take(),
+      ShapeEvent::ShapeRenamed(evt) => evt.event_context.take(),
+      ShapeEvent::ShapeRemoved(evt) => evt.event_context.take(),
+      ShapeEvent::ShapeParameterAdded(evt) => evt.event_context.take(),
+      ShapeEvent::ShapeParameterShapeSet(evt) => evt.event_context.take(),
+      ShapeEvent::ShapeParameterRenamed(evt) => evt.event_context.take(),
+      ShapeEvent::ShapeParameterRemoved(evt) => evt.event_context.take(),
+
+      ShapeEvent::FieldAdded(evt) => evt.event_context.take(),
+      ShapeEvent::FieldShapeSet(evt) => evt.event_context.take(),
+      ShapeEvent::FieldRenamed(evt) => evt.event_context.take(),
+      ShapeEvent::FieldRemoved(evt) => evt.event_context.take(),
+
+      ShapeEvent::StringFormatSet(evt) => evt.event_context.take(),
+      ShapeEvent::StringEnumSet(evt) => evt.event_context.take(),
     };
   }
 }
@@ -240,6 +286,18 @@ impl Event for FieldRemoved {
   }
 }
 
+impl Event for StringFormatSet {
+  fn event_type(&self) -> &'static str {
+    "StringFormatSet"
+  }
+}
+
+impl Event for StringEnumSet {
+  fn event_type(&self) -> &'static str {
+    "StringEnumSet"
+  }
+}
+
 impl From<ShapeAdded> for ShapeEvent {
   fn from(event: ShapeAdded) -> Self {
     Self::ShapeAdded(event)
@@ -252,6 +310,18 @@ impl From<BaseShapeSet> for ShapeEvent {
   }
 }
 
+impl From<ShapeRenamed> for ShapeEvent {
+  fn from(event: ShapeRenamed) -> Self {
+    Self::ShapeRenamed(event)
+  }
+}
+
+impl From<ShapeRemoved> for ShapeEvent {
+  fn from(event: ShapeRemoved) -> Self {
+    Self::ShapeRemoved(event)
+  }
+}
+
 impl From<FieldAdded> for ShapeEvent {
   fn from(event: FieldAdded) -> Self {
     Self::FieldAdded(event)
@@ -264,6 +334,18 @@ impl From<FieldShapeSet> for ShapeEvent {
   }
 }
 
+impl From<FieldRenamed> for ShapeEvent {
+  fn from(event: FieldRenamed) -> Self {
+    Self::FieldRenamed(event)
+  }
+}
+
+impl From<FieldRemoved> for ShapeEvent {
+  fn from(event: FieldRemoved) -> Self {
+    Self::FieldRemoved(event)
+  }
+}
+
 impl From<ShapeParameterAdded> for ShapeEvent {
   fn from(event: ShapeParameterAdded) -> Self {
     Self::ShapeParameterAdded(event)
@@ -276,6 +358,30 @@ impl From<ShapeParameterShapeSet> for ShapeEvent {
   }
 }
 
+impl From<ShapeParameterRenamed> for ShapeEvent {
+  fn from(event: ShapeParameterRenamed) -> Self {
+    Self::ShapeParameterRenamed(event)
+  }
+}
+
+impl From<ShapeParameterRemoved> for ShapeEvent {
+  fn from(event: ShapeParameterRemoved) -> Self {
+    Self::ShapeParameterRemoved(event)
+  }
+}
+
+impl From<StringFormatSet> for ShapeEvent {
+  fn from(event: StringFormatSet) -> Self {
+    Self::StringFormatSet(event)
+  }
+}
+
+impl From<StringEnumSet> for ShapeEvent {
+  fn from(event: StringEnumSet) -> Self {
+    Self::StringEnumSet(event)
+  }
+}
+
 // Conversions from commands
 // -------------------------
 
@@ -284,17 +390,26 @@ impl From<ShapeCommand> for ShapeEvent {
     match shape_command {
       ShapeCommand::AddShape(command) => ShapeEvent::from(ShapeAdded::from(command)),
       ShapeCommand::SetBaseShape(command) => ShapeEvent::from(BaseShapeSet::from(command)),
+      ShapeCommand::RenameShape(command) => ShapeEvent::from(ShapeRenamed::from(command)),
+      ShapeCommand::RemoveShape(command) => ShapeEvent::from(ShapeRemoved::from(command)),
       ShapeCommand::AddField(command) => ShapeEvent::from(FieldAdded::from(command)),
+      ShapeCommand::RenameField(command) => ShapeEvent::from(FieldRenamed::from(command)),
+      ShapeCommand::RemoveField(command) => ShapeEvent::from(FieldRemoved::from(command)),
+      ShapeCommand::SetFieldShape(command) => ShapeEvent::from(FieldShapeSet::from(command)),
       ShapeCommand::AddShapeParameter(command) => {
         ShapeEvent::from(ShapeParameterAdded::from(command))
       }
+      ShapeCommand::RenameShapeParameter(command) => {
+        ShapeEvent::from(ShapeParameterRenamed::from(command))
+      }
+      ShapeCommand::RemoveShapeParameter(command) => {
+        ShapeEvent::from(ShapeParameterRemoved::from(command))
+      }
       ShapeCommand::SetParameterShape(command) => {
         ShapeEvent::from(ShapeParameterShapeSet::from(command))
       }
-      _ => unimplemented!(
-        "conversion from shape command to shape event not implemented for variant: {:?}",
-        shape_command
-      ),
+      ShapeCommand::SetStringFormat(command) => ShapeEvent::from(StringFormatSet::from(command)),
+      ShapeCommand::SetStringEnum(command) => ShapeEvent::from(StringEnumSet::from(command)),
     }
   }
 }
@@ -321,6 +436,25 @@ impl From<shape_commands::SetBaseShape> for BaseShapeSet {
   }
 }
 
+impl From<shape_commands::RenameShape> for ShapeRenamed {
+  fn from(command: shape_commands::RenameShape) -> Self {
+    Self {
+      shape_id: command.shape_id,
+      name: command.name,
+      event_context: None,
+    }
+  }
+}
+
+impl From<shape_commands::RemoveShape> for ShapeRemoved {
+  fn from(command: shape_commands::RemoveShape) -> Self {
+    Self {
+      shape_id: command.shape_id,
+      event_context: None,
+    }
+  }
+}
+
 impl From<shape_commands::AddField> for FieldAdded {
   fn from(command: shape_commands::AddField) -> Self {
     Self {
@@ -342,6 +476,25 @@ impl From<shape_commands::SetFieldShape> for FieldShapeSet {
   }
 }
 
+impl From<shape_commands::RenameField> for FieldRenamed {
+  fn from(command: shape_commands::RenameField) -> Self {
+    Self {
+      field_id: command.field_id,
+      name: command.name,
+      event_context: None,
+    }
+  }
+}
+
+impl From<shape_commands::RemoveField> for FieldRemoved {
+  fn from(command: shape_commands::RemoveField) -> Self {
+    Self {
+      field_id: command.field_id,
+      event_context: None,
+    }
+  }
+}
+
 impl From<shape_commands::AddShapeParameter> for ShapeParameterAdded {
   fn from(command: shape_commands::AddShapeParameter) -> Self {
     Self {
@@ -366,3 +519,42 @@ impl From<shape_commands::SetParameterShape> for ShapeParameterShapeSet {
     }
   }
 }
+
+impl From<shape_commands::RenameShapeParameter> for ShapeParameterRenamed {
+  fn from(command: shape_commands::RenameShapeParameter) -> Self {
+    Self {
+      shape_parameter_id: command.shape_parameter_id,
+      name: command.name,
+      event_context: None,
+    }
+  }
+}
+
+impl From<shape_commands::RemoveShapeParameter> for ShapeParameterRemoved {
+  fn from(command: shape_commands::RemoveShapeParameter) -> Self {
+    Self {
+      shape_parameter_id: command.shape_parameter_id,
+      event_context: None,
+    }
+  }
+}
+
+impl From<shape_commands::SetStringFormat> for StringFormatSet {
+  fn from(command: shape_commands::SetStringFormat) -> Self {
+    Self {
+      shape_id: command.shape_id,
+      format: command.format,
+      event_context: None,
+    }
+  }
+}
+
+impl From<shape_commands::SetStringEnum> for StringEnumSet {
+  fn from(command: shape_commands::SetStringEnum) -> Self {
+    Self {
+      shape_id: command.shape_id,
+      values: command.values,
+      event_context: None,
+    }
+  }
+}
@@ -0,0 +1,116 @@
+use std::collections::HashSet;
+
+// Hop-by-hop headers describe a single connection rather than the API, transport headers are set by
+// clients, servers and proxies for any request, and the body headers are already covered by diffing
+// request and response bodies.
+const DEFAULT_IGNORED_HEADERS: &[&str] = &[
+  // hop-by-hop
+  "connection",
+  "keep-alive",
+  "proxy-authenticate",
+  "proxy-authorization",
+  "proxy-connection",
+  "te",
+  "trailer",
+  "transfer-encoding",
+  "upgrade",
+  // transport
+  "accept",
+  "accept-encoding",
+  "accept-language",
+  "age",
+  "cache-control",
+  "date",
+  "expect",
+  "forwarded",
+  "host",
+  "origin",
+  "pragma",
+  "referer",
+  "server",
+  "user-agent",
+  "vary",
+  "via",
+  "x-forwarded-for",
+  "x-forwarded-host",
+  "x-forwarded-proto",
+  // body
+  "content-encoding",
+  "content-length",
+  "content-type",
+];
+
+/// Options for diffing interactions against a spec
+#[derive(Debug, Clone)]
+pub struct InteractionDiffConfig {
+  ignored_headers: HashSet<String>,
+  all_undocumented_headers: bool,
+}
+
+impl InteractionDiffConfig {
+  /// Ignore a header when diffing, both in requests and responses. Names are case-insensitive.
+  pub fn with_ignored_header(mut self, name: impl AsRef<str>) -> Self {
+    self
+      .ignored_headers
+      .insert(name.as_ref().trim().to_lowercase());
+    self
+  }
+
+  /// Don't ignore any headers, not even the defaults
+  pub fn without_ignored_headers(mut self) -> Self {
+    self.ignored_headers.clear();
+    self
+  }
+
+  /// Report undocumented headers of every operation, rather than only of operations that document
+  /// headers themselves
+  pub fn with_all_undocumented_headers(mut self) -> Self {
+    self.all_undocumented_headers = true;
+    self
+  }
+
+  pub fn is_header_ignored(&self, name: &str) -> bool {
+    self.ignored_headers.contains(&name.to_lowercase())
+  }
+
+  pub fn reports_all_undocumented_headers(&self) -> bool {
+    self.all_undocumented_headers
+  }
+}
+
+impl Default for InteractionDiffConfig {
+  fn default() -> Self {
+    InteractionDiffConfig {
+      ignored_headers: DEFAULT_IGNORED_HEADERS
+        .iter()
+        .map(|name| String::from(*name))
+        .collect(),
+      all_undocumented_headers: false,
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn ignores_hop_by_hop_and_transport_headers_by_default() {
+    let config = InteractionDiffConfig::default();
+    assert!(config.is_header_ignored("Connection"));
+    assert!(config.is_header_ignored("transfer-encoding"));
+    assert!(config.is_header_ignored("Host"));
+    assert!(config.is_header_ignored("user-agent"));
+    assert!(config.is_header_ignored("Date"));
+    assert!(!config.is_header_ignored("x-request-id"));
+  }
+
+  #[test]
+  fn can_ignore_additional_headers() {
+    let config = InteractionDiffConfig::default().with_ignored_header("X-Request-Id");
+    assert!(config.is_header_ignored("x-request-id"));
+
+    let config = config.without_ignored_headers();
+    assert!(!config.is_header_ignored("connection"));
+  }
+}
@@ -0,0 +1,38 @@
+use super::parameters::{diff_parameter_values, ParameterDiff};
+use super::result::{InteractionDiffResult, MatchedHeader};
+use crate::events::http_interaction::HttpInteraction;
+use crate::projections::ShapeProjection;
+
+/// Diff the observed values of a documented request or response header against its shape,
+/// reporting it as missing when it's required but not observed.
+pub fn diff_header(
+  shape_projection: &ShapeProjection,
+  interaction: &HttpInteraction,
+  matched: MatchedHeader,
+) -> Vec<InteractionDiffResult> {
+  let is_response_header = matched
+    .interaction_trail
+    .get_response_header_name()
+    .is_some();
+  let observed_headers = if is_response_header {
+    interaction.response.header_parameters()
+  } else {
+    interaction.request.header_parameters()
+  };
+  let observed_values = matched
+    .interaction_trail
+    .get_header_name()
+    .and_then(|name| observed_headers.get(name));
+
+  diff_parameter_values(shape_projection, &matched.shape_id, observed_values)
+    .into_iter()
+    .map(|parameter_diff| match parameter_diff {
+      ParameterDiff::Missing => {
+        InteractionDiffResult::MissingHeader(matched.clone().into_missing())
+      }
+      ParameterDiff::UnmatchedShape(shape_diff) => {
+        InteractionDiffResult::UnmatchedHeaderShape(matched.clone().into_shape_diff(shape_diff))
+      }
+    })
+    .collect()
+}
@@ -1,18 +1,30 @@
 use crate::events::http_interaction::{Body, HttpInteraction};
 use crate::learn_shape::{observe_body_trails, TrailObservationsResult, TrailValues};
+use crate::projections::endpoint::{HEADER_PARAMETER_LOCATION, QUERY_PARAMETER_LOCATION};
 use crate::projections::{EndpointProjection, SpecProjection};
 use crate::protos::shapehash::ShapeDescriptor;
 use crate::queries::endpoint::EndpointQueries;
 use crate::shapes::diff as diff_shape;
 use crate::shapes::ShapeDiffResult;
 use crate::state::body::BodyDescriptor;
+use std::collections::BTreeSet;
 
+mod config;
+mod headers;
+mod parameters;
+mod query;
 pub mod result;
+mod severity;
 mod traverser;
 mod visitors;
 
+pub use config::InteractionDiffConfig;
 use result::InteractionTrail;
-pub use result::{BodyAnalysisLocation, BodyAnalysisResult, InteractionDiffResult};
+pub use result::{
+  BodyAnalysisLocation, BodyAnalysisResult, InteractionDiffResult, ParameterAnalysisLocation,
+  ParameterAnalysisResult,
+};
+pub use severity::{DiffSeverity, DiffSeverityConfig};
 use visitors::{InteractionVisitors, PathVisitor};
 
 /// Compute diffs based on a spec and an interaction.
@@ -23,22 +35,52 @@ use visitors::{InteractionVisitors, PathVisitor};
 pub fn diff(
   spec_projection: &SpecProjection,
   http_interaction: HttpInteraction,
+) -> Vec<InteractionDiffResult> {
+  diff_with_config(
+    spec_projection,
+    http_interaction,
+    &InteractionDiffConfig::default(),
+  )
+}
+
+/// Compute diffs based on a spec and an interaction, like `diff`, with options for what to diff.
+pub fn diff_with_config(
+  spec_projection: &SpecProjection,
+  http_interaction: HttpInteraction,
+  config: &InteractionDiffConfig,
 ) -> Vec<InteractionDiffResult> {
   let endpoint_projection = spec_projection.endpoint();
   let endpoint_queries = EndpointQueries::new(endpoint_projection);
   let interaction_traverser = traverser::Traverser::new(&endpoint_queries);
-  let mut diff_visitors = visitors::diff::DiffVisitors::new();
+  let mut diff_visitors = visitors::diff::DiffVisitors::with_config(config);
 
   interaction_traverser.traverse(&http_interaction, &mut diff_visitors);
 
   let results = diff_visitors.take_results().unwrap();
+  let query_string_parameter_names =
+    query::query_string_parameter_names(spec_projection.shape(), &results);
+  let observed_query_parameters = http_interaction.request.query_parameters();
 
   results
     .into_iter()
     .flat_map(move |result| match result {
+      InteractionDiffResult::UnmatchedQueryParameter(diff)
+        if diff
+          .interaction_trail
+          .get_query_parameter_name()
+          .is_some_and(|name| query_string_parameter_names.contains(name)) =>
+      {
+        vec![]
+      }
+      InteractionDiffResult::MatchedQueryParameter(result) => {
+        query::diff_query_parameter(spec_projection.shape(), &observed_query_parameters, result)
+      }
+      InteractionDiffResult::MatchedHeader(result) => {
+        headers::diff_header(spec_projection.shape(), &http_interaction, result)
+      }
       InteractionDiffResult::MatchedRequestBodyContentType(result) => {
         // eprintln!("shape diffing for matched a request body content type");
-        let body = &http_interaction.request.body.value;
+        let body = &http_interaction.request.body;
         let shape_diff_results =
           diff_shape(spec_projection.shape(), body.into(), &result.root_shape_id);
         shape_diff_results
@@ -55,7 +97,7 @@ pub fn diff(
         //   "interaction-diff: shape diffing for matched a response body content type: {:?}",
         //   &http_interaction.response.body
         // );
-        let body = &http_interaction.response.body.value;
+        let body = &http_interaction.response.body;
         let shape_diff_results =
           diff_shape(spec_projection.shape(), body.into(), &result.root_shape_id);
         shape_diff_results
@@ -95,7 +137,7 @@ pub fn analyze_undocumented_bodies(
   results.into_iter().filter_map(move |result| match result {
     InteractionDiffResult::UnmatchedRequestBodyContentType(diff) => {
       let body = &interaction.request.body;
-      let trail_observations = observe_body_trails(&body.value);
+      let trail_observations = observe_body_trails(body);
 
       Some(BodyAnalysisResult {
         body_location: BodyAnalysisLocation::from(diff),
@@ -104,7 +146,7 @@ pub fn analyze_undocumented_bodies(
     }
     InteractionDiffResult::UnmatchedResponseBodyContentType(diff) => {
       let body = &interaction.response.body;
-      let trail_observations = observe_body_trails(&body.value);
+      let trail_observations = observe_body_trails(body);
 
       Some(BodyAnalysisResult {
         body_location: BodyAnalysisLocation::from(diff),
@@ -115,6 +157,96 @@ pub fn analyze_undocumented_bodies(
   })
 }
 
+/// Analyses the query parameters and request headers of interactions with documented operations
+/// that aren't documented themselves yet. Every interaction of an operation yields a result, even
+/// when nothing undocumented was observed, so that merging the results of many interactions
+/// tells which parameters are optional, next to the types of their values.
+pub fn analyze_undocumented_parameters(
+  spec_projection: &SpecProjection,
+  interaction: HttpInteraction,
+) -> impl Iterator<Item = ParameterAnalysisResult> {
+  let endpoint_projection = spec_projection.endpoint();
+  let endpoint_queries = EndpointQueries::new(endpoint_projection);
+  let interaction_traverser = traverser::Traverser::new(&endpoint_queries);
+  // any undocumented header is worth learning, whether the operation documents headers yet or not
+  let config = InteractionDiffConfig::default().with_all_undocumented_headers();
+  let mut diff_visitors = visitors::diff::DiffVisitors::with_config(&config);
+
+  interaction_traverser.traverse(&interaction, &mut diff_visitors);
+
+  let results = diff_visitors.take_results().unwrap();
+  let query_string_parameter_names =
+    query::query_string_parameter_names(spec_projection.shape(), &results);
+
+  let mut undocumented_query_parameters = BTreeSet::new();
+  let mut undocumented_headers = BTreeSet::new();
+  for result in &results {
+    match result {
+      InteractionDiffResult::UnmatchedQueryParameter(diff) => {
+        if let Some(name) = diff.interaction_trail.get_query_parameter_name() {
+          if !query_string_parameter_names.contains(name) {
+            undocumented_query_parameters.insert(name.clone());
+          }
+        }
+      }
+      InteractionDiffResult::UnmatchedHeader(diff) => {
+        if let Some(name) = diff.interaction_trail.get_request_header_name() {
+          undocumented_headers.insert(name.clone());
+        }
+      }
+      _ => {}
+    }
+  }
+
+  // parameters are documented by path and method, so any request of the operation will do
+  let operation = endpoint_queries
+    .resolve_interaction_path(&interaction)
+    .and_then(|path_id| {
+      let (request_id, _) = endpoint_queries
+        .resolve_operations(&interaction, path_id)
+        .next()?;
+      Some((String::from(path_id), request_id.clone()))
+    });
+
+  let observed = vec![
+    (
+      QUERY_PARAMETER_LOCATION,
+      interaction.request.query_parameters(),
+      undocumented_query_parameters,
+    ),
+    (
+      HEADER_PARAMETER_LOCATION,
+      interaction.request.header_parameters(),
+      undocumented_headers,
+    ),
+  ];
+
+  let method = interaction.request.method.clone();
+  observed.into_iter().filter_map(
+    move |(location, mut observed_parameters, undocumented_names)| {
+      let (path_id, request_id) = operation.clone()?;
+      let observations_by_name = undocumented_names
+        .into_iter()
+        .filter_map(|name| {
+          let values = observed_parameters.remove(&name)?;
+          let body = parameters::observed_values_body(&values);
+          Some((name, observe_body_trails(body)))
+        })
+        .collect();
+
+      Some(ParameterAnalysisResult {
+        parameter_location: ParameterAnalysisLocation {
+          path_id,
+          method: method.clone(),
+          request_id,
+          location: String::from(location),
+        },
+        observations_by_name,
+      })
+    },
+  )
+}
+
 pub fn analyze_documented_bodies(
   spec_projection: &SpecProjection,
   interaction: HttpInteraction,
@@ -132,7 +264,7 @@ pub fn analyze_documented_bodies(
   results.into_iter().filter_map(move |result| match result {
     InteractionDiffResult::MatchedRequestBodyContentType(diff) => {
       let body = &interaction.request.body;
-      let trail_observations = observe_body_trails(&body.value);
+      let trail_observations = observe_body_trails(body);
 
       Some(BodyAnalysisResult {
         body_location: BodyAnalysisLocation::from(diff),
@@ -141,7 +273,7 @@ pub fn analyze_documented_bodies(
     }
     InteractionDiffResult::MatchedResponseBodyContentType(diff) => {
       let body = &interaction.response.body;
-      let trail_observations = observe_body_trails(&body.value);
+      let trail_observations = observe_body_trails(body);
 
       Some(BodyAnalysisResult {
         body_location: BodyAnalysisLocation::from(diff),
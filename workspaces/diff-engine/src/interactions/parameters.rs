@@ -0,0 +1,183 @@
+use crate::projections::ShapeProjection;
+use crate::queries::shape::ShapeQueries;
+use crate::shapes::{diff as diff_shape, ShapeDiffResult};
+use crate::state::body::{BodyDescriptor, ItemsDescriptor, NumberDescriptor, StringDescriptor};
+use crate::state::shape::{ShapeId, ShapeKind};
+use std::collections::HashSet;
+
+#[derive(Debug)]
+pub enum ParameterDiff {
+  Missing,
+  UnmatchedShape(ShapeDiffResult),
+}
+
+/// Diff the observed values of a documented parameter (like a query parameter or header) against
+/// its shape. Parameters that weren't observed are missing, unless their shape is optional.
+pub fn diff_parameter_values(
+  shape_projection: &ShapeProjection,
+  shape_id: &ShapeId,
+  observed_values: Option<&Vec<String>>,
+) -> Vec<ParameterDiff> {
+  let shape_queries = ShapeQueries::new(shape_projection);
+  let (resolved_shape_id, shape_kind) = resolve_shape_kind(&shape_queries, shape_id);
+
+  match observed_values {
+    None if matches!(shape_kind, ShapeKind::OptionalKind) => vec![],
+    None => vec![ParameterDiff::Missing],
+    Some(values) => {
+      // observed parameters are present by definition, so diff optional ones by their inner shape
+      let shape_id = match shape_kind {
+        ShapeKind::OptionalKind => parameter_shape(&shape_queries, &resolved_shape_id, &shape_kind),
+        _ => None,
+      }
+      .unwrap_or_else(|| shape_id.clone());
+
+      let body = values_body(&shape_queries, &shape_id, values);
+      diff_shape(shape_projection, Some(body), &shape_id)
+        .into_iter()
+        .map(ParameterDiff::UnmatchedShape)
+        .collect()
+    }
+  }
+}
+
+/// The names and shapes of the fields of a shape, when it resolves to an object
+pub fn object_fields(
+  shape_queries: &ShapeQueries,
+  shape_id: &ShapeId,
+) -> Option<Vec<(String, ShapeId)>> {
+  let (shape_id, shape_kind) = resolve_shape_kind(shape_queries, shape_id);
+  if !matches!(shape_kind, ShapeKind::ObjectKind) {
+    return None;
+  }
+
+  let fields = shape_queries
+    .resolve_shape_field_id_and_names(&shape_id)
+    .filter_map(|(field_id, field_name)| {
+      let field_shape_id = shape_queries.resolve_field_shape_node(field_id)?;
+      Some((field_name.clone(), field_shape_id))
+    })
+    .collect();
+  Some(fields)
+}
+
+/// Describe observed parameter values as a body, interpreting them as the types they look like.
+/// Parameters that were observed more than once are described as a list of their values.
+pub fn observed_values_body(values: &[String]) -> BodyDescriptor {
+  match values {
+    [value] => inferred_value_body(value),
+    values => BodyDescriptor::Array(ItemsDescriptor::from(
+      values.iter().map(|value| inferred_value_body(value)),
+    )),
+  }
+}
+
+// Parameter values are always observed as strings. Interpret them as the types the documented
+// shape expects where possible, so they can be diffed like any other body.
+fn values_body(
+  shape_queries: &ShapeQueries,
+  shape_id: &ShapeId,
+  values: &[String],
+) -> BodyDescriptor {
+  let (shape_id, shape_kind) = resolve_shape_kind(shape_queries, shape_id);
+
+  match shape_kind {
+    ShapeKind::OptionalKind | ShapeKind::NullableKind => {
+      match parameter_shape(shape_queries, &shape_id, &shape_kind) {
+        Some(inner_shape_id) => values_body(shape_queries, &inner_shape_id, values),
+        None => values_array(shape_queries, None, values),
+      }
+    }
+    ShapeKind::ListKind => {
+      let item_shape_id = parameter_shape(shape_queries, &shape_id, &shape_kind);
+      values_array(shape_queries, item_shape_id.as_ref(), values)
+    }
+    _ if values.len() == 1 => value_body(shape_queries, Some(&shape_id), &values[0]),
+    _ => values_array(shape_queries, Some(&shape_id), values),
+  }
+}
+
+fn values_array(
+  shape_queries: &ShapeQueries,
+  item_shape_id: Option<&ShapeId>,
+  values: &[String],
+) -> BodyDescriptor {
+  let items = values
+    .iter()
+    .map(|value| value_body(shape_queries, item_shape_id, value));
+  BodyDescriptor::Array(ItemsDescriptor::from(items))
+}
+
+fn value_body(
+  shape_queries: &ShapeQueries,
+  shape_id: Option<&ShapeId>,
+  value: &str,
+) -> BodyDescriptor {
+  let shape_kind = shape_id.map(|shape_id| {
+    let (shape_id, shape_kind) = resolve_shape_kind(shape_queries, shape_id);
+    match shape_kind {
+      ShapeKind::OptionalKind | ShapeKind::NullableKind => {
+        match parameter_shape(shape_queries, &shape_id, &shape_kind) {
+          Some(inner_shape_id) => resolve_shape_kind(shape_queries, &inner_shape_id).1,
+          None => ShapeKind::UnknownKind,
+        }
+      }
+      shape_kind => shape_kind,
+    }
+  });
+
+  let number = NumberDescriptor::parse(value);
+  let is_boolean = value == "true" || value == "false";
+
+  match shape_kind {
+    Some(ShapeKind::StringKind) => BodyDescriptor::String(Some(StringDescriptor::from(value))),
+    Some(ShapeKind::NumberKind) | Some(ShapeKind::IntegerKind) if number.is_some() => {
+      BodyDescriptor::Number(number)
+    }
+    Some(ShapeKind::BooleanKind) if is_boolean => BodyDescriptor::Boolean,
+    Some(ShapeKind::NumberKind) | Some(ShapeKind::IntegerKind) | Some(ShapeKind::BooleanKind) => {
+      BodyDescriptor::String(Some(StringDescriptor::from(value)))
+    }
+    _ => inferred_value_body(value),
+  }
+}
+
+fn inferred_value_body(value: &str) -> BodyDescriptor {
+  if let Some(number) = NumberDescriptor::parse(value) {
+    BodyDescriptor::Number(Some(number))
+  } else if value == "true" || value == "false" {
+    BodyDescriptor::Boolean
+  } else {
+    BodyDescriptor::String(Some(StringDescriptor::from(value)))
+  }
+}
+
+fn resolve_shape_kind(shape_queries: &ShapeQueries, shape_id: &ShapeId) -> (ShapeId, ShapeKind) {
+  let mut aliased_shape_id = shape_id;
+  let mut seen = HashSet::new();
+  while let Some(base_shape_id) = shape_queries.resolve_base_shape_id(aliased_shape_id) {
+    if !seen.insert(base_shape_id) {
+      break;
+    }
+    aliased_shape_id = base_shape_id;
+  }
+
+  let shape_kind = shape_queries
+    .resolve_to_core_shape(aliased_shape_id)
+    .clone();
+  (aliased_shape_id.clone(), shape_kind)
+}
+
+fn parameter_shape(
+  shape_queries: &ShapeQueries,
+  shape_id: &ShapeId,
+  shape_kind: &ShapeKind,
+) -> Option<ShapeId> {
+  let shape_parameter_id = shape_kind.get_parameter_descriptor()?.shape_parameter_id;
+
+  shape_queries
+    .resolve_parameters_to_shapes(shape_id)
+    .into_iter()
+    .find(|(parameter_id, _)| parameter_id == shape_parameter_id)
+    .map(|(_, parameter_shape_id)| parameter_shape_id)
+}
@@ -0,0 +1,97 @@
+use super::parameters::{diff_parameter_values, object_fields, ParameterDiff};
+use super::result::{
+  InteractionDiffResult, InteractionTrail, InteractionTrailPathComponent, MatchedQueryParameter,
+};
+use crate::projections::ShapeProjection;
+use crate::queries::shape::ShapeQueries;
+use std::collections::{BTreeMap, HashSet};
+
+/// Names of the query parameters described by the fields of object shaped query parameters. Those
+/// describe the query string as a whole, rather than a single parameter of it.
+pub fn query_string_parameter_names<'a>(
+  shape_projection: &ShapeProjection,
+  results: impl IntoIterator<Item = &'a InteractionDiffResult>,
+) -> HashSet<String> {
+  let shape_queries = ShapeQueries::new(shape_projection);
+
+  results
+    .into_iter()
+    .filter_map(|result| match result {
+      InteractionDiffResult::MatchedQueryParameter(matched) => {
+        object_fields(&shape_queries, &matched.shape_id)
+      }
+      _ => None,
+    })
+    .flatten()
+    .map(|(name, _)| name)
+    .collect()
+}
+
+/// Diff the observed values of a documented query parameter against its shape, reporting it as
+/// missing when it's required but not observed.
+pub fn diff_query_parameter(
+  shape_projection: &ShapeProjection,
+  observed_parameters: &BTreeMap<String, Vec<String>>,
+  matched: MatchedQueryParameter,
+) -> Vec<InteractionDiffResult> {
+  let shape_queries = ShapeQueries::new(shape_projection);
+
+  match object_fields(&shape_queries, &matched.shape_id) {
+    Some(fields) => fields
+      .into_iter()
+      .flat_map(|(name, shape_id)| {
+        let field_parameter = MatchedQueryParameter::new(
+          interaction_trail_with_name(&matched.interaction_trail, name),
+          matched.requests_trail.clone(),
+          shape_id,
+        );
+        diff_observed_values(shape_projection, observed_parameters, field_parameter)
+      })
+      .collect(),
+    None => diff_observed_values(shape_projection, observed_parameters, matched),
+  }
+}
+
+fn diff_observed_values(
+  shape_projection: &ShapeProjection,
+  observed_parameters: &BTreeMap<String, Vec<String>>,
+  matched: MatchedQueryParameter,
+) -> Vec<InteractionDiffResult> {
+  let observed_values = matched
+    .interaction_trail
+    .get_query_parameter_name()
+    .and_then(|name| observed_parameters.get(name));
+
+  diff_parameter_values(shape_projection, &matched.shape_id, observed_values)
+    .into_iter()
+    .map(|parameter_diff| match parameter_diff {
+      ParameterDiff::Missing => {
+        InteractionDiffResult::MissingQueryParameter(matched.clone().into_missing())
+      }
+      ParameterDiff::UnmatchedShape(shape_diff) => {
+        InteractionDiffResult::UnmatchedQueryParameterShape(
+          matched.clone().into_shape_diff(shape_diff),
+        )
+      }
+    })
+    .collect()
+}
+
+fn interaction_trail_with_name(
+  interaction_trail: &InteractionTrail,
+  name: String,
+) -> InteractionTrail {
+  let mut path = interaction_trail
+    .path
+    .iter()
+    .filter(|component| {
+      !matches!(
+        component,
+        InteractionTrailPathComponent::QueryParameter { .. }
+      )
+    })
+    .cloned()
+    .collect::<Vec<_>>();
+  path.push(InteractionTrailPathComponent::QueryParameter { name });
+  InteractionTrail::new(path)
+}
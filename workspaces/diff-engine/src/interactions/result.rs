@@ -1,9 +1,12 @@
 use crate::events::http_interaction::HttpInteraction;
 use crate::learn_shape::TrailObservationsResult;
 use crate::shapes::{JsonTrail, ShapeDiffResult};
-use crate::state::endpoint::{PathComponentId, RequestId, ResponseId, ShapeId};
+use crate::state::endpoint::{PathComponentId, RequestId, RequestParameterId, ResponseId, ShapeId};
+use crate::state::media_type::normalize_content_type;
 use serde::{Deserialize, Serialize};
 use std::collections::hash_map::{DefaultHasher, HashMap};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::hash::{Hash, Hasher};
 
 #[derive(Debug, Deserialize, Serialize, Hash)]
@@ -13,6 +16,12 @@ pub enum InteractionDiffResult {
   UnmatchedRequestBodyShape(UnmatchedRequestBodyShape),
   UnmatchedResponseBodyContentType(UnmatchedResponseBodyContentType),
   UnmatchedResponseBodyShape(UnmatchedResponseBodyShape),
+  UnmatchedQueryParameter(UnmatchedQueryParameter),
+  MissingQueryParameter(MissingQueryParameter),
+  UnmatchedQueryParameterShape(UnmatchedQueryParameterShape),
+  UnmatchedHeader(UnmatchedHeader),
+  MissingHeader(MissingHeader),
+  UnmatchedHeaderShape(UnmatchedHeaderShape),
   //
   // Matches
   // -------
@@ -20,6 +29,10 @@ pub enum InteractionDiffResult {
   MatchedRequestBodyContentType(MatchedRequestBodyContentType),
   #[serde(skip)]
   MatchedResponseBodyContentType(MatchedResponseBodyContentType),
+  #[serde(skip)]
+  MatchedQueryParameter(MatchedQueryParameter),
+  #[serde(skip)]
+  MatchedHeader(MatchedHeader),
 }
 
 impl InteractionDiffResult {
@@ -29,6 +42,31 @@ impl InteractionDiffResult {
     format!("{:x}", hash_state.finish())
   }
 
+  /// The name of the kind of result, as it's serialized
+  pub fn diff_type(&self) -> &'static str {
+    match self {
+      InteractionDiffResult::UnmatchedRequestUrl(_) => "UnmatchedRequestUrl",
+      InteractionDiffResult::UnmatchedRequestBodyContentType(_) => {
+        "UnmatchedRequestBodyContentType"
+      }
+      InteractionDiffResult::UnmatchedRequestBodyShape(_) => "UnmatchedRequestBodyShape",
+      InteractionDiffResult::UnmatchedResponseBodyContentType(_) => {
+        "UnmatchedResponseBodyContentType"
+      }
+      InteractionDiffResult::UnmatchedResponseBodyShape(_) => "UnmatchedResponseBodyShape",
+      InteractionDiffResult::UnmatchedQueryParameter(_) => "UnmatchedQueryParameter",
+      InteractionDiffResult::MissingQueryParameter(_) => "MissingQueryParameter",
+      InteractionDiffResult::UnmatchedQueryParameterShape(_) => "UnmatchedQueryParameterShape",
+      InteractionDiffResult::UnmatchedHeader(_) => "UnmatchedHeader",
+      InteractionDiffResult::MissingHeader(_) => "MissingHeader",
+      InteractionDiffResult::UnmatchedHeaderShape(_) => "UnmatchedHeaderShape",
+      InteractionDiffResult::MatchedRequestBodyContentType(_) => "MatchedRequestBodyContentType",
+      InteractionDiffResult::MatchedResponseBodyContentType(_) => "MatchedResponseBodyContentType",
+      InteractionDiffResult::MatchedQueryParameter(_) => "MatchedQueryParameter",
+      InteractionDiffResult::MatchedHeader(_) => "MatchedHeader",
+    }
+  }
+
   pub fn interaction_trail(&self) -> &InteractionTrail {
     match self {
       InteractionDiffResult::UnmatchedRequestUrl(diff) => &diff.interaction_trail,
@@ -36,8 +74,16 @@ impl InteractionDiffResult {
       InteractionDiffResult::UnmatchedRequestBodyShape(diff) => &diff.interaction_trail,
       InteractionDiffResult::UnmatchedResponseBodyContentType(diff) => &diff.interaction_trail,
       InteractionDiffResult::UnmatchedResponseBodyShape(diff) => &diff.interaction_trail,
+      InteractionDiffResult::UnmatchedQueryParameter(diff) => &diff.interaction_trail,
+      InteractionDiffResult::MissingQueryParameter(diff) => &diff.interaction_trail,
+      InteractionDiffResult::UnmatchedQueryParameterShape(diff) => &diff.interaction_trail,
+      InteractionDiffResult::UnmatchedHeader(diff) => &diff.interaction_trail,
+      InteractionDiffResult::MissingHeader(diff) => &diff.interaction_trail,
+      InteractionDiffResult::UnmatchedHeaderShape(diff) => &diff.interaction_trail,
       InteractionDiffResult::MatchedRequestBodyContentType(diff) => &diff.interaction_trail,
       InteractionDiffResult::MatchedResponseBodyContentType(diff) => &diff.interaction_trail,
+      InteractionDiffResult::MatchedQueryParameter(diff) => &diff.interaction_trail,
+      InteractionDiffResult::MatchedHeader(diff) => &diff.interaction_trail,
     }
   }
 
@@ -48,8 +94,16 @@ impl InteractionDiffResult {
       InteractionDiffResult::UnmatchedRequestBodyShape(diff) => &diff.requests_trail,
       InteractionDiffResult::UnmatchedResponseBodyContentType(diff) => &diff.requests_trail,
       InteractionDiffResult::UnmatchedResponseBodyShape(diff) => &diff.requests_trail,
+      InteractionDiffResult::UnmatchedQueryParameter(diff) => &diff.requests_trail,
+      InteractionDiffResult::MissingQueryParameter(diff) => &diff.requests_trail,
+      InteractionDiffResult::UnmatchedQueryParameterShape(diff) => &diff.requests_trail,
+      InteractionDiffResult::UnmatchedHeader(diff) => &diff.requests_trail,
+      InteractionDiffResult::MissingHeader(diff) => &diff.requests_trail,
+      InteractionDiffResult::UnmatchedHeaderShape(diff) => &diff.requests_trail,
       InteractionDiffResult::MatchedRequestBodyContentType(diff) => &diff.requests_trail,
       InteractionDiffResult::MatchedResponseBodyContentType(diff) => &diff.requests_trail,
+      InteractionDiffResult::MatchedQueryParameter(diff) => &diff.requests_trail,
+      InteractionDiffResult::MatchedHeader(diff) => &diff.requests_trail,
     }
   }
 
@@ -57,6 +111,8 @@ impl InteractionDiffResult {
     let shape_diff_result = match self {
       InteractionDiffResult::UnmatchedRequestBodyShape(diff) => Some(&diff.shape_diff_result),
       InteractionDiffResult::UnmatchedResponseBodyShape(diff) => Some(&diff.shape_diff_result),
+      InteractionDiffResult::UnmatchedQueryParameterShape(diff) => Some(&diff.shape_diff_result),
+      InteractionDiffResult::UnmatchedHeaderShape(diff) => Some(&diff.shape_diff_result),
       _ => None,
     }?;
 
@@ -67,6 +123,91 @@ impl InteractionDiffResult {
   }
 }
 
+// A human-readable explanation of the result, relative to the interaction it was found in
+impl fmt::Display for InteractionDiffResult {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let trail = self.interaction_trail();
+    let status_code = trail.path.iter().find_map(|component| match component {
+      InteractionTrailPathComponent::ResponseStatusCode { status_code }
+      | InteractionTrailPathComponent::ResponseBody { status_code, .. }
+      | InteractionTrailPathComponent::ResponseHeader { status_code, .. } => Some(*status_code),
+      _ => None,
+    });
+    let response = || match status_code {
+      Some(status_code) => format!("response {}", status_code),
+      None => String::from("response"),
+    };
+    let content_type = |content_type: Option<&String>| match content_type {
+      Some(content_type) => format!("content type `{}`", content_type),
+      None => String::from("no content type"),
+    };
+    let header = || {
+      let name = trail.get_header_name().map(String::as_str).unwrap_or("");
+      match trail.get_response_header_name() {
+        Some(_) => format!("{} header `{}`", response(), name),
+        None => format!("request header `{}`", name),
+      }
+    };
+    let query_parameter = || {
+      let name = trail
+        .get_query_parameter_name()
+        .map(String::as_str)
+        .unwrap_or("");
+      format!("query parameter `{}`", name)
+    };
+
+    match self {
+      InteractionDiffResult::UnmatchedRequestUrl(_) => write!(f, "undocumented request url"),
+      InteractionDiffResult::UnmatchedRequestBodyContentType(_) => write!(
+        f,
+        "undocumented request body with {}",
+        content_type(trail.get_request_content_type())
+      ),
+      InteractionDiffResult::UnmatchedRequestBodyShape(diff) => {
+        write!(f, "request body: {}", diff.shape_diff_result)
+      }
+      InteractionDiffResult::UnmatchedResponseBodyContentType(_) => write!(
+        f,
+        "undocumented {} body with {}",
+        response(),
+        content_type(trail.get_response_content_type())
+      ),
+      InteractionDiffResult::UnmatchedResponseBodyShape(diff) => {
+        write!(f, "{} body: {}", response(), diff.shape_diff_result)
+      }
+      InteractionDiffResult::UnmatchedQueryParameter(_) => {
+        write!(f, "undocumented {}", query_parameter())
+      }
+      InteractionDiffResult::MissingQueryParameter(_) => {
+        write!(f, "missing required {}", query_parameter())
+      }
+      InteractionDiffResult::UnmatchedQueryParameterShape(diff) => {
+        write!(f, "{}: {}", query_parameter(), diff.shape_diff_result)
+      }
+      InteractionDiffResult::UnmatchedHeader(_) => write!(f, "undocumented {}", header()),
+      InteractionDiffResult::MissingHeader(_) => write!(f, "missing required {}", header()),
+      InteractionDiffResult::UnmatchedHeaderShape(diff) => {
+        write!(f, "{}: {}", header(), diff.shape_diff_result)
+      }
+      InteractionDiffResult::MatchedRequestBodyContentType(_) => write!(
+        f,
+        "matched request body with {}",
+        content_type(trail.get_request_content_type())
+      ),
+      InteractionDiffResult::MatchedResponseBodyContentType(_) => write!(
+        f,
+        "matched {} body with {}",
+        response(),
+        content_type(trail.get_response_content_type())
+      ),
+      InteractionDiffResult::MatchedQueryParameter(_) => {
+        write!(f, "matched {}", query_parameter())
+      }
+      InteractionDiffResult::MatchedHeader(_) => write!(f, "matched {}", header()),
+    }
+  }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 #[derive(Debug, Deserialize, Serialize, Hash)]
 #[serde(rename_all = "camelCase")]
@@ -215,6 +356,180 @@ impl UnmatchedResponseBodyShape {
   }
 }
 
+#[derive(Debug, Deserialize, Serialize, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmatchedQueryParameter {
+  pub interaction_trail: InteractionTrail,
+  pub requests_trail: RequestSpecTrail,
+}
+
+impl UnmatchedQueryParameter {
+  pub fn new(interaction_trail: InteractionTrail, requests_trail: RequestSpecTrail) -> Self {
+    UnmatchedQueryParameter {
+      interaction_trail,
+      requests_trail,
+    }
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingQueryParameter {
+  pub interaction_trail: InteractionTrail,
+  pub requests_trail: RequestSpecTrail,
+}
+
+impl MissingQueryParameter {
+  pub fn new(interaction_trail: InteractionTrail, requests_trail: RequestSpecTrail) -> Self {
+    MissingQueryParameter {
+      interaction_trail,
+      requests_trail,
+    }
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmatchedQueryParameterShape {
+  pub interaction_trail: InteractionTrail,
+  pub requests_trail: RequestSpecTrail,
+  pub shape_diff_result: ShapeDiffResult,
+}
+
+impl UnmatchedQueryParameterShape {
+  pub fn new(
+    interaction_trail: InteractionTrail,
+    requests_trail: RequestSpecTrail,
+    shape_diff_result: ShapeDiffResult,
+  ) -> Self {
+    UnmatchedQueryParameterShape {
+      interaction_trail,
+      requests_trail,
+      shape_diff_result,
+    }
+  }
+}
+
+#[derive(Clone, Debug, Serialize, Hash)]
+pub struct MatchedQueryParameter {
+  pub interaction_trail: InteractionTrail,
+  pub requests_trail: RequestSpecTrail,
+  pub shape_id: ShapeId,
+}
+
+impl MatchedQueryParameter {
+  pub fn new(
+    interaction_trail: InteractionTrail,
+    requests_trail: RequestSpecTrail,
+    shape_id: ShapeId,
+  ) -> Self {
+    MatchedQueryParameter {
+      interaction_trail,
+      requests_trail,
+      shape_id,
+    }
+  }
+
+  pub fn into_missing(self) -> MissingQueryParameter {
+    MissingQueryParameter::new(self.interaction_trail, self.requests_trail)
+  }
+
+  pub fn into_shape_diff(self, shape_diff_result: ShapeDiffResult) -> UnmatchedQueryParameterShape {
+    UnmatchedQueryParameterShape::new(
+      self.interaction_trail,
+      self.requests_trail,
+      shape_diff_result,
+    )
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmatchedHeader {
+  pub interaction_trail: InteractionTrail,
+  pub requests_trail: RequestSpecTrail,
+}
+
+impl UnmatchedHeader {
+  pub fn new(interaction_trail: InteractionTrail, requests_trail: RequestSpecTrail) -> Self {
+    UnmatchedHeader {
+      interaction_trail,
+      requests_trail,
+    }
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingHeader {
+  pub interaction_trail: InteractionTrail,
+  pub requests_trail: RequestSpecTrail,
+}
+
+impl MissingHeader {
+  pub fn new(interaction_trail: InteractionTrail, requests_trail: RequestSpecTrail) -> Self {
+    MissingHeader {
+      interaction_trail,
+      requests_trail,
+    }
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmatchedHeaderShape {
+  pub interaction_trail: InteractionTrail,
+  pub requests_trail: RequestSpecTrail,
+  pub shape_diff_result: ShapeDiffResult,
+}
+
+impl UnmatchedHeaderShape {
+  pub fn new(
+    interaction_trail: InteractionTrail,
+    requests_trail: RequestSpecTrail,
+    shape_diff_result: ShapeDiffResult,
+  ) -> Self {
+    UnmatchedHeaderShape {
+      interaction_trail,
+      requests_trail,
+      shape_diff_result,
+    }
+  }
+}
+
+#[derive(Clone, Debug, Serialize, Hash)]
+pub struct MatchedHeader {
+  pub interaction_trail: InteractionTrail,
+  pub requests_trail: RequestSpecTrail,
+  pub shape_id: ShapeId,
+}
+
+impl MatchedHeader {
+  pub fn new(
+    interaction_trail: InteractionTrail,
+    requests_trail: RequestSpecTrail,
+    shape_id: ShapeId,
+  ) -> Self {
+    MatchedHeader {
+      interaction_trail,
+      requests_trail,
+      shape_id,
+    }
+  }
+
+  pub fn into_missing(self) -> MissingHeader {
+    MissingHeader::new(self.interaction_trail, self.requests_trail)
+  }
+
+  pub fn into_shape_diff(self, shape_diff_result: ShapeDiffResult) -> UnmatchedHeaderShape {
+    UnmatchedHeaderShape::new(
+      self.interaction_trail,
+      self.requests_trail,
+      shape_diff_result,
+    )
+  }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 #[derive(Clone, Debug)]
 pub struct BodyAnalysisResult {
@@ -331,6 +646,21 @@ impl From<MatchedResponseBodyContentType> for BodyAnalysisLocation {
   }
 }
 
+////////////////////////////////////////////////////////////////////////////////
+#[derive(Clone, Debug)]
+pub struct ParameterAnalysisResult {
+  pub parameter_location: ParameterAnalysisLocation,
+  pub observations_by_name: BTreeMap<String, TrailObservationsResult>,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ParameterAnalysisLocation {
+  pub path_id: PathComponentId,
+  pub method: String,
+  pub request_id: RequestId,
+  pub location: String,
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 #[derive(Clone, Debug, Deserialize, Serialize, Hash)]
 pub struct InteractionTrail {
@@ -371,6 +701,24 @@ impl InteractionTrail {
     })
   }
 
+  pub fn with_query_parameter(&mut self, name: String) {
+    self
+      .path
+      .push(InteractionTrailPathComponent::QueryParameter { name })
+  }
+
+  pub fn with_request_header(&mut self, name: String) {
+    self
+      .path
+      .push(InteractionTrailPathComponent::RequestHeader { name })
+  }
+
+  pub fn with_response_header(&mut self, name: String, status_code: u16) {
+    self
+      .path
+      .push(InteractionTrailPathComponent::ResponseHeader { name, status_code })
+  }
+
   pub fn get_method(&self) -> Option<&String> {
     self.path.iter().find_map(|component| match component {
       InteractionTrailPathComponent::Method { method } => Some(method),
@@ -378,6 +726,35 @@ impl InteractionTrail {
     })
   }
 
+  pub fn get_query_parameter_name(&self) -> Option<&String> {
+    self.path.iter().find_map(|component| match component {
+      InteractionTrailPathComponent::QueryParameter { name } => Some(name),
+      _ => None,
+    })
+  }
+
+  pub fn get_request_header_name(&self) -> Option<&String> {
+    self.path.iter().find_map(|component| match component {
+      InteractionTrailPathComponent::RequestHeader { name } => Some(name),
+      _ => None,
+    })
+  }
+
+  pub fn get_response_header_name(&self) -> Option<&String> {
+    self.path.iter().find_map(|component| match component {
+      InteractionTrailPathComponent::ResponseHeader { name, .. } => Some(name),
+      _ => None,
+    })
+  }
+
+  pub fn get_header_name(&self) -> Option<&String> {
+    self.path.iter().find_map(|component| match component {
+      InteractionTrailPathComponent::RequestHeader { name } => Some(name),
+      InteractionTrailPathComponent::ResponseHeader { name, .. } => Some(name),
+      _ => None,
+    })
+  }
+
   pub fn get_request_content_type(&self) -> Option<&String> {
     self.path.iter().find_map(|component| match component {
       InteractionTrailPathComponent::RequestBody { content_type } => Some(content_type),
@@ -421,6 +798,11 @@ impl InteractionTrail {
               InteractionTrailPathComponent::Method { method } => {
                 identifiers.method.replace(method);
               }
+              InteractionTrailPathComponent::QueryParameter { .. }
+              | InteractionTrailPathComponent::RequestHeader { .. } => {}
+              InteractionTrailPathComponent::ResponseHeader { status_code, .. } => {
+                identifiers.response_status_code.replace(*status_code);
+              }
               InteractionTrailPathComponent::RequestBody { content_type } => {
                 identifiers.request_content_type.replace(content_type);
               }
@@ -442,13 +824,18 @@ impl InteractionTrail {
     }
 
     let identifiers = InteractionIdentifiers::from(self);
+    // trails hold normalized content types
+    let normalized =
+      |content_type: &Option<String>| content_type.as_deref().map(normalize_content_type);
+    let request_content_type = normalized(&interaction.request.body.content_type);
+    let response_content_type = normalized(&interaction.response.body.content_type);
 
     let conditions = [
       matches!(identifiers.path, Some(path) if path == &interaction.request.path),
       matches!(identifiers.method, Some(method) if method == &interaction.request.method),
       matches!(identifiers.response_status_code, Some(status_code) if status_code == interaction.response.status_code),
-      identifiers.request_content_type == interaction.request.body.content_type.as_ref(),
-      identifiers.response_content_type == interaction.response.body.content_type.as_ref(),
+      identifiers.request_content_type == request_content_type.as_ref(),
+      identifiers.response_content_type == response_content_type.as_ref(),
     ];
     // dbg!(&identifiers, &conditions);
 
@@ -464,6 +851,7 @@ pub enum RequestSpecTrail {
   SpecRequestBody(SpecRequestBody),
   SpecResponseRoot(SpecResponseRoot),
   SpecResponseBody(SpecResponseBody),
+  SpecRequestParameter(SpecRequestParameter),
 }
 
 impl RequestSpecTrail {
@@ -482,6 +870,13 @@ impl RequestSpecTrail {
     }
   }
 
+  pub fn get_request_parameter_id(&self) -> Option<&String> {
+    match self {
+      RequestSpecTrail::SpecRequestParameter(spec_parameter) => Some(&spec_parameter.parameter_id),
+      _ => None,
+    }
+  }
+
   pub fn get_response_id(&self) -> Option<&String> {
     match self {
       RequestSpecTrail::SpecResponseBody(spec_body) => Some(&spec_body.response_id),
@@ -512,6 +907,12 @@ pub struct SpecRequestBody {
   pub request_id: RequestId,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecRequestParameter {
+  pub parameter_id: RequestParameterId,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, Hash)]
 #[serde(rename_all = "camelCase")]
 pub struct SpecResponseRoot {
@@ -545,4 +946,15 @@ pub enum InteractionTrailPathComponent {
     content_type: String,
     status_code: u16,
   },
+  QueryParameter {
+    name: String,
+  },
+  RequestHeader {
+    name: String,
+  },
+  #[serde(rename_all = "camelCase")]
+  ResponseHeader {
+    name: String,
+    status_code: u16,
+  },
 }
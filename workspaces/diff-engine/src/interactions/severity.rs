@@ -0,0 +1,169 @@
+use super::result::InteractionDiffResult;
+use crate::shapes::ShapeDiffResult;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How much a diff matters to consumers of the API. Additive diffs are traffic the spec doesn't
+/// describe yet (new fields, parameters or endpoints), while breaking diffs contradict what the
+/// spec promises (missing required fields, changed types, undocumented response bodies).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiffSeverity {
+  Additive,
+  Breaking,
+}
+
+impl DiffSeverity {
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "additive" => Some(DiffSeverity::Additive),
+      "breaking" => Some(DiffSeverity::Breaking),
+      _ => None,
+    }
+  }
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      DiffSeverity::Additive => "additive",
+      DiffSeverity::Breaking => "breaking",
+    }
+  }
+}
+
+impl fmt::Display for DiffSeverity {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
+
+/// Options for classifying the severity of diffs
+#[derive(Debug, Clone)]
+pub struct DiffSeverityConfig {
+  unmatched_request_url: DiffSeverity,
+}
+
+impl DiffSeverityConfig {
+  /// Whether requests to undocumented urls are an undocumented endpoint (additive, the default)
+  /// or a client calling something the API doesn't offer (breaking)
+  pub fn with_unmatched_request_url_severity(mut self, severity: DiffSeverity) -> Self {
+    self.unmatched_request_url = severity;
+    self
+  }
+}
+
+impl Default for DiffSeverityConfig {
+  fn default() -> Self {
+    DiffSeverityConfig {
+      unmatched_request_url: DiffSeverity::Additive,
+    }
+  }
+}
+
+impl InteractionDiffResult {
+  pub fn severity(&self, config: &DiffSeverityConfig) -> DiffSeverity {
+    match self {
+      InteractionDiffResult::UnmatchedRequestUrl(_) => config.unmatched_request_url,
+      InteractionDiffResult::UnmatchedRequestBodyContentType(_) => DiffSeverity::Additive,
+      InteractionDiffResult::UnmatchedRequestBodyShape(diff) => diff.shape_diff_result.severity(),
+      InteractionDiffResult::UnmatchedResponseBodyContentType(_) => DiffSeverity::Breaking,
+      InteractionDiffResult::UnmatchedResponseBodyShape(diff) => diff.shape_diff_result.severity(),
+      InteractionDiffResult::UnmatchedQueryParameter(_) => DiffSeverity::Additive,
+      InteractionDiffResult::MissingQueryParameter(_) => DiffSeverity::Breaking,
+      InteractionDiffResult::UnmatchedQueryParameterShape(diff) => {
+        diff.shape_diff_result.severity()
+      }
+      InteractionDiffResult::UnmatchedHeader(_) => DiffSeverity::Additive,
+      InteractionDiffResult::MissingHeader(_) => DiffSeverity::Breaking,
+      InteractionDiffResult::UnmatchedHeaderShape(diff) => diff.shape_diff_result.severity(),
+      InteractionDiffResult::MatchedRequestBodyContentType(_)
+      | InteractionDiffResult::MatchedResponseBodyContentType(_)
+      | InteractionDiffResult::MatchedQueryParameter(_)
+      | InteractionDiffResult::MatchedHeader(_) => DiffSeverity::Additive,
+    }
+  }
+}
+
+impl ShapeDiffResult {
+  /// Unspecified shapes are new fields or items, while unmatched shapes are missing required
+  /// fields or values of a different type
+  pub fn severity(&self) -> DiffSeverity {
+    match self {
+      ShapeDiffResult::UnspecifiedShape { .. } => DiffSeverity::Additive,
+      ShapeDiffResult::UnmatchedShape { .. } => DiffSeverity::Breaking,
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::interactions::result::{
+    InteractionTrail, InteractionTrailPathComponent, MissingHeader, RequestSpecTrail,
+    SpecRequestParameter, SpecResponseBody, SpecRoot, UnmatchedRequestUrl,
+    UnmatchedResponseBodyShape,
+  };
+  use crate::shapes::{JsonTrail, ShapeTrail};
+  use crate::state::body::BodyKind;
+  use crate::state::shape::ShapeKind;
+
+  fn response_body_shape_diff(shape_diff_result: ShapeDiffResult) -> InteractionDiffResult {
+    InteractionDiffResult::UnmatchedResponseBodyShape(UnmatchedResponseBodyShape::new(
+      InteractionTrail::empty(),
+      RequestSpecTrail::SpecResponseBody(SpecResponseBody {
+        response_id: String::from("response_1"),
+      }),
+      shape_diff_result,
+    ))
+  }
+
+  #[test]
+  fn can_classify_diff_severity() {
+    let config = DiffSeverityConfig::default();
+    let shape_trail = ShapeTrail::new(String::from("shape_1"));
+    let unspecified_field = response_body_shape_diff(ShapeDiffResult::unspecified(
+      JsonTrail::empty().with_object_key(String::from("nickname")),
+      shape_trail.clone(),
+      BodyKind::String,
+    ));
+    let missing_field = response_body_shape_diff(ShapeDiffResult::unmatched(
+      JsonTrail::empty().with_object_key(String::from("name")),
+      shape_trail,
+      vec![ShapeKind::StringKind],
+      None,
+    ));
+    let missing_header = InteractionDiffResult::MissingHeader(MissingHeader::new(
+      InteractionTrail::new(vec![InteractionTrailPathComponent::RequestHeader {
+        name: String::from("x-token"),
+      }]),
+      RequestSpecTrail::SpecRequestParameter(SpecRequestParameter {
+        parameter_id: String::from("parameter_1"),
+      }),
+    ));
+
+    assert_eq!(unspecified_field.severity(&config), DiffSeverity::Additive);
+    assert_eq!(missing_field.severity(&config), DiffSeverity::Breaking);
+    assert_eq!(missing_header.severity(&config), DiffSeverity::Breaking);
+    assert!(DiffSeverity::Breaking > DiffSeverity::Additive);
+  }
+
+  #[test]
+  fn can_configure_unmatched_request_url_severity() {
+    let unmatched_url = InteractionDiffResult::UnmatchedRequestUrl(UnmatchedRequestUrl::new(
+      InteractionTrail::new(vec![
+        InteractionTrailPathComponent::Url {
+          path: String::from("/todos/1"),
+        },
+        InteractionTrailPathComponent::Method {
+          method: String::from("PUT"),
+        },
+      ]),
+      RequestSpecTrail::SpecRoot(SpecRoot {}),
+    ));
+
+    let config = DiffSeverityConfig::default();
+    assert_eq!(unmatched_url.severity(&config), DiffSeverity::Additive);
+
+    let config = config.with_unmatched_request_url_severity(DiffSeverity::Breaking);
+    assert_eq!(unmatched_url.severity(&config), DiffSeverity::Breaking);
+  }
+}
@@ -1,9 +1,13 @@
 use super::visitors::{
-  InteractionVisitors, PathVisitor, PathVisitorContext, RequestBodyVisitor,
-  RequestBodyVisitorContext, ResponseBodyVisitor, ResponseBodyVisitorContext,
+  InteractionVisitors, ParametersVisitorContext, PathVisitor, PathVisitorContext,
+  QueryParametersVisitor, RequestBodyVisitor, RequestBodyVisitorContext, RequestHeadersVisitor,
+  ResponseBodyVisitor, ResponseBodyVisitorContext, ResponseHeadersVisitor,
 };
 use crate::events::HttpInteraction;
-use crate::projections::endpoint::ROOT_PATH_ID;
+use crate::projections::endpoint::{
+  HEADER_PARAMETER_LOCATION, QUERY_PARAMETER_LOCATION, RESPONSE_HEADER_PARAMETER_LOCATION,
+  ROOT_PATH_ID,
+};
 use crate::queries::endpoint::EndpointQueries;
 use crate::state::endpoint::PathComponentId;
 
@@ -28,6 +32,41 @@ impl<'a> Traverser<'a> {
     };
     path_visitor.visit(interaction, &path_context);
 
+    if let Some(path_id) = resolved_path {
+      // only operations that are documented have parameters to match against
+      let has_operations = self
+        .endpoint_queries
+        .resolve_operations(interaction, path_id)
+        .next()
+        .is_some();
+      if has_operations {
+        let query_parameters_visitor = visitors.query_parameters();
+        query_parameters_visitor.visit(
+          interaction,
+          &self.parameters_context(interaction, path_id, QUERY_PARAMETER_LOCATION),
+        );
+
+        let request_headers_visitor = visitors.request_headers();
+        request_headers_visitor.visit(
+          interaction,
+          &self.parameters_context(interaction, path_id, HEADER_PARAMETER_LOCATION),
+        );
+      }
+
+      let has_responses = self
+        .endpoint_queries
+        .resolve_responses(interaction, path_id)
+        .next()
+        .is_some();
+      if has_responses {
+        let response_headers_visitor = visitors.response_headers();
+        response_headers_visitor.visit(
+          interaction,
+          &self.parameters_context(interaction, path_id, RESPONSE_HEADER_PARAMETER_LOCATION),
+        );
+      }
+    }
+
     let request_body_visitor = visitors.request_body();
     request_body_visitor.begin();
     match resolved_path {
@@ -73,6 +112,22 @@ impl<'a> Traverser<'a> {
     response_body_visitor.end(interaction, &path_context);
     // eprintln!("ended response body visiting");
   }
+
+  fn parameters_context(
+    &self,
+    interaction: &'a HttpInteraction,
+    path_id: &'a str,
+    location: &'a str,
+  ) -> ParametersVisitorContext<'a> {
+    let parameters = self
+      .endpoint_queries
+      .resolve_request_parameters(path_id, &interaction.request.method, location)
+      .collect();
+    ParametersVisitorContext {
+      path: path_id,
+      parameters,
+    }
+  }
 }
 
 #[cfg(test)]
@@ -1,28 +1,44 @@
 use super::{
-  InteractionVisitor, InteractionVisitors, PathVisitor, PathVisitorContext, RequestBodyVisitor,
-  RequestBodyVisitorContext, ResponseBodyVisitor, ResponseBodyVisitorContext, VisitorResults,
+  InteractionVisitor, InteractionVisitors, ParametersVisitorContext, PathVisitor,
+  PathVisitorContext, QueryParametersVisitor, RequestBodyVisitor, RequestBodyVisitorContext,
+  RequestHeadersVisitor, ResponseBodyVisitor, ResponseBodyVisitorContext, ResponseHeadersVisitor,
+  VisitorResults,
 };
+use crate::interactions::config::InteractionDiffConfig;
 use crate::interactions::result::{
-  InteractionDiffResult, MatchedRequestBodyContentType, MatchedResponseBodyContentType, SpecRoot,
+  InteractionDiffResult, MatchedHeader, MatchedQueryParameter, MatchedRequestBodyContentType,
+  MatchedResponseBodyContentType, SpecRoot, UnmatchedHeader, UnmatchedQueryParameter,
   UnmatchedRequestBodyContentType, UnmatchedRequestUrl, UnmatchedResponseBodyContentType,
 };
 use crate::interactions::result::{
   InteractionTrail, InteractionTrailPathComponent, RequestSpecTrail, SpecPath, SpecRequestBody,
-  SpecResponseBody,
+  SpecRequestParameter, SpecResponseBody,
 };
 use crate::state::endpoint::{RequestId, ResponseId};
+use crate::state::media_type::{match_content_types, normalize_content_type, MediaTypeSpecificity};
 use crate::HttpInteraction;
+use std::collections::BTreeMap;
 
 pub struct DiffVisitors {
   path: DiffPathVisitor,
+  query_parameters: DiffQueryParametersVisitor,
+  request_headers: DiffRequestHeadersVisitor,
+  response_headers: DiffResponseHeadersVisitor,
   request_body: DiffRequestBodyVisitor,
   response_body: DiffResponseBodyVisitor,
 }
 
 impl DiffVisitors {
   pub fn new() -> Self {
+    Self::with_config(&InteractionDiffConfig::default())
+  }
+
+  pub fn with_config(config: &InteractionDiffConfig) -> Self {
     DiffVisitors {
       path: DiffPathVisitor::new(),
+      query_parameters: DiffQueryParametersVisitor::new(),
+      request_headers: DiffRequestHeadersVisitor::new(config.clone()),
+      response_headers: DiffResponseHeadersVisitor::new(config.clone()),
       request_body: DiffRequestBodyVisitor::new(),
       response_body: DiffResponseBodyVisitor::new(),
     }
@@ -33,12 +49,24 @@ type DiffResults = VisitorResults<InteractionDiffResult>;
 
 impl InteractionVisitors<InteractionDiffResult> for DiffVisitors {
   type Path = DiffPathVisitor;
+  type QueryParameters = DiffQueryParametersVisitor;
+  type RequestHeaders = DiffRequestHeadersVisitor;
+  type ResponseHeaders = DiffResponseHeadersVisitor;
   type RequestBody = DiffRequestBodyVisitor;
   type ResponseBody = DiffResponseBodyVisitor;
 
   fn path(&mut self) -> &mut DiffPathVisitor {
     &mut self.path
   }
+  fn query_parameters(&mut self) -> &mut DiffQueryParametersVisitor {
+    &mut self.query_parameters
+  }
+  fn request_headers(&mut self) -> &mut DiffRequestHeadersVisitor {
+    &mut self.request_headers
+  }
+  fn response_headers(&mut self) -> &mut DiffResponseHeadersVisitor {
+    &mut self.response_headers
+  }
   fn request_body(&mut self) -> &mut DiffRequestBodyVisitor {
     &mut self.request_body
   }
@@ -83,10 +111,240 @@ impl PathVisitor<InteractionDiffResult> for DiffPathVisitor {
 }
 ///////////////////////////////////////////////////////////////////////////////
 
+pub struct DiffQueryParametersVisitor {
+  results: DiffResults,
+}
+
+impl DiffQueryParametersVisitor {
+  fn new() -> Self {
+    DiffQueryParametersVisitor {
+      results: DiffResults::new(),
+    }
+  }
+}
+
+impl InteractionVisitor<InteractionDiffResult> for DiffQueryParametersVisitor {
+  fn results(&mut self) -> Option<&mut DiffResults> {
+    Some(&mut self.results)
+  }
+}
+
+impl QueryParametersVisitor<InteractionDiffResult> for DiffQueryParametersVisitor {
+  fn visit(&mut self, interaction: &HttpInteraction, context: &ParametersVisitorContext) {
+    let observed_parameters = interaction.request.query_parameters();
+    let interaction_trail_for = |name: &String| {
+      let mut interaction_trail = InteractionTrail::empty();
+      interaction_trail.with_method(interaction.request.method.clone());
+      interaction_trail.with_query_parameter(name.clone());
+      interaction_trail
+    };
+
+    for (parameter_id, parameter_descriptor) in &context.parameters {
+      // parameters without a shape aren't described enough to be matched against
+      if let Some(shape_id) = &parameter_descriptor.shape_id {
+        let requests_trail = RequestSpecTrail::SpecRequestParameter(SpecRequestParameter {
+          parameter_id: String::from(*parameter_id),
+        });
+        self.push(InteractionDiffResult::MatchedQueryParameter(
+          MatchedQueryParameter::new(
+            interaction_trail_for(&parameter_descriptor.name),
+            requests_trail,
+            shape_id.clone(),
+          ),
+        ));
+      }
+    }
+
+    let unknown_names = observed_parameters.keys().filter(|name| {
+      !context
+        .parameters
+        .iter()
+        .any(|(_, parameter_descriptor)| parameter_descriptor.name == **name)
+    });
+    for name in unknown_names {
+      let requests_trail = RequestSpecTrail::SpecPath(SpecPath {
+        path_id: String::from(context.path),
+      });
+      self.push(InteractionDiffResult::UnmatchedQueryParameter(
+        UnmatchedQueryParameter::new(interaction_trail_for(name), requests_trail),
+      ));
+    }
+  }
+}
+///////////////////////////////////////////////////////////////////////////////
+
+pub struct DiffRequestHeadersVisitor {
+  results: DiffResults,
+  config: InteractionDiffConfig,
+}
+
+impl DiffRequestHeadersVisitor {
+  fn new(config: InteractionDiffConfig) -> Self {
+    DiffRequestHeadersVisitor {
+      results: DiffResults::new(),
+      config,
+    }
+  }
+}
+
+impl InteractionVisitor<InteractionDiffResult> for DiffRequestHeadersVisitor {
+  fn results(&mut self) -> Option<&mut DiffResults> {
+    Some(&mut self.results)
+  }
+}
+
+impl RequestHeadersVisitor<InteractionDiffResult> for DiffRequestHeadersVisitor {
+  fn visit(&mut self, interaction: &HttpInteraction, context: &ParametersVisitorContext) {
+    let observed_headers = interaction.request.header_parameters();
+    let interaction_trail_for = |name: &str| {
+      let mut interaction_trail = InteractionTrail::empty();
+      interaction_trail.with_method(interaction.request.method.clone());
+      interaction_trail.with_request_header(name.to_lowercase());
+      interaction_trail
+    };
+
+    for diff in diff_headers(
+      &self.config,
+      &observed_headers,
+      context,
+      interaction_trail_for,
+    ) {
+      self.push(diff);
+    }
+  }
+}
+///////////////////////////////////////////////////////////////////////////////
+
+pub struct DiffResponseHeadersVisitor {
+  results: DiffResults,
+  config: InteractionDiffConfig,
+}
+
+impl DiffResponseHeadersVisitor {
+  fn new(config: InteractionDiffConfig) -> Self {
+    DiffResponseHeadersVisitor {
+      results: DiffResults::new(),
+      config,
+    }
+  }
+}
+
+impl InteractionVisitor<InteractionDiffResult> for DiffResponseHeadersVisitor {
+  fn results(&mut self) -> Option<&mut DiffResults> {
+    Some(&mut self.results)
+  }
+}
+
+impl ResponseHeadersVisitor<InteractionDiffResult> for DiffResponseHeadersVisitor {
+  fn visit(&mut self, interaction: &HttpInteraction, context: &ParametersVisitorContext) {
+    let observed_headers = interaction.response.header_parameters();
+    let interaction_trail_for = |name: &str| {
+      let mut interaction_trail = InteractionTrail::empty();
+      interaction_trail.with_method(interaction.request.method.clone());
+      interaction_trail.with_response_header(name.to_lowercase(), interaction.response.status_code);
+      interaction_trail
+    };
+
+    for diff in diff_headers(
+      &self.config,
+      &observed_headers,
+      context,
+      interaction_trail_for,
+    ) {
+      self.push(diff);
+    }
+  }
+}
+
+// Header names are case-insensitive, so they're matched and reported lowercased. That keeps the
+// fingerprints of their diffs stable, no matter how a client happens to capitalize them.
+fn diff_headers(
+  config: &InteractionDiffConfig,
+  observed_headers: &BTreeMap<String, Vec<String>>,
+  context: &ParametersVisitorContext,
+  interaction_trail_for: impl Fn(&str) -> InteractionTrail,
+) -> Vec<InteractionDiffResult> {
+  let mut results = vec![];
+
+  for (parameter_id, parameter_descriptor) in &context.parameters {
+    if config.is_header_ignored(&parameter_descriptor.name) {
+      continue;
+    }
+    // headers without a shape aren't described enough to be matched against
+    if let Some(shape_id) = &parameter_descriptor.shape_id {
+      let requests_trail = RequestSpecTrail::SpecRequestParameter(SpecRequestParameter {
+        parameter_id: String::from(*parameter_id),
+      });
+      results.push(InteractionDiffResult::MatchedHeader(MatchedHeader::new(
+        interaction_trail_for(&parameter_descriptor.name),
+        requests_trail,
+        shape_id.clone(),
+      )));
+    }
+  }
+
+  // operations that don't document any headers say nothing about which headers to expect, so
+  // unless asked for, only operations that do document headers get their undocumented ones reported
+  let documents_headers = context
+    .parameters
+    .iter()
+    .any(|(_, parameter_descriptor)| !config.is_header_ignored(&parameter_descriptor.name));
+  if !documents_headers && !config.reports_all_undocumented_headers() {
+    return results;
+  }
+
+  let unknown_names = observed_headers.keys().filter(|name| {
+    !config.is_header_ignored(name)
+      && !context
+        .parameters
+        .iter()
+        .any(|(_, parameter_descriptor)| parameter_descriptor.name.eq_ignore_ascii_case(name))
+  });
+  for name in unknown_names {
+    let requests_trail = RequestSpecTrail::SpecPath(SpecPath {
+      path_id: String::from(context.path),
+    });
+    results.push(InteractionDiffResult::UnmatchedHeader(
+      UnmatchedHeader::new(interaction_trail_for(name), requests_trail),
+    ));
+  }
+
+  results
+}
+///////////////////////////////////////////////////////////////////////////////
+
+// Bodies matching the observed content type, of which only the ones with the most specifically
+// matching media type are kept, so a wildcard body doesn't match when a more specific one does
+#[derive(Default)]
+struct MatchedBodies {
+  specificity: Option<MediaTypeSpecificity>,
+  results: Vec<InteractionDiffResult>,
+}
+
+impl MatchedBodies {
+  fn insert(&mut self, specificity: MediaTypeSpecificity, result: InteractionDiffResult) {
+    match self.specificity {
+      Some(best_specificity) if best_specificity > specificity => {}
+      Some(best_specificity) if best_specificity == specificity => self.results.push(result),
+      _ => {
+        self.specificity = Some(specificity);
+        self.results = vec![result];
+      }
+    }
+  }
+
+  fn take(&mut self) -> Vec<InteractionDiffResult> {
+    self.specificity = None;
+    std::mem::take(&mut self.results)
+  }
+}
+
 pub struct DiffRequestBodyVisitor {
   results: DiffResults,
   visited_with_matched_content_types: std::collections::HashSet<RequestId>,
   visited_with_unmatched_content_types: std::collections::HashSet<RequestId>,
+  // only bodies with the most specifically matching media type are matched
+  matched_bodies: MatchedBodies,
 }
 
 impl DiffRequestBodyVisitor {
@@ -95,6 +353,7 @@ impl DiffRequestBodyVisitor {
       results: DiffResults::new(),
       visited_with_matched_content_types: std::collections::HashSet::new(),
       visited_with_unmatched_content_types: std::collections::HashSet::new(),
+      matched_bodies: MatchedBodies::default(),
     }
   }
 }
@@ -132,25 +391,29 @@ impl RequestBodyVisitor<InteractionDiffResult> for DiffRequestBodyVisitor {
             .insert(request_id.clone());
         }
         (Some(body), Some(content_type)) => {
-          if body.http_content_type == *content_type {
+          let specificity = match_content_types(&body.http_content_type, content_type);
+          if let Some(specificity) = specificity {
             self
               .visited_with_matched_content_types
               .insert(request_id.clone());
             let interaction_trail_components = vec![InteractionTrailPathComponent::RequestBody {
-              content_type: String::from(content_type),
+              content_type: normalize_content_type(content_type),
             }];
             let requests_trail = RequestSpecTrail::SpecRequestBody(SpecRequestBody {
               request_id: String::from(request_id),
             });
             let interaction_trail = InteractionTrail::new(interaction_trail_components);
 
-            self.push(InteractionDiffResult::MatchedRequestBodyContentType(
-              MatchedRequestBodyContentType::new(
-                interaction_trail,
-                requests_trail,
-                body.root_shape_id.clone(),
+            self.matched_bodies.insert(
+              specificity,
+              InteractionDiffResult::MatchedRequestBodyContentType(
+                MatchedRequestBodyContentType::new(
+                  interaction_trail,
+                  requests_trail,
+                  body.root_shape_id.clone(),
+                ),
               ),
-            ));
+            );
           } else {
             self
               .visited_with_unmatched_content_types
@@ -162,6 +425,10 @@ impl RequestBodyVisitor<InteractionDiffResult> for DiffRequestBodyVisitor {
   }
 
   fn end(&mut self, interaction: &HttpInteraction, context: &PathVisitorContext) {
+    for matched_body in self.matched_bodies.take() {
+      self.results.push(matched_body);
+    }
+
     if let Some(path_id) = context.path {
       if self.visited_with_matched_content_types.is_empty() {
         let actual_content_type = &interaction.request.body.content_type;
@@ -175,7 +442,7 @@ impl RequestBodyVisitor<InteractionDiffResult> for DiffRequestBodyVisitor {
         ];
         if let Some(content_type) = actual_content_type {
           interaction_trail_components.push(InteractionTrailPathComponent::RequestBody {
-            content_type: content_type.clone(),
+            content_type: normalize_content_type(content_type),
           });
         }
         let interaction_trail = InteractionTrail::new(interaction_trail_components);
@@ -196,6 +463,8 @@ pub struct DiffResponseBodyVisitor {
   results: DiffResults,
   visited_with_matched_content_types: std::collections::HashSet<ResponseId>,
   visited_with_unmatched_content_types: std::collections::HashSet<ResponseId>,
+  // only bodies with the most specifically matching media type are matched
+  matched_bodies: MatchedBodies,
 }
 
 impl DiffResponseBodyVisitor {
@@ -204,6 +473,7 @@ impl DiffResponseBodyVisitor {
       results: DiffResults::new(),
       visited_with_matched_content_types: std::collections::HashSet::new(),
       visited_with_unmatched_content_types: std::collections::HashSet::new(),
+      matched_bodies: MatchedBodies::default(),
     }
   }
 }
@@ -245,13 +515,14 @@ impl ResponseBodyVisitor<InteractionDiffResult> for DiffResponseBodyVisitor {
             .insert(response_id.clone());
         }
         (Some(body), Some(content_type)) => {
-          if body.http_content_type == *content_type {
+          let specificity = match_content_types(&body.http_content_type, content_type);
+          if let Some(specificity) = specificity {
             self
               .visited_with_matched_content_types
               .insert(response_id.clone());
 
             let interaction_trail_components = vec![InteractionTrailPathComponent::ResponseBody {
-              content_type: String::from(content_type),
+              content_type: normalize_content_type(content_type),
               status_code: interaction.response.status_code,
             }];
             let requests_trail = RequestSpecTrail::SpecResponseBody(SpecResponseBody {
@@ -259,13 +530,16 @@ impl ResponseBodyVisitor<InteractionDiffResult> for DiffResponseBodyVisitor {
             });
             let interaction_trail = InteractionTrail::new(interaction_trail_components);
 
-            self.push(InteractionDiffResult::MatchedResponseBodyContentType(
-              MatchedResponseBodyContentType::new(
-                interaction_trail,
-                requests_trail,
-                body.root_shape_id.clone(),
+            self.matched_bodies.insert(
+              specificity,
+              InteractionDiffResult::MatchedResponseBodyContentType(
+                MatchedResponseBodyContentType::new(
+                  interaction_trail,
+                  requests_trail,
+                  body.root_shape_id.clone(),
+                ),
               ),
-            ));
+            );
           } else {
             self
               .visited_with_unmatched_content_types
@@ -277,6 +551,10 @@ impl ResponseBodyVisitor<InteractionDiffResult> for DiffResponseBodyVisitor {
   }
 
   fn end(&mut self, interaction: &HttpInteraction, context: &PathVisitorContext) {
+    for matched_body in self.matched_bodies.take() {
+      self.results.push(matched_body);
+    }
+
     if let Some(path_id) = context.path {
       if self.visited_with_matched_content_types.is_empty() {
         let actual_content_type = &interaction.response.body.content_type;
@@ -288,7 +566,7 @@ impl ResponseBodyVisitor<InteractionDiffResult> for DiffResponseBodyVisitor {
         ];
         if let Some(content_type) = actual_content_type {
           interaction_trail_components.push(InteractionTrailPathComponent::ResponseBody {
-            content_type: content_type.clone(),
+            content_type: normalize_content_type(content_type),
             status_code: interaction.response.status_code,
           });
         } else {
@@ -1,16 +1,29 @@
 pub mod diff;
 
-use crate::projections::endpoint::{RequestBodyDescriptor, ResponseBodyDescriptor};
-use crate::state::endpoint::{PathComponentId, PathComponentIdRef, RequestId, ResponseId};
+use crate::projections::endpoint::{
+  RequestBodyDescriptor, RequestParameterDescriptor, ResponseBodyDescriptor,
+};
+use crate::state::endpoint::{
+  PathComponentId, PathComponentIdRef, RequestId, RequestParameterId, ResponseId,
+};
 use crate::HttpInteraction;
 
 pub trait InteractionVisitors<R> {
   type Path: PathVisitor<R>;
+  type QueryParameters: QueryParametersVisitor<R>;
+  type RequestHeaders: RequestHeadersVisitor<R>;
+  type ResponseHeaders: ResponseHeadersVisitor<R>;
   type RequestBody: RequestBodyVisitor<R>;
   type ResponseBody: ResponseBodyVisitor<R>;
 
   fn path(&mut self) -> &mut Self::Path;
 
+  fn query_parameters(&mut self) -> &mut Self::QueryParameters;
+
+  fn request_headers(&mut self) -> &mut Self::RequestHeaders;
+
+  fn response_headers(&mut self) -> &mut Self::ResponseHeaders;
+
   fn request_body(&mut self) -> &mut Self::RequestBody;
 
   fn response_body(&mut self) -> &mut Self::ResponseBody;
@@ -18,6 +31,9 @@ pub trait InteractionVisitors<R> {
   fn take_results(&mut self) -> Option<Vec<R>> {
     let flattened = vec![
       self.path().take_results(),
+      self.query_parameters().take_results(),
+      self.request_headers().take_results(),
+      self.response_headers().take_results(),
       self.request_body().take_results(),
       self.response_body().take_results(),
     ]
@@ -64,9 +80,25 @@ pub trait PathVisitor<R>: InteractionVisitor<R> {
   fn visit(&mut self, interaction: &HttpInteraction, context: &PathVisitorContext);
 }
 
+pub trait QueryParametersVisitor<R>: InteractionVisitor<R> {
+  fn visit(&mut self, interaction: &HttpInteraction, context: &ParametersVisitorContext);
+}
+
+pub trait RequestHeadersVisitor<R>: InteractionVisitor<R> {
+  fn visit(&mut self, interaction: &HttpInteraction, context: &ParametersVisitorContext);
+}
+
+pub trait ResponseHeadersVisitor<R>: InteractionVisitor<R> {
+  fn visit(&mut self, interaction: &HttpInteraction, context: &ParametersVisitorContext);
+}
+
 pub struct PathVisitorContext<'a> {
   pub path: Option<PathComponentIdRef<'a>>,
 }
+pub struct ParametersVisitorContext<'a> {
+  pub path: PathComponentIdRef<'a>,
+  pub parameters: Vec<(&'a RequestParameterId, &'a RequestParameterDescriptor)>,
+}
 pub struct RequestBodyVisitorContext<'a> {
   pub path: PathComponentIdRef<'a>,
   pub operation: Option<(&'a RequestId, &'a RequestBodyDescriptor)>,
@@ -0,0 +1,343 @@
+use crate::queries::shape::ShapeQueries;
+use crate::state::shape::{FieldId, ShapeId, ShapeKind};
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SchemaDialect {
+  OpenApi30,
+  JsonSchema202012,
+}
+
+impl SchemaDialect {
+  fn definitions_pointer(&self) -> &'static str {
+    match self {
+      SchemaDialect::OpenApi30 => "#/components/schemas/",
+      SchemaDialect::JsonSchema202012 => "#/$defs/",
+    }
+  }
+
+  fn nullable_schema(&self, inner: JsonValue) -> JsonValue {
+    let mut schema = match inner {
+      JsonValue::Object(schema) => schema,
+      _ => unreachable!("schemas should always be described as json objects"),
+    };
+    // enums only allow the values they list, so null has to be listed as well
+    if let Some(JsonValue::Array(values)) = schema.get_mut("enum") {
+      values.push(JsonValue::Null);
+    }
+
+    match self {
+      // siblings of $ref are ignored in OpenAPI 3.0, so wrap the reference instead
+      SchemaDialect::OpenApi30 if schema.contains_key("$ref") => json!({
+        "allOf": [schema],
+        "nullable": true
+      }),
+      SchemaDialect::OpenApi30 => {
+        schema.insert(String::from("nullable"), JsonValue::from(true));
+        JsonValue::Object(schema)
+      }
+      SchemaDialect::JsonSchema202012 if schema.is_empty() => JsonValue::Object(schema),
+      SchemaDialect::JsonSchema202012 => match schema.get("type") {
+        Some(JsonValue::String(schema_type)) if !schema.contains_key("$ref") => {
+          let schema_types = json!([schema_type, "null"]);
+          schema.insert(String::from("type"), schema_types);
+          JsonValue::Object(schema)
+        }
+        _ => json!({ "anyOf": [schema, { "type": "null" }] }),
+      },
+    }
+  }
+}
+
+/// Describes shapes as schemas, collecting the ones that are described once and
+/// referenced from everywhere they're used as named definitions.
+pub struct SchemasCollector<'a> {
+  shape_queries: &'a ShapeQueries<'a>,
+  dialect: SchemaDialect,
+  definitions: BTreeMap<String, JsonValue>,
+  definition_names: HashMap<ShapeId, String>,
+  visiting: HashSet<ShapeId>,
+}
+
+impl<'a> SchemasCollector<'a> {
+  pub fn new(shape_queries: &'a ShapeQueries<'a>, dialect: SchemaDialect) -> Self {
+    Self {
+      shape_queries,
+      dialect,
+      definitions: BTreeMap::new(),
+      definition_names: HashMap::new(),
+      visiting: HashSet::new(),
+    }
+  }
+
+  pub fn into_definitions(self) -> BTreeMap<String, JsonValue> {
+    self.definitions
+  }
+
+  /// Register shapes that are used more than once from the given roots as definitions,
+  /// rather than describing them in place every time they're used
+  pub fn with_shared_shapes<'b>(&mut self, root_shape_ids: impl IntoIterator<Item = &'b ShapeId>) {
+    let mut use_counts: HashMap<ShapeId, usize> = HashMap::new();
+    let mut pending = root_shape_ids.into_iter().cloned().collect::<Vec<_>>();
+
+    while let Some(shape_id) = pending.pop() {
+      let use_count = use_counts.entry(shape_id.clone()).or_insert(0);
+      *use_count += 1;
+      if *use_count == 1 {
+        pending.extend(self.child_shape_ids(&shape_id));
+      }
+    }
+
+    let mut shared_shape_ids = use_counts
+      .into_iter()
+      .filter(|(_, use_count)| *use_count > 1)
+      .map(|(shape_id, _)| shape_id)
+      .filter(|shape_id| !self.child_shape_ids(shape_id).is_empty()) // primitives are cheap to repeat
+      .collect::<Vec<_>>();
+    shared_shape_ids.sort(); // stable definition names
+    for shape_id in shared_shape_ids {
+      if !self.definition_names.contains_key(&shape_id) {
+        self.register_definition(&shape_id);
+      }
+    }
+  }
+
+  pub fn schema_for(&mut self, shape_id: &ShapeId) -> JsonValue {
+    if self.definitions_contain(shape_id) || self.visiting.contains(shape_id) {
+      // the shape refers back to itself, which can only be described through a definition
+      let definition_name = match self.definition_names.get(shape_id) {
+        Some(definition_name) => definition_name.clone(),
+        None => self.register_definition(shape_id),
+      };
+      return self.definition_reference(&definition_name);
+    }
+
+    if self.shape_queries.resolve_shape_name(shape_id).is_some()
+      && !self.definition_names.contains_key(shape_id)
+    {
+      self.register_definition(shape_id);
+    }
+
+    self.visiting.insert(shape_id.clone());
+    let schema = self.inline_schema_for(shape_id);
+    self.visiting.remove(shape_id);
+
+    match self.definition_names.get(shape_id) {
+      Some(definition_name) => {
+        let definition_name = definition_name.clone();
+        self.definitions.insert(definition_name.clone(), schema);
+        self.definition_reference(&definition_name)
+      }
+      None => schema,
+    }
+  }
+
+  /// The schema of a field or parameter's values, along with whether a value is required.
+  /// Optionality is described by what's required rather than by the schema itself.
+  pub fn value_schema_for(&mut self, shape_id: &ShapeId) -> (JsonValue, bool) {
+    let aliased_shape_id = self.resolve_aliased_shape_id(shape_id);
+    match self.shape_queries.resolve_to_core_shape(&aliased_shape_id) {
+      ShapeKind::OptionalKind => {
+        let schema = match self.parameter_shape(&aliased_shape_id, &ShapeKind::OptionalKind) {
+          Some(inner_shape_id) => self.schema_for(&inner_shape_id),
+          None => json!({}),
+        };
+        (schema, false)
+      }
+      _ => (self.schema_for(shape_id), true),
+    }
+  }
+
+  fn definitions_contain(&self, shape_id: &ShapeId) -> bool {
+    match self.definition_names.get(shape_id) {
+      Some(definition_name) => self.definitions.contains_key(definition_name),
+      None => false,
+    }
+  }
+
+  fn register_definition(&mut self, shape_id: &ShapeId) -> String {
+    let base_name = definition_name(
+      self
+        .shape_queries
+        .resolve_shape_name(shape_id)
+        .unwrap_or(shape_id),
+    );
+    let is_taken = |name: &String| self.definition_names.values().any(|taken| taken == name);
+
+    let name = if is_taken(&base_name) {
+      format!("{}_{}", base_name, definition_name(shape_id))
+    } else {
+      base_name
+    };
+
+    self.definition_names.insert(shape_id.clone(), name.clone());
+    name
+  }
+
+  fn definition_reference(&self, definition_name: &str) -> JsonValue {
+    json!({ "$ref": format!("{}{}", self.dialect.definitions_pointer(), definition_name) })
+  }
+
+  fn inline_schema_for(&mut self, shape_id: &ShapeId) -> JsonValue {
+    if let Some(base_shape_id) = self.shape_queries.resolve_base_shape_id(shape_id) {
+      return self.schema_for(&base_shape_id.clone());
+    }
+
+    let core_shape_kind = self.shape_queries.resolve_to_core_shape(shape_id).clone();
+
+    match core_shape_kind {
+      ShapeKind::ObjectKind => self.object_schema_for(shape_id),
+      ShapeKind::ListKind => {
+        let items = match self.parameter_shape(shape_id, &ShapeKind::ListKind) {
+          Some(item_shape_id) => self.schema_for(&item_shape_id),
+          None => json!({}),
+        };
+        json!({ "type": "array", "items": items })
+      }
+      ShapeKind::MapKind => {
+        let values = match self.parameter_shape(shape_id, &ShapeKind::MapKind) {
+          Some(value_shape_id) => self.schema_for(&value_shape_id),
+          None => json!({}),
+        };
+        json!({ "type": "object", "additionalProperties": values })
+      }
+      ShapeKind::OneOfKind => {
+        let branch_shape_ids = self
+          .shape_queries
+          .resolve_parameters_to_shapes(shape_id)
+          .into_iter()
+          .map(|(_, branch_shape_id)| branch_shape_id)
+          .collect::<Vec<_>>();
+        let branches = branch_shape_ids
+          .iter()
+          .map(|branch_shape_id| self.schema_for(branch_shape_id))
+          .collect::<Vec<_>>();
+        json!({ "oneOf": branches })
+      }
+      ShapeKind::NullableKind => {
+        let inner = match self.parameter_shape(shape_id, &ShapeKind::NullableKind) {
+          Some(inner_shape_id) => self.schema_for(&inner_shape_id),
+          None => json!({}),
+        };
+        self.dialect.nullable_schema(inner)
+      }
+      ShapeKind::OptionalKind => match self.parameter_shape(shape_id, &ShapeKind::OptionalKind) {
+        Some(inner_shape_id) => self.schema_for(&inner_shape_id),
+        None => json!({}),
+      },
+      ShapeKind::StringKind => {
+        let mut schema = json!({ "type": "string" });
+        if let Some(format) = self.shape_queries.resolve_string_format(shape_id) {
+          schema["format"] = json!(format.name());
+        }
+        if let Some(values) = self.shape_queries.resolve_string_enum(shape_id) {
+          schema["enum"] = json!(values);
+        }
+        schema
+      }
+      ShapeKind::NumberKind => json!({ "type": "number" }),
+      ShapeKind::IntegerKind => json!({ "type": "integer" }),
+      ShapeKind::BooleanKind => json!({ "type": "boolean" }),
+      ShapeKind::AnyKind
+      | ShapeKind::UnknownKind
+      | ShapeKind::IdentifierKind
+      | ShapeKind::ReferenceKind => json!({}),
+    }
+  }
+
+  fn object_schema_for(&mut self, shape_id: &ShapeId) -> JsonValue {
+    let mut properties = JsonMap::new();
+    let mut required = vec![];
+
+    for (field_name, field_shape_id) in self.field_shape_ids(shape_id) {
+      let (field_schema, is_required) = self.value_schema_for(&field_shape_id);
+      if is_required {
+        required.push(field_name.clone());
+      }
+
+      properties.insert(field_name, field_schema);
+    }
+
+    let mut schema = JsonMap::new();
+    schema.insert(String::from("type"), JsonValue::from("object"));
+    schema.insert(String::from("properties"), JsonValue::Object(properties));
+    if !required.is_empty() {
+      required.sort();
+      schema.insert(String::from("required"), JsonValue::from(required));
+    }
+    JsonValue::Object(schema)
+  }
+
+  fn field_shape_ids(&self, shape_id: &ShapeId) -> Vec<(String, ShapeId)> {
+    self
+      .shape_queries
+      .resolve_shape_field_id_and_names(shape_id)
+      .map(|(field_id, field_name)| (field_id.clone(), field_name.clone()))
+      .collect::<Vec<(FieldId, String)>>()
+      .into_iter()
+      .filter_map(|(field_id, field_name)| {
+        let field_shape_id = self.shape_queries.resolve_field_shape_node(&field_id)?;
+        Some((field_name, field_shape_id))
+      })
+      .collect()
+  }
+
+  fn child_shape_ids(&self, shape_id: &ShapeId) -> Vec<ShapeId> {
+    if let Some(base_shape_id) = self.shape_queries.resolve_base_shape_id(shape_id) {
+      return vec![base_shape_id.clone()];
+    }
+
+    match self.shape_queries.resolve_to_core_shape(shape_id) {
+      ShapeKind::ObjectKind => self
+        .field_shape_ids(shape_id)
+        .into_iter()
+        .map(|(_, field_shape_id)| field_shape_id)
+        .collect(),
+      ShapeKind::ListKind
+      | ShapeKind::MapKind
+      | ShapeKind::OneOfKind
+      | ShapeKind::NullableKind
+      | ShapeKind::OptionalKind => self
+        .shape_queries
+        .resolve_parameters_to_shapes(shape_id)
+        .into_iter()
+        .map(|(_, parameter_shape_id)| parameter_shape_id)
+        .collect(),
+      _ => vec![],
+    }
+  }
+
+  fn resolve_aliased_shape_id(&self, shape_id: &ShapeId) -> ShapeId {
+    let mut aliased_shape_id = shape_id;
+    let mut seen = HashSet::new();
+    while let Some(base_shape_id) = self.shape_queries.resolve_base_shape_id(aliased_shape_id) {
+      if !seen.insert(base_shape_id) {
+        break;
+      }
+      aliased_shape_id = base_shape_id;
+    }
+    aliased_shape_id.clone()
+  }
+
+  fn parameter_shape(&self, shape_id: &ShapeId, shape_kind: &ShapeKind) -> Option<ShapeId> {
+    let shape_parameter_id = shape_kind.get_parameter_descriptor()?.shape_parameter_id;
+
+    self
+      .shape_queries
+      .resolve_parameters_to_shapes(shape_id)
+      .into_iter()
+      .find(|(parameter_id, _)| parameter_id == shape_parameter_id)
+      .map(|(_, parameter_shape_id)| parameter_shape_id)
+  }
+}
+
+fn definition_name(name: &str) -> String {
+  name
+    .chars()
+    .map(|c| match c {
+      'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' | '_' => c,
+      _ => '_',
+    })
+    .collect()
+}
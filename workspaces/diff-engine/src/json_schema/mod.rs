@@ -0,0 +1,118 @@
+use crate::projections::ShapeProjection;
+use crate::queries::shape::ShapeQueries;
+use crate::state::shape::ShapeId;
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+mod collector;
+
+pub use collector::{SchemaDialect, SchemasCollector};
+
+pub const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Describe a shape as a JSON Schema (draft 2020-12) document. Named shapes, shapes
+/// that are used more than once and shapes that refer back to themselves are described
+/// under `$defs`. Returns `None` when the shape doesn't exist.
+pub fn from_shape(shape_projection: &ShapeProjection, shape_id: &ShapeId) -> Option<JsonValue> {
+  shape_projection.get_shape_node_index(shape_id)?;
+
+  let shape_queries = ShapeQueries::new(shape_projection);
+  let mut schemas = SchemasCollector::new(&shape_queries, SchemaDialect::JsonSchema202012);
+  schemas.with_shared_shapes(std::iter::once(shape_id));
+
+  let root_schema = schemas.schema_for(shape_id);
+  let definitions = schemas.into_definitions();
+
+  let mut document = JsonMap::new();
+  document.insert(String::from("$schema"), JsonValue::from(JSON_SCHEMA_DIALECT));
+  if let JsonValue::Object(root_schema) = root_schema {
+    document.extend(root_schema);
+  }
+  if !definitions.is_empty() {
+    document.insert(
+      String::from("$defs"),
+      JsonValue::Object(definitions.into_iter().collect()),
+    );
+  }
+
+  Some(JsonValue::Object(document))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::events::SpecEvent;
+  use crate::projections::SpecProjection;
+  use insta::assert_json_snapshot;
+  use serde_json::json;
+
+  #[test]
+  fn can_describe_recursive_and_shared_shapes() {
+    let events: Vec<SpecEvent> = serde_json::from_value(json!([
+      {"ShapeAdded": {"shapeId": "todo_shape","baseShapeId": "$object","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": "Todo"}},
+      {"ShapeAdded": {"shapeId": "person_shape","baseShapeId": "$object","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeAdded": {"shapeId": "person_name_shape","baseShapeId": "$string","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"FieldAdded": {"fieldId": "person_name_field","shapeId": "person_shape","name": "name","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "person_name_field","shapeId": "person_name_shape"}}}},
+      {"FieldAdded": {"fieldId": "author_field","shapeId": "todo_shape","name": "author","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "author_field","shapeId": "person_shape"}}}},
+      {"FieldAdded": {"fieldId": "assignee_field","shapeId": "todo_shape","name": "assignee","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "assignee_field","shapeId": "person_shape"}}}},
+      {"ShapeAdded": {"shapeId": "due_inner_shape","baseShapeId": "$number","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeAdded": {"shapeId": "due_nullable_shape","baseShapeId": "$nullable","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "due_nullable_shape","providerDescriptor": {"ShapeProvider": {"shapeId": "due_inner_shape"}},"consumingParameterId": "$nullableInner"}}}},
+      {"ShapeAdded": {"shapeId": "due_optional_shape","baseShapeId": "$optional","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "due_optional_shape","providerDescriptor": {"ShapeProvider": {"shapeId": "due_nullable_shape"}},"consumingParameterId": "$optionalInner"}}}},
+      {"FieldAdded": {"fieldId": "due_field","shapeId": "todo_shape","name": "due","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "due_field","shapeId": "due_optional_shape"}}}},
+      {"ShapeAdded": {"shapeId": "subtasks_shape","baseShapeId": "$list","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "subtasks_shape","providerDescriptor": {"ShapeProvider": {"shapeId": "todo_shape"}},"consumingParameterId": "$listItem"}}}},
+      {"FieldAdded": {"fieldId": "subtasks_field","shapeId": "todo_shape","name": "subtasks","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "subtasks_field","shapeId": "subtasks_shape"}}}},
+      {"ShapeAdded": {"shapeId": "todos_shape","baseShapeId": "$list","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "todos_shape","providerDescriptor": {"ShapeProvider": {"shapeId": "todo_shape"}},"consumingParameterId": "$listItem"}}}},
+    ]))
+    .expect("initial events should be valid spec events");
+
+    let spec_projection = SpecProjection::from(events);
+
+    let todos_schema = from_shape(spec_projection.shape(), &String::from("todos_shape"))
+      .expect("json schema should be described for existing shape");
+    assert_json_snapshot!("can_describe_recursive_and_shared_shapes__todos", todos_schema);
+
+    let todo_schema = from_shape(spec_projection.shape(), &String::from("todo_shape"))
+      .expect("json schema should be described for existing shape");
+    assert_json_snapshot!("can_describe_recursive_and_shared_shapes__todo", todo_schema);
+  }
+
+  #[test]
+  fn can_describe_nullable_references() {
+    let events: Vec<SpecEvent> = serde_json::from_value(json!([
+      {"ShapeAdded": {"shapeId": "user_shape","baseShapeId": "$object","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": "User"}},
+      {"ShapeAdded": {"shapeId": "name_shape","baseShapeId": "$string","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"FieldAdded": {"fieldId": "name_field","shapeId": "user_shape","name": "name","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "name_field","shapeId": "name_shape"}}}},
+      {"ShapeAdded": {"shapeId": "nullable_user_shape","baseShapeId": "$nullable","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "nullable_user_shape","providerDescriptor": {"ShapeProvider": {"shapeId": "user_shape"}},"consumingParameterId": "$nullableInner"}}}},
+    ]))
+    .expect("initial events should be valid spec events");
+
+    let spec_projection = SpecProjection::from(events);
+
+    let schema = from_shape(spec_projection.shape(), &String::from("nullable_user_shape"))
+      .expect("json schema should be described for existing shape");
+    assert_json_snapshot!("can_describe_nullable_references__schema", schema);
+
+    assert!(from_shape(spec_projection.shape(), &String::from("not_a_shape")).is_none());
+  }
+
+  #[test]
+  fn can_describe_nullable_enums() {
+    let events: Vec<SpecEvent> = serde_json::from_value(json!([
+      {"ShapeAdded": {"shapeId": "status_shape","baseShapeId": "$string","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"StringEnumSet": {"shapeId": "status_shape","values": ["open", "closed"]}},
+      {"ShapeAdded": {"shapeId": "nullable_status_shape","baseShapeId": "$nullable","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "nullable_status_shape","providerDescriptor": {"ShapeProvider": {"shapeId": "status_shape"}},"consumingParameterId": "$nullableInner"}}}},
+    ]))
+    .expect("initial events should be valid spec events");
+
+    let spec_projection = SpecProjection::from(events);
+
+    let schema = from_shape(spec_projection.shape(), &String::from("nullable_status_shape"))
+      .expect("json schema should be described for existing shape");
+    assert_json_snapshot!("can_describe_nullable_enums__schema", schema);
+  }
+}
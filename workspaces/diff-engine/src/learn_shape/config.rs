@@ -0,0 +1,17 @@
+/// Options for learning shapes from the trail values of observed bodies
+#[derive(Debug, Clone, Default)]
+pub struct ShapeLearningConfig {
+  enums: bool,
+}
+
+impl ShapeLearningConfig {
+  /// Learn strings as enums when only a few distinct values were observed, each of them repeatedly
+  pub fn with_enums(mut self) -> Self {
+    self.enums = true;
+    self
+  }
+
+  pub fn learns_enums(&self) -> bool {
+    self.enums
+  }
+}
@@ -3,10 +3,12 @@ use crate::BodyDescriptor;
 use crate::HttpInteraction;
 use std::collections::HashMap;
 
+mod config;
 mod result;
 mod traverser;
 mod visitors;
 
+pub use config::ShapeLearningConfig;
 pub use result::{TrailObservationsResult, TrailValues};
 use traverser::Traverser;
 use visitors::learn_json_values::LearnVisitors;
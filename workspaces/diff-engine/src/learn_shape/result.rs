@@ -1,11 +1,23 @@
 use crate::commands::shape as shape_commands;
 use crate::commands::{ShapeCommand, SpecCommand};
+use crate::learn_shape::ShapeLearningConfig;
 use crate::shapes::JsonTrail;
-use crate::state::shape::{FieldId, ShapeId, ShapeKind, ShapeKindDescriptor};
+use crate::state::body::NumberDescriptor;
+use crate::state::shape::{FieldId, ShapeId, ShapeKind, ShapeKindDescriptor, StringFormat};
 use crate::state::SpecIdGenerator;
 use crate::BodyDescriptor;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+// Objects with more distinct keys than this are learned as maps, even when their keys don't look
+// like identifiers, as they're unlikely to describe a fixed set of fields
+const MAP_KEYS_CARDINALITY_THRESHOLD: usize = 50;
+
+// Strings are only learned as enums when there are few distinct values, each of them observed
+// several times, so that a handful of samples of a free-form string isn't mistaken for an enum
+const ENUM_MAX_VALUES: usize = 10;
+const ENUM_MAX_VALUE_LENGTH: usize = 64;
+const ENUM_MIN_OCCURRENCES_PER_VALUE: usize = 3;
 
 #[derive(Clone, Debug, Default)]
 pub struct TrailObservationsResult {
@@ -61,10 +73,21 @@ impl TrailObservationsResult {
   }
 
   pub fn into_commands(
+    self,
+    id_generator: &mut impl SpecIdGenerator,
+    root_trail: &JsonTrail,
+  ) -> (Option<String>, impl Iterator<Item = SpecCommand>) {
+    self.into_commands_with_config(id_generator, root_trail, &ShapeLearningConfig::default())
+  }
+
+  pub fn into_commands_with_config(
     mut self,
     id_generator: &mut impl SpecIdGenerator,
     root_trail: &JsonTrail,
+    config: &ShapeLearningConfig,
   ) -> (Option<String>, impl Iterator<Item = SpecCommand>) {
+    let map_value_keys = self.collapse_map_trails(root_trail);
+
     let sorted_trails = {
       let mut trails = self
         .values_by_trail
@@ -81,8 +104,12 @@ impl TrailObservationsResult {
     for json_trail in sorted_trails.into_iter().rev() {
       let trail_values = self.values_by_trail.remove(&json_trail).unwrap();
 
-      let shape_prototype =
-        trail_values.into_shape_prototype(id_generator, &shape_prototypes_by_trail);
+      let shape_prototype = trail_values.into_shape_prototype(
+        id_generator,
+        &shape_prototypes_by_trail,
+        map_value_keys.get(&json_trail),
+        config,
+      );
 
       shape_prototypes_by_trail.insert(json_trail, shape_prototype.clone());
       shape_prototypes.push(shape_prototype);
@@ -97,6 +124,54 @@ impl TrailObservationsResult {
 
     (root_shape_id, commands)
   }
+
+  // Objects that look like maps have the values of all their keys observed as the values of a
+  // single key, so they are learned as a single shape. Maps are collapsed parents first, as nested
+  // maps only become apparent once the values of their parents are merged. Returns the key by
+  // which the values of each map can be found.
+  fn collapse_map_trails(&mut self, root_trail: &JsonTrail) -> HashMap<JsonTrail, String> {
+    let mut value_keys_by_trail = HashMap::new();
+
+    loop {
+      let next_map = self
+        .values_by_trail
+        .iter()
+        .filter(|(json_trail, trail_values)| {
+          (*json_trail == root_trail || json_trail.is_descendant_of(root_trail))
+            && !value_keys_by_trail.contains_key(*json_trail)
+            && trail_values.is_map_like()
+        })
+        .map(|(json_trail, trail_values)| (json_trail.clone(), trail_values.map_value_key()))
+        .min_by(|(trail_a, _), (trail_b, _)| trail_a.cmp(trail_b));
+
+      let (map_trail, value_key) = match next_map {
+        Some((map_trail, Some(value_key))) => (map_trail, value_key),
+        _ => break,
+      };
+
+      let value_trails = self
+        .values_by_trail
+        .keys()
+        .filter_map(|json_trail| {
+          let value_trail = json_trail.with_ancestor_object_key(&map_trail, &value_key)?;
+          Some((json_trail.clone(), value_trail))
+        })
+        .collect::<Vec<_>>();
+
+      for (json_trail, value_trail) in value_trails {
+        let trail_values = self.values_by_trail.remove(&json_trail).unwrap();
+        self
+          .values_by_trail
+          .entry(value_trail)
+          .or_insert_with_key(TrailValues::new)
+          .union(trail_values);
+      }
+
+      value_keys_by_trail.insert(map_trail, value_key);
+    }
+
+    value_keys_by_trail
+  }
 }
 
 fn shape_prototypes_to_commands(
@@ -111,6 +186,24 @@ fn shape_prototypes_to_commands(
             ShapeCommand::add_shape(shape_prototype.id, base_shape_kind, String::from(""));
           Some(vec![add_command])
         }
+        ShapePrototypeDescriptor::FormattedString { format } => {
+          let add_command = ShapeCommand::add_shape(
+            shape_prototype.id.clone(),
+            ShapeKind::StringKind,
+            String::from(""),
+          );
+          let format_command = ShapeCommand::set_string_format(shape_prototype.id, format);
+          Some(vec![add_command, format_command])
+        }
+        ShapePrototypeDescriptor::EnumString { values } => {
+          let add_command = ShapeCommand::add_shape(
+            shape_prototype.id.clone(),
+            ShapeKind::StringKind,
+            String::from(""),
+          );
+          let enum_command = ShapeCommand::set_string_enum(shape_prototype.id, values);
+          Some(vec![add_command, enum_command])
+        }
         ShapePrototypeDescriptor::OneOfShape {
           branches,
           parameter_ids,
@@ -206,6 +299,25 @@ fn shape_prototypes_to_commands(
 
           Some(commands)
         }
+        ShapePrototypeDescriptor::MapOfShape { value_shape_id } => {
+          let add_command = ShapeCommand::add_shape(
+            shape_prototype.id.clone(),
+            ShapeKind::MapKind,
+            String::from(""),
+          );
+          let set_value_command = ShapeCommand::set_parameter_shape(
+            shape_prototype.id,
+            String::from(
+              ShapeKind::MapKind
+                .get_parameter_descriptor()
+                .unwrap()
+                .shape_parameter_id,
+            ),
+            value_shape_id,
+          );
+
+          Some(vec![add_command, set_value_command])
+        }
         ShapePrototypeDescriptor::ObjectWithFields { fields } => {
           let mut commands = vec![];
           commands.push(ShapeCommand::add_shape(
@@ -277,6 +389,30 @@ pub struct TrailValues {
 
   #[serde(rename = "fieldSet")]
   pub field_sets: Vec<FieldSet>,
+
+  // formats of observed string values, and whether any string was seen without a known format
+  #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+  pub string_formats: BTreeSet<StringFormat>,
+  #[serde(default, skip_serializing_if = "is_false")]
+  pub was_unformatted_string: bool,
+
+  // occurrences of distinct string values, until there are too many of them to be an enum
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub string_values: BTreeMap<String, usize>,
+  #[serde(default, skip_serializing_if = "is_false")]
+  pub was_high_cardinality_string: bool,
+
+  // whether any observed number was an integer, and whether any wasn't or couldn't be told apart
+  #[serde(default, skip_serializing_if = "is_false")]
+  pub was_integer: bool,
+  #[serde(default, skip_serializing_if = "is_false")]
+  pub was_non_integer: bool,
+
+  // range of observed number values
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub number_minimum: Option<f64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub number_maximum: Option<f64>,
 }
 
 impl From<JsonTrail> for TrailValues {
@@ -297,6 +433,14 @@ impl TrailValues {
       was_object: false,
       was_empty_array: false,
       field_sets: Default::default(),
+      string_formats: Default::default(),
+      was_unformatted_string: false,
+      string_values: Default::default(),
+      was_high_cardinality_string: false,
+      was_integer: false,
+      was_non_integer: false,
+      number_minimum: None,
+      number_maximum: None,
     }
   }
 
@@ -308,6 +452,18 @@ impl TrailValues {
     self.was_array = self.was_array || new_values.was_array;
     self.was_empty_array = self.was_empty_array || new_values.was_empty_array;
     self.was_object = self.was_object || new_values.was_object;
+    self.was_unformatted_string = self.was_unformatted_string || new_values.was_unformatted_string;
+    self.string_formats.extend(new_values.string_formats);
+    self.was_high_cardinality_string =
+      self.was_high_cardinality_string || new_values.was_high_cardinality_string;
+    for (value, occurrences) in new_values.string_values {
+      self.insert_string_value(value, occurrences);
+    }
+    self.was_integer = self.was_integer || new_values.was_integer;
+    self.was_non_integer = self.was_non_integer || new_values.was_non_integer;
+    if let Some(minimum) = new_values.number_minimum {
+      self.insert_number_range(minimum, new_values.number_maximum.unwrap_or(minimum));
+    }
 
     for new_field_set in new_values.field_sets {
       self.insert_field_set(new_field_set);
@@ -329,35 +485,139 @@ impl TrailValues {
   }
 
   pub fn insert_field_set(&mut self, field_set: FieldSet) {
-    let exists = self.field_sets.iter().any(|existing_set| {
-      if let None = existing_set.difference(&field_set).next() {
-        true
-      } else {
-        false
-      }
-    });
+    let exists = self
+      .field_sets
+      .iter()
+      .any(|existing_set| existing_set == &field_set);
 
     if !exists {
       self.field_sets.push(field_set);
     }
   }
 
+  pub fn insert_string_format(&mut self, format: Option<StringFormat>) {
+    match format {
+      Some(format) => {
+        self.string_formats.insert(format);
+      }
+      None => self.was_unformatted_string = true,
+    }
+  }
+
+  pub fn insert_string_value(&mut self, value: String, occurrences: usize) {
+    if self.was_high_cardinality_string || value.len() > ENUM_MAX_VALUE_LENGTH {
+      self.was_high_cardinality_string = true;
+      self.string_values.clear();
+      return;
+    }
+
+    *self.string_values.entry(value).or_insert(0) += occurrences;
+    if self.string_values.len() > ENUM_MAX_VALUES {
+      self.was_high_cardinality_string = true;
+      self.string_values.clear();
+    }
+  }
+
+  pub fn insert_number(&mut self, number: Option<NumberDescriptor>) {
+    match number {
+      Some(observed) => {
+        if observed.is_integer {
+          self.was_integer = true;
+        } else {
+          self.was_non_integer = true;
+        }
+        self.insert_number_range(observed.minimum, observed.maximum);
+      }
+      None => self.was_non_integer = true,
+    }
+  }
+
+  fn insert_number_range(&mut self, minimum: f64, maximum: f64) {
+    self.number_minimum = Some(match self.number_minimum {
+      Some(existing) => existing.min(minimum),
+      None => minimum,
+    });
+    self.number_maximum = Some(match self.number_maximum {
+      Some(existing) => existing.max(maximum),
+      None => maximum,
+    });
+  }
+
+  // numbers are only learned as integers when every observed number was one
+  fn is_integer(&self) -> bool {
+    self.was_integer && !self.was_non_integer
+  }
+
+  fn string_enum(&self) -> Option<Vec<String>> {
+    let distinct_count = self.string_values.len();
+    let sample_count: usize = self.string_values.values().sum();
+
+    if self.was_high_cardinality_string
+      || distinct_count == 0
+      || sample_count < distinct_count * ENUM_MIN_OCCURRENCES_PER_VALUE
+    {
+      None
+    } else {
+      Some(self.string_values.keys().cloned().collect())
+    }
+  }
+
+  // a format is only learned when every observed string conformed to it
+  fn string_format(&self) -> Option<StringFormat> {
+    if self.was_unformatted_string || self.string_formats.len() != 1 {
+      None
+    } else {
+      self.string_formats.iter().next().cloned()
+    }
+  }
+
+  fn is_map_like(&self) -> bool {
+    if !self.was_object {
+      return false;
+    }
+
+    let keys = self.field_sets.iter().flatten().collect::<HashSet<_>>();
+    !keys.is_empty()
+      && (keys.len() > MAP_KEYS_CARDINALITY_THRESHOLD
+        || keys.iter().all(|key| is_identifier_like(key)))
+  }
+
+  fn map_value_key(&self) -> Option<String> {
+    self.field_sets.iter().flatten().min().cloned()
+  }
+
   fn into_shape_prototype(
     self,
     id_generator: &mut impl SpecIdGenerator,
     existing_prototypes: &HashMap<JsonTrail, ShapePrototype>,
+    map_value_key: Option<&String>,
+    config: &ShapeLearningConfig,
   ) -> ShapePrototype {
+    let string_enum = if config.learns_enums() {
+      self.string_enum()
+    } else {
+      None
+    };
+
     let mut descriptors: Vec<_> = vec![
       if self.was_string {
-        Some(ShapePrototypeDescriptor::PrimitiveKind {
-          base_shape_kind: ShapeKind::StringKind,
-        })
+        match (string_enum, self.string_format()) {
+          (Some(values), _) => Some(ShapePrototypeDescriptor::EnumString { values }),
+          (None, Some(format)) => Some(ShapePrototypeDescriptor::FormattedString { format }),
+          (None, None) => Some(ShapePrototypeDescriptor::PrimitiveKind {
+            base_shape_kind: ShapeKind::StringKind,
+          }),
+        }
       } else {
         None
       },
       if self.was_number {
         Some(ShapePrototypeDescriptor::PrimitiveKind {
-          base_shape_kind: ShapeKind::NumberKind,
+          base_shape_kind: if self.is_integer() {
+            ShapeKind::IntegerKind
+          } else {
+            ShapeKind::NumberKind
+          },
         })
       } else {
         None
@@ -387,17 +647,34 @@ impl TrailValues {
       } else {
         None
       },
-      if self.was_object {
+      match map_value_key {
+        Some(value_key) if self.was_object => {
+          let value_trail = self.trail.with_object_key(value_key.clone());
+          let value_prototype = existing_prototypes
+            .get(&value_trail)
+            .expect("map value shape prototype should have been generated before its parent map");
+
+          Some(ShapePrototypeDescriptor::MapOfShape {
+            value_shape_id: value_prototype.id.clone(),
+          })
+        }
+        _ => None,
+      },
+      if self.was_object && map_value_key.is_none() {
         let (field_keys, optional_keys) = {
-          let (all_keys_set, optional_keys_set) = self.field_sets.iter().fold(
-            (HashSet::new(), HashSet::new()),
-            |(all_keys, optional_keys): (HashSet<String>, HashSet<String>), field_set| {
-              let updated_all_keys: HashSet<String> = all_keys.union(&field_set).cloned().collect();
-              let missing_keys = updated_all_keys.difference(&field_set).cloned().collect();
-              let updated_optional_keys = optional_keys.union(&missing_keys).cloned().collect();
-              (updated_all_keys, updated_optional_keys)
-            },
-          );
+          let all_keys_set = self
+            .field_sets
+            .iter()
+            .flatten()
+            .cloned()
+            .collect::<HashSet<String>>();
+          // keys missing from any of the observed field sets are optional, whether they were
+          // observed before or after
+          let optional_keys_set = self
+            .field_sets
+            .iter()
+            .flat_map(|field_set| all_keys_set.difference(field_set).cloned())
+            .collect::<HashSet<String>>();
 
           let mut all_keys = all_keys_set.into_iter().collect::<Vec<_>>();
           all_keys.sort();
@@ -507,9 +784,18 @@ enum ShapePrototypeDescriptor {
     item_shape_id: ShapeId,
     item_is_unknown: bool,
   },
+  MapOfShape {
+    value_shape_id: ShapeId,
+  },
   PrimitiveKind {
     base_shape_kind: ShapeKind,
   },
+  FormattedString {
+    format: StringFormat,
+  },
+  EnumString {
+    values: Vec<String>,
+  },
   Unknown,
 }
 
@@ -521,11 +807,29 @@ struct FieldPrototypeDescriptor {
   value_shape_id: ShapeId,
 }
 
+fn is_false(value: &bool) -> bool {
+  !*value
+}
+
+// Keys of objects that are keyed by identifiers, like numeric ids, uuids, object ids or dates
+fn is_identifier_like(key: &str) -> bool {
+  let is_numeric = !key.is_empty() && key.chars().all(|c| c.is_ascii_digit());
+  let is_hex_id = key.len() >= 16
+    && key.chars().all(|c| c.is_ascii_hexdigit())
+    && key.chars().any(|c| c.is_ascii_digit());
+  let is_uuid = uuid::Uuid::parse_str(key).is_ok();
+  let is_date = chrono::NaiveDate::parse_from_str(key, "%Y-%m-%d").is_ok()
+    || chrono::DateTime::parse_from_rfc3339(key).is_ok();
+
+  is_numeric || is_hex_id || is_uuid || is_date
+}
+
 #[cfg(test)]
 mod test {
   use super::*;
   use crate::learn_shape::observe_body_trails;
   use crate::projections::SpecProjection;
+  use crate::shapes::diff as diff_shape;
   use crate::state::body::BodyDescriptor;
   use cqrs_core::Aggregate;
   use insta::assert_debug_snapshot;
@@ -735,6 +1039,42 @@ mod test {
     );
   }
 
+  #[test]
+  fn trail_observations_can_generate_commands_for_fields_observed_later() {
+    // observing more fields than before doesn't make the observation a duplicate of the earlier one
+    let partial_object_body = BodyDescriptor::from(json!({ "b-field": false }));
+    let complete_object_body = BodyDescriptor::from(json!({ "a-str": "a-value", "b-field": true }));
+
+    let observations = {
+      let mut result = observe_body_trails(partial_object_body.clone());
+      result.union(observe_body_trails(complete_object_body.clone()));
+      result
+    };
+
+    let mut test_id_generator = TestIdGenerator::default();
+    let (root_shape_id, commands) =
+      collect_commands(observations.into_commands(&mut test_id_generator, &JsonTrail::empty()));
+    let root_shape_id = root_shape_id.expect("object bodies should produce a root shape");
+
+    let mut spec_projection = SpecProjection::default();
+    for command in commands {
+      let events = spec_projection
+        .execute(command)
+        .expect("generated commands must be valid");
+      for event in events {
+        spec_projection.apply(event)
+      }
+    }
+
+    for body in [partial_object_body, complete_object_body] {
+      let diffs = diff_shape(spec_projection.shape(), Some(body), &root_shape_id);
+      assert!(
+        diffs.is_empty(),
+        "fields observed later should be learned as optional fields"
+      );
+    }
+  }
+
   #[test]
   fn trail_observations_can_generate_commands_for_nullable_bodies() {
     let nullable_primitive_observations = {
@@ -913,6 +1253,272 @@ mod test {
     );
   }
 
+  #[test]
+  fn trail_observations_can_generate_commands_for_map_bodies() {
+    let identifier_keyed_body = BodyDescriptor::from(json!({
+      "users": {
+        "1": { "name": "Homer" },
+        "2": { "name": "Marge", "age": 36 }
+      },
+      "visits": {
+        "2021-01-01": 3,
+        "2021-01-02": 8
+      }
+    }));
+    let identifier_keyed_observations = observe_body_trails(identifier_keyed_body).normalized();
+
+    let mut test_id_generator = TestIdGenerator::default();
+
+    let identifier_keyed_results = collect_commands(
+      identifier_keyed_observations.into_commands(&mut test_id_generator, &JsonTrail::empty()),
+    );
+    assert!(identifier_keyed_results.0.is_some());
+    assert_debug_snapshot!(
+      "trail_observations_can_generate_commands_for_map_bodies__identifier_keyed_results",
+      &identifier_keyed_results
+    );
+
+    let mut spec_projection = SpecProjection::default();
+    for command in identifier_keyed_results.1 {
+      let events = spec_projection
+        .execute(command)
+        .expect("generated commands must be valid");
+      for event in events {
+        spec_projection.apply(event)
+      }
+    }
+    let other_keys_body = BodyDescriptor::from(json!({
+      "users": {
+        "3": { "name": "Bart", "age": 10 }
+      },
+      "visits": {
+        "2021-02-01": 1
+      }
+    }));
+    let diff_results = diff_shape(
+      spec_projection.shape(),
+      Some(other_keys_body),
+      identifier_keyed_results.0.as_ref().unwrap(),
+    );
+    assert_eq!(
+      diff_results.len(),
+      0,
+      "values of maps with other keys should match the learned shape"
+    );
+
+    let many_keys_body = BodyDescriptor::from(json!((0..=MAP_KEYS_CARDINALITY_THRESHOLD)
+      .map(|i| (format!("key-{}", i), json!(i)))
+      .collect::<serde_json::Map<_, _>>()));
+    let many_keys_results = collect_commands(
+      observe_body_trails(many_keys_body)
+        .into_commands(&mut test_id_generator, &JsonTrail::empty()),
+    );
+    assert_valid_commands(many_keys_results.1.clone());
+    assert_eq!(
+      many_keys_results.1.len(),
+      3,
+      "objects with many keys should be learned as a map of a single value shape"
+    );
+
+    let named_keys_body = BodyDescriptor::from(json!({ "id": 1, "name": "Homer" }));
+    let named_keys_results = collect_commands(
+      observe_body_trails(named_keys_body)
+        .into_commands(&mut test_id_generator, &JsonTrail::empty()),
+    );
+    assert!(
+      named_keys_results.1.iter().all(|command| !matches!(
+        command,
+        SpecCommand::ShapeCommand(ShapeCommand::SetParameterShape(_))
+      )),
+      "objects with a few named keys should be learned as objects"
+    );
+  }
+
+  #[test]
+  fn trail_observations_can_generate_commands_for_formatted_strings() {
+    let mut observations = observe_body_trails(BodyDescriptor::from(json!({
+      "id": "8a9e6fb5-5c3d-4a52-8a4e-3fcb3a3e0f4b",
+      "createdAt": "2021-03-14T15:09:26.535Z",
+      "email": "homer@example.com",
+      "website": "https://example.com/homer",
+      "nickname": "homer@example.com",
+    })));
+    observations.union(observe_body_trails(BodyDescriptor::from(json!({
+      "id": "0f0b2a3c-7d4e-4f5a-9b6c-1d2e3f4a5b6c",
+      "createdAt": "2021-03-15T09:00:00+01:00",
+      "email": "marge@example.com",
+      "website": "http://example.com/marge",
+      "nickname": "Marge",
+    }))));
+
+    let mut test_id_generator = TestIdGenerator::default();
+    let results =
+      collect_commands(observations.into_commands(&mut test_id_generator, &JsonTrail::empty()));
+    assert!(results.0.is_some());
+    assert_debug_snapshot!(
+      "trail_observations_can_generate_commands_for_formatted_strings__results",
+      &results
+    );
+    assert_eq!(
+      results
+        .1
+        .iter()
+        .filter(|command| matches!(
+          command,
+          SpecCommand::ShapeCommand(ShapeCommand::SetStringFormat(_))
+        ))
+        .count(),
+      4,
+      "only strings that always conformed to a single format should be learned with a format"
+    );
+
+    let mut spec_projection = SpecProjection::default();
+    for command in results.1 {
+      let events = spec_projection
+        .execute(command)
+        .expect("generated commands must be valid");
+      for event in events {
+        spec_projection.apply(event)
+      }
+    }
+    let non_conforming_body = BodyDescriptor::from(json!({
+      "id": "8a9e6fb5-5c3d-4a52-8a4e-3fcb3a3e0f4b",
+      "createdAt": "yesterday",
+      "email": "homer@example.com",
+      "website": "https://example.com/homer",
+      "nickname": "Homer",
+    }));
+    let diff_results = diff_shape(
+      spec_projection.shape(),
+      Some(non_conforming_body),
+      results.0.as_ref().unwrap(),
+    );
+    assert_eq!(diff_results.len(), 1);
+  }
+
+  #[test]
+  fn trail_observations_can_generate_commands_for_enum_strings() {
+    let mut observations = observe_body_trails(BodyDescriptor::from(json!([
+      { "status": "active", "name": "Homer" },
+      { "status": "suspended", "name": "Marge" },
+      { "status": "active", "name": "Bart" },
+    ])));
+    observations.union(observe_body_trails(BodyDescriptor::from(json!([
+      { "status": "suspended", "name": "Lisa" },
+      { "status": "active", "name": "Maggie" },
+      { "status": "suspended", "name": "Abe" },
+    ]))));
+    let observations = observations.normalized();
+
+    let mut test_id_generator = TestIdGenerator::default();
+    let default_results = collect_commands(
+      observations
+        .clone()
+        .into_commands(&mut test_id_generator, &JsonTrail::empty()),
+    );
+    assert!(
+      default_results.1.iter().all(|command| !matches!(
+        command,
+        SpecCommand::ShapeCommand(ShapeCommand::SetStringEnum(_))
+      )),
+      "enums should only be learned when opted into"
+    );
+
+    let mut test_id_generator = TestIdGenerator::default();
+    let results = collect_commands(observations.into_commands_with_config(
+      &mut test_id_generator,
+      &JsonTrail::empty(),
+      &ShapeLearningConfig::default().with_enums(),
+    ));
+    assert!(results.0.is_some());
+    assert_debug_snapshot!(
+      "trail_observations_can_generate_commands_for_enum_strings__results",
+      &results
+    );
+    assert_eq!(
+      results
+        .1
+        .iter()
+        .filter(|command| matches!(
+          command,
+          SpecCommand::ShapeCommand(ShapeCommand::SetStringEnum(_))
+        ))
+        .count(),
+      1,
+      "only strings with few, repeatedly observed values should be learned as enums"
+    );
+
+    let mut spec_projection = SpecProjection::default();
+    for command in results.1 {
+      let events = spec_projection
+        .execute(command)
+        .expect("generated commands must be valid");
+      for event in events {
+        spec_projection.apply(event)
+      }
+    }
+    let new_member_body = BodyDescriptor::from(json!([
+      { "status": "active", "name": "Ned" },
+      { "status": "deleted", "name": "Maude" },
+    ]));
+    let diff_results = diff_shape(
+      spec_projection.shape(),
+      Some(new_member_body),
+      results.0.as_ref().unwrap(),
+    );
+    assert_eq!(diff_results.len(), 1);
+  }
+
+  #[test]
+  fn trail_observations_can_generate_commands_for_integers() {
+    let mut observations = observe_body_trails(BodyDescriptor::from(json!({
+      "count": 3,
+      "price": 10,
+    })));
+    observations.union(observe_body_trails(BodyDescriptor::from(json!({
+      "count": 42,
+      "price": 12.5,
+    }))));
+
+    let count_trail = JsonTrail::empty().with_object_key(String::from("count"));
+    let count_values = observations.get(&count_trail).unwrap();
+    assert_eq!(count_values.number_minimum, Some(3.0));
+    assert_eq!(count_values.number_maximum, Some(42.0));
+
+    let mut test_id_generator = TestIdGenerator::default();
+    let results =
+      collect_commands(observations.into_commands(&mut test_id_generator, &JsonTrail::empty()));
+    assert!(results.0.is_some());
+    assert_debug_snapshot!(
+      "trail_observations_can_generate_commands_for_integers__results",
+      &results
+    );
+
+    let mut spec_projection = SpecProjection::default();
+    for command in results.1 {
+      let events = spec_projection
+        .execute(command)
+        .expect("generated commands must be valid");
+      for event in events {
+        spec_projection.apply(event)
+      }
+    }
+    let float_count_body = BodyDescriptor::from(json!({
+      "count": 7.5,
+      "price": 9,
+    }));
+    let diff_results = diff_shape(
+      spec_projection.shape(),
+      Some(float_count_body),
+      results.0.as_ref().unwrap(),
+    );
+    assert_eq!(
+      diff_results.len(),
+      1,
+      "only numbers that were always integers should be learned as integers"
+    );
+  }
+
   fn collect_commands(
     (root_shape_id, commands): (Option<String>, impl Iterator<Item = SpecCommand>),
   ) -> (Option<String>, Vec<SpecCommand>) {
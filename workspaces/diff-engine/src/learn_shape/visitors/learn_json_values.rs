@@ -112,8 +112,22 @@ impl BodyPrimitiveVisitor<TrailValues> for LearnPrimitiveVisitor {
 
     match body {
       BodyDescriptor::Boolean => trail_values.was_boolean = true,
-      BodyDescriptor::Number => trail_values.was_number = true,
-      BodyDescriptor::String => trail_values.was_string = true,
+      BodyDescriptor::Number(number_descriptor) => {
+        trail_values.was_number = true;
+        trail_values.insert_number(number_descriptor);
+      }
+      BodyDescriptor::String(string_descriptor) => {
+        trail_values.was_string = true;
+        match string_descriptor {
+          Some(observed) => {
+            trail_values.insert_string_format(observed.format);
+            for value in observed.values {
+              trail_values.insert_string_value(value, 1);
+            }
+          }
+          None => trail_values.insert_string_format(None),
+        }
+      }
       BodyDescriptor::Null => trail_values.was_null = true,
       _ => unreachable!("should not call primitive visitor without a primitive value"),
     }
@@ -1,9 +1,15 @@
 #![allow(dead_code, unused_imports, unused_variables)]
 
 mod commands;
+mod compact;
+mod compare;
 mod events;
 mod interactions;
+mod json_schema;
 mod learn_shape;
+mod lint;
+mod openapi;
+mod orphans;
 mod projections;
 mod protos;
 mod queries;
@@ -15,25 +21,52 @@ mod state;
 pub mod streams;
 
 pub use commands::{CommandContext, EndpointCommand, RfcCommand, SpecCommand, SpecCommandHandler};
+pub use compact::compact as compact_spec;
+pub use compact::{CompactionConfig, CompactionError};
+pub use compare::compare as compare_specs;
+pub use compare::{ChangeLocation, SpecChange, SpecChangeKind};
 pub use cqrs_core::Aggregate;
 pub use events::{
   http_interaction::{ArbitraryData, Body, HttpInteraction, Request, Response},
-  RfcEvent, SpecChunkEvent, SpecEvent,
+  EventContext, RfcEvent, SpecChunkEvent, SpecEvent,
 };
 pub use interactions::diff as diff_interaction;
-pub use interactions::result::{BodyAnalysisLocation, BodyAnalysisResult, InteractionDiffResult};
-pub use interactions::{analyze_documented_bodies, analyze_undocumented_bodies};
-pub use learn_shape::{TrailObservationsResult, TrailValues};
+pub use interactions::diff_with_config as diff_interaction_with_config;
+pub use interactions::InteractionDiffConfig;
+pub use interactions::result::{
+  BodyAnalysisLocation, BodyAnalysisResult, InteractionDiffResult, ParameterAnalysisResult,
+  RequestSpecTrail,
+};
+pub use interactions::{
+  analyze_documented_bodies, analyze_undocumented_bodies, analyze_undocumented_parameters,
+  DiffSeverity, DiffSeverityConfig,
+};
+pub use json_schema::from_shape as json_schema_from_shape;
+pub use learn_shape::{ShapeLearningConfig, TrailObservationsResult, TrailValues};
+pub use lint::lint as lint_spec;
+pub use lint::{LintFinding, LintRule};
+pub use openapi::export as export_openapi;
+pub use openapi::import as import_openapi;
+pub use openapi::{OpenApiImport, OpenApiImportError, UnsupportedConstruct};
+pub use orphans::{orphaned_shapes, OrphanedShape};
 pub use projections::{
+  ChangedEndpoint, ChangedShape, ChangelogChanges, ChangelogEntry, ChangelogProjection,
   EndpointProjection, LearnedShapeDiffAffordancesProjection, LearnedUndocumentedBodiesProjection,
+  LearnedUndocumentedParametersProjection,
   ShapeProjection, SpecAssemblerProjection, SpecProjection, ResponseBodyDescriptor
 };
 pub use protos::shapehash;
 pub use queries::endpoint::EndpointQueries;
 pub use state::endpoint::ResponseId;
-pub use shapes::{diff as diff_shape, JsonTrail};
+pub use shapes::{diff as diff_shape, JsonTrail, ShapeDiffResult};
 pub use spec::append_batch as append_batch_to_spec;
-pub use state::{body::BodyDescriptor, SpecIdGenerator, TaggedInput, Tags};
+pub use spec::events_until as spec_events_until;
+pub use spec::{SpecHistoryError, SpecHistoryPoint};
+pub use state::{
+  body::{BodyDescriptor, BodyKind},
+  shape::ShapeKind,
+  SpecIdGenerator, TaggedInput, Tags,
+};
 
 pub mod errors {
   pub use super::events::EventLoadingError;
@@ -0,0 +1,709 @@
+use crate::commands::endpoint::CommandValidationQueries as EndpointValidationQueries;
+use crate::commands::rfc::CommandValidationQueries as HistoryValidationQueries;
+use crate::commands::shape::CommandValidationQueries as ShapeValidationQueries;
+use crate::commands::CommandContext;
+use crate::events::{EndpointEvent, EventContext, RfcEvent, ShapeEvent, SpecEvent};
+use crate::projections::SpecProjection;
+use crate::state::shape::{FieldShapeDescriptor, ParameterShapeDescriptor, ProviderDescriptor};
+use cqrs_core::{Aggregate, Event};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Check whether a stream of spec events is consistent with itself, the way it is when every event
+/// was produced by handling a command: events only refer to paths, requests, responses, parameters,
+/// shapes, fields and batch commits that exist at that point, shapes aren't removed while still in
+/// use, operations have a single body per content type and every batch commit ends.
+///
+/// Events are replayed in order, each checked against the spec as it was before the event. Events
+/// that refer to something that doesn't exist can't be applied to the spec, so they are skipped.
+pub fn lint<I>(events: I) -> Vec<LintFinding>
+where
+  I: IntoIterator<Item = SpecEvent>,
+{
+  let mut linter = Linter::default();
+  for (event_index, event) in events.into_iter().enumerate() {
+    linter.check(event_index, event);
+  }
+  linter.finish()
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintFinding {
+  // position of the offending event in the event stream, starting at 0
+  pub event_index: usize,
+  pub event_type: &'static str,
+  pub rule: LintRule,
+  pub message: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum LintRule {
+  /// The event refers to something that doesn't exist (anymore)
+  DanglingReference,
+  /// The event adds something with an id that is already taken
+  DuplicateId,
+  /// More than one body with the same content type for a request or response
+  DuplicateContentType,
+  /// A shape is removed while bodies, parameters, fields or other shapes still refer to it
+  RemovedShapeInUse,
+  /// The event uses a shape descriptor the spec can't be built from
+  UnsupportedDescriptor,
+  /// A batch commit is started without ever being ended
+  UnendedBatchCommit,
+  /// A batch commit is ended more than once
+  RepeatedBatchCommitEnd,
+  /// A batch commit is started without an event context
+  MissingEventContext,
+}
+
+impl LintRule {
+  // whether applying an event with this finding would corrupt the spec, or fail outright
+  fn prevents_applying(&self) -> bool {
+    matches!(
+      self,
+      LintRule::DanglingReference | LintRule::DuplicateId | LintRule::UnsupportedDescriptor
+    )
+  }
+}
+
+impl fmt::Display for LintFinding {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "event {} ({}): {}",
+      self.event_index, self.event_type, self.message
+    )
+  }
+}
+
+#[derive(Debug, Default)]
+struct Linter {
+  spec_projection: SpecProjection,
+  findings: Vec<LintFinding>,
+  // removed ids and the index of the event that removed them, to explain dangling references
+  removed_ids: HashMap<String, usize>,
+  // started batch commits that haven't ended yet and the index of the event that started them
+  open_batch_commits: BTreeMap<String, usize>,
+}
+
+impl Linter {
+  fn check(&mut self, event_index: usize, mut event: SpecEvent) {
+    let mut problems = Problems::new(&self.removed_ids);
+    match &event {
+      SpecEvent::EndpointEvent(endpoint_event) => {
+        check_endpoint_event(&self.spec_projection, endpoint_event, &mut problems)
+      }
+      SpecEvent::ShapeEvent(shape_event) => {
+        check_shape_event(&self.spec_projection, shape_event, &mut problems)
+      }
+      SpecEvent::RfcEvent(rfc_event) => {
+        check_rfc_event(&self.spec_projection, rfc_event, &mut problems)
+      }
+    }
+
+    let problems = problems.list;
+    let can_apply = problems.iter().all(|(rule, _)| !rule.prevents_applying());
+    let event_type = event.event_type();
+    self
+      .findings
+      .extend(problems.into_iter().map(|(rule, message)| LintFinding {
+        event_index,
+        event_type,
+        rule,
+        message,
+      }));
+    if !can_apply {
+      return;
+    }
+
+    match &mut event {
+      SpecEvent::RfcEvent(RfcEvent::BatchCommitStarted(e)) => {
+        self
+          .open_batch_commits
+          .insert(e.batch_id.clone(), event_index);
+        // the spec can't be built from batch commits without context, so make some up to continue
+        if e.event_context.is_none() {
+          e.event_context = Some(EventContext::from(CommandContext {
+            client_command_batch_id: e.batch_id.clone(),
+            ..CommandContext::default()
+          }));
+        }
+      }
+      SpecEvent::RfcEvent(RfcEvent::BatchCommitEnded(e)) => {
+        self.open_batch_commits.remove(&e.batch_id);
+      }
+      _ => {
+        if let Some(removed_id) = removed_id(&event) {
+          self.removed_ids.insert(removed_id, event_index);
+        }
+      }
+    }
+    self.spec_projection.apply(event);
+  }
+
+  fn finish(mut self) -> Vec<LintFinding> {
+    for (batch_id, event_index) in self.open_batch_commits {
+      self.findings.push(LintFinding {
+        event_index,
+        event_type: "BatchCommitStarted",
+        rule: LintRule::UnendedBatchCommit,
+        message: format!("batch commit `{}` is never ended", batch_id),
+      });
+    }
+    self.findings.sort_by_key(|finding| finding.event_index);
+    self.findings
+  }
+}
+
+struct Problems<'a> {
+  removed_ids: &'a HashMap<String, usize>,
+  list: Vec<(LintRule, String)>,
+}
+
+impl<'a> Problems<'a> {
+  fn new(removed_ids: &'a HashMap<String, usize>) -> Self {
+    Self {
+      removed_ids,
+      list: vec![],
+    }
+  }
+
+  fn push(&mut self, rule: LintRule, message: String) {
+    self.list.push((rule, message));
+  }
+
+  fn require_existing(&mut self, exists: bool, kind: &str, id: &str) {
+    if exists {
+      return;
+    }
+    let message = match self.removed_ids.get(id) {
+      Some(event_index) => format!("{} `{}` was removed by event {}", kind, id, event_index),
+      None => format!("{} `{}` does not exist", kind, id),
+    };
+    self.push(LintRule::DanglingReference, message);
+  }
+
+  fn require_assignable(&mut self, exists: bool, kind: &str, id: &str) {
+    if exists {
+      self.push(
+        LintRule::DuplicateId,
+        format!("{} `{}` already exists", kind, id),
+      );
+    }
+  }
+
+  fn require_unique_content_type(&mut self, exists: bool, kind: &str, content_type: &str) {
+    if exists {
+      self.push(
+        LintRule::DuplicateContentType,
+        format!(
+          "{} already has a body with content type `{}`",
+          kind, content_type
+        ),
+      );
+    }
+  }
+}
+
+fn check_endpoint_event(
+  spec_projection: &SpecProjection,
+  event: &EndpointEvent,
+  problems: &mut Problems,
+) {
+  let endpoint = EndpointValidationQueries::from((spec_projection.endpoint(), event));
+  let shape = ShapeValidationQueries::from((spec_projection.shape(), event));
+
+  match event {
+    EndpointEvent::PathComponentAdded(e) => {
+      problems.require_existing(
+        endpoint.path_component_id_exists(&e.parent_path_id),
+        "parent path component",
+        &e.parent_path_id,
+      );
+      problems.require_assignable(
+        endpoint.path_component_id_exists(&e.path_id),
+        "path component",
+        &e.path_id,
+      );
+    }
+    EndpointEvent::PathParameterAdded(e) => {
+      problems.require_existing(
+        endpoint.path_component_id_exists(&e.parent_path_id),
+        "parent path component",
+        &e.parent_path_id,
+      );
+      problems.require_assignable(
+        endpoint.path_component_id_exists(&e.path_id),
+        "path component",
+        &e.path_id,
+      );
+    }
+    EndpointEvent::PathComponentRenamed(e) => problems.require_existing(
+      endpoint.path_component_id_exists(&e.path_id),
+      "path component",
+      &e.path_id,
+    ),
+    EndpointEvent::PathComponentRemoved(e) => problems.require_existing(
+      endpoint.path_component_id_exists(&e.path_id),
+      "path component",
+      &e.path_id,
+    ),
+    EndpointEvent::PathParameterRenamed(e) => problems.require_existing(
+      endpoint.path_component_id_exists(&e.path_id),
+      "path component",
+      &e.path_id,
+    ),
+    EndpointEvent::PathParameterRemoved(e) => problems.require_existing(
+      endpoint.path_component_id_exists(&e.path_id),
+      "path component",
+      &e.path_id,
+    ),
+    EndpointEvent::PathParameterShapeSet(e) => {
+      problems.require_existing(
+        endpoint.path_component_id_exists(&e.path_id),
+        "path component",
+        &e.path_id,
+      );
+      problems.require_existing(
+        shape.shape_id_exists(&e.shape_descriptor.shape_id),
+        "shape",
+        &e.shape_descriptor.shape_id,
+      );
+    }
+
+    EndpointEvent::RequestParameterAddedByPathAndMethod(e) => {
+      problems.require_existing(
+        endpoint.path_component_id_exists(&e.path_id),
+        "path component",
+        &e.path_id,
+      );
+      problems.require_assignable(
+        endpoint.request_parameter_exists(&e.parameter_id),
+        "request parameter",
+        &e.parameter_id,
+      );
+    }
+    EndpointEvent::RequestParameterRenamed(e) => problems.require_existing(
+      endpoint.request_parameter_exists(&e.parameter_id),
+      "request parameter",
+      &e.parameter_id,
+    ),
+    EndpointEvent::RequestParameterShapeSet(e) => {
+      problems.require_existing(
+        endpoint.request_parameter_exists(&e.parameter_id),
+        "request parameter",
+        &e.parameter_id,
+      );
+      problems.require_existing(
+        shape.shape_id_exists(&e.parameter_descriptor.shape_id),
+        "shape",
+        &e.parameter_descriptor.shape_id,
+      );
+    }
+    EndpointEvent::RequestParameterShapeUnset(e) => problems.require_existing(
+      endpoint.request_parameter_exists(&e.parameter_id),
+      "request parameter",
+      &e.parameter_id,
+    ),
+    EndpointEvent::RequestParameterRemoved(e) => problems.require_existing(
+      endpoint.request_parameter_exists(&e.parameter_id),
+      "request parameter",
+      &e.parameter_id,
+    ),
+
+    EndpointEvent::RequestAdded(e) => {
+      problems.require_existing(
+        endpoint.path_component_id_exists(&e.path_id),
+        "path component",
+        &e.path_id,
+      );
+      problems.require_assignable(
+        endpoint.request_exists(&e.request_id),
+        "request",
+        &e.request_id,
+      );
+    }
+    EndpointEvent::RequestContentTypeSet(e) => {
+      problems.require_existing(
+        endpoint.request_exists(&e.request_id),
+        "request",
+        &e.request_id,
+      );
+      problems.require_unique_content_type(
+        endpoint.request_body_content_type_exists(&e.request_id, &e.http_content_type),
+        "operation",
+        &e.http_content_type,
+      );
+    }
+    EndpointEvent::RequestBodySet(e) => {
+      problems.require_existing(
+        endpoint.request_exists(&e.request_id),
+        "request",
+        &e.request_id,
+      );
+      problems.require_existing(
+        shape.shape_id_exists(&e.body_descriptor.shape_id),
+        "shape",
+        &e.body_descriptor.shape_id,
+      );
+      problems.require_unique_content_type(
+        endpoint
+          .request_body_content_type_exists(&e.request_id, &e.body_descriptor.http_content_type),
+        "operation",
+        &e.body_descriptor.http_content_type,
+      );
+    }
+    EndpointEvent::RequestBodyUnset(e) => problems.require_existing(
+      endpoint.request_exists(&e.request_id),
+      "request",
+      &e.request_id,
+    ),
+    EndpointEvent::RequestRemoved(e) => problems.require_existing(
+      endpoint.request_exists(&e.request_id),
+      "request",
+      &e.request_id,
+    ),
+
+    EndpointEvent::ResponseAddedByPathAndMethod(e) => {
+      problems.require_existing(
+        endpoint.path_component_id_exists(&e.path_id),
+        "path component",
+        &e.path_id,
+      );
+      problems.require_assignable(
+        endpoint.response_exists(&e.response_id),
+        "response",
+        &e.response_id,
+      );
+    }
+    EndpointEvent::ResponseStatusCodeSet(e) => problems.require_existing(
+      endpoint.response_exists(&e.response_id),
+      "response",
+      &e.response_id,
+    ),
+    EndpointEvent::ResponseContentTypeSet(e) => {
+      problems.require_existing(
+        endpoint.response_exists(&e.response_id),
+        "response",
+        &e.response_id,
+      );
+      problems.require_unique_content_type(
+        endpoint.response_body_content_type_exists(&e.response_id, &e.http_content_type),
+        "status code of operation",
+        &e.http_content_type,
+      );
+    }
+    EndpointEvent::ResponseBodySet(e) => {
+      problems.require_existing(
+        endpoint.response_exists(&e.response_id),
+        "response",
+        &e.response_id,
+      );
+      problems.require_existing(
+        shape.shape_id_exists(&e.body_descriptor.shape_id),
+        "shape",
+        &e.body_descriptor.shape_id,
+      );
+      problems.require_unique_content_type(
+        endpoint
+          .response_body_content_type_exists(&e.response_id, &e.body_descriptor.http_content_type),
+        "status code of operation",
+        &e.body_descriptor.http_content_type,
+      );
+    }
+    EndpointEvent::ResponseBodyUnset(e) => problems.require_existing(
+      endpoint.response_exists(&e.response_id),
+      "response",
+      &e.response_id,
+    ),
+    EndpointEvent::ResponseRemoved(e) => problems.require_existing(
+      endpoint.response_exists(&e.response_id),
+      "response",
+      &e.response_id,
+    ),
+  }
+}
+
+fn check_shape_event(
+  spec_projection: &SpecProjection,
+  event: &ShapeEvent,
+  problems: &mut Problems,
+) {
+  let shape = ShapeValidationQueries::from((spec_projection.shape(), event));
+
+  match event {
+    ShapeEvent::ShapeAdded(e) => {
+      problems.require_assignable(shape.shape_id_exists(&e.shape_id), "shape", &e.shape_id);
+      problems.require_existing(
+        shape.shape_id_exists(&e.base_shape_id),
+        "base shape",
+        &e.base_shape_id,
+      );
+    }
+    ShapeEvent::BaseShapeSet(e) => {
+      problems.require_existing(shape.shape_id_exists(&e.shape_id), "shape", &e.shape_id);
+      problems.require_existing(
+        shape.shape_id_exists(&e.base_shape_id),
+        "base shape",
+        &e.base_shape_id,
+      );
+    }
+    ShapeEvent::ShapeRenamed(e) => {
+      problems.require_existing(shape.shape_id_exists(&e.shape_id), "shape", &e.shape_id)
+    }
+    ShapeEvent::ShapeRemoved(e) => {
+      problems.require_existing(shape.shape_id_exists(&e.shape_id), "shape", &e.shape_id);
+
+      let endpoint = EndpointValidationQueries::from((spec_projection.endpoint(), event));
+      if shape.shape_is_referenced(&e.shape_id) || endpoint.shape_is_referenced(&e.shape_id) {
+        problems.push(
+          LintRule::RemovedShapeInUse,
+          format!("shape `{}` is removed while still in use", e.shape_id),
+        );
+      }
+    }
+
+    ShapeEvent::ShapeParameterAdded(e) => {
+      problems.require_existing(shape.shape_id_exists(&e.shape_id), "shape", &e.shape_id);
+      problems.require_assignable(
+        shape.shape_parameter_id_exists(&e.shape_parameter_id),
+        "shape parameter",
+        &e.shape_parameter_id,
+      );
+    }
+    ShapeEvent::ShapeParameterShapeSet(e) => match &e.shape_descriptor {
+      ParameterShapeDescriptor::ProviderInShape(descriptor) => {
+        problems.require_existing(
+          shape.shape_id_exists(&descriptor.shape_id),
+          "shape",
+          &descriptor.shape_id,
+        );
+        problems.require_existing(
+          shape.shape_parameter_id_exists(&descriptor.consuming_parameter_id),
+          "shape parameter",
+          &descriptor.consuming_parameter_id,
+        );
+        match &descriptor.provider_descriptor {
+          ProviderDescriptor::ShapeProvider(provider) => problems.require_existing(
+            shape.shape_id_exists(&provider.shape_id),
+            "provided shape",
+            &provider.shape_id,
+          ),
+          ProviderDescriptor::NoProvider(_) | ProviderDescriptor::ParameterProvider(_) => problems
+            .push(
+              LintRule::UnsupportedDescriptor,
+              format!(
+                "shape parameter `{}` can only be provided by a shape",
+                descriptor.consuming_parameter_id
+              ),
+            ),
+        }
+      }
+      ParameterShapeDescriptor::ProviderInField(_) => problems.push(
+        LintRule::UnsupportedDescriptor,
+        String::from("shape parameters can only be provided in shapes, not in fields"),
+      ),
+    },
+    ShapeEvent::ShapeParameterRenamed(e) => problems.require_existing(
+      shape.shape_parameter_id_exists(&e.shape_parameter_id),
+      "shape parameter",
+      &e.shape_parameter_id,
+    ),
+    ShapeEvent::ShapeParameterRemoved(e) => problems.require_existing(
+      shape.shape_parameter_id_exists(&e.shape_parameter_id),
+      "shape parameter",
+      &e.shape_parameter_id,
+    ),
+
+    ShapeEvent::FieldAdded(e) => {
+      problems.require_existing(shape.shape_id_exists(&e.shape_id), "shape", &e.shape_id);
+      problems.require_assignable(shape.field_id_exists(&e.field_id), "field", &e.field_id);
+      check_field_shape_descriptor(&shape, &e.shape_descriptor, problems);
+    }
+    ShapeEvent::FieldShapeSet(e) => {
+      if let FieldShapeDescriptor::FieldShapeFromShape(descriptor) = &e.shape_descriptor {
+        problems.require_existing(
+          shape.field_id_exists(&descriptor.field_id),
+          "field",
+          &descriptor.field_id,
+        );
+      }
+      check_field_shape_descriptor(&shape, &e.shape_descriptor, problems);
+    }
+    ShapeEvent::FieldRenamed(e) => {
+      problems.require_existing(shape.field_id_exists(&e.field_id), "field", &e.field_id)
+    }
+    ShapeEvent::FieldRemoved(e) => {
+      problems.require_existing(shape.field_id_exists(&e.field_id), "field", &e.field_id)
+    }
+
+    ShapeEvent::StringFormatSet(e) => {
+      problems.require_existing(shape.shape_id_exists(&e.shape_id), "shape", &e.shape_id)
+    }
+    ShapeEvent::StringEnumSet(e) => {
+      problems.require_existing(shape.shape_id_exists(&e.shape_id), "shape", &e.shape_id)
+    }
+  }
+}
+
+fn check_field_shape_descriptor(
+  shape: &ShapeValidationQueries,
+  descriptor: &FieldShapeDescriptor,
+  problems: &mut Problems,
+) {
+  match descriptor {
+    FieldShapeDescriptor::FieldShapeFromShape(descriptor) => problems.require_existing(
+      shape.shape_id_exists(&descriptor.shape_id),
+      "shape",
+      &descriptor.shape_id,
+    ),
+    FieldShapeDescriptor::FieldShapeFromParameter(descriptor) => problems.push(
+      LintRule::UnsupportedDescriptor,
+      format!(
+        "field `{}` can only get its shape from a shape, not a shape parameter",
+        descriptor.field_id
+      ),
+    ),
+  }
+}
+
+fn check_rfc_event(spec_projection: &SpecProjection, event: &RfcEvent, problems: &mut Problems) {
+  let history = HistoryValidationQueries::from((spec_projection.history(), event));
+
+  match event {
+    RfcEvent::BatchCommitStarted(e) => {
+      problems.require_assignable(
+        history.batch_commit_id_exists(&e.batch_id),
+        "batch commit",
+        &e.batch_id,
+      );
+      if let Some(parent_id) = &e.parent_id {
+        problems.require_existing(
+          history.batch_commit_id_exists(parent_id),
+          "parent batch commit",
+          parent_id,
+        );
+      }
+      if e.event_context.is_none() {
+        problems.push(
+          LintRule::MissingEventContext,
+          format!("batch commit `{}` has no event context", e.batch_id),
+        );
+      }
+    }
+    RfcEvent::BatchCommitEnded(e) => {
+      problems.require_existing(
+        history.batch_commit_id_exists(&e.batch_id),
+        "batch commit",
+        &e.batch_id,
+      );
+      if history.batch_commit_completed(&e.batch_id) {
+        problems.push(
+          LintRule::RepeatedBatchCommitEnd,
+          format!("batch commit `{}` has already ended", e.batch_id),
+        );
+      }
+    }
+    RfcEvent::APINamed(_) | RfcEvent::ContributionAdded(_) | RfcEvent::GitStateSet(_) => {}
+  }
+}
+
+// the id of what a removal event removes, if any
+fn removed_id(event: &SpecEvent) -> Option<String> {
+  let removed_id = match event {
+    SpecEvent::EndpointEvent(EndpointEvent::PathComponentRemoved(e)) => &e.path_id,
+    SpecEvent::EndpointEvent(EndpointEvent::PathParameterRemoved(e)) => &e.path_id,
+    SpecEvent::EndpointEvent(EndpointEvent::RequestParameterRemoved(e)) => &e.parameter_id,
+    SpecEvent::EndpointEvent(EndpointEvent::RequestRemoved(e)) => &e.request_id,
+    SpecEvent::EndpointEvent(EndpointEvent::ResponseRemoved(e)) => &e.response_id,
+    SpecEvent::ShapeEvent(ShapeEvent::ShapeRemoved(e)) => &e.shape_id,
+    SpecEvent::ShapeEvent(ShapeEvent::ShapeParameterRemoved(e)) => &e.shape_parameter_id,
+    SpecEvent::ShapeEvent(ShapeEvent::FieldRemoved(e)) => &e.field_id,
+    _ => return None,
+  };
+  Some(removed_id.clone())
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use insta::assert_json_snapshot;
+  use serde_json::json;
+
+  fn lint_events(events: serde_json::Value) -> Vec<LintFinding> {
+    let events: Vec<SpecEvent> =
+      serde_json::from_value(events).expect("initial events should be valid spec events");
+    lint(events)
+  }
+
+  fn context() -> serde_json::Value {
+    json!({"clientId": "anonymous","clientSessionId": "session_1","clientCommandBatchId": "batch_1","createdAt": "2021-03-01T00:00:00.000Z"})
+  }
+
+  #[test]
+  fn consistent_spec_has_no_findings() {
+    let findings = lint_events(json!([
+      {"BatchCommitStarted": {"batchId": "batch_1","commitMessage": "","eventContext": context()}},
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"ShapeAdded": {"shapeId": "todo_shape","baseShapeId": "$object","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": "Todo"}},
+      {"ShapeAdded": {"shapeId": "title_shape","baseShapeId": "$string","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"FieldAdded": {"fieldId": "title_field","shapeId": "todo_shape","name": "title","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "title_field","shapeId": "title_shape"}}}},
+      {"ShapeAdded": {"shapeId": "todos_shape","baseShapeId": "$list","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "todos_shape","providerDescriptor": {"ShapeProvider": {"shapeId": "todo_shape"}},"consumingParameterId": "$listItem"}}}},
+      {"RequestAdded": {"requestId": "request_1","pathId": "path_1","httpMethod": "GET"}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_1","pathId": "path_1","httpMethod": "GET","httpStatusCode": 200}},
+      {"ResponseBodySet": {"responseId": "response_1","bodyDescriptor": {"httpContentType": "application/json","shapeId": "todos_shape","isRemoved": false}}},
+      {"ResponseBodySet": {"responseId": "response_1","bodyDescriptor": {"httpContentType": "application/json","shapeId": "todos_shape","isRemoved": false}}},
+      {"FieldRemoved": {"fieldId": "title_field"}},
+      {"ShapeRemoved": {"shapeId": "title_shape"}},
+      {"BatchCommitEnded": {"batchId": "batch_1"}},
+    ]));
+
+    assert!(findings.is_empty(), "unexpected findings: {:?}", findings);
+  }
+
+  #[test]
+  fn can_find_inconsistent_events() {
+    let findings = lint_events(json!([
+      {"BatchCommitStarted": {"batchId": "batch_1","commitMessage": "","eventContext": context()}},
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"PathComponentAdded": {"pathId": "path_2","parentPathId": "root","name": "users"}},
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"ShapeAdded": {"shapeId": "todo_shape","baseShapeId": "$object","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": "Todo"}},
+      {"FieldAdded": {"fieldId": "title_field","shapeId": "todo_shape","name": "title","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "title_field","shapeId": "title_shape"}}}},
+      {"ShapeAdded": {"shapeId": "todos_shape","baseShapeId": "$list","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "todos_shape","providerDescriptor": {"ShapeProvider": {"shapeId": "todo_shape"}},"consumingParameterId": "$itemsOfList"}}}},
+      {"RequestAdded": {"requestId": "request_1","pathId": "path_1","httpMethod": "GET"}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_1","pathId": "path_1","httpMethod": "GET","httpStatusCode": 200}},
+      {"ResponseBodySet": {"responseId": "response_1","bodyDescriptor": {"httpContentType": "application/json","shapeId": "todos_shape","isRemoved": false}}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_2","pathId": "path_1","httpMethod": "GET","httpStatusCode": 200}},
+      {"ResponseBodySet": {"responseId": "response_2","bodyDescriptor": {"httpContentType": "application/json","shapeId": "todo_shape","isRemoved": false}}},
+      {"ShapeRemoved": {"shapeId": "todo_shape"}},
+      {"BatchCommitEnded": {"batchId": "batch_1"}},
+      {"BatchCommitEnded": {"batchId": "batch_1"}},
+      {"BatchCommitStarted": {"batchId": "batch_2","parentId": "batch_1","commitMessage": ""}},
+      {"PathComponentRemoved": {"pathId": "path_2"}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_3","pathId": "path_2","httpMethod": "GET","httpStatusCode": 200}},
+      {"ResponseStatusCodeSet": {"responseId": "response_3","httpStatusCode": 404}},
+    ]));
+
+    assert_json_snapshot!("can_find_inconsistent_events__findings", findings);
+  }
+
+  #[test]
+  fn findings_explain_themselves() {
+    let findings = lint_events(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"PathComponentRemoved": {"pathId": "path_1"}},
+      {"RequestAdded": {"requestId": "request_1","pathId": "path_1","httpMethod": "GET"}},
+    ]));
+
+    let messages: Vec<_> = findings.iter().map(|finding| finding.to_string()).collect();
+    assert_eq!(
+      messages,
+      vec!["event 2 (RequestAdded): path component `path_1` was removed by event 1"]
+    );
+  }
+}
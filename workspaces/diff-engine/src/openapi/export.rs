@@ -0,0 +1,312 @@
+use crate::projections::endpoint::{
+  EndpointProjection, Node, RequestBodyDescriptor, ResponseBodyDescriptor,
+  HEADER_PARAMETER_LOCATION, QUERY_PARAMETER_LOCATION, ROOT_PATH_ID,
+};
+use crate::projections::SpecProjection;
+use crate::json_schema::{SchemaDialect, SchemasCollector};
+use crate::queries::endpoint::EndpointQueries;
+use crate::queries::shape::ShapeQueries;
+use crate::state::endpoint::PathComponentId;
+use petgraph::graph::NodeIndex;
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+
+pub const OPENAPI_VERSION: &str = "3.0.3";
+
+/// Describe the endpoints and shapes of a spec as an OpenAPI 3.0 document. Named
+/// shapes (and shapes that refer back to themselves) are described once under
+/// `components/schemas` and referenced from everywhere they're used.
+pub fn export(spec_projection: &SpecProjection) -> JsonValue {
+  let shape_queries = ShapeQueries::new(spec_projection.shape());
+  let mut schemas = SchemasCollector::new(&shape_queries, SchemaDialect::OpenApi30);
+
+  let paths = export_paths(spec_projection.endpoint(), &mut schemas);
+
+  json!({
+    "openapi": OPENAPI_VERSION,
+    "info": {
+      "title": "Optic API",
+      "version": "1.0.0"
+    },
+    "paths": paths,
+    "components": {
+      "schemas": schemas.into_definitions()
+    }
+  })
+}
+
+// path parameters as their names and the ids of the path components they describe
+type PathParameters<'a> = Vec<(&'a String, &'a PathComponentId)>;
+
+fn export_paths(
+  endpoint_projection: &EndpointProjection,
+  schemas: &mut SchemasCollector,
+) -> JsonMap<String, JsonValue> {
+  let graph = &endpoint_projection.graph;
+  let root_path_id = String::from(ROOT_PATH_ID);
+  let root_node_index = *endpoint_projection
+    .node_id_to_index
+    .get(ROOT_PATH_ID)
+    .expect("root path component should always exist");
+
+  let mut paths = JsonMap::new();
+  let mut pending: Vec<(NodeIndex, &PathComponentId, String, PathParameters)> =
+    vec![(root_node_index, &root_path_id, String::from(""), vec![])];
+
+  while let Some((path_node_index, path_id, path, path_parameters)) = pending.pop() {
+    let mut operations = JsonMap::new();
+
+    for child_node_index in children(endpoint_projection, path_node_index) {
+      match graph.node_weight(child_node_index).unwrap() {
+        Node::PathComponent(child_path_id, descriptor) => {
+          let mut child_parameters = path_parameters.clone();
+          let segment = if descriptor.is_parameter {
+            child_parameters.push((&descriptor.name, child_path_id));
+            format!("{{{}}}", descriptor.name)
+          } else {
+            descriptor.name.clone()
+          };
+          pending.push((
+            child_node_index,
+            child_path_id,
+            format!("{}/{}", path, segment),
+            child_parameters,
+          ));
+        }
+        Node::HttpMethod(http_method) => {
+          let parameters = export_parameters(
+            endpoint_projection,
+            path_id,
+            http_method,
+            &path_parameters,
+            schemas,
+          );
+          let operation =
+            export_operation(endpoint_projection, child_node_index, parameters, schemas);
+          operations.insert(http_method.to_lowercase(), operation);
+        }
+        _ => {}
+      }
+    }
+
+    if !operations.is_empty() {
+      let path = if path.is_empty() {
+        String::from("/")
+      } else {
+        path
+      };
+      paths.insert(path, JsonValue::Object(operations));
+    }
+  }
+
+  paths
+}
+
+fn export_parameters(
+  endpoint_projection: &EndpointProjection,
+  path_id: &PathComponentId,
+  http_method: &str,
+  path_parameters: &[(&String, &PathComponentId)],
+  schemas: &mut SchemasCollector,
+) -> Vec<JsonValue> {
+  let path_parameters = path_parameters.iter().map(|(name, parameter_path_id)| {
+    let schema = match endpoint_projection
+      .path_parameter_shapes
+      .get(*parameter_path_id)
+    {
+      Some(shape_id) => schemas.schema_for(shape_id),
+      None => json!({ "type": "string" }),
+    };
+    json!({
+      "name": name,
+      "in": "path",
+      "required": true,
+      "schema": schema
+    })
+  });
+  let mut parameters = path_parameters.collect::<Vec<_>>();
+
+  let endpoint_queries = EndpointQueries::new(endpoint_projection);
+  for location in [QUERY_PARAMETER_LOCATION, HEADER_PARAMETER_LOCATION].iter() {
+    for (_, parameter) in
+      endpoint_queries.resolve_request_parameters(path_id, http_method, location)
+    {
+      let (schema, is_required) = match &parameter.shape_id {
+        Some(shape_id) => schemas.value_schema_for(shape_id),
+        None => (json!({}), false),
+      };
+      parameters.push(json!({
+        "name": parameter.name,
+        "in": location,
+        "required": is_required,
+        "schema": schema
+      }));
+    }
+  }
+
+  parameters
+}
+
+fn export_operation(
+  endpoint_projection: &EndpointProjection,
+  method_node_index: NodeIndex,
+  parameters: Vec<JsonValue>,
+  schemas: &mut SchemasCollector,
+) -> JsonValue {
+  let graph = &endpoint_projection.graph;
+  let mut request_content = JsonMap::new();
+  let mut responses = JsonMap::new();
+
+  for child_node_index in children(endpoint_projection, method_node_index) {
+    match graph.node_weight(child_node_index).unwrap() {
+      Node::Request(_, RequestBodyDescriptor { body: Some(body) }) => {
+        let schema = schemas.schema_for(&body.root_shape_id);
+        request_content.insert(body.http_content_type.clone(), json!({ "schema": schema }));
+      }
+      Node::HttpStatusCode(status_code) => {
+        let mut response_content = JsonMap::new();
+        for response_node_index in children(endpoint_projection, child_node_index) {
+          if let Some(Node::Response(_, ResponseBodyDescriptor { body: Some(body) })) =
+            graph.node_weight(response_node_index)
+          {
+            let schema = schemas.schema_for(&body.root_shape_id);
+            response_content.insert(body.http_content_type.clone(), json!({ "schema": schema }));
+          }
+        }
+
+        let mut response = JsonMap::new();
+        response.insert(
+          String::from("description"),
+          JsonValue::from(format!("{} response", status_code)),
+        );
+        if !response_content.is_empty() {
+          response.insert(
+            String::from("content"),
+            JsonValue::Object(response_content),
+          );
+        }
+        responses.insert(status_code.to_string(), JsonValue::Object(response));
+      }
+      _ => {}
+    }
+  }
+
+  if responses.is_empty() {
+    // OpenAPI requires every operation to describe at least one response
+    responses.insert(
+      String::from("default"),
+      json!({ "description": "Undocumented response" }),
+    );
+  }
+
+  let mut operation = JsonMap::new();
+  if !parameters.is_empty() {
+    operation.insert(String::from("parameters"), JsonValue::from(parameters));
+  }
+  if !request_content.is_empty() {
+    operation.insert(
+      String::from("requestBody"),
+      json!({ "content": request_content }),
+    );
+  }
+  operation.insert(String::from("responses"), JsonValue::Object(responses));
+
+  JsonValue::Object(operation)
+}
+
+fn children(
+  endpoint_projection: &EndpointProjection,
+  node_index: NodeIndex,
+) -> impl Iterator<Item = NodeIndex> + '_ {
+  endpoint_projection
+    .graph
+    .neighbors_directed(node_index, petgraph::Direction::Incoming)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::events::SpecEvent;
+  use insta::assert_json_snapshot;
+
+  #[test]
+  fn can_export_endpoints_with_bodies() {
+    let events: Vec<SpecEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"PathParameterAdded": {"pathId": "path_2","parentPathId": "path_1","name": "todoId"}},
+      {"ShapeAdded": {"shapeId": "todo_shape","baseShapeId": "$object","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": "Todo"}},
+      {"ShapeAdded": {"shapeId": "title_shape","baseShapeId": "$string","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"FieldAdded": {"fieldId": "title_field","shapeId": "todo_shape","name": "title","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "title_field","shapeId": "title_shape"}}}},
+      {"ShapeAdded": {"shapeId": "due_inner_shape","baseShapeId": "$number","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeAdded": {"shapeId": "due_nullable_shape","baseShapeId": "$nullable","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "due_nullable_shape","providerDescriptor": {"ShapeProvider": {"shapeId": "due_inner_shape"}},"consumingParameterId": "$nullableInner"}}}},
+      {"ShapeAdded": {"shapeId": "due_optional_shape","baseShapeId": "$optional","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "due_optional_shape","providerDescriptor": {"ShapeProvider": {"shapeId": "due_nullable_shape"}},"consumingParameterId": "$optionalInner"}}}},
+      {"FieldAdded": {"fieldId": "due_field","shapeId": "todo_shape","name": "due","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "due_field","shapeId": "due_optional_shape"}}}},
+      {"ShapeAdded": {"shapeId": "todos_shape","baseShapeId": "$list","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "todos_shape","providerDescriptor": {"ShapeProvider": {"shapeId": "todo_shape"}},"consumingParameterId": "$listItem"}}}},
+      {"RequestAdded": {"requestId": "request_1","pathId": "path_1","httpMethod": "GET"}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_1","pathId": "path_1","httpMethod": "GET","httpStatusCode": 200}},
+      {"ResponseBodySet": {"responseId": "response_1","bodyDescriptor": {"httpContentType": "application/json","shapeId": "todos_shape","isRemoved": false}}},
+      {"RequestAdded": {"requestId": "request_2","pathId": "path_2","httpMethod": "PUT"}},
+      {"RequestBodySet": {"requestId": "request_2","bodyDescriptor": {"httpContentType": "application/json","shapeId": "todo_shape","isRemoved": false}}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_2","pathId": "path_2","httpMethod": "PUT","httpStatusCode": 204}},
+    ]))
+    .expect("initial events should be valid spec events");
+
+    let spec_projection = SpecProjection::from(events);
+    let document = export(&spec_projection);
+
+    assert_json_snapshot!("can_export_endpoints_with_bodies__document", document);
+  }
+
+  #[test]
+  fn can_export_request_parameters() {
+    let events: Vec<SpecEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"PathParameterAdded": {"pathId": "path_2","parentPathId": "path_1","name": "todoId"}},
+      {"ShapeAdded": {"shapeId": "todo_id_shape","baseShapeId": "$number","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"PathParameterShapeSet": {"pathId": "path_2","shapeDescriptor": {"shapeId": "todo_id_shape","isRemoved": false}}},
+      {"RequestAdded": {"requestId": "request_1","pathId": "path_2","httpMethod": "GET"}},
+      {"ShapeAdded": {"shapeId": "fields_inner_shape","baseShapeId": "$string","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeAdded": {"shapeId": "fields_shape","baseShapeId": "$optional","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "fields_shape","providerDescriptor": {"ShapeProvider": {"shapeId": "fields_inner_shape"}},"consumingParameterId": "$optionalInner"}}}},
+      {"RequestParameterAddedByPathAndMethod": {"parameterId": "parameter_1","pathId": "path_2","httpMethod": "GET","parameterLocation": "query","name": "fields"}},
+      {"RequestParameterShapeSet": {"parameterId": "parameter_1","parameterDescriptor": {"shapeId": "fields_shape","isRemoved": false}}},
+      {"ShapeAdded": {"shapeId": "tenant_shape","baseShapeId": "$string","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"RequestParameterAddedByPathAndMethod": {"parameterId": "parameter_2","pathId": "path_2","httpMethod": "GET","parameterLocation": "header","name": "x-tenant"}},
+      {"RequestParameterShapeSet": {"parameterId": "parameter_2","parameterDescriptor": {"shapeId": "tenant_shape","isRemoved": false}}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_1","pathId": "path_2","httpMethod": "GET","httpStatusCode": 200}},
+    ]))
+    .expect("initial events should be valid spec events");
+
+    let spec_projection = SpecProjection::from(events);
+    let document = export(&spec_projection);
+
+    assert_json_snapshot!(
+      "can_export_request_parameters__parameters",
+      document["paths"]["/todos/{todoId}"]["get"]["parameters"]
+    );
+  }
+
+  #[test]
+  fn can_export_polymorphic_shapes() {
+    let events: Vec<SpecEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "values"}},
+      {"ShapeAdded": {"shapeId": "string_shape","baseShapeId": "$string","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeAdded": {"shapeId": "boolean_shape","baseShapeId": "$boolean","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeAdded": {"shapeId": "one_of_shape","baseShapeId": "$oneOf","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeParameterAdded": {"shapeParameterId": "param_1","shapeId": "one_of_shape","name": "","shapeDescriptor": {"ProviderInShape": {"shapeId": "one_of_shape","providerDescriptor": {"NoProvider": {}},"consumingParameterId": "param_1"}}}},
+      {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "one_of_shape","providerDescriptor": {"ShapeProvider": {"shapeId": "string_shape"}},"consumingParameterId": "param_1"}}}},
+      {"ShapeParameterAdded": {"shapeParameterId": "param_2","shapeId": "one_of_shape","name": "","shapeDescriptor": {"ProviderInShape": {"shapeId": "one_of_shape","providerDescriptor": {"NoProvider": {}},"consumingParameterId": "param_2"}}}},
+      {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "one_of_shape","providerDescriptor": {"ShapeProvider": {"shapeId": "boolean_shape"}},"consumingParameterId": "param_2"}}}},
+      {"RequestAdded": {"requestId": "request_1","pathId": "path_1","httpMethod": "POST"}},
+      {"RequestBodySet": {"requestId": "request_1","bodyDescriptor": {"httpContentType": "application/json","shapeId": "one_of_shape","isRemoved": false}}},
+    ]))
+    .expect("initial events should be valid spec events");
+
+    let spec_projection = SpecProjection::from(events);
+    let document = export(&spec_projection);
+
+    assert_json_snapshot!("can_export_polymorphic_shapes__document", document);
+  }
+}
@@ -0,0 +1,1738 @@
+use crate::commands::{
+  CommandContext, EndpointCommand, ShapeCommand, SpecCommand, SpecCommandError,
+};
+use crate::events::SpecEvent;
+use crate::projections::endpoint::{
+  BodyDescriptor, EndpointProjection, Node, HEADER_PARAMETER_LOCATION, QUERY_PARAMETER_LOCATION,
+  ROOT_PATH_ID,
+};
+use crate::projections::SpecProjection;
+use crate::queries::endpoint::EndpointQueries;
+use crate::spec::append_batch;
+use crate::state::endpoint::{PathComponentId, RequestId, ResponseId};
+use crate::state::shape::{ShapeId, ShapeKind, StringFormat};
+use crate::state::SpecIdGenerator;
+use serde::Serialize;
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use thiserror::Error;
+
+const HTTP_METHODS: [&str; 8] = [
+  "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+// schema keywords that constrain values in ways shapes can't describe
+const UNSUPPORTED_SCHEMA_KEYWORDS: [&str; 17] = [
+  "const",
+  "not",
+  "pattern",
+  "minimum",
+  "maximum",
+  "exclusiveMinimum",
+  "exclusiveMaximum",
+  "multipleOf",
+  "minLength",
+  "maxLength",
+  "minItems",
+  "maxItems",
+  "uniqueItems",
+  "minProperties",
+  "maxProperties",
+  "patternProperties",
+  "discriminator",
+];
+
+/// Translate an OpenAPI 3.x document into the commands that describe its endpoints and
+/// shapes. Paths, requests and responses already described by the spec are reused, and
+/// their bodies are left as they are. Anything in the document that can't be represented
+/// by the spec, or overlaps with it, is reported rather than failing the import.
+pub fn import(
+  spec_projection: &SpecProjection,
+  document: &JsonValue,
+  id_generator: &mut impl SpecIdGenerator,
+) -> Result<OpenApiImport, OpenApiImportError> {
+  let version = document
+    .get("openapi")
+    .and_then(JsonValue::as_str)
+    .ok_or(OpenApiImportError::MissingVersion)?;
+  if !version.starts_with("3.") {
+    return Err(OpenApiImportError::UnsupportedVersion(String::from(
+      version,
+    )));
+  }
+
+  let mut importer = Importer::new(spec_projection.endpoint(), document, id_generator);
+  importer.import_paths();
+
+  Ok(importer.into_import())
+}
+
+#[derive(Debug, Default)]
+pub struct OpenApiImport {
+  pub commands: Vec<SpecCommand>,
+  pub unsupported: Vec<UnsupportedConstruct>,
+}
+
+impl OpenApiImport {
+  /// Commit the imported commands to the spec as a single batch commit
+  pub fn commit(
+    self,
+    spec_projection: SpecProjection,
+    commit_message: String,
+    batch_command_context: CommandContext,
+  ) -> Result<Vec<SpecEvent>, OpenApiImportError> {
+    let mut batch = append_batch(spec_projection, commit_message, batch_command_context);
+
+    for command in self.commands {
+      batch
+        .with_command(command)
+        .map_err(|err| OpenApiImportError::InvalidCommand(Box::new(err)))?;
+    }
+
+    Ok(batch.commit())
+  }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsupportedConstruct {
+  // JSON pointer into the imported document
+  pub location: String,
+  pub description: String,
+}
+
+#[derive(Debug, Error)]
+pub enum OpenApiImportError {
+  #[error("document is missing the openapi version field")]
+  MissingVersion,
+  #[error("unsupported openapi version: {}", .0)]
+  UnsupportedVersion(String),
+  #[error("imported command could not be committed: {:?}", .0)]
+  InvalidCommand(Box<SpecCommandError>),
+}
+
+// Importer
+// --------
+
+struct Importer<'a, G: SpecIdGenerator> {
+  endpoint_projection: &'a EndpointProjection,
+  document: &'a JsonValue,
+  id_generator: &'a mut G,
+  commands: Vec<SpecCommand>,
+  unsupported: Vec<UnsupportedConstruct>,
+  path_ids: HashMap<(PathComponentId, PathSegment), PathComponentId>,
+  shape_ids_by_reference: HashMap<String, ShapeId>,
+}
+
+struct OperationParameter<'b> {
+  location: &'static str,
+  name: String,
+  is_required: bool,
+  schema: Option<&'b JsonValue>,
+  schema_location: String,
+}
+
+impl OperationParameter<'_> {
+  fn has_name(&self, name: &str) -> bool {
+    // header names are case insensitive
+    if self.location == HEADER_PARAMETER_LOCATION {
+      self.name.eq_ignore_ascii_case(name)
+    } else {
+      self.name == name
+    }
+  }
+
+  fn is_same_parameter(&self, other: &OperationParameter) -> bool {
+    self.location == other.location && self.has_name(&other.name)
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum PathSegment {
+  Component(String),
+  Parameter(String),
+}
+
+impl<'a, G: SpecIdGenerator> Importer<'a, G> {
+  fn new(
+    endpoint_projection: &'a EndpointProjection,
+    document: &'a JsonValue,
+    id_generator: &'a mut G,
+  ) -> Self {
+    Self {
+      endpoint_projection,
+      document,
+      id_generator,
+      commands: vec![],
+      unsupported: vec![],
+      path_ids: HashMap::new(),
+      shape_ids_by_reference: HashMap::new(),
+    }
+  }
+
+  fn into_import(self) -> OpenApiImport {
+    OpenApiImport {
+      commands: self.commands,
+      unsupported: self.unsupported,
+    }
+  }
+
+  fn report(&mut self, location: &str, description: String) {
+    self.unsupported.push(UnsupportedConstruct {
+      location: String::from(location),
+      description,
+    });
+  }
+
+  // Paths
+  // -----
+
+  fn import_paths(&mut self) {
+    let paths = match self.document.get("paths").and_then(JsonValue::as_object) {
+      Some(paths) => paths,
+      None => return,
+    };
+
+    for (path, path_item) in paths {
+      let location = pointer(&["paths", path]);
+      let (path_item, location) = self.resolve(path_item, &location);
+
+      let path_id = match self.import_path(path, &location) {
+        Some(path_id) => path_id,
+        None => continue,
+      };
+
+      for method in HTTP_METHODS.iter() {
+        if let Some(operation) = path_item.get(*method) {
+          let operation_location = format!("{}/{}", location, method);
+          let parameters =
+            self.operation_parameters(&[(path_item, &location), (operation, &operation_location)]);
+          self.import_operation(
+            &path_id,
+            &method.to_uppercase(),
+            operation,
+            parameters,
+            &operation_location,
+          );
+        }
+      }
+    }
+  }
+
+  fn import_path(&mut self, path: &str, location: &str) -> Option<PathComponentId> {
+    let mut segments = vec![];
+    for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+      if segment.starts_with('{') && segment.ends_with('}') {
+        let name = &segment[1..segment.len() - 1];
+        segments.push(PathSegment::Parameter(String::from(name)));
+      } else if segment.contains('{') {
+        self.report(
+          location,
+          format!(
+            "path segment '{}' mixes parameters with other characters and can not be represented",
+            segment
+          ),
+        );
+        return None;
+      } else {
+        segments.push(PathSegment::Component(String::from(segment)));
+      }
+    }
+
+    let mut parent_path_id = String::from(ROOT_PATH_ID);
+    for segment in segments {
+      parent_path_id = self.path_component_id(parent_path_id, segment);
+    }
+
+    Some(parent_path_id)
+  }
+
+  fn path_component_id(
+    &mut self,
+    parent_path_id: PathComponentId,
+    segment: PathSegment,
+  ) -> PathComponentId {
+    // path parameters at the same position are the same path component, whatever their name
+    let key = match &segment {
+      PathSegment::Parameter(_) => (parent_path_id.clone(), PathSegment::Parameter(String::new())),
+      PathSegment::Component(_) => (parent_path_id.clone(), segment.clone()),
+    };
+    if let Some(path_id) = self.path_ids.get(&key) {
+      return path_id.clone();
+    }
+
+    let path_id = match self.existing_path_component_id(&parent_path_id, &segment) {
+      Some(path_id) => path_id,
+      None => {
+        let path_id = self.id_generator.path();
+        let command = match &segment {
+          PathSegment::Component(name) => EndpointCommand::add_path_component(
+            path_id.clone(),
+            parent_path_id.clone(),
+            name.clone(),
+          ),
+          PathSegment::Parameter(name) => EndpointCommand::add_path_parameter(
+            path_id.clone(),
+            parent_path_id.clone(),
+            name.clone(),
+          ),
+        };
+        self.commands.push(SpecCommand::from(command));
+        path_id
+      }
+    };
+
+    self.path_ids.insert(key, path_id.clone());
+    path_id
+  }
+
+  fn existing_path_component_id(
+    &self,
+    parent_path_id: &PathComponentId,
+    segment: &PathSegment,
+  ) -> Option<PathComponentId> {
+    let projection = self.endpoint_projection;
+    let parent_node_index = *projection.node_id_to_index.get(parent_path_id)?;
+
+    projection
+      .graph
+      .neighbors_directed(parent_node_index, petgraph::Direction::Incoming)
+      .find_map(|child_node_index| match projection.graph.node_weight(child_node_index) {
+        Some(Node::PathComponent(path_id, descriptor)) => {
+          let matches = match segment {
+            PathSegment::Component(name) => !descriptor.is_parameter && descriptor.name == *name,
+            PathSegment::Parameter(_) => descriptor.is_parameter,
+          };
+          if matches {
+            Some(path_id.clone())
+          } else {
+            None
+          }
+        }
+        _ => None,
+      })
+  }
+
+  // Parameters
+  // ----------
+
+  // parameters of an operation, including those of its path item unless the operation overrides them
+  fn operation_parameters<'b>(
+    &mut self,
+    parents: &[(&'b JsonValue, &str)],
+  ) -> Vec<OperationParameter<'b>>
+  where
+    'a: 'b,
+  {
+    let mut operation_parameters: Vec<OperationParameter<'b>> = vec![];
+
+    for (parent, location) in parents {
+      let parameters = match parent.get("parameters").and_then(JsonValue::as_array) {
+        Some(parameters) => parameters,
+        None => continue,
+      };
+
+      for (index, parameter) in parameters.iter().enumerate() {
+        let parameter_location = format!("{}/parameters/{}", location, index);
+        let (parameter, parameter_location) = self.resolve(parameter, &parameter_location);
+
+        let parameter_in = parameter
+          .get("in")
+          .and_then(JsonValue::as_str)
+          .unwrap_or("unknown");
+        let name = parameter
+          .get("name")
+          .and_then(JsonValue::as_str)
+          .unwrap_or("");
+        let location = match parameter_in {
+          "path" => continue, // path parameters are described by the path itself
+          "query" => QUERY_PARAMETER_LOCATION,
+          "header" if is_reserved_header(name) => {
+            self.report(
+              &parameter_location,
+              format!(
+                "header parameter '{}' is described by the operation itself and was ignored",
+                name
+              ),
+            );
+            continue;
+          }
+          "header" => HEADER_PARAMETER_LOCATION,
+          _ => {
+            self.report(
+              &parameter_location,
+              format!(
+                "{} parameter '{}' can not be represented and was ignored",
+                parameter_in, name
+              ),
+            );
+            continue;
+          }
+        };
+
+        let operation_parameter = OperationParameter {
+          location,
+          name: String::from(name),
+          is_required: parameter.get("required") == Some(&JsonValue::Bool(true)),
+          schema: parameter.get("schema"),
+          schema_location: format!("{}/schema", parameter_location),
+        };
+        match operation_parameters
+          .iter_mut()
+          .find(|existing| existing.is_same_parameter(&operation_parameter))
+        {
+          Some(existing) => *existing = operation_parameter,
+          None => operation_parameters.push(operation_parameter),
+        }
+      }
+    }
+
+    operation_parameters
+  }
+
+  fn import_parameters(
+    &mut self,
+    path_id: &PathComponentId,
+    http_method: &str,
+    request_id: &RequestId,
+    parameters: Vec<OperationParameter>,
+  ) {
+    for parameter in parameters {
+      if self.parameter_exists(path_id, http_method, &parameter) {
+        continue;
+      }
+
+      let value_shape_id = match parameter.schema {
+        Some(schema) => self.import_schema(schema, &parameter.schema_location),
+        None => {
+          let shape_id = self.id_generator.shape();
+          self.push_shape(shape_id.clone(), ShapeKind::UnknownKind, String::from(""));
+          shape_id
+        }
+      };
+      let shape_id = if parameter.is_required {
+        value_shape_id
+      } else {
+        let optional_shape_id = self.id_generator.shape();
+        self.push_shape(
+          optional_shape_id.clone(),
+          ShapeKind::OptionalKind,
+          String::from(""),
+        );
+        self.push_parameter_shape(
+          optional_shape_id.clone(),
+          ShapeKind::OptionalKind,
+          value_shape_id,
+        );
+        optional_shape_id
+      };
+
+      let parameter_id = self.id_generator.request_parameter();
+      let request_id = request_id.clone();
+      let (add_command, set_shape_command) = if parameter.location == QUERY_PARAMETER_LOCATION {
+        (
+          EndpointCommand::add_query_parameter(parameter_id.clone(), request_id, parameter.name),
+          EndpointCommand::set_query_parameter_shape(parameter_id, shape_id),
+        )
+      } else {
+        (
+          EndpointCommand::add_header_parameter(parameter_id.clone(), request_id, parameter.name),
+          EndpointCommand::set_header_parameter_shape(parameter_id, shape_id),
+        )
+      };
+      self.commands.push(SpecCommand::from(add_command));
+      self.commands.push(SpecCommand::from(set_shape_command));
+    }
+  }
+
+  fn parameter_exists(
+    &self,
+    path_id: &PathComponentId,
+    http_method: &str,
+    parameter: &OperationParameter,
+  ) -> bool {
+    self
+      .endpoint_projection
+      .request_parameters
+      .values()
+      .any(|descriptor| {
+        descriptor.path_id == *path_id
+          && descriptor.http_method == http_method
+          && descriptor.location == parameter.location
+          && parameter.has_name(&descriptor.name)
+      })
+  }
+
+  // Operations
+  // ----------
+
+  fn import_operation(
+    &mut self,
+    path_id: &PathComponentId,
+    http_method: &str,
+    operation: &JsonValue,
+    parameters: Vec<OperationParameter>,
+    location: &str,
+  ) {
+    let request_body = operation
+      .get("requestBody")
+      .map(|request_body| self.resolve(request_body, &format!("{}/requestBody", location)));
+    let request_contents = request_body
+      .map(|(request_body, request_body_location)| {
+        contents(request_body, &request_body_location)
+      })
+      .unwrap_or_default();
+
+    let mut request_ids = vec![];
+    if request_contents.is_empty() {
+      let request_id = match self.existing_request_id(path_id, http_method, None) {
+        Some(request_id) => request_id,
+        None => self.push_request(path_id, http_method),
+      };
+      request_ids.push(request_id);
+    }
+    for (content_type, schema, schema_location) in request_contents {
+      if let Some(request_id) = self.existing_request_id(path_id, http_method, Some(&content_type))
+      {
+        self.report(
+          &schema_location,
+          format!(
+            "request body '{}' is already described by the spec and was not imported",
+            content_type
+          ),
+        );
+        request_ids.push(request_id);
+        continue;
+      }
+      let request_id = self.push_request(path_id, http_method);
+      request_ids.push(request_id.clone());
+
+      if let Some(schema) = schema {
+        let shape_id = self.import_schema(schema, &schema_location);
+        self
+          .commands
+          .push(SpecCommand::from(EndpointCommand::set_request_body_shape(
+            request_id,
+            shape_id,
+            content_type,
+            false,
+          )));
+      }
+    }
+
+    // parameters are shared by all requests of an operation, so adding them to one request will do
+    self.import_parameters(path_id, http_method, &request_ids[0], parameters);
+
+    let responses = match operation.get("responses").and_then(JsonValue::as_object) {
+      Some(responses) => responses,
+      None => return,
+    };
+    for (status_code, response) in responses {
+      let response_location = format!("{}/responses/{}", location, escape(status_code));
+      let http_status_code = match status_code.parse::<u16>() {
+        Ok(http_status_code) => http_status_code,
+        Err(_) => {
+          self.report(
+            &response_location,
+            format!(
+              "response status code '{}' is not a specific status code and was ignored",
+              status_code
+            ),
+          );
+          continue;
+        }
+      };
+
+      let (response, response_location) = self.resolve(response, &response_location);
+      let response_contents = contents(response, &response_location);
+
+      if response_contents.is_empty()
+        && self
+          .existing_response_id(path_id, http_method, http_status_code, None)
+          .is_none()
+      {
+        self.push_response(path_id, http_method, http_status_code);
+      }
+      for (content_type, schema, schema_location) in response_contents {
+        if self
+          .existing_response_id(path_id, http_method, http_status_code, Some(&content_type))
+          .is_some()
+        {
+          self.report(
+            &schema_location,
+            format!(
+              "{} response body '{}' is already described by the spec and was not imported",
+              http_status_code, content_type
+            ),
+          );
+          continue;
+        }
+        let response_id = self.push_response(path_id, http_method, http_status_code);
+
+        if let Some(schema) = schema {
+          let shape_id = self.import_schema(schema, &schema_location);
+          self
+            .commands
+            .push(SpecCommand::from(EndpointCommand::set_response_body_shape(
+              response_id,
+              shape_id,
+              content_type,
+              false,
+            )));
+        }
+      }
+    }
+  }
+
+  fn push_request(&mut self, path_id: &PathComponentId, http_method: &str) -> RequestId {
+    let request_id = self.id_generator.request();
+    self.commands.push(SpecCommand::from(EndpointCommand::add_request(
+      request_id.clone(),
+      path_id.clone(),
+      String::from(http_method),
+    )));
+    request_id
+  }
+
+  fn push_response(
+    &mut self,
+    path_id: &PathComponentId,
+    http_method: &str,
+    http_status_code: u16,
+  ) -> ResponseId {
+    let response_id = self.id_generator.response();
+    self.commands.push(SpecCommand::from(
+      EndpointCommand::add_response_by_path_and_method(
+        response_id.clone(),
+        path_id.clone(),
+        String::from(http_method),
+        http_status_code,
+      ),
+    ));
+    response_id
+  }
+
+  // requests of the spec for the exact content type, or without a body when there is none
+  fn existing_request_id(
+    &self,
+    path_id: &PathComponentId,
+    http_method: &str,
+    content_type: Option<&str>,
+  ) -> Option<RequestId> {
+    let http_method = String::from(http_method);
+    let endpoint_queries = EndpointQueries::new(self.endpoint_projection);
+    let mut requests = endpoint_queries.resolve_requests(path_id, &http_method)?;
+    requests
+      .find(|(_, request)| has_content_type(&request.body, content_type))
+      .map(|(request_id, _)| request_id.clone())
+  }
+
+  fn existing_response_id(
+    &self,
+    path_id: &PathComponentId,
+    http_method: &str,
+    http_status_code: u16,
+    content_type: Option<&str>,
+  ) -> Option<ResponseId> {
+    // paths added by the import itself don't have any responses yet
+    if !self
+      .endpoint_projection
+      .node_id_to_index
+      .contains_key(path_id)
+    {
+      return None;
+    }
+    let endpoint_queries = EndpointQueries::new(self.endpoint_projection);
+    let response_id = endpoint_queries
+      .resolve_responses_by_method_and_status_code(http_method, http_status_code, path_id)
+      .find(|(_, response)| has_content_type(&response.body, content_type))
+      .map(|(response_id, _)| response_id.clone());
+    response_id
+  }
+
+  // Schemas
+  // -------
+
+  fn import_schema(&mut self, schema: &JsonValue, location: &str) -> ShapeId {
+    if let Some(reference) = schema.get("$ref").and_then(JsonValue::as_str) {
+      return self.import_reference(reference, location);
+    }
+
+    let shape_id = self.id_generator.shape();
+    self.import_schema_as(schema, shape_id.clone(), String::from(""), location);
+    shape_id
+  }
+
+  fn import_reference(&mut self, reference: &str, location: &str) -> ShapeId {
+    if let Some(shape_id) = self.shape_ids_by_reference.get(reference) {
+      return shape_id.clone();
+    }
+
+    let mut target_reference = String::from(reference);
+    let mut seen_references = HashSet::new();
+    let target = loop {
+      if !seen_references.insert(target_reference.clone()) {
+        break None;
+      }
+      match self.resolve_reference(&target_reference) {
+        Some(target) => match target.get("$ref").and_then(JsonValue::as_str) {
+          Some(next_reference) => target_reference = String::from(next_reference),
+          None => break Some(target),
+        },
+        None => break None,
+      }
+    };
+
+    let target = match target {
+      Some(target) => target,
+      None => {
+        self.report(
+          location,
+          format!("reference '{}' could not be resolved", reference),
+        );
+        let shape_id = self.id_generator.shape();
+        self.push_shape(shape_id.clone(), ShapeKind::UnknownKind, String::from(""));
+        return shape_id;
+      }
+    };
+
+    // references to the same schema resolve to the same shape, so register it before
+    // importing, allowing schemas to refer back to themselves
+    let shape_id = self.id_generator.shape();
+    for seen_reference in seen_references {
+      self
+        .shape_ids_by_reference
+        .insert(seen_reference, shape_id.clone());
+    }
+
+    let name = target_reference
+      .strip_prefix("#/components/schemas/")
+      .map(unescape)
+      .unwrap_or_default();
+    let target_location = target_reference.trim_start_matches('#').to_owned();
+    self.import_schema_as(target, shape_id.clone(), name, &target_location);
+
+    shape_id
+  }
+
+  fn import_schema_as(
+    &mut self,
+    schema: &JsonValue,
+    shape_id: ShapeId,
+    name: String,
+    location: &str,
+  ) {
+    let schema = match schema {
+      // without any constraints, there's nothing more known about values than that they exist
+      JsonValue::Bool(true) => return self.push_shape(shape_id, ShapeKind::UnknownKind, name),
+      JsonValue::Object(schema) => schema,
+      _ => {
+        self.report(
+          location,
+          String::from("schema does not allow any values and can not be represented"),
+        );
+        return self.push_shape(shape_id, ShapeKind::UnknownKind, name);
+      }
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(JsonValue::as_str) {
+      // a shape can't be an alias of another, so describe the referenced schema in place
+      let target = match self.resolve_reference(reference) {
+        Some(target) if target.get("$ref").is_none() => target,
+        _ => {
+          self.report(
+            location,
+            format!("reference '{}' could not be resolved", reference),
+          );
+          return self.push_shape(shape_id, ShapeKind::UnknownKind, name);
+        }
+      };
+      return self.import_schema_as(target, shape_id, name, location);
+    }
+
+    let types = schema_types(schema);
+    let is_nullable = schema.get("nullable") == Some(&JsonValue::Bool(true))
+      || types.contains(&"null");
+
+    if is_nullable {
+      self.push_shape(shape_id.clone(), ShapeKind::NullableKind, name);
+      let inner_shape_id = self.id_generator.shape();
+      self.import_non_nullable_schema(schema, inner_shape_id.clone(), String::from(""), location);
+      self.push_parameter_shape(shape_id, ShapeKind::NullableKind, inner_shape_id);
+    } else {
+      self.import_non_nullable_schema(schema, shape_id, name, location);
+    }
+  }
+
+  fn import_non_nullable_schema(
+    &mut self,
+    schema: &JsonMap<String, JsonValue>,
+    shape_id: ShapeId,
+    name: String,
+    location: &str,
+  ) {
+    for keyword in UNSUPPORTED_SCHEMA_KEYWORDS.iter() {
+      if schema.contains_key(*keyword) {
+        self.report(
+          &format!("{}/{}", location, keyword),
+          format!("'{}' can not be represented by a shape and was ignored", keyword),
+        );
+      }
+    }
+    if let Some(format) = schema.get("format") {
+      if string_format(schema).is_none() {
+        self.report(
+          &format!("{}/format", location),
+          format!("format {} can not be represented by a shape and was ignored", format),
+        );
+      }
+    }
+    if schema.contains_key("enum") && string_enum(schema).is_none() {
+      self.report(
+        &format!("{}/enum", location),
+        String::from("'enum' can only be represented by a shape for strings and was ignored"),
+      );
+    }
+
+    if let Some(branches) = schema.get("oneOf").and_then(JsonValue::as_array) {
+      return self.import_one_of(branches, shape_id, name, &format!("{}/oneOf", location));
+    }
+    if let Some(branches) = schema.get("anyOf").and_then(JsonValue::as_array) {
+      self.report(
+        &format!("{}/anyOf", location),
+        String::from("'anyOf' was imported as 'oneOf', as shapes can only match a single branch"),
+      );
+      return self.import_one_of(branches, shape_id, name, &format!("{}/anyOf", location));
+    }
+    if let Some(members) = schema.get("allOf").and_then(JsonValue::as_array) {
+      return self.import_all_of(schema, members, shape_id, name, location);
+    }
+
+    let types = schema_types(schema)
+      .into_iter()
+      .filter(|schema_type| *schema_type != "null")
+      .collect::<Vec<_>>();
+
+    let schema_type = match types.as_slice() {
+      [] if schema.contains_key("properties") || schema.contains_key("additionalProperties") => {
+        "object"
+      }
+      [] if schema.contains_key("items") => "array",
+      [] => return self.push_shape(shape_id, ShapeKind::UnknownKind, name),
+      [schema_type] => schema_type,
+      _ => {
+        // a schema of multiple types is one of schemas of a single type each
+        let branches = types
+          .iter()
+          .map(|schema_type| {
+            let mut branch = schema.clone();
+            branch.insert(String::from("type"), JsonValue::from(*schema_type));
+            JsonValue::Object(branch)
+          })
+          .collect::<Vec<_>>();
+        return self.import_one_of(&branches, shape_id, name, location);
+      }
+    };
+
+    match schema_type {
+      "string" => {
+        self.push_shape(shape_id.clone(), ShapeKind::StringKind, name);
+        if let Some(format) = string_format(schema) {
+          let command = ShapeCommand::set_string_format(shape_id.clone(), format);
+          self.commands.push(SpecCommand::from(command));
+        }
+        if let Some(values) = string_enum(schema) {
+          let command = ShapeCommand::set_string_enum(shape_id, values);
+          self.commands.push(SpecCommand::from(command));
+        }
+      }
+      "number" => self.push_shape(shape_id, ShapeKind::NumberKind, name),
+      "integer" => self.push_shape(shape_id, ShapeKind::IntegerKind, name),
+      "boolean" => self.push_shape(shape_id, ShapeKind::BooleanKind, name),
+      "array" => {
+        self.push_shape(shape_id.clone(), ShapeKind::ListKind, name);
+        let item_shape_id = match schema.get("items") {
+          Some(items) => self.import_schema(items, &format!("{}/items", location)),
+          None => {
+            let item_shape_id = self.id_generator.shape();
+            self.push_shape(
+              item_shape_id.clone(),
+              ShapeKind::UnknownKind,
+              String::from(""),
+            );
+            item_shape_id
+          }
+        };
+        self.push_parameter_shape(shape_id, ShapeKind::ListKind, item_shape_id);
+      }
+      "object" => self.import_object(schema, shape_id, name, location),
+      unknown_type => {
+        self.report(
+          &format!("{}/type", location),
+          format!("type '{}' can not be represented by a shape", unknown_type),
+        );
+        self.push_shape(shape_id, ShapeKind::UnknownKind, name);
+      }
+    }
+  }
+
+  fn import_map(
+    &mut self,
+    additional_properties: &JsonValue,
+    shape_id: ShapeId,
+    name: String,
+    location: &str,
+  ) {
+    self.push_shape(shape_id.clone(), ShapeKind::MapKind, name);
+    let value_shape_id = match additional_properties {
+      JsonValue::Object(_) => self.import_schema(
+        additional_properties,
+        &format!("{}/additionalProperties", location),
+      ),
+      _ => {
+        let value_shape_id = self.id_generator.shape();
+        self.push_shape(
+          value_shape_id.clone(),
+          ShapeKind::UnknownKind,
+          String::from(""),
+        );
+        value_shape_id
+      }
+    };
+    self.push_parameter_shape(shape_id, ShapeKind::MapKind, value_shape_id);
+  }
+
+  fn import_object(
+    &mut self,
+    schema: &JsonMap<String, JsonValue>,
+    shape_id: ShapeId,
+    name: String,
+    location: &str,
+  ) {
+    let properties = schema.get("properties").and_then(JsonValue::as_object);
+    let additional_properties = schema.get("additionalProperties");
+    let has_additional_properties = match additional_properties {
+      None | Some(JsonValue::Bool(false)) => false,
+      Some(_) => true,
+    };
+
+    let has_properties = matches!(properties, Some(properties) if !properties.is_empty());
+    if !has_properties && has_additional_properties {
+      return self.import_map(additional_properties.unwrap(), shape_id, name, location);
+    }
+    if has_additional_properties {
+      self.report(
+        &format!("{}/additionalProperties", location),
+        String::from("additional properties of objects with fields can not be represented"),
+      );
+    }
+
+    self.push_shape(shape_id.clone(), ShapeKind::ObjectKind, name);
+
+    let properties = match properties {
+      Some(properties) => properties,
+      None => return,
+    };
+    let required = schema
+      .get("required")
+      .and_then(JsonValue::as_array)
+      .map(|required| {
+        required
+          .iter()
+          .filter_map(JsonValue::as_str)
+          .collect::<HashSet<_>>()
+      })
+      .unwrap_or_default();
+
+    for (key, property_schema) in properties {
+      let property_location = format!("{}/properties/{}", location, escape(key));
+      let value_shape_id = self.import_schema(property_schema, &property_location);
+
+      let field_shape_id = if required.contains(key.as_str()) {
+        value_shape_id
+      } else {
+        let optional_shape_id = self.id_generator.shape();
+        self.push_shape(
+          optional_shape_id.clone(),
+          ShapeKind::OptionalKind,
+          String::from(""),
+        );
+        self.push_parameter_shape(
+          optional_shape_id.clone(),
+          ShapeKind::OptionalKind,
+          value_shape_id,
+        );
+        optional_shape_id
+      };
+
+      let field_id = self.id_generator.field();
+      self.commands.push(SpecCommand::from(ShapeCommand::add_field(
+        key.clone(),
+        field_id,
+        shape_id.clone(),
+        field_shape_id,
+      )));
+    }
+  }
+
+  fn import_one_of(
+    &mut self,
+    branches: &[JsonValue],
+    shape_id: ShapeId,
+    name: String,
+    location: &str,
+  ) {
+    self.push_shape(shape_id.clone(), ShapeKind::OneOfKind, name);
+
+    for (index, branch) in branches.iter().enumerate() {
+      let branch_shape_id = self.import_schema(branch, &format!("{}/{}", location, index));
+      let parameter_id = self.id_generator.shape_param();
+      self
+        .commands
+        .push(SpecCommand::from(ShapeCommand::add_shape_parameter(
+          parameter_id.clone(),
+          shape_id.clone(),
+          String::from(""),
+        )));
+      self
+        .commands
+        .push(SpecCommand::from(ShapeCommand::set_parameter_shape(
+          shape_id.clone(),
+          parameter_id,
+          branch_shape_id,
+        )));
+    }
+  }
+
+  fn import_all_of(
+    &mut self,
+    schema: &JsonMap<String, JsonValue>,
+    members: &[JsonValue],
+    shape_id: ShapeId,
+    name: String,
+    location: &str,
+  ) {
+    let mut siblings = schema.clone();
+    siblings.remove("allOf");
+    let has_siblings = siblings
+      .keys()
+      .any(|keyword| is_structural_keyword(keyword));
+
+    if let ([member], false) = (members, has_siblings) {
+      return self.import_schema_as(member, shape_id, name, &format!("{}/allOf/0", location));
+    }
+
+    // objects composed of other objects are described by all of their fields combined
+    let mut merged_members = vec![];
+    let mut pending_members = members.iter().collect::<Vec<_>>();
+    let mut seen_references = HashSet::new();
+    while let Some(member) = pending_members.pop() {
+      let member = match member.get("$ref").and_then(JsonValue::as_str) {
+        Some(reference) if seen_references.insert(reference) => {
+          match self.resolve_reference(reference) {
+            Some(target) => {
+              pending_members.push(target);
+              continue;
+            }
+            None => None,
+          }
+        }
+        Some(_) => None,
+        None => member.as_object(),
+      };
+
+      match member {
+        Some(member) if member.contains_key("allOf") => {
+          let mut member_siblings = member.clone();
+          member_siblings.remove("allOf");
+          merged_members.push(member_siblings);
+          if let Some(nested_members) = member.get("allOf").and_then(JsonValue::as_array) {
+            pending_members.extend(nested_members.iter());
+          }
+        }
+        Some(member) if is_object_schema(member) => merged_members.push(member.clone()),
+        _ => {
+          self.report(
+            &format!("{}/allOf", location),
+            String::from("'allOf' can only be represented when combining object schemas"),
+          );
+          return self.push_shape(shape_id, ShapeKind::UnknownKind, name);
+        }
+      }
+    }
+    merged_members.push(siblings);
+
+    let mut properties = JsonMap::new();
+    let mut required = BTreeSet::new();
+    for member in merged_members.into_iter().rev() {
+      if let Some(member_properties) = member.get("properties").and_then(JsonValue::as_object) {
+        for (key, property_schema) in member_properties {
+          properties.insert(key.clone(), property_schema.clone());
+        }
+      }
+      if let Some(member_required) = member.get("required").and_then(JsonValue::as_array) {
+        required.extend(
+          member_required
+            .iter()
+            .filter_map(JsonValue::as_str)
+            .map(String::from),
+        );
+      }
+    }
+
+    let merged = json!({
+      "type": "object",
+      "properties": properties,
+      "required": required,
+    });
+    self.import_non_nullable_schema(merged.as_object().unwrap(), shape_id, name, location);
+  }
+
+  fn push_shape(&mut self, shape_id: ShapeId, shape_kind: ShapeKind, name: String) {
+    self.commands.push(SpecCommand::from(ShapeCommand::add_shape(
+      shape_id, shape_kind, name,
+    )));
+  }
+
+  fn push_parameter_shape(
+    &mut self,
+    shape_id: ShapeId,
+    shape_kind: ShapeKind,
+    provided_shape_id: ShapeId,
+  ) {
+    let parameter_id = shape_kind
+      .get_parameter_descriptor()
+      .expect("shape kind should have a parameter")
+      .shape_parameter_id;
+    self
+      .commands
+      .push(SpecCommand::from(ShapeCommand::set_parameter_shape(
+        shape_id,
+        String::from(parameter_id),
+        provided_shape_id,
+      )));
+  }
+
+  // References
+  // ----------
+
+  fn resolve<'b>(&self, value: &'b JsonValue, location: &str) -> (&'b JsonValue, String)
+  where
+    'a: 'b,
+  {
+    match value.get("$ref").and_then(JsonValue::as_str) {
+      Some(reference) => match self.resolve_reference(reference) {
+        Some(target) => (target, reference.trim_start_matches('#').to_owned()),
+        None => (value, String::from(location)),
+      },
+      None => (value, String::from(location)),
+    }
+  }
+
+  fn resolve_reference(&self, reference: &str) -> Option<&'a JsonValue> {
+    // only references within the document itself are supported
+    let json_pointer = reference.strip_prefix('#')?;
+    self.document.pointer(json_pointer)
+  }
+}
+
+fn contents<'a>(
+  parent: &'a JsonValue,
+  location: &str,
+) -> Vec<(String, Option<&'a JsonValue>, String)> {
+  parent
+    .get("content")
+    .and_then(JsonValue::as_object)
+    .map(|content| {
+      content
+        .iter()
+        .map(|(content_type, media_type)| {
+          (
+            content_type.clone(),
+            media_type.get("schema"),
+            format!("{}/content/{}/schema", location, escape(content_type)),
+          )
+        })
+        .collect()
+    })
+    .unwrap_or_default()
+}
+
+fn has_content_type(body: &Option<BodyDescriptor>, content_type: Option<&str>) -> bool {
+  match (body, content_type) {
+    (Some(body), Some(content_type)) => body.http_content_type == content_type,
+    (None, None) => true,
+    _ => false,
+  }
+}
+
+fn schema_types(schema: &JsonMap<String, JsonValue>) -> Vec<&str> {
+  match schema.get("type") {
+    Some(JsonValue::String(schema_type)) => vec![schema_type.as_str()],
+    Some(JsonValue::Array(schema_types)) => {
+      schema_types.iter().filter_map(JsonValue::as_str).collect()
+    }
+    _ => vec![],
+  }
+}
+
+// formats of string schemas that shapes can describe
+fn string_format(schema: &JsonMap<String, JsonValue>) -> Option<StringFormat> {
+  if !schema_types(schema).contains(&"string") {
+    return None;
+  }
+
+  let format = schema.get("format")?;
+  serde_json::from_value(format.clone()).ok()
+}
+
+// enums of string schemas, of which all values are strings
+fn string_enum(schema: &JsonMap<String, JsonValue>) -> Option<Vec<String>> {
+  if !schema_types(schema).contains(&"string") {
+    return None;
+  }
+
+  let values = schema.get("enum")?.as_array()?;
+  if values.is_empty() {
+    return None;
+  }
+  values
+    .iter()
+    .map(|value| value.as_str().map(String::from))
+    .collect()
+}
+
+// headers OpenAPI describes by other means than parameters
+fn is_reserved_header(name: &str) -> bool {
+  ["accept", "content-type", "authorization"]
+    .iter()
+    .any(|reserved| name.eq_ignore_ascii_case(reserved))
+}
+
+fn is_object_schema(schema: &JsonMap<String, JsonValue>) -> bool {
+  match schema.get("type") {
+    Some(schema_type) => schema_type == "object",
+    None => !schema.contains_key("items"),
+  }
+}
+
+fn is_structural_keyword(keyword: &str) -> bool {
+  matches!(
+    keyword,
+    "type" | "properties" | "required" | "additionalProperties" | "items" | "oneOf" | "anyOf"
+  )
+}
+
+fn pointer(segments: &[&str]) -> String {
+  segments
+    .iter()
+    .map(|segment| format!("/{}", escape(segment)))
+    .collect()
+}
+
+fn escape(segment: &str) -> String {
+  segment.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape(segment: &str) -> String {
+  segment.replace("~1", "/").replace("~0", "~")
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::openapi::export;
+  use insta::assert_json_snapshot;
+
+  #[test]
+  pub fn can_import_endpoints_with_bodies() {
+    let document = json!({
+      "openapi": "3.0.3",
+      "info": { "title": "Todos", "version": "1.0.0" },
+      "paths": {
+        "/todos": {
+          "get": {
+            "responses": {
+              "200": {
+                "description": "all todos",
+                "content": {
+                  "application/json": {
+                    "schema": { "type": "array", "items": { "$ref": "#/components/schemas/Todo" } }
+                  }
+                }
+              }
+            }
+          },
+          "post": {
+            "requestBody": { "$ref": "#/components/requestBodies/NewTodo" },
+            "responses": {
+              "201": { "description": "created" }
+            }
+          }
+        },
+        "/todos/{todoId}": {
+          "get": {
+            "parameters": [{ "name": "todoId", "in": "path", "required": true }],
+            "responses": {
+              "200": {
+                "description": "a todo",
+                "content": {
+                  "application/json": { "schema": { "$ref": "#/components/schemas/Todo" } }
+                }
+              }
+            }
+          }
+        }
+      },
+      "components": {
+        "requestBodies": {
+          "NewTodo": {
+            "content": {
+              "application/json": { "schema": { "$ref": "#/components/schemas/Todo" } }
+            }
+          }
+        },
+        "schemas": {
+          "Todo": {
+            "type": "object",
+            "required": ["title", "tags"],
+            "properties": {
+              "title": { "type": "string" },
+              "due": { "type": "string", "nullable": true },
+              "tags": { "type": "array", "items": { "type": "string" } },
+              "parent": { "$ref": "#/components/schemas/Todo" }
+            }
+          }
+        }
+      }
+    });
+
+    let spec_projection = SpecProjection::default();
+    let import = import(&spec_projection, &document, &mut TestIdGenerator::default())
+      .expect("valid openapi document should import");
+    assert!(import.unsupported.is_empty());
+
+    let events = import
+      .commit(
+        spec_projection,
+        String::from("imported todos"),
+        CommandContext::default(),
+      )
+      .expect("imported commands should be valid");
+    let imported_spec = SpecProjection::from(events);
+
+    assert_json_snapshot!(
+      "can_import_endpoints_with_bodies__exported_document",
+      export(&imported_spec)
+    );
+  }
+
+  #[test]
+  pub fn can_import_polymorphic_shapes() {
+    let document = json!({
+      "openapi": "3.1.0",
+      "paths": {
+        "/things": {
+          "get": {
+            "responses": {
+              "200": {
+                "description": "things",
+                "content": {
+                  "application/json": {
+                    "schema": {
+                      "type": "object",
+                      "required": ["id", "value", "labels", "pet"],
+                      "properties": {
+                        "id": { "type": ["string", "null"] },
+                        "value": { "oneOf": [{ "type": "string" }, { "type": "number" }] },
+                        "labels": { "type": "object", "properties": {} },
+                        "pet": {
+                          "allOf": [
+                            { "$ref": "#/components/schemas/Named" },
+                            { "type": "object", "required": ["legs"], "properties": { "legs": { "type": "integer" } } }
+                          ]
+                        }
+                      }
+                    }
+                  }
+                }
+              }
+            }
+          }
+        }
+      },
+      "components": {
+        "schemas": {
+          "Named": {
+            "type": "object",
+            "required": ["name"],
+            "properties": { "name": { "type": "string" } }
+          }
+        }
+      }
+    });
+
+    let spec_projection = SpecProjection::default();
+    let import = import(&spec_projection, &document, &mut TestIdGenerator::default())
+      .expect("valid openapi document should import");
+    assert!(import.unsupported.is_empty());
+
+    let events = import
+      .commit(
+        spec_projection,
+        String::from("imported things"),
+        CommandContext::default(),
+      )
+      .expect("imported commands should be valid");
+
+    assert_json_snapshot!(
+      "can_import_polymorphic_shapes__exported_document",
+      export(&SpecProjection::from(events))
+    );
+  }
+
+  #[test]
+  pub fn can_report_unsupported_constructs() {
+    let document = json!({
+      "openapi": "3.0.0",
+      "paths": {
+        "/reports/{reportId}.pdf": {
+          "get": { "responses": { "200": { "description": "report" } } }
+        },
+        "/reports": {
+          "parameters": [{ "name": "X-Tenant", "in": "header" }],
+          "get": {
+            "parameters": [
+              { "name": "limit", "in": "query", "schema": { "type": "integer" } },
+              { "name": "session", "in": "cookie", "schema": { "type": "string" } },
+              { "name": "Accept", "in": "header", "schema": { "type": "string" } }
+            ],
+            "responses": {
+              "default": { "description": "error" },
+              "200": {
+                "description": "reports",
+                "content": {
+                  "application/json": {
+                    "schema": {
+                      "type": "object",
+                      "required": ["kind", "created", "scores"],
+                      "properties": {
+                        "kind": { "type": "string", "enum": ["daily", "weekly"] },
+                        "created": { "type": "string", "format": "date-time" },
+                        "scores": { "type": "object", "additionalProperties": { "type": "number" } },
+                        "owner": { "anyOf": [{ "type": "string" }, { "type": "number" }] },
+                        "range": { "allOf": [{ "type": "string" }, { "minLength": 1 }] },
+                        "missing": { "$ref": "#/components/schemas/Missing" }
+                      }
+                    }
+                  }
+                }
+              }
+            }
+          }
+        }
+      }
+    });
+
+    let spec_projection = SpecProjection::default();
+    let import = import(&spec_projection, &document, &mut TestIdGenerator::default())
+      .expect("valid openapi document should import");
+
+    assert_json_snapshot!(
+      "can_report_unsupported_constructs__unsupported",
+      import.unsupported
+    );
+
+    import
+      .commit(
+        spec_projection,
+        String::from("imported reports"),
+        CommandContext::default(),
+      )
+      .expect("imported commands should be valid despite unsupported constructs");
+  }
+
+  #[test]
+  pub fn can_import_query_and_header_parameters() {
+    let initial_events: Vec<SpecEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"RequestAdded": {"requestId": "request_1","pathId": "path_1","httpMethod": "GET"}},
+      {"RequestParameterAddedByPathAndMethod": {"parameterId": "parameter_1","pathId": "path_1","httpMethod": "GET","parameterLocation": "header","name": "x-tenant"}},
+    ]))
+    .expect("initial events should be valid spec events");
+    let spec_projection = SpecProjection::from(initial_events.clone());
+
+    let document = json!({
+      "openapi": "3.0.3",
+      "paths": {
+        "/todos": {
+          "parameters": [
+            { "name": "X-Tenant", "in": "header", "required": true, "schema": { "type": "string" } },
+            { "name": "limit", "in": "query", "required": true, "schema": { "type": "string" } }
+          ],
+          "get": {
+            "parameters": [
+              { "name": "limit", "in": "query", "schema": { "type": "integer" } },
+              { "name": "X-Request-Id", "in": "header", "required": true, "schema": { "type": "string" } }
+            ],
+            "responses": { "200": { "description": "todos" } }
+          }
+        }
+      }
+    });
+
+    let import = import(&spec_projection, &document, &mut TestIdGenerator::default())
+      .expect("valid openapi document should import");
+    assert!(import.unsupported.is_empty());
+
+    let events = import
+      .commit(
+        spec_projection,
+        String::from("imported todos"),
+        CommandContext::default(),
+      )
+      .expect("imported commands should be valid");
+    let imported_spec = SpecProjection::from(initial_events.into_iter().chain(events));
+    let shape_projection = imported_spec.shape();
+
+    let mut parameters = imported_spec
+      .endpoint()
+      .request_parameters
+      .values()
+      .map(|parameter| {
+        let base_shape_id = parameter.shape_id.as_ref().map(|shape_id| {
+          let node_index = shape_projection
+            .get_shape_node_index(shape_id)
+            .expect("parameter shapes should exist");
+          let base_index = shape_projection
+            .get_ancestor_shape_node_index(node_index)
+            .expect("parameter shapes should have a base shape");
+          String::from(shape_projection.graph[base_index].node_id())
+        });
+        (
+          parameter.location.clone(),
+          parameter.name.clone(),
+          base_shape_id,
+        )
+      })
+      .collect::<Vec<_>>();
+    parameters.sort();
+
+    assert_eq!(
+      parameters,
+      vec![
+        (
+          String::from("header"),
+          String::from("X-Request-Id"),
+          Some(String::from("$string"))
+        ),
+        (String::from("header"), String::from("x-tenant"), None),
+        (
+          String::from("query"),
+          String::from("limit"),
+          Some(String::from("$optional"))
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  pub fn can_import_exported_parameters() {
+    let events: Vec<SpecEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"PathParameterAdded": {"pathId": "path_2","parentPathId": "path_1","name": "todoId"}},
+      {"RequestAdded": {"requestId": "request_1","pathId": "path_2","httpMethod": "GET"}},
+      {"ShapeAdded": {"shapeId": "fields_inner_shape","baseShapeId": "$string","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeAdded": {"shapeId": "fields_shape","baseShapeId": "$optional","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "fields_shape","providerDescriptor": {"ShapeProvider": {"shapeId": "fields_inner_shape"}},"consumingParameterId": "$optionalInner"}}}},
+      {"RequestParameterAddedByPathAndMethod": {"parameterId": "parameter_1","pathId": "path_2","httpMethod": "GET","parameterLocation": "query","name": "fields"}},
+      {"RequestParameterShapeSet": {"parameterId": "parameter_1","parameterDescriptor": {"shapeId": "fields_shape","isRemoved": false}}},
+      {"ShapeAdded": {"shapeId": "tenant_shape","baseShapeId": "$string","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"RequestParameterAddedByPathAndMethod": {"parameterId": "parameter_2","pathId": "path_2","httpMethod": "GET","parameterLocation": "header","name": "x-tenant"}},
+      {"RequestParameterShapeSet": {"parameterId": "parameter_2","parameterDescriptor": {"shapeId": "tenant_shape","isRemoved": false}}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_1","pathId": "path_2","httpMethod": "GET","httpStatusCode": 200}},
+    ]))
+    .expect("initial events should be valid spec events");
+    let document = export(&SpecProjection::from(events));
+
+    let spec_projection = SpecProjection::default();
+    let import = import(&spec_projection, &document, &mut TestIdGenerator::default())
+      .expect("exported openapi document should import");
+    let events = import
+      .commit(
+        spec_projection,
+        String::from("imported todos"),
+        CommandContext::default(),
+      )
+      .expect("imported commands should be valid");
+    let reexported_document = export(&SpecProjection::from(events));
+
+    assert_eq!(
+      reexported_document["paths"]["/todos/{todoId}"]["get"]["parameters"],
+      document["paths"]["/todos/{todoId}"]["get"]["parameters"],
+      "parameters should survive exporting and importing"
+    );
+  }
+
+  #[test]
+  pub fn can_import_into_existing_paths() {
+    let initial_events: Vec<SpecEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"PathParameterAdded": {"pathId": "path_2","parentPathId": "path_1","name": "id"}},
+    ]))
+    .expect("initial events should be valid spec events");
+    let spec_projection = SpecProjection::from(initial_events);
+
+    let document = json!({
+      "openapi": "3.0.3",
+      "paths": {
+        "/todos/{todoId}/comments": {
+          "get": { "responses": { "200": { "description": "comments" } } }
+        }
+      }
+    });
+
+    let import = import(&spec_projection, &document, &mut TestIdGenerator::default())
+      .expect("valid openapi document should import");
+
+    let added_paths = import
+      .commands
+      .iter()
+      .filter_map(|command| match command {
+        SpecCommand::EndpointCommand(EndpointCommand::AddPathComponent(command)) => {
+          Some((command.parent_path_id.clone(), command.name.clone()))
+        }
+        SpecCommand::EndpointCommand(EndpointCommand::AddPathParameter(command)) => {
+          Some((command.parent_path_id.clone(), command.name.clone()))
+        }
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(
+      added_paths,
+      vec![(String::from("path_2"), String::from("comments"))]
+    );
+
+    import
+      .commit(
+        spec_projection,
+        String::from("imported comments"),
+        CommandContext::default(),
+      )
+      .expect("imported commands should be valid");
+  }
+
+  #[test]
+  pub fn can_import_operations_already_in_the_spec() {
+    let document = json!({
+      "openapi": "3.0.3",
+      "paths": {
+        "/todos": {
+          "get": {
+            "parameters": [{ "name": "limit", "in": "query", "schema": { "type": "integer" } }],
+            "responses": {
+              "200": {
+                "description": "all todos",
+                "content": {
+                  "application/json": { "schema": { "type": "array", "items": { "type": "string" } } }
+                }
+              }
+            }
+          },
+          "post": {
+            "requestBody": {
+              "content": { "application/json": { "schema": { "type": "string" } } }
+            },
+            "responses": { "201": { "description": "created" } }
+          }
+        }
+      }
+    });
+
+    let first_import = import(
+      &SpecProjection::default(),
+      &document,
+      &mut TestIdGenerator::default(),
+    )
+    .expect("valid openapi document should import");
+    let events = first_import
+      .commit(
+        SpecProjection::default(),
+        String::from("imported todos"),
+        CommandContext::default(),
+      )
+      .expect("imported commands should be valid");
+    let spec_projection = SpecProjection::from(events.clone());
+
+    let mut id_generator = TestIdGenerator { counter: 1000 };
+    let second_import = import(&spec_projection, &document, &mut id_generator)
+      .expect("valid openapi document should import");
+
+    let added_endpoints = second_import
+      .commands
+      .iter()
+      .filter(|command| {
+        matches!(
+          command,
+          SpecCommand::EndpointCommand(EndpointCommand::AddRequest(_))
+            | SpecCommand::EndpointCommand(EndpointCommand::AddResponseByPathAndMethod(_))
+            | SpecCommand::EndpointCommand(EndpointCommand::AddQueryParameter(_))
+        )
+      })
+      .count();
+    assert_eq!(added_endpoints, 0);
+    assert_eq!(
+      second_import
+        .unsupported
+        .iter()
+        .map(|construct| construct.location.as_str())
+        .collect::<Vec<_>>(),
+      vec![
+        "/paths/~1todos/get/responses/200/content/application~1json/schema",
+        "/paths/~1todos/post/requestBody/content/application~1json/schema",
+      ]
+    );
+
+    let reimported_events = second_import
+      .commit(
+        spec_projection,
+        String::from("imported todos again"),
+        CommandContext::default(),
+      )
+      .expect("imported commands should be valid");
+    let reimported_spec = SpecProjection::from(events.into_iter().chain(reimported_events));
+    let endpoint_nodes = reimported_spec
+      .endpoint()
+      .graph
+      .raw_nodes()
+      .iter()
+      .filter(|node| matches!(node.weight, Node::Request(..) | Node::Response(..)))
+      .count();
+    assert_eq!(endpoint_nodes, 4);
+  }
+
+  #[test]
+  pub fn rejects_documents_of_other_versions() {
+    let document = json!({ "swagger": "2.0", "paths": {} });
+
+    let result = import(
+      &SpecProjection::default(),
+      &document,
+      &mut TestIdGenerator::default(),
+    );
+    assert!(matches!(result, Err(OpenApiImportError::MissingVersion)));
+  }
+
+  #[derive(Debug, Default)]
+  struct TestIdGenerator {
+    counter: usize,
+  }
+
+  impl SpecIdGenerator for TestIdGenerator {
+    fn generate_id(&mut self, prefix: &str) -> String {
+      let id = format!("{}{}", prefix, self.counter);
+      self.counter += 1;
+      id
+    }
+  }
+}
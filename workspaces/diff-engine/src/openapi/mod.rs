@@ -0,0 +1,5 @@
+mod export;
+mod import;
+
+pub use export::export;
+pub use import::{import, OpenApiImport, OpenApiImportError, UnsupportedConstruct};
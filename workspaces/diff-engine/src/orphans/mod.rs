@@ -0,0 +1,344 @@
+use crate::commands::{ShapeCommand, SpecCommand};
+use crate::projections::endpoint::Node as EndpointNode;
+use crate::projections::shape::{Edge, Node};
+use crate::projections::{ShapeProjection, SpecProjection};
+use crate::state::shape::ShapeId;
+use petgraph::algo::tarjan_scc;
+use petgraph::graph::NodeIndex;
+use petgraph::graphmap::DiGraphMap;
+use petgraph::visit::EdgeRef;
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// Find the shapes no endpoint uses: shapes that can't be reached from request and response bodies
+/// or path and request parameters, through fields, shape parameters and base shapes.
+///
+/// Shapes are listed before the shapes they use themselves, so removing them in order never removes
+/// a shape that is still in use. Shapes that use each other, and the shapes they use, can't be
+/// removed one by one and are listed as not removable.
+pub fn orphaned_shapes(spec_projection: &SpecProjection) -> Vec<OrphanedShape> {
+  let shape_projection = spec_projection.shape();
+  let endpoint_projection = spec_projection.endpoint();
+
+  let body_shape_ids = endpoint_projection
+    .graph
+    .raw_nodes()
+    .iter()
+    .filter_map(|node| match &node.weight {
+      EndpointNode::Request(_, descriptor) => descriptor.body.as_ref(),
+      EndpointNode::Response(_, descriptor) => descriptor.body.as_ref(),
+      _ => None,
+    })
+    .map(|body| &body.root_shape_id);
+  let parameter_shape_ids = endpoint_projection
+    .request_parameters
+    .values()
+    .filter_map(|parameter| parameter.shape_id.as_ref());
+  let root_node_indices = body_shape_ids
+    .chain(parameter_shape_ids)
+    .chain(endpoint_projection.path_parameter_shapes.values())
+    .filter_map(|shape_id| shape_projection.get_shape_node_index(shape_id).copied());
+
+  let reachable = reachable_shapes(shape_projection, root_node_indices);
+  let orphaned_node_indices = shape_projection
+    .graph
+    .node_indices()
+    .filter(|node_index| match &shape_projection.graph[*node_index] {
+      Node::Shape(shape_node) => {
+        !reachable.contains(node_index) && !shape_projection.is_removed_shape(&shape_node.shape_id)
+      }
+      _ => false,
+    })
+    .collect::<Vec<_>>();
+
+  let unremovable = unremovable_orphans(shape_projection, &orphaned_node_indices);
+  removal_order(shape_projection, &orphaned_node_indices)
+    .into_iter()
+    .map(|node_index| {
+      let shape_id = String::from(shape_projection.graph[node_index].node_id());
+      OrphanedShape {
+        name: shape_projection.shape_names.get(&shape_id).cloned(),
+        removable: !unremovable.contains(&node_index),
+        shape_id,
+      }
+    })
+    .collect()
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanedShape {
+  pub shape_id: ShapeId,
+  pub name: Option<String>,
+  pub removable: bool,
+}
+
+impl OrphanedShape {
+  /// The command removing the shape, unless other orphaned shapes keep it in use
+  pub fn into_command(self) -> Option<SpecCommand> {
+    if self.removable {
+      Some(SpecCommand::from(ShapeCommand::remove_shape(self.shape_id)))
+    } else {
+      None
+    }
+  }
+}
+
+fn reachable_shapes(
+  shape_projection: &ShapeProjection,
+  root_node_indices: impl Iterator<Item = NodeIndex>,
+) -> HashSet<NodeIndex> {
+  let mut reachable = HashSet::new();
+  let mut pending = root_node_indices.collect::<Vec<_>>();
+  while let Some(node_index) = pending.pop() {
+    if reachable.insert(node_index) {
+      pending.extend(used_shapes(shape_projection, node_index));
+    }
+  }
+  reachable
+}
+
+// shapes used by a shape directly: its base shape, the shapes of its fields and the shapes provided
+// for its shape parameters
+fn used_shapes(shape_projection: &ShapeProjection, node_index: NodeIndex) -> Vec<NodeIndex> {
+  let graph = &shape_projection.graph;
+  let base_shapes = graph
+    .edges_directed(node_index, petgraph::Direction::Outgoing)
+    .filter(|edge| matches!(edge.weight(), Edge::IsDescendantOf))
+    .map(|edge| edge.target());
+  let field_shapes = graph
+    .edges_directed(node_index, petgraph::Direction::Incoming)
+    .filter(|edge| matches!(edge.weight(), Edge::IsFieldOf))
+    .flat_map(|field_edge| {
+      graph
+        .edges_directed(field_edge.source(), petgraph::Direction::Incoming)
+        .filter(|edge| matches!(edge.weight(), Edge::BelongsTo))
+        .map(|edge| edge.source())
+    });
+  let provided_shapes = graph
+    .edges_directed(node_index, petgraph::Direction::Outgoing)
+    .filter_map(|edge| match edge.weight() {
+      Edge::HasBinding(binding) => shape_projection
+        .get_shape_node_index(&binding.shape_id)
+        .copied(),
+      _ => None,
+    });
+
+  base_shapes
+    .chain(field_shapes)
+    .chain(provided_shapes)
+    .filter(|used_index| matches!(graph[*used_index], Node::Shape(_)))
+    .collect()
+}
+
+// Shapes in use can't be removed, so orphans in a cycle of orphans using each other can't be removed
+// one by one, and neither can the orphans used by them.
+fn unremovable_orphans(
+  shape_projection: &ShapeProjection,
+  orphaned: &[NodeIndex],
+) -> HashSet<NodeIndex> {
+  let orphaned_set = orphaned.iter().copied().collect::<HashSet<_>>();
+  let mut uses = DiGraphMap::<NodeIndex, ()>::new();
+  for node_index in orphaned {
+    uses.add_node(*node_index);
+    // shapes using themselves don't keep themselves in use
+    for used_index in used_shapes(shape_projection, *node_index) {
+      if orphaned_set.contains(&used_index) && used_index != *node_index {
+        uses.add_edge(*node_index, used_index, ());
+      }
+    }
+  }
+
+  let mut unremovable = HashSet::new();
+  let mut pending = tarjan_scc(&uses)
+    .into_iter()
+    .filter(|component| component.len() > 1)
+    .flatten()
+    .collect::<Vec<_>>();
+  while let Some(node_index) = pending.pop() {
+    if unremovable.insert(node_index) {
+      pending.extend(uses.neighbors(node_index));
+    }
+  }
+  unremovable
+}
+
+// Orders orphaned shapes so shapes come before the orphaned shapes they use, by reversing the order
+// in which a depth-first search finishes them. Searching from the last shape keeps shapes that don't
+// use each other in their original order.
+fn removal_order(shape_projection: &ShapeProjection, orphaned: &[NodeIndex]) -> Vec<NodeIndex> {
+  let orphaned_set = orphaned.iter().copied().collect::<HashSet<_>>();
+  let mut visited = HashSet::new();
+  let mut finished = vec![];
+
+  for root_index in orphaned.iter().rev() {
+    if !visited.insert(*root_index) {
+      continue;
+    }
+    // every entry holds a shape with the shapes it uses that are still to be visited
+    let mut stack = vec![(*root_index, used_shapes(shape_projection, *root_index))];
+    while let Some((node_index, used)) = stack.last_mut() {
+      match used.pop() {
+        Some(used_index) if orphaned_set.contains(&used_index) && visited.insert(used_index) => {
+          let used_by_used = used_shapes(shape_projection, used_index);
+          stack.push((used_index, used_by_used));
+        }
+        Some(_) => {}
+        None => {
+          finished.push(*node_index);
+          stack.pop();
+        }
+      }
+    }
+  }
+
+  finished.reverse();
+  finished
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::events::SpecEvent;
+  use cqrs_core::Aggregate;
+  use serde_json::json;
+
+  fn spec_events() -> Vec<SpecEvent> {
+    serde_json::from_value(json!([
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"PathParameterAdded": {"pathId": "path_2","parentPathId": "path_1","name": "todoId"}},
+      {"ShapeAdded": {"shapeId": "id_shape","baseShapeId": "$string","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"PathParameterShapeSet": {"pathId": "path_2","shapeDescriptor": {"shapeId": "id_shape","isRemoved": false}}},
+      {"ShapeAdded": {"shapeId": "limit_shape","baseShapeId": "$number","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"RequestParameterAddedByPathAndMethod": {"parameterId": "parameter_1","pathId": "path_1","httpMethod": "GET","parameterLocation": "query","name": "limit"}},
+      {"RequestParameterShapeSet": {"parameterId": "parameter_1","parameterDescriptor": {"shapeId": "limit_shape","isRemoved": false}}},
+      // the first time the todo shape was learned
+      {"ShapeAdded": {"shapeId": "old_todo_shape","baseShapeId": "$object","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": "Todo"}},
+      {"ShapeAdded": {"shapeId": "old_title_shape","baseShapeId": "$string","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"FieldAdded": {"fieldId": "old_title_field","shapeId": "old_todo_shape","name": "title","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "old_title_field","shapeId": "old_title_shape"}}}},
+      {"ShapeAdded": {"shapeId": "old_todos_shape","baseShapeId": "$list","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "old_todos_shape","providerDescriptor": {"ShapeProvider": {"shapeId": "old_todo_shape"}},"consumingParameterId": "$listItem"}}}},
+      // and the second time
+      {"ShapeAdded": {"shapeId": "todo_shape","baseShapeId": "$object","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": "Todo"}},
+      {"ShapeAdded": {"shapeId": "title_shape","baseShapeId": "$string","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"FieldAdded": {"fieldId": "title_field","shapeId": "todo_shape","name": "title","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "title_field","shapeId": "title_shape"}}}},
+      {"ShapeAdded": {"shapeId": "todos_shape","baseShapeId": "$list","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "todos_shape","providerDescriptor": {"ShapeProvider": {"shapeId": "todo_shape"}},"consumingParameterId": "$listItem"}}}},
+      {"ShapeAdded": {"shapeId": "unused_shape","baseShapeId": "$boolean","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"RequestAdded": {"requestId": "request_1","pathId": "path_1","httpMethod": "GET"}},
+      {"ResponseAddedByPathAndMethod": {"responseId": "response_1","pathId": "path_1","httpMethod": "GET","httpStatusCode": 200}},
+      {"ResponseBodySet": {"responseId": "response_1","bodyDescriptor": {"httpContentType": "application/json","shapeId": "old_todos_shape","isRemoved": false}}},
+      {"ResponseBodySet": {"responseId": "response_1","bodyDescriptor": {"httpContentType": "application/json","shapeId": "todos_shape","isRemoved": false}}},
+    ]))
+    .expect("initial events should be valid spec events")
+  }
+
+  #[test]
+  fn can_find_orphaned_shapes() {
+    let spec_projection = SpecProjection::from(spec_events());
+
+    let orphans = orphaned_shapes(&spec_projection);
+
+    assert_eq!(
+      orphans,
+      vec![
+        OrphanedShape {
+          shape_id: String::from("old_todos_shape"),
+          name: None,
+          removable: true
+        },
+        OrphanedShape {
+          shape_id: String::from("old_todo_shape"),
+          name: Some(String::from("Todo")),
+          removable: true
+        },
+        OrphanedShape {
+          shape_id: String::from("old_title_shape"),
+          name: None,
+          removable: true
+        },
+        OrphanedShape {
+          shape_id: String::from("unused_shape"),
+          name: None,
+          removable: true
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn orphaned_shapes_can_be_removed_in_order() {
+    let mut events = spec_events();
+    let mut spec_projection = SpecProjection::from(events.clone());
+    for orphan in orphaned_shapes(&spec_projection) {
+      let command = orphan
+        .clone()
+        .into_command()
+        .expect("orphans without cycles should be removable");
+      assert_eq!(
+        serde_json::to_value(&command).unwrap(),
+        json!({"RemoveShape": {"shapeId": orphan.shape_id}})
+      );
+      // shapes still in use can't be removed, so this fails when removing out of order
+      let new_events = spec_projection
+        .execute(command)
+        .expect("orphaned shapes should be removable in order");
+      for event in new_events {
+        spec_projection.apply(event.clone());
+        events.push(event);
+      }
+    }
+
+    assert_eq!(crate::lint::lint(events.clone()), vec![]);
+    assert!(orphaned_shapes(&SpecProjection::from(events)).is_empty());
+  }
+
+  #[test]
+  fn orphaned_shapes_using_each_other_are_not_removable() {
+    let events: Vec<SpecEvent> = serde_json::from_value(json!([
+      {"ShapeAdded": {"shapeId": "node_shape","baseShapeId": "$object","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": "Node"}},
+      {"ShapeAdded": {"shapeId": "link_shape","baseShapeId": "$object","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": "Link"}},
+      {"ShapeAdded": {"shapeId": "label_shape","baseShapeId": "$string","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"FieldAdded": {"fieldId": "next_field","shapeId": "node_shape","name": "next","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "next_field","shapeId": "link_shape"}}}},
+      {"FieldAdded": {"fieldId": "previous_field","shapeId": "link_shape","name": "previous","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "previous_field","shapeId": "node_shape"}}}},
+      {"FieldAdded": {"fieldId": "label_field","shapeId": "link_shape","name": "label","shapeDescriptor": {"FieldShapeFromShape": {"fieldId": "label_field","shapeId": "label_shape"}}}},
+      {"ShapeAdded": {"shapeId": "nodes_shape","baseShapeId": "$list","parameters": {"DynamicParameterList": {"shapeParameterIds": []}},"name": ""}},
+      {"ShapeParameterShapeSet": {"shapeDescriptor": {"ProviderInShape": {"shapeId": "nodes_shape","providerDescriptor": {"ShapeProvider": {"shapeId": "node_shape"}},"consumingParameterId": "$listItem"}}}},
+    ]))
+    .expect("initial events should be valid spec events");
+    let mut spec_projection = SpecProjection::from(events);
+
+    let orphans = orphaned_shapes(&spec_projection);
+    let mut removability = orphans
+      .iter()
+      .map(|orphan| (orphan.shape_id.as_str(), orphan.removable))
+      .collect::<Vec<_>>();
+    removability.sort();
+    assert_eq!(
+      removability,
+      vec![
+        ("label_shape", false),
+        ("link_shape", false),
+        ("node_shape", false),
+        ("nodes_shape", true),
+      ]
+    );
+
+    let commands = orphans
+      .into_iter()
+      .filter_map(OrphanedShape::into_command)
+      .collect::<Vec<_>>();
+    assert_eq!(commands.len(), 1);
+    for command in commands {
+      let new_events = spec_projection
+        .execute(command)
+        .expect("removable orphans should be removable");
+      for event in new_events {
+        spec_projection.apply(event);
+      }
+    }
+    assert!(orphaned_shapes(&spec_projection)
+      .iter()
+      .all(|orphan| !orphan.removable));
+  }
+}
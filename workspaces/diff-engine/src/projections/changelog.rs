@@ -0,0 +1,602 @@
+use crate::events::{EndpointEvent, EventContext, RfcEvent, ShapeEvent, SpecEvent};
+use crate::projections::endpoint::ROOT_PATH_ID;
+use crate::state::endpoint::{
+  HttpMethod, PathComponentId, RequestId, RequestParameterId, ResponseId,
+};
+use crate::state::shape::{
+  FieldId, FieldShapeDescriptor, ParameterShapeDescriptor, ShapeId, ShapeParameterId,
+};
+use cqrs_core::{Aggregate, AggregateEvent};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+
+/// The endpoints and shapes changed by every batch commit of a spec, in the order they were
+/// committed. Only changes made between a `BatchCommitStarted` and its `BatchCommitEnded` end up in
+/// the changelog, as events outside of batches have no commit message to describe them.
+#[derive(Debug, Default)]
+pub struct ChangelogProjection {
+  entries: Vec<ChangelogEntry>,
+  current_entry: Option<ChangelogEntry>,
+
+  // what's needed to resolve the events of a batch to endpoints and shapes
+  paths: HashMap<PathComponentId, PathComponent>,
+  endpoints: BTreeSet<(PathComponentId, HttpMethod)>,
+  requests: HashMap<RequestId, (PathComponentId, HttpMethod)>,
+  responses: HashMap<ResponseId, (PathComponentId, HttpMethod)>,
+  request_parameters: HashMap<RequestParameterId, (PathComponentId, HttpMethod)>,
+  shapes: HashMap<ShapeId, ChangedShape>,
+  fields: HashMap<FieldId, ShapeId>,
+  shape_parameters: HashMap<ShapeParameterId, ShapeId>,
+}
+
+#[derive(Debug)]
+struct PathComponent {
+  parent_id: PathComponentId,
+  name: String,
+  is_parameter: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangelogEntry {
+  pub batch_id: String,
+  pub commit_message: String,
+  pub event_context: Option<EventContext>,
+  pub endpoints: ChangelogChanges<ChangedEndpoint>,
+  pub shapes: ChangelogChanges<ChangedShape>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangelogChanges<T> {
+  pub added: Vec<T>,
+  pub updated: Vec<T>,
+  pub removed: Vec<T>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedEndpoint {
+  pub method: HttpMethod,
+  /// The absolute path of the endpoint, with path parameters like `{todoId}`
+  pub path: String,
+  #[serde(skip)]
+  path_id: PathComponentId,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedShape {
+  pub shape_id: ShapeId,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub name: Option<String>,
+  pub base_shape_id: ShapeId,
+}
+
+// changed endpoints and shapes are the same when they are about the same endpoint or shape, as
+// their descriptions can change within a batch
+impl PartialEq for ChangedEndpoint {
+  fn eq(&self, other: &Self) -> bool {
+    self.path_id == other.path_id && self.method == other.method
+  }
+}
+
+impl PartialEq for ChangedShape {
+  fn eq(&self, other: &Self) -> bool {
+    self.shape_id == other.shape_id
+  }
+}
+
+impl<T> Default for ChangelogChanges<T> {
+  fn default() -> Self {
+    Self {
+      added: vec![],
+      updated: vec![],
+      removed: vec![],
+    }
+  }
+}
+
+impl<T: PartialEq> ChangelogChanges<T> {
+  pub fn is_empty(&self) -> bool {
+    self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+  }
+
+  // changes are netted out within a batch: something added and updated is only added, something
+  // removed and added again is updated, and something added and removed isn't changed at all
+  fn add(&mut self, item: T) {
+    if let Some(index) = self.removed.iter().position(|other| *other == item) {
+      self.removed.remove(index);
+      self.updated.push(item);
+    } else if !self.added.contains(&item) {
+      self.added.push(item);
+    }
+  }
+
+  fn update(&mut self, item: T) {
+    let is_changed = self
+      .added
+      .iter()
+      .chain(self.updated.iter())
+      .chain(self.removed.iter())
+      .any(|other| *other == item);
+    if !is_changed {
+      self.updated.push(item);
+    }
+  }
+
+  fn remove(&mut self, item: T) {
+    if let Some(index) = self.added.iter().position(|other| *other == item) {
+      self.added.remove(index);
+      return;
+    }
+    self.updated.retain(|other| *other != item);
+    if !self.removed.contains(&item) {
+      self.removed.push(item);
+    }
+  }
+}
+
+impl ChangelogProjection {
+  pub fn entries(&self) -> &[ChangelogEntry] {
+    &self.entries
+  }
+
+  pub fn to_json_string(&self) -> String {
+    serde_json::to_string(&self.entries).expect("changelog entries should be serializable")
+  }
+
+  pub fn with_batch_commit_started(
+    &mut self,
+    batch_id: String,
+    commit_message: String,
+    event_context: Option<EventContext>,
+  ) {
+    self.current_entry = Some(ChangelogEntry {
+      batch_id,
+      commit_message,
+      event_context,
+      endpoints: ChangelogChanges::default(),
+      shapes: ChangelogChanges::default(),
+    });
+  }
+
+  pub fn with_batch_commit_ended(&mut self, batch_id: String) {
+    match self.current_entry.take() {
+      Some(entry) if entry.batch_id == batch_id => self.entries.push(entry),
+      Some(entry) => {
+        eprintln!(
+          "Ignoring end of batch commit '{}' while batch commit '{}' is in progress",
+          batch_id, entry.batch_id
+        );
+        self.current_entry = Some(entry);
+      }
+      None => {}
+    }
+  }
+
+  pub fn with_path_component(
+    &mut self,
+    path_id: PathComponentId,
+    parent_id: PathComponentId,
+    name: String,
+    is_parameter: bool,
+  ) {
+    self.paths.insert(
+      path_id,
+      PathComponent {
+        parent_id,
+        name,
+        is_parameter,
+      },
+    );
+  }
+
+  pub fn with_path_component_renamed(&mut self, path_id: PathComponentId, name: String) {
+    if let Some(path_component) = self.paths.get_mut(&path_id) {
+      path_component.name = name;
+    }
+    self.with_updated_endpoints_under_path(&path_id);
+  }
+
+  pub fn with_path_component_removed(&mut self, path_id: PathComponentId) {
+    let removed_endpoints = self
+      .endpoints
+      .iter()
+      .filter(|(endpoint_path_id, _)| self.is_path_under(endpoint_path_id, &path_id))
+      .cloned()
+      .collect::<Vec<_>>();
+    for (endpoint_path_id, method) in removed_endpoints {
+      let endpoint = self.changed_endpoint(&endpoint_path_id, &method);
+      self.endpoints.remove(&(endpoint_path_id, method));
+      if let Some(entry) = &mut self.current_entry {
+        entry.endpoints.remove(endpoint);
+      }
+    }
+  }
+
+  fn with_updated_endpoints_under_path(&mut self, path_id: &str) {
+    let updated_endpoints = self
+      .endpoints
+      .iter()
+      .filter(|(endpoint_path_id, _)| self.is_path_under(endpoint_path_id, path_id))
+      .map(|(endpoint_path_id, method)| self.changed_endpoint(endpoint_path_id, method))
+      .collect::<Vec<_>>();
+    if let Some(entry) = &mut self.current_entry {
+      for endpoint in updated_endpoints {
+        entry.endpoints.update(endpoint);
+      }
+    }
+  }
+
+  // Requests, responses and parameters are all added by path and method, the first one of which
+  // adds the endpoint
+  pub fn with_endpoint(&mut self, path_id: PathComponentId, method: HttpMethod) {
+    let endpoint = self.changed_endpoint(&path_id, &method);
+    let is_new = self.endpoints.insert((path_id, method));
+    if let Some(entry) = &mut self.current_entry {
+      if is_new {
+        entry.endpoints.add(endpoint);
+      } else {
+        entry.endpoints.update(endpoint);
+      }
+    }
+  }
+
+  fn with_updated_endpoint(&mut self, endpoint: Option<(PathComponentId, HttpMethod)>) {
+    if let Some((path_id, method)) = endpoint {
+      let endpoint = self.changed_endpoint(&path_id, &method);
+      if let Some(entry) = &mut self.current_entry {
+        entry.endpoints.update(endpoint);
+      }
+    }
+  }
+
+  pub fn with_shape(&mut self, shape_id: ShapeId, base_shape_id: ShapeId, name: String) {
+    let shape = ChangedShape {
+      shape_id: shape_id.clone(),
+      name: Some(name).filter(|name| !name.is_empty()),
+      base_shape_id,
+    };
+    self.shapes.insert(shape_id, shape.clone());
+    if let Some(entry) = &mut self.current_entry {
+      entry.shapes.add(shape);
+    }
+  }
+
+  pub fn with_shape_removed(&mut self, shape_id: ShapeId) {
+    if let Some(shape) = self.shapes.remove(&shape_id) {
+      if let Some(entry) = &mut self.current_entry {
+        entry.shapes.remove(shape);
+      }
+    }
+  }
+
+  fn with_updated_shape(&mut self, shape_id: Option<ShapeId>) {
+    let shape = shape_id.and_then(|shape_id| self.shapes.get(&shape_id).cloned());
+    if let (Some(shape), Some(entry)) = (shape, &mut self.current_entry) {
+      entry.shapes.update(shape);
+    }
+  }
+
+  fn is_path_under(&self, path_id: &str, ancestor_id: &str) -> bool {
+    let mut current_id = path_id;
+    loop {
+      if current_id == ancestor_id {
+        return true;
+      }
+      match self.paths.get(current_id) {
+        Some(path_component) => current_id = &path_component.parent_id,
+        None => return false,
+      }
+    }
+  }
+
+  fn changed_endpoint(&self, path_id: &str, method: &str) -> ChangedEndpoint {
+    let mut segments = vec![];
+    let mut current_id = path_id;
+    while current_id != ROOT_PATH_ID {
+      match self.paths.get(current_id) {
+        Some(path_component) => {
+          segments.push(if path_component.is_parameter {
+            format!("{{{}}}", path_component.name)
+          } else {
+            path_component.name.clone()
+          });
+          current_id = &path_component.parent_id;
+        }
+        None => break,
+      }
+    }
+    segments.reverse();
+
+    ChangedEndpoint {
+      method: String::from(method),
+      path: format!("/{}", segments.join("/")),
+      path_id: String::from(path_id),
+    }
+  }
+}
+
+impl Aggregate for ChangelogProjection {
+  fn aggregate_type() -> &'static str {
+    "changelog_projection"
+  }
+}
+
+impl<I> From<I> for ChangelogProjection
+where
+  I: IntoIterator,
+  I::Item: AggregateEvent<Self>,
+{
+  fn from(events: I) -> Self {
+    let mut projection = ChangelogProjection::default();
+    for event in events.into_iter() {
+      projection.apply(event);
+    }
+    projection
+  }
+}
+
+// Events
+// ------
+
+impl AggregateEvent<ChangelogProjection> for SpecEvent {
+  fn apply_to(self, projection: &mut ChangelogProjection) {
+    match self {
+      SpecEvent::EndpointEvent(event) => event.apply_to(projection),
+      SpecEvent::RfcEvent(event) => event.apply_to(projection),
+      SpecEvent::ShapeEvent(event) => event.apply_to(projection),
+    }
+  }
+}
+
+impl AggregateEvent<ChangelogProjection> for RfcEvent {
+  fn apply_to(self, projection: &mut ChangelogProjection) {
+    match self {
+      RfcEvent::BatchCommitStarted(e) => {
+        projection.with_batch_commit_started(e.batch_id, e.commit_message, e.event_context)
+      }
+      RfcEvent::BatchCommitEnded(e) => projection.with_batch_commit_ended(e.batch_id),
+      _ => {}
+    }
+  }
+}
+
+impl AggregateEvent<ChangelogProjection> for EndpointEvent {
+  fn apply_to(self, projection: &mut ChangelogProjection) {
+    match self {
+      EndpointEvent::PathComponentAdded(e) => {
+        projection.with_path_component(e.path_id, e.parent_path_id, e.name, false)
+      }
+      EndpointEvent::PathParameterAdded(e) => {
+        projection.with_path_component(e.path_id, e.parent_path_id, e.name, true)
+      }
+      EndpointEvent::PathComponentRenamed(e) => {
+        projection.with_path_component_renamed(e.path_id, e.name)
+      }
+      EndpointEvent::PathParameterRenamed(e) => {
+        projection.with_path_component_renamed(e.path_id, e.name)
+      }
+      EndpointEvent::PathParameterShapeSet(e) => {
+        projection.with_updated_endpoints_under_path(&e.path_id)
+      }
+      EndpointEvent::PathComponentRemoved(e) => projection.with_path_component_removed(e.path_id),
+      EndpointEvent::PathParameterRemoved(e) => projection.with_path_component_removed(e.path_id),
+
+      EndpointEvent::RequestAdded(e) => {
+        let endpoint = (e.path_id, e.http_method);
+        projection.requests.insert(e.request_id, endpoint.clone());
+        projection.with_endpoint(endpoint.0, endpoint.1);
+      }
+      EndpointEvent::ResponseAddedByPathAndMethod(e) => {
+        let endpoint = (e.path_id, e.http_method);
+        projection.responses.insert(e.response_id, endpoint.clone());
+        projection.with_endpoint(endpoint.0, endpoint.1);
+      }
+      EndpointEvent::RequestParameterAddedByPathAndMethod(e) => {
+        let endpoint = (e.path_id, e.http_method);
+        let parameters = &mut projection.request_parameters;
+        parameters.insert(e.parameter_id, endpoint.clone());
+        projection.with_endpoint(endpoint.0, endpoint.1);
+      }
+
+      EndpointEvent::RequestContentTypeSet(e) => {
+        projection.with_updated_endpoint(projection.requests.get(&e.request_id).cloned())
+      }
+      EndpointEvent::RequestBodySet(e) => {
+        projection.with_updated_endpoint(projection.requests.get(&e.request_id).cloned())
+      }
+      EndpointEvent::RequestBodyUnset(e) => {
+        projection.with_updated_endpoint(projection.requests.get(&e.request_id).cloned())
+      }
+      EndpointEvent::RequestRemoved(e) => {
+        let endpoint = projection.requests.remove(&e.request_id);
+        projection.with_updated_endpoint(endpoint)
+      }
+      EndpointEvent::ResponseStatusCodeSet(e) => {
+        projection.with_updated_endpoint(projection.responses.get(&e.response_id).cloned())
+      }
+      EndpointEvent::ResponseContentTypeSet(e) => {
+        projection.with_updated_endpoint(projection.responses.get(&e.response_id).cloned())
+      }
+      EndpointEvent::ResponseBodySet(e) => {
+        projection.with_updated_endpoint(projection.responses.get(&e.response_id).cloned())
+      }
+      EndpointEvent::ResponseBodyUnset(e) => {
+        projection.with_updated_endpoint(projection.responses.get(&e.response_id).cloned())
+      }
+      EndpointEvent::ResponseRemoved(e) => {
+        let endpoint = projection.responses.remove(&e.response_id);
+        projection.with_updated_endpoint(endpoint)
+      }
+      EndpointEvent::RequestParameterRenamed(e) => projection
+        .with_updated_endpoint(projection.request_parameters.get(&e.parameter_id).cloned()),
+      EndpointEvent::RequestParameterShapeSet(e) => projection
+        .with_updated_endpoint(projection.request_parameters.get(&e.parameter_id).cloned()),
+      EndpointEvent::RequestParameterShapeUnset(e) => projection
+        .with_updated_endpoint(projection.request_parameters.get(&e.parameter_id).cloned()),
+      EndpointEvent::RequestParameterRemoved(e) => {
+        let endpoint = projection.request_parameters.remove(&e.parameter_id);
+        projection.with_updated_endpoint(endpoint)
+      }
+    }
+  }
+}
+
+impl AggregateEvent<ChangelogProjection> for ShapeEvent {
+  fn apply_to(self, projection: &mut ChangelogProjection) {
+    match self {
+      ShapeEvent::ShapeAdded(e) => projection.with_shape(e.shape_id, e.base_shape_id, e.name),
+      ShapeEvent::ShapeRemoved(e) => projection.with_shape_removed(e.shape_id),
+      ShapeEvent::BaseShapeSet(e) => {
+        if let Some(shape) = projection.shapes.get_mut(&e.shape_id) {
+          shape.base_shape_id = e.base_shape_id;
+        }
+        projection.with_updated_shape(Some(e.shape_id))
+      }
+      ShapeEvent::ShapeRenamed(e) => {
+        if let Some(shape) = projection.shapes.get_mut(&e.shape_id) {
+          shape.name = Some(e.name).filter(|name| !name.is_empty());
+        }
+        projection.with_updated_shape(Some(e.shape_id))
+      }
+      ShapeEvent::StringFormatSet(e) => projection.with_updated_shape(Some(e.shape_id)),
+      ShapeEvent::StringEnumSet(e) => projection.with_updated_shape(Some(e.shape_id)),
+
+      // fields and shape parameters are part of the shape they were added to
+      ShapeEvent::FieldAdded(e) => {
+        projection.fields.insert(e.field_id, e.shape_id.clone());
+        projection.with_updated_shape(Some(e.shape_id))
+      }
+      ShapeEvent::FieldShapeSet(e) => {
+        let field_id = match e.shape_descriptor {
+          FieldShapeDescriptor::FieldShapeFromShape(descriptor) => descriptor.field_id,
+          FieldShapeDescriptor::FieldShapeFromParameter(descriptor) => descriptor.field_id,
+        };
+        projection.with_updated_shape(projection.fields.get(&field_id).cloned())
+      }
+      ShapeEvent::FieldRenamed(e) => {
+        projection.with_updated_shape(projection.fields.get(&e.field_id).cloned())
+      }
+      ShapeEvent::FieldRemoved(e) => {
+        let shape_id = projection.fields.remove(&e.field_id);
+        projection.with_updated_shape(shape_id)
+      }
+      ShapeEvent::ShapeParameterAdded(e) => {
+        projection
+          .shape_parameters
+          .insert(e.shape_parameter_id, e.shape_id.clone());
+        projection.with_updated_shape(Some(e.shape_id))
+      }
+      ShapeEvent::ShapeParameterShapeSet(e) => match e.shape_descriptor {
+        ParameterShapeDescriptor::ProviderInShape(descriptor) => {
+          projection.with_updated_shape(Some(descriptor.shape_id))
+        }
+        ParameterShapeDescriptor::ProviderInField(_) => {}
+      },
+      ShapeEvent::ShapeParameterRenamed(e) => projection.with_updated_shape(
+        projection
+          .shape_parameters
+          .get(&e.shape_parameter_id)
+          .cloned(),
+      ),
+      ShapeEvent::ShapeParameterRemoved(e) => {
+        let shape_id = projection.shape_parameters.remove(&e.shape_parameter_id);
+        projection.with_updated_shape(shape_id)
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  pub fn can_list_changes_per_batch_commit() {
+    let events: Vec<SpecEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded":{"pathId":"path_1","parentPathId":"root","name":"todos"}},
+      {"BatchCommitStarted":{"batchId":"batch_1","commitMessage":"Document todos","eventContext":{"clientId":"ci","clientSessionId":"session_1","clientCommandBatchId":"batch_1","createdAt":"2021-01-01T10:00:00Z"}}},
+      {"PathParameterAdded":{"pathId":"path_2","parentPathId":"path_1","name":"todoId"}},
+      {"RequestAdded":{"requestId":"request_1","pathId":"path_1","httpMethod":"GET"}},
+      {"ResponseAddedByPathAndMethod":{"responseId":"response_1","pathId":"path_1","httpMethod":"GET","httpStatusCode":200}},
+      {"ResponseAddedByPathAndMethod":{"responseId":"response_2","pathId":"path_2","httpMethod":"DELETE","httpStatusCode":204}},
+      {"ShapeAdded":{"shapeId":"shape_1","baseShapeId":"$object","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":"Todo"}},
+      {"ShapeAdded":{"shapeId":"shape_2","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"FieldAdded":{"fieldId":"field_1","shapeId":"shape_1","name":"task","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_1","shapeId":"shape_2"}}}},
+      {"ResponseBodySet":{"responseId":"response_1","bodyDescriptor":{"httpContentType":"application/json","shapeId":"shape_1","isRemoved":false}}},
+      {"BatchCommitEnded":{"batchId":"batch_1"}},
+      {"BatchCommitStarted":{"batchId":"batch_2","commitMessage":"Rename todos to tasks"}},
+      {"PathComponentRenamed":{"pathId":"path_1","name":"tasks"}},
+      {"ShapeAdded":{"shapeId":"shape_3","baseShapeId":"$boolean","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"FieldAdded":{"fieldId":"field_2","shapeId":"shape_1","name":"done","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_2","shapeId":"shape_3"}}}},
+      {"PathParameterRemoved":{"pathId":"path_2","name":"todoId"}},
+      {"ShapeRemoved":{"shapeId":"shape_2"}},
+      {"BatchCommitEnded":{"batchId":"batch_2"}},
+      {"BatchCommitStarted":{"batchId":"batch_3","commitMessage":"Unfinished"}},
+      {"ShapeRemoved":{"shapeId":"shape_3"}},
+    ]))
+    .expect("should be able to deserialize events");
+
+    let projection = ChangelogProjection::from(events);
+    let entries = projection.entries();
+    assert_eq!(
+      entries.len(),
+      2,
+      "only ended batch commits are part of the changelog"
+    );
+
+    let initial = &entries[0];
+    assert_eq!(initial.batch_id, "batch_1");
+    assert_eq!(initial.commit_message, "Document todos");
+    assert_eq!(
+      initial
+        .event_context
+        .as_ref()
+        .map(|context| context.client_id.as_str()),
+      Some("ci")
+    );
+    let added_endpoints = initial
+      .endpoints
+      .added
+      .iter()
+      .map(|endpoint| format!("{} {}", endpoint.method, endpoint.path))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      added_endpoints,
+      vec!["GET /todos", "DELETE /todos/{todoId}"]
+    );
+    assert!(initial.endpoints.updated.is_empty());
+    let added_shapes = initial
+      .shapes
+      .added
+      .iter()
+      .map(|shape| shape.shape_id.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(added_shapes, vec!["shape_1", "shape_2"]);
+    assert_eq!(initial.shapes.added[0].name.as_deref(), Some("Todo"));
+    assert!(initial.shapes.updated.is_empty());
+
+    let rename = &entries[1];
+    assert!(rename.event_context.is_none());
+    let updated_endpoints = rename
+      .endpoints
+      .updated
+      .iter()
+      .map(|endpoint| format!("{} {}", endpoint.method, endpoint.path))
+      .collect::<Vec<_>>();
+    assert_eq!(updated_endpoints, vec!["GET /tasks"]);
+    let removed_endpoints = rename
+      .endpoints
+      .removed
+      .iter()
+      .map(|endpoint| format!("{} {}", endpoint.method, endpoint.path))
+      .collect::<Vec<_>>();
+    assert_eq!(removed_endpoints, vec!["DELETE /tasks/{todoId}"]);
+    assert_eq!(rename.shapes.added[0].shape_id, "shape_3");
+    assert_eq!(rename.shapes.updated[0].shape_id, "shape_1");
+    assert_eq!(rename.shapes.removed[0].shape_id, "shape_2");
+  }
+}
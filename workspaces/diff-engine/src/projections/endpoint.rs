@@ -8,33 +8,48 @@ use crate::{
 };
 use cqrs_core::{Aggregate, AggregateCommand, AggregateEvent, Event};
 use petgraph::graph::{Graph, NodeIndex};
+use petgraph::visit::EdgeRef;
 use std::collections::HashMap;
 
 pub const ROOT_PATH_ID: &str = "root";
 
-#[derive(Debug)]
+// locations of request parameters, as recorded by RequestParameterAddedByPathAndMethod
+pub const QUERY_PARAMETER_LOCATION: &str = "query";
+pub const HEADER_PARAMETER_LOCATION: &str = "header";
+pub const RESPONSE_HEADER_PARAMETER_LOCATION: &str = "response-header";
+
+#[derive(Debug, PartialEq)]
 pub struct PathComponentDescriptor {
   pub is_parameter: bool,
   pub name: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, PartialEq)]
 pub struct BodyDescriptor {
   pub http_content_type: HttpContentType,
   pub root_shape_id: ShapeId,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, PartialEq)]
 pub struct RequestBodyDescriptor {
   pub body: Option<BodyDescriptor>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, PartialEq)]
 pub struct ResponseBodyDescriptor {
   pub body: Option<BodyDescriptor>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+pub struct RequestParameterDescriptor {
+  pub path_id: PathComponentId,
+  pub http_method: HttpMethod,
+  pub location: String,
+  pub name: String,
+  pub shape_id: Option<ShapeId>,
+}
+
+#[derive(Debug, PartialEq)]
 pub enum Node {
   PathComponent(PathComponentId, PathComponentDescriptor),
   HttpMethod(HttpMethod),
@@ -43,7 +58,18 @@ pub enum Node {
   Response(ResponseId, ResponseBodyDescriptor),
 }
 
-#[derive(Debug)]
+impl Node {
+  pub(crate) fn node_id(&self) -> Option<&str> {
+    match self {
+      Node::PathComponent(path_id, _) => Some(path_id),
+      Node::Request(request_id, _) => Some(request_id),
+      Node::Response(response_id, _) => Some(response_id),
+      Node::HttpMethod(_) | Node::HttpStatusCode(_) => None,
+    }
+  }
+}
+
+#[derive(Debug, PartialEq)]
 pub enum Edge {
   IsChildOf,
 }
@@ -52,9 +78,11 @@ pub enum Edge {
 pub struct EndpointProjection {
   pub graph: Graph<Node, Edge>,
   // SAFETY: node indices are not stable upon removing of nodes from graph -> node indices might be referred to
-  // which no longer exist or point to a different node. Compiler can't track these nodes for us. Do not delete nodes
-  // without rebuilding this map.
+  // which no longer exist or point to a different node. Compiler can't track these nodes for us. Only delete nodes
+  // through `remove_nodes`, which keeps this map up to date.
   pub node_id_to_index: HashMap<String, petgraph::graph::NodeIndex>,
+  pub request_parameters: HashMap<RequestParameterId, RequestParameterDescriptor>,
+  pub path_parameter_shapes: HashMap<PathComponentId, ShapeId>,
 }
 
 impl EndpointProjection {
@@ -255,6 +283,267 @@ impl EndpointProjection {
       .insert(response_id, response_node_index);
   }
 
+  pub fn with_request_parameter(
+    &mut self,
+    parameter_id: RequestParameterId,
+    path_id: PathComponentId,
+    http_method: HttpMethod,
+    location: String,
+    name: String,
+  ) {
+    self.request_parameters.insert(
+      parameter_id,
+      RequestParameterDescriptor {
+        path_id,
+        http_method,
+        location,
+        name,
+        shape_id: None,
+      },
+    );
+  }
+
+  pub fn with_request_parameter_name(&mut self, parameter_id: RequestParameterId, name: String) {
+    if let Some(descriptor) = self.request_parameters.get_mut(&parameter_id) {
+      descriptor.name = name;
+    }
+  }
+
+  pub fn with_request_parameter_shape(
+    &mut self,
+    parameter_id: RequestParameterId,
+    shape_id: Option<ShapeId>,
+  ) {
+    if let Some(descriptor) = self.request_parameters.get_mut(&parameter_id) {
+      descriptor.shape_id = shape_id;
+    }
+  }
+
+  pub fn without_request_parameter(&mut self, parameter_id: RequestParameterId) {
+    self.request_parameters.remove(&parameter_id);
+  }
+
+  pub fn with_path_component_name(&mut self, path_id: PathComponentId, name: String) {
+    if let Some(Node::PathComponent(_, descriptor)) = self.path_component_node_mut(&path_id) {
+      descriptor.name = name;
+    }
+  }
+
+  pub fn with_path_parameter_shape(&mut self, path_id: PathComponentId, shape_id: Option<ShapeId>) {
+    match shape_id {
+      Some(shape_id) => self.path_parameter_shapes.insert(path_id, shape_id),
+      None => self.path_parameter_shapes.remove(&path_id),
+    };
+  }
+
+  // Removes the path component with everything below it: nested path components, and the
+  // operations, responses and parameters of all of them
+  pub fn without_path_component(&mut self, path_id: PathComponentId) {
+    let path_node_index = match self.get_path_component_node_index(&path_id) {
+      Some(node_index) if path_id != ROOT_PATH_ID => *node_index,
+      _ => return,
+    };
+
+    let mut removed_node_indices = vec![];
+    let mut pending = vec![path_node_index];
+    while let Some(node_index) = pending.pop() {
+      removed_node_indices.push(node_index);
+      pending.extend(
+        self
+          .graph
+          .neighbors_directed(node_index, petgraph::Direction::Incoming),
+      );
+    }
+
+    let removed_path_ids = removed_node_indices
+      .iter()
+      .filter_map(|node_index| match self.graph.node_weight(*node_index) {
+        Some(Node::PathComponent(path_id, _)) => Some(path_id.clone()),
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+    self
+      .request_parameters
+      .retain(|_, descriptor| !removed_path_ids.contains(&descriptor.path_id));
+    self
+      .path_parameter_shapes
+      .retain(|path_id, _| !removed_path_ids.contains(path_id));
+
+    self.remove_nodes(removed_node_indices);
+  }
+
+  pub fn with_request_content_type(
+    &mut self,
+    request_id: RequestId,
+    http_content_type: HttpContentType,
+  ) {
+    if let Some(Node::Request(_, RequestBodyDescriptor { body: Some(body) })) =
+      self.request_node_mut(&request_id)
+    {
+      body.http_content_type = http_content_type;
+    }
+  }
+
+  pub fn without_request_body(&mut self, request_id: RequestId) {
+    if let Some(Node::Request(_, body_descriptor)) = self.request_node_mut(&request_id) {
+      body_descriptor.body = None;
+    }
+  }
+
+  pub fn without_request(&mut self, request_id: RequestId) {
+    if let Some(request_node_index) = self.get_request_node_index(&request_id).cloned() {
+      self.without_operation_node(request_node_index);
+    }
+  }
+
+  pub fn with_response_status_code(
+    &mut self,
+    response_id: ResponseId,
+    http_status_code: HttpStatusCode,
+  ) {
+    let response_node_index = match self.get_response_node_index(&response_id) {
+      Some(node_index) => *node_index,
+      None => return,
+    };
+    let (status_code_edge_index, status_code_node_index) = match self
+      .graph
+      .edges_directed(response_node_index, petgraph::Direction::Outgoing)
+      .next()
+    {
+      Some(edge) => (edge.id(), edge.target()),
+      None => return,
+    };
+    let method_node_index = match self
+      .graph
+      .neighbors_directed(status_code_node_index, petgraph::Direction::Outgoing)
+      .next()
+    {
+      Some(node_index) => node_index,
+      None => return,
+    };
+
+    self.graph.remove_edge(status_code_edge_index);
+    let new_status_code_node_index =
+      self.ensure_status_code_node(method_node_index, http_status_code);
+    self.graph.add_edge(
+      response_node_index,
+      new_status_code_node_index,
+      Edge::IsChildOf,
+    );
+    self.remove_nodes_until_non_empty(vec![], status_code_node_index);
+  }
+
+  pub fn with_response_content_type(
+    &mut self,
+    response_id: ResponseId,
+    http_content_type: HttpContentType,
+  ) {
+    if let Some(Node::Response(_, ResponseBodyDescriptor { body: Some(body) })) =
+      self.response_node_mut(&response_id)
+    {
+      body.http_content_type = http_content_type;
+    }
+  }
+
+  pub fn without_response_body(&mut self, response_id: ResponseId) {
+    if let Some(Node::Response(_, body_descriptor)) = self.response_node_mut(&response_id) {
+      body_descriptor.body = None;
+    }
+  }
+
+  pub fn without_response(&mut self, response_id: ResponseId) {
+    if let Some(response_node_index) = self.get_response_node_index(&response_id).cloned() {
+      self.without_operation_node(response_node_index);
+    }
+  }
+
+  // Removes a request or response node, along with the status code and method nodes that no
+  // longer have any requests or responses
+  fn without_operation_node(&mut self, node_index: NodeIndex) {
+    let parent_node_index = self
+      .graph
+      .neighbors_directed(node_index, petgraph::Direction::Outgoing)
+      .next();
+    match parent_node_index {
+      Some(parent_node_index) => {
+        self.remove_nodes_until_non_empty(vec![node_index], parent_node_index)
+      }
+      None => self.remove_nodes(vec![node_index]),
+    }
+  }
+
+  fn remove_nodes_until_non_empty(
+    &mut self,
+    mut removed_node_indices: Vec<NodeIndex>,
+    node_index: NodeIndex,
+  ) {
+    let mut current_node_index = node_index;
+    loop {
+      let is_operation_node = matches!(
+        self.graph.node_weight(current_node_index),
+        Some(Node::HttpMethod(_)) | Some(Node::HttpStatusCode(_))
+      );
+      let has_children = self
+        .graph
+        .neighbors_directed(current_node_index, petgraph::Direction::Incoming)
+        .any(|child_node_index| !removed_node_indices.contains(&child_node_index));
+      if !is_operation_node || has_children {
+        break;
+      }
+      removed_node_indices.push(current_node_index);
+      match self
+        .graph
+        .neighbors_directed(current_node_index, petgraph::Direction::Outgoing)
+        .next()
+      {
+        Some(parent_node_index) => current_node_index = parent_node_index,
+        None => break,
+      }
+    }
+    self.remove_nodes(removed_node_indices);
+  }
+
+  // Removing a node from the graph moves the last node into its place, so whenever that node has an
+  // id, its index has to be updated. Removing the highest indices first keeps the indices of the
+  // nodes still to be removed valid.
+  fn remove_nodes(&mut self, mut node_indices: Vec<NodeIndex>) {
+    node_indices.sort_unstable();
+    node_indices.dedup();
+    for node_index in node_indices.into_iter().rev() {
+      let removed_node = match self.graph.remove_node(node_index) {
+        Some(node) => node,
+        None => continue,
+      };
+      if let Some(node_id) = removed_node.node_id() {
+        self.node_id_to_index.remove(node_id);
+      }
+      if let Some(moved_node_id) = self
+        .graph
+        .node_weight(node_index)
+        .and_then(|moved_node| moved_node.node_id())
+      {
+        self
+          .node_id_to_index
+          .insert(String::from(moved_node_id), node_index);
+      }
+    }
+  }
+
+  fn path_component_node_mut(&mut self, path_id: &PathComponentId) -> Option<&mut Node> {
+    let node_index = *self.get_path_component_node_index(path_id)?;
+    self.graph.node_weight_mut(node_index)
+  }
+
+  fn request_node_mut(&mut self, request_id: &RequestId) -> Option<&mut Node> {
+    let node_index = *self.get_request_node_index(request_id)?;
+    self.graph.node_weight_mut(node_index)
+  }
+
+  fn response_node_mut(&mut self, response_id: &ResponseId) -> Option<&mut Node> {
+    let node_index = *self.get_response_node_index(response_id)?;
+    self.graph.node_weight_mut(node_index)
+  }
+
   pub fn get_path_component_node_index(
     &self,
     path_component_id: &PathComponentId,
@@ -278,6 +567,29 @@ impl EndpointProjection {
     }
   }
 
+  pub fn get_request_path_and_method(
+    &self,
+    request_id: &RequestId,
+  ) -> Option<(PathComponentId, HttpMethod)> {
+    let request_node_index = *self.get_request_node_index(request_id)?;
+    let method_node_index = self
+      .graph
+      .neighbors_directed(request_node_index, petgraph::Direction::Outgoing)
+      .next()?;
+    let http_method = match self.graph.node_weight(method_node_index)? {
+      Node::HttpMethod(http_method) => http_method.clone(),
+      _ => return None,
+    };
+    let path_node_index = self
+      .graph
+      .neighbors_directed(method_node_index, petgraph::Direction::Outgoing)
+      .next()?;
+    match self.graph.node_weight(path_node_index)? {
+      Node::PathComponent(path_id, _) => Some((path_id.clone(), http_method)),
+      _ => None,
+    }
+  }
+
   pub fn get_response_node_index(&self, response_id: &ResponseId) -> Option<&NodeIndex> {
     let node_index = self.node_id_to_index.get(response_id)?;
     let node = self.graph.node_weight(*node_index)?;
@@ -287,6 +599,56 @@ impl EndpointProjection {
       None
     }
   }
+
+  pub fn get_response_path_and_method(
+    &self,
+    response_id: &ResponseId,
+  ) -> Option<(PathComponentId, HttpMethod)> {
+    let response_node_index = *self.get_response_node_index(response_id)?;
+    let status_code_node_index = self
+      .graph
+      .neighbors_directed(response_node_index, petgraph::Direction::Outgoing)
+      .next()?;
+    let method_node_index = self
+      .graph
+      .neighbors_directed(status_code_node_index, petgraph::Direction::Outgoing)
+      .next()?;
+    let http_method = match self.graph.node_weight(method_node_index)? {
+      Node::HttpMethod(http_method) => http_method.clone(),
+      _ => return None,
+    };
+    let path_node_index = self
+      .graph
+      .neighbors_directed(method_node_index, petgraph::Direction::Outgoing)
+      .next()?;
+    match self.graph.node_weight(path_node_index)? {
+      Node::PathComponent(path_id, _) => Some((path_id.clone(), http_method)),
+      _ => None,
+    }
+  }
+
+  /// The absolute path of a path component, with path parameters like `{todoId}`
+  pub fn get_absolute_path(&self, path_id: &PathComponentId) -> Option<String> {
+    let mut path_node_index = *self.get_path_component_node_index(path_id)?;
+    let mut segments = vec![];
+    while let Some(parent_node_index) = self
+      .graph
+      .neighbors_directed(path_node_index, petgraph::Direction::Outgoing)
+      .next()
+    {
+      match self.graph.node_weight(path_node_index)? {
+        Node::PathComponent(_, descriptor) if descriptor.is_parameter => {
+          segments.push(format!("{{{}}}", descriptor.name))
+        }
+        Node::PathComponent(_, descriptor) => segments.push(descriptor.name.clone()),
+        _ => return None,
+      }
+      path_node_index = parent_node_index;
+    }
+
+    segments.reverse();
+    Some(format!("/{}", segments.join("/")))
+  }
 }
 
 impl Default for EndpointProjection {
@@ -306,6 +668,8 @@ impl Default for EndpointProjection {
     EndpointProjection {
       graph,
       node_id_to_index,
+      request_parameters: HashMap::new(),
+      path_parameter_shapes: HashMap::new(),
     }
   }
 }
@@ -362,10 +726,73 @@ impl AggregateEvent<EndpointProjection> for EndpointEvent {
           e.body_descriptor.shape_id,
         );
       }
-      _ => eprintln!(
-        "Ignoring applying event of type '{}' for EndpointProjection",
-        self.event_type()
-      ),
+      EndpointEvent::RequestParameterAddedByPathAndMethod(e) => {
+        aggregate.with_request_parameter(
+          e.parameter_id,
+          e.path_id,
+          e.http_method,
+          e.parameter_location,
+          e.name,
+        );
+      }
+      EndpointEvent::RequestParameterRenamed(e) => {
+        aggregate.with_request_parameter_name(e.parameter_id, e.name);
+      }
+      EndpointEvent::RequestParameterShapeSet(e) => {
+        let shape_id = if e.parameter_descriptor.is_removed {
+          None
+        } else {
+          Some(e.parameter_descriptor.shape_id)
+        };
+        aggregate.with_request_parameter_shape(e.parameter_id, shape_id);
+      }
+      EndpointEvent::RequestParameterShapeUnset(e) => {
+        aggregate.with_request_parameter_shape(e.parameter_id, None);
+      }
+      EndpointEvent::RequestParameterRemoved(e) => {
+        aggregate.without_request_parameter(e.parameter_id);
+      }
+      EndpointEvent::PathComponentRenamed(e) => {
+        aggregate.with_path_component_name(e.path_id, e.name);
+      }
+      EndpointEvent::PathParameterRenamed(e) => {
+        aggregate.with_path_component_name(e.path_id, e.name);
+      }
+      EndpointEvent::PathParameterShapeSet(e) => {
+        let shape_id = if e.shape_descriptor.is_removed {
+          None
+        } else {
+          Some(e.shape_descriptor.shape_id)
+        };
+        aggregate.with_path_parameter_shape(e.path_id, shape_id);
+      }
+      EndpointEvent::PathComponentRemoved(e) => {
+        aggregate.without_path_component(e.path_id);
+      }
+      EndpointEvent::PathParameterRemoved(e) => {
+        aggregate.without_path_component(e.path_id);
+      }
+      EndpointEvent::RequestContentTypeSet(e) => {
+        aggregate.with_request_content_type(e.request_id, e.http_content_type);
+      }
+      EndpointEvent::RequestBodyUnset(e) => {
+        aggregate.without_request_body(e.request_id);
+      }
+      EndpointEvent::RequestRemoved(e) => {
+        aggregate.without_request(e.request_id);
+      }
+      EndpointEvent::ResponseStatusCodeSet(e) => {
+        aggregate.with_response_status_code(e.response_id, e.http_status_code);
+      }
+      EndpointEvent::ResponseContentTypeSet(e) => {
+        aggregate.with_response_content_type(e.response_id, e.http_content_type);
+      }
+      EndpointEvent::ResponseBodyUnset(e) => {
+        aggregate.without_response_body(e.response_id);
+      }
+      EndpointEvent::ResponseRemoved(e) => {
+        aggregate.without_response(e.response_id);
+      }
     }
   }
 }
@@ -377,3 +804,106 @@ impl AggregateEvent<EndpointProjection> for SpecEvent {
     }
   }
 }
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use serde_json::json;
+
+  fn assert_node_indices(projection: &EndpointProjection) {
+    for (node_id, node_index) in &projection.node_id_to_index {
+      let node = projection
+        .graph
+        .node_weight(*node_index)
+        .expect("node ids should refer to existing nodes");
+      assert_eq!(node.node_id(), Some(node_id.as_str()));
+    }
+  }
+
+  #[test]
+  fn keeps_node_indices_up_to_date_when_removing_nodes() {
+    let events: Vec<EndpointEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded":{"pathId":"path_1","parentPathId":"root","name":"todos"}},
+      {"PathParameterAdded":{"pathId":"path_2","parentPathId":"path_1","name":"todoId"}},
+      {"RequestAdded":{"requestId":"request_1","pathId":"path_1","httpMethod":"GET"}},
+      {"ResponseAddedByPathAndMethod":{"responseId":"response_1","pathId":"path_1","httpMethod":"GET","httpStatusCode":200}},
+      {"RequestAdded":{"requestId":"request_2","pathId":"path_2","httpMethod":"GET"}},
+      {"ResponseAddedByPathAndMethod":{"responseId":"response_2","pathId":"path_2","httpMethod":"GET","httpStatusCode":200}},
+      {"RequestParameterAddedByPathAndMethod":{"parameterId":"parameter_1","pathId":"path_2","httpMethod":"GET","parameterLocation":"query","name":"fields"}},
+      {"PathComponentAdded":{"pathId":"path_3","parentPathId":"root","name":"users"}},
+      {"RequestAdded":{"requestId":"request_3","pathId":"path_3","httpMethod":"GET"}},
+      {"ResponseAddedByPathAndMethod":{"responseId":"response_3","pathId":"path_3","httpMethod":"GET","httpStatusCode":200}},
+      {"ResponseAddedByPathAndMethod":{"responseId":"response_4","pathId":"path_3","httpMethod":"GET","httpStatusCode":404}},
+      {"ResponseStatusCodeSet":{"responseId":"response_3","httpStatusCode":201}},
+      {"ResponseRemoved":{"responseId":"response_4"}},
+      {"PathParameterRemoved":{"pathId":"path_2","name":"todoId"}},
+      {"PathComponentRenamed":{"pathId":"path_3","name":"customers"}},
+    ]))
+    .expect("should deserialize endpoint events");
+
+    let projection = EndpointProjection::from(events);
+
+    assert_node_indices(&projection);
+    assert!(projection
+      .get_path_component_node_index(&String::from("path_2"))
+      .is_none());
+    assert!(projection
+      .get_request_node_index(&String::from("request_2"))
+      .is_none());
+    assert!(projection
+      .get_response_node_index(&String::from("response_4"))
+      .is_none());
+    assert!(projection.request_parameters.is_empty());
+
+    let status_codes = projection
+      .graph
+      .node_indices()
+      .filter_map(
+        |node_index| match projection.graph.node_weight(node_index) {
+          Some(Node::HttpStatusCode(status_code)) => Some(*status_code),
+          _ => None,
+        },
+      )
+      .collect::<Vec<_>>();
+    assert_eq!(status_codes.len(), 2, "empty status code nodes are removed");
+    assert!(status_codes.contains(&200));
+    assert!(status_codes.contains(&201));
+
+    let users_node_index = *projection
+      .get_path_component_node_index(&String::from("path_3"))
+      .unwrap();
+    match projection.graph.node_weight(users_node_index) {
+      Some(Node::PathComponent(_, descriptor)) => assert_eq!(descriptor.name, "customers"),
+      _ => panic!("expected path component node"),
+    }
+  }
+
+  #[test]
+  fn can_resolve_absolute_paths_of_requests_and_responses() {
+    let events: Vec<EndpointEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded":{"pathId":"path_1","parentPathId":"root","name":"todos"}},
+      {"PathParameterAdded":{"pathId":"path_2","parentPathId":"path_1","name":"todoId"}},
+      {"RequestAdded":{"requestId":"request_1","pathId":"path_2","httpMethod":"PUT"}},
+      {"ResponseAddedByPathAndMethod":{"responseId":"response_1","pathId":"path_2","httpMethod":"PUT","httpStatusCode":200}},
+    ]))
+    .expect("should deserialize endpoint events");
+
+    let projection = EndpointProjection::from(events);
+
+    assert_eq!(
+      projection.get_absolute_path(&String::from("root")),
+      Some(String::from("/"))
+    );
+    assert_eq!(
+      projection.get_absolute_path(&String::from("path_2")),
+      Some(String::from("/todos/{todoId}"))
+    );
+    assert_eq!(
+      projection.get_response_path_and_method(&String::from("response_1")),
+      Some((String::from("path_2"), String::from("PUT")))
+    );
+    assert!(projection
+      .get_absolute_path(&String::from("request_1"))
+      .is_none());
+  }
+}
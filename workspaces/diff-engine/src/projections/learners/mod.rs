@@ -1,2 +1,3 @@
 pub mod shape_diff_affordances;
 pub mod undocumented_bodies;
+pub mod undocumented_parameters;
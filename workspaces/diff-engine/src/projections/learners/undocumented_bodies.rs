@@ -4,7 +4,7 @@ use std::collections::HashMap;
 
 use crate::commands::{EndpointCommand, SpecCommand};
 use crate::interactions::{BodyAnalysisLocation, BodyAnalysisResult};
-use crate::learn_shape::TrailObservationsResult;
+use crate::learn_shape::{ShapeLearningConfig, TrailObservationsResult};
 use crate::state::SpecIdGenerator;
 use crate::JsonTrail;
 
@@ -26,11 +26,19 @@ impl LearnedUndocumentedBodiesProjection {
   pub fn into_endpoint_bodies(
     self,
     id_generator: &mut impl SpecIdGenerator,
+  ) -> impl Iterator<Item = EndpointBodies> {
+    self.into_endpoint_bodies_with_config(id_generator, &ShapeLearningConfig::default())
+  }
+
+  pub fn into_endpoint_bodies_with_config(
+    self,
+    id_generator: &mut impl SpecIdGenerator,
+    config: &ShapeLearningConfig,
   ) -> impl Iterator<Item = EndpointBodies> {
     let mut endpoints_by_endpoint = HashMap::new();
     for (body_location, observations) in self.observations_by_body_location {
       let (root_shape_id, body_commands) =
-        observations.into_commands(id_generator, &JsonTrail::empty());
+        observations.into_commands_with_config(id_generator, &JsonTrail::empty(), config);
       let endpoint_body =
         EndpointBody::new(&body_location, root_shape_id, body_commands, id_generator);
 
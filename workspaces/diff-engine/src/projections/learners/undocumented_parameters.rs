@@ -0,0 +1,333 @@
+use cqrs_core::{Aggregate, AggregateEvent, Event};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::commands::{EndpointCommand, ShapeCommand, SpecCommand};
+use crate::interactions::{ParameterAnalysisLocation, ParameterAnalysisResult};
+use crate::learn_shape::TrailObservationsResult;
+use crate::projections::endpoint::{HEADER_PARAMETER_LOCATION, QUERY_PARAMETER_LOCATION};
+use crate::state::shape::ShapeKind;
+use crate::state::SpecIdGenerator;
+use crate::JsonTrail;
+
+#[derive(Default, Debug)]
+pub struct LearnedUndocumentedParametersProjection {
+  observations_by_location: BTreeMap<ParameterAnalysisLocation, ParameterObservations>,
+}
+
+#[derive(Default, Debug)]
+struct ParameterObservations {
+  interactions_count: usize,
+  observations_by_name: BTreeMap<String, (usize, TrailObservationsResult)>,
+}
+
+impl LearnedUndocumentedParametersProjection {
+  fn with_parameter_analysis_result(&mut self, analysis: ParameterAnalysisResult) {
+    let existing_observations = self
+      .observations_by_location
+      .entry(analysis.parameter_location)
+      .or_default();
+
+    existing_observations.interactions_count += 1;
+    for (name, trail_observations) in analysis.observations_by_name {
+      let (observed_count, observations) = existing_observations
+        .observations_by_name
+        .entry(name)
+        .or_insert_with(|| (0, TrailObservationsResult::default()));
+
+      *observed_count += 1;
+      observations.union(trail_observations.normalized());
+    }
+  }
+
+  pub fn into_endpoint_parameters(
+    self,
+    id_generator: &mut impl SpecIdGenerator,
+  ) -> impl Iterator<Item = EndpointParameters> {
+    let mut parameters_by_endpoint = BTreeMap::new();
+    for (parameter_location, observations) in self.observations_by_location {
+      let endpoint_parameters = parameters_by_endpoint
+        .entry((
+          parameter_location.path_id.clone(),
+          parameter_location.method.clone(),
+        ))
+        .or_insert_with(|| {
+          EndpointParameters::new(
+            parameter_location.path_id.clone(),
+            parameter_location.method.clone(),
+          )
+        });
+
+      let interactions_count = observations.interactions_count;
+      for (name, (observed_count, trail_observations)) in observations.observations_by_name {
+        // parameters that weren't observed in every interaction of an operation are optional
+        let is_optional = observed_count < interactions_count;
+        let endpoint_parameter = EndpointParameter::new(
+          &parameter_location,
+          name,
+          is_optional,
+          trail_observations,
+          id_generator,
+        );
+
+        if let Some(endpoint_parameter) = endpoint_parameter {
+          endpoint_parameters.push(&parameter_location, endpoint_parameter);
+        }
+      }
+    }
+
+    parameters_by_endpoint
+      .into_values()
+      .filter(|endpoint_parameters| !endpoint_parameters.is_empty())
+  }
+}
+
+impl Aggregate for LearnedUndocumentedParametersProjection {
+  fn aggregate_type() -> &'static str {
+    "learned_undocumented_parameters"
+  }
+}
+
+impl Event for ParameterAnalysisResult {
+  fn event_type(&self) -> &'static str {
+    "parameter_analysis_result"
+  }
+}
+
+impl AggregateEvent<LearnedUndocumentedParametersProjection> for ParameterAnalysisResult {
+  fn apply_to(self, aggregate: &mut LearnedUndocumentedParametersProjection) {
+    aggregate.with_parameter_analysis_result(self)
+  }
+}
+
+// Output structs
+// --------------
+
+#[derive(Default, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointParameters {
+  path_id: String,
+  method: String,
+  query_parameters: Vec<EndpointParameter>,
+  headers: Vec<EndpointParameter>,
+}
+
+impl EndpointParameters {
+  pub fn new(path_id: String, method: String) -> Self {
+    Self {
+      path_id,
+      method,
+      query_parameters: vec![],
+      headers: vec![],
+    }
+  }
+
+  fn push(&mut self, parameter_location: &ParameterAnalysisLocation, parameter: EndpointParameter) {
+    match parameter_location.location.as_str() {
+      QUERY_PARAMETER_LOCATION => self.query_parameters.push(parameter),
+      HEADER_PARAMETER_LOCATION => self.headers.push(parameter),
+      location => unreachable!(
+        "analyzing undocumented parameters should only yield query parameters and headers, not {}",
+        location
+      ),
+    }
+  }
+
+  fn is_empty(&self) -> bool {
+    self.query_parameters.is_empty() && self.headers.is_empty()
+  }
+}
+
+#[derive(Default, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointParameter {
+  name: String,
+  is_optional: bool,
+  parameter_id: String,
+  shape_id: String,
+  commands: Vec<SpecCommand>,
+}
+
+impl EndpointParameter {
+  fn new(
+    parameter_location: &ParameterAnalysisLocation,
+    name: String,
+    is_optional: bool,
+    trail_observations: TrailObservationsResult,
+    ids: &mut impl SpecIdGenerator,
+  ) -> Option<Self> {
+    let (value_shape_id, shape_commands) =
+      trail_observations.into_commands(ids, &JsonTrail::empty());
+    let value_shape_id = value_shape_id?;
+    let mut commands = shape_commands.collect::<Vec<_>>();
+
+    let shape_id = if is_optional {
+      let optional_shape_id = ids.shape();
+      let parameter_id = ShapeKind::OptionalKind
+        .get_parameter_descriptor()
+        .unwrap()
+        .shape_parameter_id;
+      commands.push(SpecCommand::from(ShapeCommand::add_shape(
+        optional_shape_id.clone(),
+        ShapeKind::OptionalKind,
+        String::from(""),
+      )));
+      commands.push(SpecCommand::from(ShapeCommand::set_parameter_shape(
+        optional_shape_id.clone(),
+        String::from(parameter_id),
+        value_shape_id,
+      )));
+      optional_shape_id
+    } else {
+      value_shape_id
+    };
+
+    let parameter_id = ids.request_parameter();
+    let request_id = parameter_location.request_id.clone();
+    let (add_command, set_shape_command) = match parameter_location.location.as_str() {
+      QUERY_PARAMETER_LOCATION => (
+        EndpointCommand::add_query_parameter(parameter_id.clone(), request_id, name.clone()),
+        EndpointCommand::set_query_parameter_shape(parameter_id.clone(), shape_id.clone()),
+      ),
+      HEADER_PARAMETER_LOCATION => (
+        EndpointCommand::add_header_parameter(parameter_id.clone(), request_id, name.clone()),
+        EndpointCommand::set_header_parameter_shape(parameter_id.clone(), shape_id.clone()),
+      ),
+      location => unreachable!(
+        "analyzing undocumented parameters should only yield query parameters and headers, not {}",
+        location
+      ),
+    };
+    commands.push(SpecCommand::from(add_command));
+    commands.push(SpecCommand::from(set_shape_command));
+
+    Some(Self {
+      name,
+      is_optional,
+      parameter_id,
+      shape_id,
+      commands,
+    })
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::events::SpecEvent;
+  use crate::interactions::{analyze_undocumented_parameters, diff};
+  use crate::projections::SpecProjection;
+  use crate::spec::append_batch;
+  use crate::{CommandContext, HttpInteraction};
+  use insta::assert_json_snapshot;
+  use serde_json::json;
+
+  #[derive(Debug, Default)]
+  struct TestIdGenerator {
+    count: usize,
+  }
+
+  impl SpecIdGenerator for TestIdGenerator {
+    fn generate_id(&mut self, prefix: &str) -> String {
+      self.count += 1;
+      format!("{}{}", prefix, self.count)
+    }
+  }
+
+  fn interaction(query: &str, headers: serde_json::Value) -> HttpInteraction {
+    serde_json::from_value(json!({
+      "uuid": "1",
+      "request": {
+        "host": "localhost",
+        "method": "GET",
+        "path": "/todos",
+        "query": { "asJsonString": null, "asText": query, "shapeHashV1Base64": null },
+        "headers": { "asJsonString": headers.to_string(), "asText": null, "shapeHashV1Base64": null },
+        "body": {
+          "contentType": null,
+          "value": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null }
+        }
+      },
+      "response": {
+        "statusCode": 200,
+        "headers": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null },
+        "body": {
+          "contentType": null,
+          "value": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null }
+        }
+      },
+      "tags": []
+    }))
+    .expect("should be a valid interaction")
+  }
+
+  #[test]
+  fn undocumented_parameters_can_be_learned_from_interactions() {
+    let spec_events: Vec<SpecEvent> = serde_json::from_value(json!([
+      {"PathComponentAdded":{"pathId":"path_1","parentPathId":"root","name":"todos"}},
+      {"RequestAdded":{"requestId":"request_1","pathId":"path_1","httpMethod":"GET"}},
+      {"ResponseAddedByPathAndMethod":{"responseId":"response_1","pathId":"path_1","httpMethod":"GET","httpStatusCode":200}},
+    ]))
+    .expect("initial spec events should be valid events");
+    let spec_projection = SpecProjection::from(spec_events.clone());
+
+    let interactions = vec![
+      interaction("limit=10&status=open", json!({ "X-Api-Key": "secret" })),
+      interaction(
+        "status=done&tags=a&tags=b",
+        json!({ "x-api-key": "secret" }),
+      ),
+    ];
+
+    let mut projection = LearnedUndocumentedParametersProjection::default();
+    for interaction in interactions.clone() {
+      for analysis in analyze_undocumented_parameters(&spec_projection, interaction) {
+        projection.apply(analysis);
+      }
+    }
+
+    let endpoint_parameters = projection
+      .into_endpoint_parameters(&mut TestIdGenerator::default())
+      .collect::<Vec<_>>();
+    assert_eq!(endpoint_parameters.len(), 1);
+    assert_json_snapshot!(
+      "undocumented_parameters_can_be_learned_from_interactions__endpoint_parameters",
+      endpoint_parameters
+    );
+
+    let mut batch = append_batch(
+      spec_projection,
+      String::from("learn parameters"),
+      CommandContext::new(
+        String::from("batch_1"),
+        String::from("client_1"),
+        String::from("session_1"),
+        chrono::Utc::now(),
+      ),
+    );
+    for endpoint in endpoint_parameters {
+      for parameter in endpoint
+        .query_parameters
+        .into_iter()
+        .chain(endpoint.headers)
+      {
+        for command in parameter.commands {
+          batch
+            .with_command(command)
+            .expect("learned commands should be valid for the spec");
+        }
+      }
+    }
+    let learned_spec_projection = SpecProjection::from(
+      spec_events
+        .into_iter()
+        .chain(batch.commit())
+        .collect::<Vec<_>>(),
+    );
+
+    for interaction in interactions {
+      let results = diff(&learned_spec_projection, interaction);
+      assert_eq!(results.len(), 0, "{:?}", results);
+    }
+  }
+}
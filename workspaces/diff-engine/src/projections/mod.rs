@@ -1,3 +1,4 @@
+pub mod changelog;
 pub mod conflicts;
 pub mod contributions;
 pub mod endpoint;
@@ -7,6 +8,9 @@ pub mod shape;
 pub mod spec_events;
 pub mod spectacle;
 
+pub use changelog::{
+  ChangedEndpoint, ChangedShape, ChangelogChanges, ChangelogEntry, ChangelogProjection,
+};
 pub use conflicts::ConflictsProjection;
 pub use contributions::ContributionsProjection;
 pub use endpoint::{EndpointProjection, ResponseBodyDescriptor};
@@ -14,6 +18,7 @@ pub use history::{CommitId, HistoryProjection};
 pub use learners::{
   shape_diff_affordances::LearnedShapeDiffAffordancesProjection,
   undocumented_bodies::LearnedUndocumentedBodiesProjection,
+  undocumented_parameters::LearnedUndocumentedParametersProjection,
 };
 pub use shape::ShapeProjection;
 pub use spec_events::{SpecAssemblerError, SpecAssemblerProjection};
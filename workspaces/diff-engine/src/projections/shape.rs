@@ -6,16 +6,17 @@ use crate::shapes::ShapeTrail;
 use crate::state::shape::{
   FieldId, FieldShapeDescriptor, ParameterShapeDescriptor, ProviderDescriptor, ShapeId, ShapeIdRef,
   ShapeKind, ShapeKindDescriptor, ShapeParameterId, ShapeParameterIdRef, ShapeParametersDescriptor,
+  StringFormat,
 };
 use crate::RfcEvent;
 use cqrs_core::{Aggregate, AggregateEvent, Event};
 use petgraph::graph::{Graph, NodeIndex};
 use petgraph::visit::EdgeRef;
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::iter::FromIterator;
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, PartialEq)]
 #[serde(tag = "type", content = "data")]
 pub enum Node {
   CoreShape(CoreShapeNode),
@@ -25,38 +26,50 @@ pub enum Node {
   BatchCommit(BatchCommitNode),
 }
 
-#[derive(Debug, Clone, Serialize)]
+impl Node {
+  pub(crate) fn node_id(&self) -> &str {
+    match self {
+      Node::CoreShape(node) => &node.shape_id,
+      Node::Shape(node) => &node.shape_id,
+      Node::Field(node) => &node.field_id,
+      Node::ShapeParameter(node) => &node.parameter_id,
+      Node::BatchCommit(node) => &node.batch_id,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ShapeNode {
   pub shape_id: ShapeId,
 }
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct CoreShapeNode {
   pub shape_id: ShapeId,
   pub descriptor: CoreShapeNodeDescriptor,
 }
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct FieldNode {
   pub field_id: FieldId,
   pub descriptor: FieldNodeDescriptor,
 }
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ShapeParameterNode {
   pub parameter_id: ShapeParameterId,
   pub descriptor: ShapeParameterNodeDescriptor,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct BatchCommitNode {
   batch_id: String,
   created_at: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, PartialEq)]
 #[serde(tag = "type", content = "data")]
 pub enum Edge {
   BelongsTo,
@@ -68,7 +81,7 @@ pub enum Edge {
   UpdatedIn,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ShapeParameterBinding {
   pub shape_id: ShapeId,
@@ -76,15 +89,15 @@ pub struct ShapeParameterBinding {
 
 pub type NodeId = String;
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct CoreShapeNodeDescriptor {
   pub kind: ShapeKind,
 }
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, PartialEq)]
 pub struct ShapeParameterNodeDescriptor {}
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct FieldNodeDescriptor {
   pub name: String,
@@ -93,7 +106,15 @@ pub struct FieldNodeDescriptor {
 #[derive(Debug, Clone)]
 pub struct ShapeProjection {
   pub graph: Graph<Node, Edge>,
+  // node indices aren't stable when removing nodes from the graph, so only remove them through
+  // `remove_nodes`, which keeps this map up to date
   pub node_id_to_index: HashMap<NodeId, petgraph::graph::NodeIndex>,
+  pub shape_names: HashMap<ShapeId, String>,
+  pub shape_parameter_names: HashMap<ShapeParameterId, String>,
+  pub string_formats: HashMap<ShapeId, StringFormat>,
+  pub string_enums: HashMap<ShapeId, Vec<String>>,
+  // removed shapes stay in the graph, as fields, shape parameters and bodies can still refer to them
+  pub removed_shapes: HashSet<ShapeId>,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -146,6 +167,14 @@ pub struct ArrayChoice {
   item_shape_id: ShapeId,
 }
 
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MapChoice {
+  json_type: JsonType,
+  shape_id: ShapeId,
+  value_shape_id: ShapeId,
+}
+
 #[derive(Debug, Serialize, Clone)]
 #[serde(tag = "type")]
 pub enum ShapeChoice {
@@ -154,6 +183,7 @@ pub enum ShapeChoice {
   Array(ArrayChoice),
   Any,
   Unknown,
+  Map(MapChoice),
 }
 pub type ShapeChoiceMapping = BTreeMap<ShapeId, Vec<ShapeChoice>>;
 impl From<&ShapeProjection> for ShapeChoiceMapping {
@@ -219,7 +249,23 @@ impl From<&ShapeProjection> for ShapeChoiceMapping {
                 };
                 ShapeChoice::Array(output)
               }
-              ShapeKind::MapKind => unimplemented!(),
+              ShapeKind::MapKind => {
+                let shape_parameter_id = &String::from(
+                  choice
+                    .core_shape_kind
+                    .get_parameter_descriptor()
+                    .expect("expected $map to have a parameter descriptor")
+                    .shape_parameter_id,
+                );
+                let map_value_shape_id =
+                  queries.resolve_parameter_to_shape(&choice.shape_id, shape_parameter_id);
+                let output = MapChoice {
+                  shape_id: choice.shape_id.clone(),
+                  json_type: JsonType::Object,
+                  value_shape_id: map_value_shape_id,
+                };
+                ShapeChoice::Map(output)
+              }
               ShapeKind::OneOfKind => unreachable!(),
               ShapeKind::AnyKind => ShapeChoice::Any,
               ShapeKind::UnknownKind => ShapeChoice::Unknown,
@@ -227,7 +273,7 @@ impl From<&ShapeProjection> for ShapeChoiceMapping {
                 shape_id: choice.shape_id.clone(),
                 json_type: JsonType::String,
               }),
-              ShapeKind::NumberKind => Primitive(PrimitiveChoice {
+              ShapeKind::NumberKind | ShapeKind::IntegerKind => Primitive(PrimitiveChoice {
                 shape_id: choice.shape_id.clone(),
                 json_type: JsonType::Number,
               }),
@@ -298,13 +344,24 @@ impl Default for ShapeProjection {
   fn default() -> Self {
     let graph: Graph<Node, Edge> = Graph::new();
     let node_id_to_index = HashMap::new();
+    let shape_names = HashMap::new();
+    let shape_parameter_names = HashMap::new();
+    let string_formats = HashMap::new();
+    let string_enums = HashMap::new();
+    let removed_shapes = HashSet::new();
     let mut projection = ShapeProjection {
       graph,
       node_id_to_index,
+      shape_names,
+      shape_parameter_names,
+      string_formats,
+      string_enums,
+      removed_shapes,
     };
 
     add_core_shape_to_projection(&mut projection, ShapeKind::StringKind);
     add_core_shape_to_projection(&mut projection, ShapeKind::NumberKind);
+    add_core_shape_to_projection(&mut projection, ShapeKind::IntegerKind);
     add_core_shape_to_projection(&mut projection, ShapeKind::BooleanKind);
     add_core_shape_to_projection(&mut projection, ShapeKind::ListKind);
     //@TODO: incomplete
@@ -313,6 +370,7 @@ impl Default for ShapeProjection {
     add_core_shape_to_projection(&mut projection, ShapeKind::UnknownKind);
     add_core_shape_to_projection(&mut projection, ShapeKind::OptionalKind);
     add_core_shape_to_projection(&mut projection, ShapeKind::OneOfKind);
+    add_core_shape_to_projection(&mut projection, ShapeKind::MapKind);
     projection
   }
 }
@@ -390,7 +448,15 @@ impl ShapeProjection {
     }
   }
   ////////////////////////////////////////////////////////////////////////////////
-  pub fn with_shape_parameter(&mut self, shape_parameter_id: ShapeParameterId, shape_id: ShapeId) {
+  pub fn with_shape_parameter(
+    &mut self,
+    shape_parameter_id: ShapeParameterId,
+    shape_id: ShapeId,
+    name: String,
+  ) {
+    self
+      .shape_parameter_names
+      .insert(shape_parameter_id.clone(), name);
     let shape_node_index = *self.get_shape_node_index(&shape_id).unwrap();
     let shape_parameter_node = Node::ShapeParameter(ShapeParameterNode {
       parameter_id: shape_parameter_id.clone(),
@@ -414,11 +480,23 @@ impl ShapeProjection {
     parameters: ShapeParametersDescriptor,
     name: String,
   ) {
-    let shape_node = Node::Shape(ShapeNode {
-      shape_id: shape_id.clone(),
-    });
-    let shape_node_index = self.graph.add_node(shape_node);
-    self.node_id_to_index.insert(shape_id, shape_node_index);
+    // the id of a removed shape can be used again, with the new shape taking over the references
+    // to the removed one
+    let shape_node_index = if self.removed_shapes.remove(&shape_id) {
+      self.without_removed_shape_descriptors(&shape_id)
+    } else {
+      let shape_node = Node::Shape(ShapeNode {
+        shape_id: shape_id.clone(),
+      });
+      let shape_node_index = self.graph.add_node(shape_node);
+      self
+        .node_id_to_index
+        .insert(shape_id.clone(), shape_node_index);
+      shape_node_index
+    };
+    if !name.is_empty() {
+      self.shape_names.insert(shape_id, name);
+    }
 
     let base_shape_node_index = self
       .node_id_to_index
@@ -437,6 +515,20 @@ impl ShapeProjection {
     );
   }
 
+  pub fn with_string_format(&mut self, shape_id: ShapeId, format: Option<StringFormat>) {
+    match format {
+      Some(format) => self.string_formats.insert(shape_id, format),
+      None => self.string_formats.remove(&shape_id),
+    };
+  }
+
+  pub fn with_string_enum(&mut self, shape_id: ShapeId, values: Option<Vec<String>>) {
+    match values {
+      Some(values) => self.string_enums.insert(shape_id, values),
+      None => self.string_enums.remove(&shape_id),
+    };
+  }
+
   pub fn with_base_shape(&mut self, shape_id: ShapeId, base_shape_id: ShapeId) {
     let shape_node_index = *self.node_id_to_index.get(&shape_id).unwrap_or_else(|| {
       panic!(
@@ -476,6 +568,134 @@ impl ShapeProjection {
     );
   }
 
+  pub fn with_shape_name(&mut self, shape_id: ShapeId, name: String) {
+    if name.is_empty() {
+      self.shape_names.remove(&shape_id);
+    } else {
+      self.shape_names.insert(shape_id, name);
+    }
+  }
+
+  pub fn with_shape_parameter_name(&mut self, shape_parameter_id: ShapeParameterId, name: String) {
+    if self
+      .get_shape_parameter_node_index(&shape_parameter_id)
+      .is_some()
+    {
+      self.shape_parameter_names.insert(shape_parameter_id, name);
+    }
+  }
+
+  pub fn with_field_name(&mut self, field_id: FieldId, name: String) {
+    let field_node_index = match self.get_field_node_index(&field_id) {
+      Some(node_index) => *node_index,
+      None => return,
+    };
+    if let Some(Node::Field(field_node)) = self.graph.node_weight_mut(field_node_index) {
+      field_node.descriptor.name = name;
+    }
+  }
+
+  // Like the Scala projection, removed shapes are only marked as removed, so whatever still refers
+  // to them keeps resolving. Core shapes can't be removed.
+  pub fn with_shape_removed(&mut self, shape_id: ShapeId) {
+    let shape_node_index = match self.node_id_to_index.get(&shape_id) {
+      Some(node_index) => *node_index,
+      None => return,
+    };
+    if matches!(
+      self.graph.node_weight(shape_node_index),
+      Some(Node::Shape(_))
+    ) {
+      self.removed_shapes.insert(shape_id);
+    }
+  }
+
+  pub fn is_removed_shape(&self, shape_id: &ShapeId) -> bool {
+    self.removed_shapes.contains(shape_id)
+  }
+
+  // Removes everything describing a removed shape: its fields and parameters, its name, format and
+  // enum, and its edges to its base shape, bindings and history. What refers to the shape is kept.
+  fn without_removed_shape_descriptors(&mut self, shape_id: &ShapeId) -> NodeIndex {
+    let shape_node_index = self.node_id_to_index[shape_id];
+    let removed_node_indices = self
+      .graph
+      .edges_directed(shape_node_index, petgraph::Direction::Incoming)
+      .filter(|edge| matches!(edge.weight(), Edge::IsFieldOf | Edge::IsParameterOf))
+      .map(|edge| edge.source())
+      .collect::<Vec<_>>();
+    for node_index in &removed_node_indices {
+      if let Some(Node::ShapeParameter(parameter_node)) = self.graph.node_weight(*node_index) {
+        self
+          .shape_parameter_names
+          .remove(&parameter_node.parameter_id);
+      }
+    }
+    self.shape_names.remove(shape_id);
+    self.string_formats.remove(shape_id);
+    self.string_enums.remove(shape_id);
+    self.remove_nodes(removed_node_indices);
+
+    // removing nodes can move the shape's node to another index
+    let shape_node_index = self.node_id_to_index[shape_id];
+    while let Some(edge_index) = self
+      .graph
+      .first_edge(shape_node_index, petgraph::Direction::Outgoing)
+    {
+      self.graph.remove_edge(edge_index);
+    }
+    shape_node_index
+  }
+
+  pub fn without_shape_parameter(&mut self, shape_parameter_id: ShapeParameterId) {
+    if let Some(node_index) = self.get_shape_parameter_node_index(&shape_parameter_id) {
+      let node_index = *node_index;
+      self.shape_parameter_names.remove(&shape_parameter_id);
+      self.remove_nodes(vec![node_index]);
+    }
+  }
+
+  pub fn without_field(&mut self, field_id: FieldId) {
+    if let Some(node_index) = self.get_field_node_index(&field_id) {
+      let node_index = *node_index;
+      self.remove_nodes(vec![node_index]);
+    }
+  }
+
+  // Removing a node from the graph moves the last node into its place, so the index of that node
+  // has to be updated. Removing the highest indices first keeps the indices of the nodes still to
+  // be removed valid.
+  fn remove_nodes(&mut self, mut node_indices: Vec<NodeIndex>) {
+    node_indices.sort_unstable();
+    node_indices.dedup();
+    for node_index in node_indices.into_iter().rev() {
+      let removed_node = match self.graph.remove_node(node_index) {
+        Some(node) => node,
+        None => continue,
+      };
+      self.node_id_to_index.remove(removed_node.node_id());
+      if let Some(moved_node) = self.graph.node_weight(node_index) {
+        self
+          .node_id_to_index
+          .insert(String::from(moved_node.node_id()), node_index);
+      }
+    }
+  }
+
+  pub fn get_owner_shape_id(&self, node_id: &NodeId) -> Option<ShapeId> {
+    let node_index = self.node_id_to_index.get(node_id)?;
+    let owner_node_index = self
+      .graph
+      .edges_directed(*node_index, petgraph::Direction::Outgoing)
+      .find(|edge| matches!(edge.weight(), Edge::IsFieldOf | Edge::IsParameterOf))?
+      .target();
+    match self.graph.node_weight(owner_node_index)? {
+      Node::Shape(shape_node) => Some(shape_node.shape_id.clone()),
+      Node::CoreShape(shape_node) => Some(shape_node.shape_id.clone()),
+      _ => None,
+    }
+  }
+
   pub fn get_ancestor_shape_node_index(&self, parent_node_index: &NodeIndex) -> Option<NodeIndex> {
     let mut edges = self
       .graph
@@ -765,7 +985,7 @@ impl AggregateEvent<ShapeProjection> for ShapeEvent {
         }
       }
       ShapeEvent::ShapeParameterAdded(e) => {
-        projection.with_shape_parameter(e.shape_parameter_id.clone(), e.shape_id);
+        projection.with_shape_parameter(e.shape_parameter_id.clone(), e.shape_id, e.name);
         if let Some(c) = e.event_context {
           projection.with_creation_history(&c.client_command_batch_id, &e.shape_parameter_id);
         }
@@ -801,9 +1021,59 @@ impl AggregateEvent<ShapeProjection> for ShapeEvent {
           projection.with_update_history(&c.client_command_batch_id, &e.shape_id);
         }
       }
-      x => {
-        //dbg!("skipping ShapeEvent in ShapeProjection. warning?");
-        //dbg!(&x);
+      ShapeEvent::StringFormatSet(e) => {
+        projection.with_string_format(e.shape_id.clone(), e.format);
+
+        if let Some(c) = e.event_context {
+          projection.with_update_history(&c.client_command_batch_id, &e.shape_id);
+        }
+      }
+      ShapeEvent::StringEnumSet(e) => {
+        projection.with_string_enum(e.shape_id.clone(), e.values);
+
+        if let Some(c) = e.event_context {
+          projection.with_update_history(&c.client_command_batch_id, &e.shape_id);
+        }
+      }
+      ShapeEvent::ShapeRenamed(e) => {
+        projection.with_shape_name(e.shape_id.clone(), e.name);
+
+        if let Some(c) = e.event_context {
+          projection.with_update_history(&c.client_command_batch_id, &e.shape_id);
+        }
+      }
+      ShapeEvent::ShapeRemoved(e) => {
+        projection.with_shape_removed(e.shape_id);
+      }
+      ShapeEvent::ShapeParameterRenamed(e) => {
+        projection.with_shape_parameter_name(e.shape_parameter_id.clone(), e.name);
+
+        if let Some(c) = e.event_context {
+          projection.with_update_history(&c.client_command_batch_id, &e.shape_parameter_id);
+        }
+      }
+      ShapeEvent::ShapeParameterRemoved(e) => {
+        let shape_id = projection.get_owner_shape_id(&e.shape_parameter_id);
+        projection.without_shape_parameter(e.shape_parameter_id);
+
+        if let (Some(c), Some(shape_id)) = (e.event_context, shape_id) {
+          projection.with_update_history(&c.client_command_batch_id, &shape_id);
+        }
+      }
+      ShapeEvent::FieldRenamed(e) => {
+        projection.with_field_name(e.field_id.clone(), e.name);
+
+        if let Some(c) = e.event_context {
+          projection.with_update_history(&c.client_command_batch_id, &e.field_id);
+        }
+      }
+      ShapeEvent::FieldRemoved(e) => {
+        let shape_id = projection.get_owner_shape_id(&e.field_id);
+        projection.without_field(e.field_id);
+
+        if let (Some(c), Some(shape_id)) = (e.event_context, shape_id) {
+          projection.with_update_history(&c.client_command_batch_id, &shape_id);
+        }
       }
     }
   }
@@ -845,4 +1115,54 @@ mod test {
     let mut projection = ShapeProjection::default();
     projection.apply(event);
   }
+
+  #[test]
+  fn keeps_node_indices_up_to_date_when_removing_nodes() {
+    let events: Vec<ShapeEvent> = serde_json::from_value(serde_json::json!([
+      {"ShapeAdded":{"shapeId":"object_1","baseShapeId":"$object","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":"Person"}},
+      {"ShapeAdded":{"shapeId":"string_1","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"FieldAdded":{"fieldId":"field_1","shapeId":"object_1","name":"firstName","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_1","shapeId":"string_1"}}}},
+      {"ShapeAdded":{"shapeId":"object_2","baseShapeId":"$object","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"FieldAdded":{"fieldId":"field_2","shapeId":"object_2","name":"street","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_2","shapeId":"string_1"}}}},
+      {"FieldAdded":{"fieldId":"field_3","shapeId":"object_1","name":"address","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_3","shapeId":"object_2"}}}},
+      {"ShapeAdded":{"shapeId":"number_1","baseShapeId":"$number","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"FieldAdded":{"fieldId":"field_4","shapeId":"object_1","name":"age","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_4","shapeId":"number_1"}}}},
+      {"FieldRemoved":{"fieldId":"field_3"}},
+      {"ShapeRemoved":{"shapeId":"object_2"}},
+      {"FieldRenamed":{"fieldId":"field_1","name":"name"}},
+      {"ShapeRenamed":{"shapeId":"object_1","name":"Customer"}},
+    ]))
+    .expect("should deserialize shape events");
+
+    let projection = ShapeProjection::from(events);
+
+    assert_eq!(
+      projection.node_id_to_index.len(),
+      projection.graph.node_count()
+    );
+    for (node_id, node_index) in &projection.node_id_to_index {
+      let node = projection
+        .graph
+        .node_weight(*node_index)
+        .expect("node ids should refer to existing nodes");
+      assert_eq!(node.node_id(), node_id);
+    }
+    assert!(projection
+      .get_field_node_index(&String::from("field_3"))
+      .is_none());
+    // removed shapes are only marked as removed, keeping their fields
+    assert!(projection.is_removed_shape(&String::from("object_2")));
+    assert!(projection
+      .get_field_node_index(&String::from("field_2"))
+      .is_some());
+
+    let field_names = ShapeQueries::new(&projection)
+      .resolve_shape_field_id_and_names(&String::from("object_1"))
+      .map(|(_, name)| name.clone())
+      .collect::<Vec<_>>();
+    assert_eq!(field_names.len(), 2);
+    assert!(field_names.contains(&String::from("name")));
+    assert!(field_names.contains(&String::from("age")));
+    assert_eq!(projection.shape_names.get("object_1").unwrap(), "Customer");
+  }
 }
@@ -1,8 +1,12 @@
 use crate::projections::endpoint::{Edge, EndpointProjection, Node, ROOT_PATH_ID};
-use crate::projections::endpoint::{RequestBodyDescriptor, ResponseBodyDescriptor};
+use crate::projections::endpoint::{
+  RequestBodyDescriptor, RequestParameterDescriptor, ResponseBodyDescriptor,
+};
 use crate::state::endpoint::{
-  HttpMethod, HttpStatusCode, PathComponentId, PathComponentIdRef, RequestId, ResponseId,
+  HttpMethod, HttpStatusCode, PathComponentId, PathComponentIdRef, RequestId, RequestParameterId,
+  ResponseId,
 };
+use crate::state::media_type::{match_content_types, MediaTypeSpecificity};
 use crate::HttpInteraction;
 use petgraph::graph::Graph;
 use petgraph::visit::EdgeFilteredNeighborsDirected;
@@ -155,15 +159,35 @@ impl<'a> EndpointQueries<'a> {
     method: &'a String,
     content_type: Option<&'a String>,
   ) -> Option<(&RequestId, &RequestBodyDescriptor)> {
-    self.resolve_requests(path_id, method).and_then(|mut it| {
-      it.find(|(id, body)| match content_type {
-        Some(content_type) => match body.body {
-          Some(ref body) => body.http_content_type.eq(content_type),
-          None => false,
-        },
-        None => body.body.is_none(),
+    let mut requests = self.resolve_requests(path_id, method)?;
+    match content_type {
+      Some(content_type) => most_specific_match(requests, |(_, request)| {
+        let body = request.body.as_ref()?;
+        match_content_types(&body.http_content_type, content_type)
+      }),
+      None => requests.find(|(_, request)| request.body.is_none()),
+    }
+  }
+
+  pub fn resolve_request_parameters(
+    &self,
+    path_id: PathComponentIdRef<'a>,
+    method: &'a str,
+    location: &'a str,
+  ) -> impl Iterator<Item = (&RequestParameterId, &RequestParameterDescriptor)> {
+    let mut parameters = self
+      .endpoint_projection
+      .request_parameters
+      .iter()
+      .filter(move |(_, descriptor)| {
+        descriptor.path_id == path_id
+          && descriptor.http_method == method
+          && descriptor.location == location
       })
-    })
+      .collect::<Vec<_>>();
+    // parameters are kept in a map, sort them for results in a stable order
+    parameters.sort_by_key(|(id, _)| *id);
+    parameters.into_iter()
   }
 
   pub fn resolve_responses(
@@ -185,15 +209,15 @@ impl<'a> EndpointQueries<'a> {
     status_code: u16,
     content_type: Option<&'a String>,
   ) -> Option<(&ResponseId, &ResponseBodyDescriptor)> {
-    self
-      .resolve_responses_by_method_and_status_code(method, status_code, path_id)
-      .find(|(id, body)| match content_type {
-        Some(content_type) => match body.body {
-          Some(ref body) => body.http_content_type.eq(content_type),
-          None => false,
-        },
-        None => body.body.is_none(),
-      })
+    let mut responses =
+      self.resolve_responses_by_method_and_status_code(method, status_code, path_id);
+    match content_type {
+      Some(content_type) => most_specific_match(responses, |(_, response)| {
+        let body = response.body.as_ref()?;
+        match_content_types(&body.http_content_type, content_type)
+      }),
+      None => responses.find(|(_, response)| response.body.is_none()),
+    }
   }
 
   pub fn resolve_responses_by_method_and_status_code(
@@ -290,6 +314,23 @@ impl<'a> EndpointQueries<'a> {
   }
 }
 
+// the first of the candidates with the most specifically matching media type
+fn most_specific_match<T>(
+  candidates: impl Iterator<Item = T>,
+  specificity: impl Fn(&T) -> Option<MediaTypeSpecificity>,
+) -> Option<T> {
+  candidates
+    .filter_map(|candidate| Some((specificity(&candidate)?, candidate)))
+    .fold(
+      None,
+      |best_match, (candidate_specificity, candidate)| match best_match {
+        Some((best_specificity, _)) if best_specificity >= candidate_specificity => best_match,
+        _ => Some((candidate_specificity, candidate)),
+      },
+    )
+    .map(|(_, candidate)| candidate)
+}
+
 #[cfg(test)]
 mod test {
   use super::*;
@@ -1,7 +1,7 @@
 use crate::projections::shape::{CoreShapeNode, Edge, Node};
 use crate::projections::shape::{FieldNode, FieldNodeDescriptor, ShapeNode, ShapeProjection};
 use crate::shapes::traverser::{ShapeTrail, ShapeTrailPathComponent};
-use crate::state::shape::{FieldId, ShapeId, ShapeKind, ShapeParameterId};
+use crate::state::shape::{FieldId, ShapeId, ShapeKind, ShapeParameterId, StringFormat};
 use petgraph::visit::EdgeRef;
 
 pub struct ShapeQueries<'a> {
@@ -83,6 +83,8 @@ impl<'a> ShapeQueries<'a> {
               }],
               shape_id: shape_id.clone(),
               core_shape_kind: core_shape_node.descriptor.kind.clone(),
+              string_format: None,
+              string_enum: None,
             }];
             output.append(&mut self.list_trail_choices(&trail));
             output
@@ -111,6 +113,8 @@ impl<'a> ShapeQueries<'a> {
               }],
               shape_id: shape_id.clone(),
               core_shape_kind: core_shape_node.descriptor.kind.clone(),
+              string_format: None,
+              string_enum: None,
             }];
             output.append(&mut self.list_trail_choices(&trail));
             output
@@ -138,6 +142,8 @@ impl<'a> ShapeQueries<'a> {
             additional_components: vec![],
             shape_id: shape_id.clone(),
             core_shape_kind: core_shape_node.descriptor.kind.clone(),
+            string_format: self.resolve_string_format(&shape_id),
+            string_enum: self.resolve_string_enum(&shape_id).cloned(),
           }],
         };
         trails
@@ -249,10 +255,24 @@ impl<'a> ShapeQueries<'a> {
         },
         _ => unreachable!("should only receive ObjectFieldTrail relative to ObjectKind"),
       },
+      ShapeKind::MapKind => match path_component {
+        ShapeTrailPathComponent::MapValueTrail {
+          map_shape_id,
+          value_shape_id,
+        } => ResolvedTrail {
+          shape_id: value_shape_id.clone(),
+          core_shape_kind: self.resolve_to_core_shape(value_shape_id),
+        },
+        _ => unreachable!("should only receive MapValueTrail relative to MapKind"),
+      },
       ShapeKind::NumberKind => ResolvedTrail {
         shape_id: parent.shape_id.clone(),
         core_shape_kind: &ShapeKind::NumberKind,
       },
+      ShapeKind::IntegerKind => ResolvedTrail {
+        shape_id: parent.shape_id.clone(),
+        core_shape_kind: &ShapeKind::IntegerKind,
+      },
       ShapeKind::BooleanKind => ResolvedTrail {
         shape_id: parent.shape_id.clone(),
         core_shape_kind: &ShapeKind::BooleanKind,
@@ -405,6 +425,47 @@ impl<'a> ShapeQueries<'a> {
       })
   }
 
+  pub fn resolve_shape_name(&self, shape_id: &ShapeId) -> Option<&String> {
+    self.shape_projection.shape_names.get(shape_id)
+  }
+
+  // formats are inherited by shapes aliasing a formatted string shape
+  pub fn resolve_string_format(&self, shape_id: &ShapeId) -> Option<StringFormat> {
+    let mut current_shape_id = shape_id;
+    loop {
+      if let Some(format) = self.shape_projection.string_formats.get(current_shape_id) {
+        return Some(*format);
+      }
+      current_shape_id = self.resolve_base_shape_id(current_shape_id)?;
+    }
+  }
+
+  pub fn resolve_string_enum(&self, shape_id: &ShapeId) -> Option<&Vec<String>> {
+    let mut current_shape_id = shape_id;
+    loop {
+      if let Some(values) = self.shape_projection.string_enums.get(current_shape_id) {
+        return Some(values);
+      }
+      current_shape_id = self.resolve_base_shape_id(current_shape_id)?;
+    }
+  }
+
+  // shapes can descend from other (non-core) shapes, effectively aliasing them
+  pub fn resolve_base_shape_id(&self, shape_id: &ShapeId) -> Option<&ShapeId> {
+    let projection = &self.shape_projection;
+
+    let shape_node_index = *projection.get_shape_node_index(shape_id)?;
+    let base_shape_node_index = projection.get_ancestor_shape_node_index(&shape_node_index)?;
+    if base_shape_node_index == shape_node_index {
+      return None;
+    }
+
+    match projection.graph.node_weight(base_shape_node_index) {
+      Some(Node::Shape(ShapeNode { shape_id })) => Some(shape_id),
+      _ => None,
+    }
+  }
+
   pub fn resolve_shape_field_id_and_names(
     &self,
     shape_id: &ShapeId,
@@ -434,6 +495,8 @@ pub struct ChoiceOutput {
   pub additional_components: Vec<ShapeTrailPathComponent>,
   pub shape_id: ShapeId,
   pub core_shape_kind: ShapeKind,
+  pub string_format: Option<StringFormat>,
+  pub string_enum: Option<Vec<String>>,
 }
 
 impl ChoiceOutput {
@@ -9,6 +9,7 @@ use crate::projections::shape::ShapeProjection;
 use crate::queries::shape::ShapeQueries;
 use crate::state::shape::ShapeId;
 use crate::InteractionDiffResult;
+pub(crate) use result::shape_kind_name;
 pub use result::ShapeDiffResult;
 use std::collections::HashMap;
 pub use traverser::{JsonTrail, JsonTrailPathComponent, ShapeTrail, ShapeTrailPathComponent};
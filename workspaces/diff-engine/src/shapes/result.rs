@@ -1,23 +1,69 @@
-use crate::shapes::{JsonTrail, ShapeTrail};
+use crate::shapes::{JsonTrail, JsonTrailPathComponent, ShapeTrail};
+use crate::state::body::BodyKind;
+use crate::state::shape::ShapeKind;
 use serde::{Deserialize, Serialize};
 use std::collections::hash_map::DefaultHasher;
+use std::fmt;
 use std::hash::{Hash, Hasher};
 
+// Besides the trails, results explain themselves with the kinds expected and observed and the
+// name of the field they concern. These explanations don't contribute to the fingerprint.
 #[derive(Debug, Deserialize, Serialize)]
 pub enum ShapeDiffResult {
   #[serde(rename_all = "camelCase")]
   UnspecifiedShape {
     json_trail: JsonTrail,
     shape_trail: ShapeTrail,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    observed_kind: Option<BodyKind>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    field_name: Option<String>,
   },
   #[serde(rename_all = "camelCase")]
   UnmatchedShape {
     json_trail: JsonTrail,
     shape_trail: ShapeTrail,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    expected_kinds: Vec<ShapeKind>,
+    // none when a required field is missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    observed_kind: Option<BodyKind>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    field_name: Option<String>,
   },
 }
 
 impl ShapeDiffResult {
+  pub fn unspecified(
+    json_trail: JsonTrail,
+    shape_trail: ShapeTrail,
+    observed_kind: BodyKind,
+  ) -> Self {
+    let field_name = field_name(&json_trail, &shape_trail);
+    ShapeDiffResult::UnspecifiedShape {
+      json_trail,
+      shape_trail,
+      observed_kind: Some(observed_kind),
+      field_name,
+    }
+  }
+
+  pub fn unmatched(
+    json_trail: JsonTrail,
+    shape_trail: ShapeTrail,
+    expected_kinds: Vec<ShapeKind>,
+    observed_kind: Option<BodyKind>,
+  ) -> Self {
+    let field_name = field_name(&json_trail, &shape_trail);
+    ShapeDiffResult::UnmatchedShape {
+      json_trail,
+      shape_trail,
+      expected_kinds,
+      observed_kind,
+      field_name,
+    }
+  }
+
   pub fn fingerprint(&self) -> String {
     let mut hash_state = DefaultHasher::new();
     Hash::hash(self, &mut hash_state);
@@ -25,23 +71,95 @@ impl ShapeDiffResult {
   }
 }
 
+// keys of map values are data rather than the names of fields
+fn field_name(json_trail: &JsonTrail, shape_trail: &ShapeTrail) -> Option<String> {
+  if json_trail.ends_in_map_key(shape_trail) {
+    return None;
+  }
+  match json_trail.last_component() {
+    Some(JsonTrailPathComponent::JsonObjectKey { key }) => Some(key.clone()),
+    _ => None,
+  }
+}
+
+// how shape kinds are named in explanations, using the JSON names where there are any
+pub(crate) fn shape_kind_name(shape_kind: &ShapeKind) -> String {
+  match shape_kind {
+    ShapeKind::ListKind => String::from("array"),
+    ShapeKind::NullableKind => String::from("null"),
+    shape_kind => shape_kind.get_descriptor().name.to_lowercase(),
+  }
+}
+
+impl fmt::Display for ShapeDiffResult {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let json_trail = match self {
+      ShapeDiffResult::UnspecifiedShape { json_trail, .. } => json_trail,
+      ShapeDiffResult::UnmatchedShape { json_trail, .. } => json_trail,
+    };
+    if !json_trail.is_empty() {
+      write!(f, "`{}`: ", json_trail)?;
+    }
+
+    match self {
+      ShapeDiffResult::UnspecifiedShape { observed_kind, .. } => {
+        write!(f, "undocumented")?;
+        if let Some(observed_kind) = observed_kind {
+          write!(f, ", observed {}", observed_kind.name())?;
+        }
+      }
+      ShapeDiffResult::UnmatchedShape {
+        expected_kinds,
+        observed_kind,
+        field_name,
+        ..
+      } => {
+        if observed_kind.is_none() && field_name.is_some() {
+          write!(f, "missing required field")?;
+        } else {
+          write!(f, "unmatched shape")?;
+        }
+        if !expected_kinds.is_empty() {
+          let expected_names = expected_kinds
+            .iter()
+            .map(shape_kind_name)
+            .collect::<Vec<_>>();
+          write!(f, ", expected {}", expected_names.join(" or "))?;
+        }
+        if let Some(observed_kind) = observed_kind {
+          write!(f, ", observed {}", observed_kind.name())?;
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
 impl Hash for ShapeDiffResult {
   fn hash<H: Hasher>(&self, hash_state: &mut H) {
     match self {
       ShapeDiffResult::UnspecifiedShape {
         json_trail,
         shape_trail,
+        ..
       } => {
         Hash::hash(&core::mem::discriminant(self), hash_state);
-        Hash::hash(&json_trail.normalized(), hash_state);
+        Hash::hash(
+          &json_trail.normalized().without_map_keys(shape_trail),
+          hash_state,
+        );
         Hash::hash(shape_trail, hash_state);
       }
       ShapeDiffResult::UnmatchedShape {
         json_trail,
         shape_trail,
+        ..
       } => {
         Hash::hash(&core::mem::discriminant(self), hash_state);
-        Hash::hash(&json_trail.normalized(), hash_state);
+        Hash::hash(
+          &json_trail.normalized().without_map_keys(shape_trail),
+          hash_state,
+        );
         Hash::hash(shape_trail, hash_state);
       }
     }
@@ -51,60 +169,50 @@ impl Hash for ShapeDiffResult {
 #[cfg(test)]
 mod test {
   use super::*;
+  use crate::shapes::ShapeTrailPathComponent;
   use insta::assert_debug_snapshot;
 
   #[test]
   fn can_produce_stable_fingerprint() {
     let shape_diffs = vec![
-      ShapeDiffResult::UnspecifiedShape {
-        json_trail: JsonTrail::empty().with_array_item(0),
-        shape_trail: ShapeTrail {
-          root_shape_id: String::from("some-shape-id"),
-          path: vec![],
-        },
-      },
-      ShapeDiffResult::UnspecifiedShape {
-        json_trail: JsonTrail::empty().with_array_item(1),
-        shape_trail: ShapeTrail {
-          root_shape_id: String::from("some-shape-id"),
-          path: vec![],
-        },
-      },
-      ShapeDiffResult::UnspecifiedShape {
-        json_trail: JsonTrail::empty().with_array_item(2),
-        shape_trail: ShapeTrail {
-          root_shape_id: String::from("some-shape-id"),
-          path: vec![],
-        },
-      },
-      ShapeDiffResult::UnspecifiedShape {
-        json_trail: JsonTrail::empty().with_array_item(0),
-        shape_trail: ShapeTrail {
-          root_shape_id: String::from("other-shape-id"),
-          path: vec![],
-        },
-      },
-      ShapeDiffResult::UnmatchedShape {
-        json_trail: JsonTrail::empty().with_array_item(0),
-        shape_trail: ShapeTrail {
-          root_shape_id: String::from("some-shape-id"),
-          path: vec![],
-        },
-      },
-      ShapeDiffResult::UnmatchedShape {
-        json_trail: JsonTrail::empty().with_array_item(2),
-        shape_trail: ShapeTrail {
-          root_shape_id: String::from("some-shape-id"),
-          path: vec![],
-        },
-      },
-      ShapeDiffResult::UnmatchedShape {
-        json_trail: JsonTrail::empty().with_array_item(0),
-        shape_trail: ShapeTrail {
-          root_shape_id: String::from("other-shape-id"),
-          path: vec![],
-        },
-      },
+      ShapeDiffResult::unspecified(
+        JsonTrail::empty().with_array_item(0),
+        ShapeTrail::new(String::from("some-shape-id")),
+        BodyKind::String,
+      ),
+      ShapeDiffResult::unspecified(
+        JsonTrail::empty().with_array_item(1),
+        ShapeTrail::new(String::from("some-shape-id")),
+        BodyKind::String,
+      ),
+      ShapeDiffResult::unspecified(
+        JsonTrail::empty().with_array_item(2),
+        ShapeTrail::new(String::from("some-shape-id")),
+        BodyKind::String,
+      ),
+      ShapeDiffResult::unspecified(
+        JsonTrail::empty().with_array_item(0),
+        ShapeTrail::new(String::from("other-shape-id")),
+        BodyKind::String,
+      ),
+      ShapeDiffResult::unmatched(
+        JsonTrail::empty().with_array_item(0),
+        ShapeTrail::new(String::from("some-shape-id")),
+        vec![ShapeKind::NumberKind],
+        Some(BodyKind::String),
+      ),
+      ShapeDiffResult::unmatched(
+        JsonTrail::empty().with_array_item(2),
+        ShapeTrail::new(String::from("some-shape-id")),
+        vec![ShapeKind::NumberKind],
+        Some(BodyKind::String),
+      ),
+      ShapeDiffResult::unmatched(
+        JsonTrail::empty().with_array_item(0),
+        ShapeTrail::new(String::from("other-shape-id")),
+        vec![ShapeKind::NumberKind],
+        Some(BodyKind::String),
+      ),
     ];
 
     let fingerprints = shape_diffs
@@ -138,4 +246,101 @@ mod test {
     );
     assert_debug_snapshot!("can_produce_stable_fingerprint__fingerprints", fingerprints);
   }
+
+  #[test]
+  fn explanations_do_not_affect_fingerprint() {
+    let json_trail = JsonTrail::empty().with_object_key(String::from("age"));
+    let shape_trail = ShapeTrail::new(String::from("some-shape-id"));
+    let explained = ShapeDiffResult::unmatched(
+      json_trail.clone(),
+      shape_trail.clone(),
+      vec![ShapeKind::NumberKind],
+      Some(BodyKind::String),
+    );
+    let unexplained: ShapeDiffResult = serde_json::from_value(serde_json::json!({
+      "UnmatchedShape": {
+        "jsonTrail": json_trail,
+        "shapeTrail": shape_trail,
+      }
+    }))
+    .expect("shape diff results without explanations should deserialize");
+
+    assert_eq!(explained.fingerprint(), unexplained.fingerprint());
+  }
+
+  #[test]
+  fn can_explain_shape_diffs() {
+    let field_trail = JsonTrail::empty().with_object_key(String::from("order"));
+    let shape_trail = ShapeTrail::new(String::from("some-shape-id"));
+
+    let messages = [
+      ShapeDiffResult::unmatched(
+        field_trail
+          .with_object_key(String::from("items"))
+          .with_array_item(1)
+          .with_object_key(String::from("price")),
+        shape_trail.clone(),
+        vec![ShapeKind::NumberKind, ShapeKind::NullableKind],
+        Some(BodyKind::String),
+      ),
+      ShapeDiffResult::unmatched(
+        field_trail.with_object_key(String::from("customer")),
+        shape_trail.clone(),
+        vec![ShapeKind::ObjectKind],
+        None,
+      ),
+      ShapeDiffResult::unspecified(
+        field_trail.with_object_key(String::from("nickname")),
+        shape_trail.clone(),
+        BodyKind::String,
+      ),
+      ShapeDiffResult::unmatched(
+        JsonTrail::empty(),
+        shape_trail,
+        vec![ShapeKind::IntegerKind],
+        Some(BodyKind::Number),
+      ),
+    ]
+    .iter()
+    .map(|shape_diff| shape_diff.to_string())
+    .collect::<Vec<_>>();
+
+    assert_eq!(
+      messages,
+      vec![
+        "`order.items[1].price`: unmatched shape, expected number or null, observed string",
+        "`order.customer`: missing required field, expected object",
+        "`order.nickname`: undocumented, observed string",
+        "unmatched shape, expected integer, observed number",
+      ]
+    );
+  }
+
+  #[test]
+  fn map_keys_are_not_field_names() {
+    let json_trail = JsonTrail::empty()
+      .with_object_key(String::from("scores"))
+      .with_object_key(String::from("alice"));
+    let shape_trail = ShapeTrail::new(String::from("some-shape-id"))
+      .with_component(ShapeTrailPathComponent::ObjectFieldTrail {
+        field_id: String::from("scores-field-id"),
+        field_shape_id: String::from("scores-shape-id"),
+      })
+      .with_component(ShapeTrailPathComponent::MapValueTrail {
+        map_shape_id: String::from("scores-shape-id"),
+        value_shape_id: String::from("score-shape-id"),
+      });
+
+    let missing_value =
+      ShapeDiffResult::unmatched(json_trail, shape_trail, vec![ShapeKind::NumberKind], None);
+
+    match &missing_value {
+      ShapeDiffResult::UnmatchedShape { field_name, .. } => assert!(field_name.is_none()),
+      _ => unreachable!("expected an unmatched shape"),
+    }
+    assert_eq!(
+      missing_value.to_string(),
+      "`scores.alice`: unmatched shape, expected number"
+    );
+  }
 }
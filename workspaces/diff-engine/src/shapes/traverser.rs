@@ -7,6 +7,7 @@ use crate::state::shape::{FieldId, ShapeId, ShapeKind, ShapeParameterId};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use std::cmp::Ordering;
+use std::fmt;
 use std::hash::{Hash, Hasher};
 
 pub struct Traverser<'a> {
@@ -121,16 +122,20 @@ impl<'a> Traverser<'a> {
           _ => unreachable!("expect body to be an object"),
         };
 
+        // maps don't describe which keys to expect, so only objects are checked for missing keys
         let object_key_choices = matching_choices
           .iter()
-          .map(|choice| {
+          .filter_map(|choice| {
+            if let ShapeKind::MapKind = &choice.core_shape_kind {
+              return None;
+            }
             if let ShapeKind::ObjectKind = &choice.core_shape_kind {
               // - find field node by key in object's field node edges
               let field_ids = self
                 .shape_queries
                 .resolve_shape_field_id_and_names(&choice.shape_id);
 
-              (
+              Some((
                 choice,
                 field_ids
                   .map(|(field_id, field_name)| {
@@ -148,9 +153,9 @@ impl<'a> Traverser<'a> {
                     )
                   })
                   .collect::<Vec<_>>(),
-              )
+              ))
             } else {
-              unreachable!("should only contain choices of object kind");
+              unreachable!("should only contain choices of object or map kind");
             }
           })
           .collect::<Vec<_>>();
@@ -192,8 +197,28 @@ impl<'a> Traverser<'a> {
                       field_shape_id: field_shape_id,
                     });
                 self.shape_queries.list_trail_choices(&field_trail)
+              } else if let ShapeKind::MapKind = &choice.core_shape_kind {
+                // every value of a map is described by the same shape, regardless of its key
+                let value_shape_id = self.shape_queries.resolve_parameter_to_shape(
+                  &choice.shape_id,
+                  &String::from(
+                    choice
+                      .core_shape_kind
+                      .get_parameter_descriptor()
+                      .unwrap()
+                      .shape_parameter_id,
+                  ),
+                );
+                let value_trail =
+                  choice
+                    .shape_trail()
+                    .with_component(ShapeTrailPathComponent::MapValueTrail {
+                      map_shape_id: choice.shape_id.clone(),
+                      value_shape_id,
+                    });
+                self.shape_queries.list_trail_choices(&value_trail)
               } else {
-                unreachable!("should only contain choices of object kind");
+                unreachable!("should only contain choices of object or map kind");
               }
             })
             .collect::<Vec<ChoiceOutput>>();
@@ -263,6 +288,11 @@ pub enum ShapeTrailPathComponent {
   },
   #[serde(rename_all = "camelCase")]
   UnknownTrail {},
+  #[serde(rename_all = "camelCase")]
+  MapValueTrail {
+    map_shape_id: ShapeId,
+    value_shape_id: ShapeId,
+  },
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, Hash)]
@@ -356,9 +386,104 @@ impl JsonTrail {
     self.path.len() == (parent_trail.path.len() + 1) && self.is_descendant_of(parent_trail)
   }
 
+  /// For trails descending from an object at the ancestor trail, returns the trail with the key
+  /// of that object replaced.
+  pub fn with_ancestor_object_key(&self, ancestor_trail: &JsonTrail, key: &str) -> Option<Self> {
+    if !self.is_descendant_of(ancestor_trail) {
+      return None;
+    }
+
+    let mut new_trail = self.clone();
+    match new_trail.path.get_mut(ancestor_trail.path.len()) {
+      Some(JsonTrailPathComponent::JsonObjectKey { key: ancestor_key }) => {
+        *ancestor_key = String::from(key);
+        Some(new_trail)
+      }
+      _ => None,
+    }
+  }
+
   pub fn last_component(&self) -> Option<&JsonTrailPathComponent> {
     self.path.last()
   }
+
+  /// Returns the trail without the keys that map values were observed at, following the shape trail
+  /// of the same value. Maps describe all their values by the same shape, so unlike field names,
+  /// their keys don't tell where a value is described.
+  pub fn without_map_keys(&self, shape_trail: &ShapeTrail) -> Self {
+    let map_key_positions = self.map_key_positions(shape_trail);
+    Self {
+      path: self
+        .path
+        .iter()
+        .enumerate()
+        .filter(|(position, _)| !map_key_positions.contains(position))
+        .map(|(_, component)| component.clone())
+        .collect(),
+    }
+  }
+
+  /// Whether the last component of the trail is the key of a map value, following the shape trail
+  /// of the same value
+  pub fn ends_in_map_key(&self, shape_trail: &ShapeTrail) -> bool {
+    match self.path.len() {
+      0 => false,
+      length => self.map_key_positions(shape_trail).contains(&(length - 1)),
+    }
+  }
+
+  // Object keys and array items step into values just like fields, list items and map values do
+  // in the shape trail, so pairing them up in order tells which keys are the keys of map values.
+  fn map_key_positions(&self, shape_trail: &ShapeTrail) -> Vec<usize> {
+    let json_steps = self.path.iter().enumerate().filter(|(_, component)| {
+      matches!(
+        component,
+        JsonTrailPathComponent::JsonObjectKey { .. } | JsonTrailPathComponent::JsonArrayItem { .. }
+      )
+    });
+    let shape_steps = shape_trail.path.iter().filter(|component| {
+      matches!(
+        component,
+        ShapeTrailPathComponent::ObjectFieldTrail { .. }
+          | ShapeTrailPathComponent::ListItemTrail { .. }
+          | ShapeTrailPathComponent::MapValueTrail { .. }
+      )
+    });
+
+    json_steps
+      .zip(shape_steps)
+      .filter_map(|((position, json_component), shape_component)| {
+        match (json_component, shape_component) {
+          (
+            JsonTrailPathComponent::JsonObjectKey { .. },
+            ShapeTrailPathComponent::MapValueTrail { .. },
+          ) => Some(position),
+          _ => None,
+        }
+      })
+      .collect()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.path.is_empty()
+  }
+}
+
+// Formats as a path into the JSON value, like `items[0].name`
+impl fmt::Display for JsonTrail {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut is_first = true;
+    for component in &self.path {
+      match component {
+        JsonTrailPathComponent::JsonObjectKey { key } if is_first => write!(f, "{}", key)?,
+        JsonTrailPathComponent::JsonObjectKey { key } => write!(f, ".{}", key)?,
+        JsonTrailPathComponent::JsonArrayItem { index } => write!(f, "[{}]", index)?,
+        JsonTrailPathComponent::JsonObject {} | JsonTrailPathComponent::JsonArray {} => continue,
+      }
+      is_first = false;
+    }
+    Ok(())
+  }
 }
 
 impl PartialEq for JsonTrail {
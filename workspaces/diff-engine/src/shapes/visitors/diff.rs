@@ -52,6 +52,17 @@ impl BodyVisitors<ShapeDiffResult> for DiffVisitors {
   }
 }
 
+// the kinds of all choices at a trail, as expected by unmatched shape diffs
+fn expected_kinds(trail_choices: &[ChoiceOutput]) -> Vec<ShapeKind> {
+  let mut expected_kinds: Vec<ShapeKind> = vec![];
+  for choice in trail_choices {
+    if !expected_kinds.contains(&choice.core_shape_kind) {
+      expected_kinds.push(choice.core_shape_kind.clone());
+    }
+  }
+  expected_kinds
+}
+
 // Primitive visitor
 // -----------------
 
@@ -82,10 +93,11 @@ impl BodyPrimitiveVisitor<ShapeDiffResult> for DiffPrimitiveVisitor {
     trail_choices: &Vec<ChoiceOutput>,
   ) {
     if trail_choices.is_empty() {
-      self.results.push(ShapeDiffResult::UnspecifiedShape {
+      self.results.push(ShapeDiffResult::unspecified(
         json_trail,
-        shape_trail: trail_origin,
-      });
+        trail_origin,
+        body.kind(),
+      ));
       return;
     }
 
@@ -95,12 +107,31 @@ impl BodyPrimitiveVisitor<ShapeDiffResult> for DiffPrimitiveVisitor {
           ShapeKind::BooleanKind => true,
           _ => false,
         },
-        BodyDescriptor::Number => match choice.core_shape_kind {
+        BodyDescriptor::Number(number_descriptor) => match choice.core_shape_kind {
           ShapeKind::NumberKind => true,
+          // only observed values can be told apart as integers
+          ShapeKind::IntegerKind => match number_descriptor {
+            Some(observed) => observed.is_integer,
+            None => true,
+          },
           _ => false,
         },
-        BodyDescriptor::String => match choice.core_shape_kind {
-          ShapeKind::StringKind => true,
+        BodyDescriptor::String(string_descriptor) => match choice.core_shape_kind {
+          // only observed values can be checked against a documented format or enum
+          ShapeKind::StringKind => match string_descriptor {
+            Some(observed) => {
+              let format_matches = match &choice.string_format {
+                Some(format) => observed.format.as_ref() == Some(format),
+                None => true,
+              };
+              let enum_matches = match &choice.string_enum {
+                Some(members) => observed.values.iter().all(|value| members.contains(value)),
+                None => true,
+              };
+              format_matches && enum_matches
+            }
+            None => true,
+          },
           _ => false,
         },
         BodyDescriptor::Null => match choice.core_shape_kind {
@@ -110,11 +141,14 @@ impl BodyPrimitiveVisitor<ShapeDiffResult> for DiffPrimitiveVisitor {
         _ => unreachable!("should not call primitive visitor without a primitive value"),
       });
     if matched.is_empty() {
+      let expected_kinds = expected_kinds(trail_choices);
       unmatched.iter().for_each(|&choice| {
-        self.results.push(ShapeDiffResult::UnmatchedShape {
-          json_trail: json_trail.clone(),
-          shape_trail: choice.shape_trail(),
-        });
+        self.results.push(ShapeDiffResult::unmatched(
+          json_trail.clone(),
+          choice.shape_trail(),
+          expected_kinds.clone(),
+          Some(body.kind()),
+        ));
       });
     }
   }
@@ -150,10 +184,11 @@ impl BodyArrayVisitor<ShapeDiffResult> for DiffArrayVisitor {
     trail_choices: &Vec<ChoiceOutput>,
   ) -> Vec<ChoiceOutput> {
     if trail_choices.is_empty() {
-      self.results.push(ShapeDiffResult::UnspecifiedShape {
-        json_trail: json_trail.clone(),
-        shape_trail: trail_origin.clone(),
-      });
+      self.results.push(ShapeDiffResult::unspecified(
+        json_trail.clone(),
+        trail_origin.clone(),
+        body.kind(),
+      ));
       return vec![];
     }
 
@@ -167,11 +202,14 @@ impl BodyArrayVisitor<ShapeDiffResult> for DiffArrayVisitor {
       });
 
     if matched.is_empty() {
+      let expected_kinds = expected_kinds(trail_choices);
       unmatched.into_iter().for_each(|choice| {
-        self.results.push(ShapeDiffResult::UnmatchedShape {
-          json_trail: json_trail.clone(),
-          shape_trail: choice.shape_trail(),
-        });
+        self.results.push(ShapeDiffResult::unmatched(
+          json_trail.clone(),
+          choice.shape_trail(),
+          expected_kinds.clone(),
+          Some(body.kind()),
+        ));
       });
     }
 
@@ -209,28 +247,32 @@ impl BodyObjectVisitor<ShapeDiffResult> for DiffObjectVisitor {
     trail_choices: &Vec<ChoiceOutput>,
   ) -> Vec<ChoiceOutput> {
     if trail_choices.is_empty() {
-      self.results.push(ShapeDiffResult::UnspecifiedShape {
-        json_trail: json_trail.clone(),
-        shape_trail: trail_origin.clone(),
-      });
+      self.results.push(ShapeDiffResult::unspecified(
+        json_trail.clone(),
+        trail_origin.clone(),
+        body.kind(),
+      ));
       return vec![];
     }
 
     let (matched, unmatched): (Vec<&ChoiceOutput>, Vec<&ChoiceOutput>) =
       trail_choices.into_iter().partition(|choice| match body {
         BodyDescriptor::Object(_) => match choice.core_shape_kind {
-          ShapeKind::ObjectKind => true,
+          ShapeKind::ObjectKind | ShapeKind::MapKind => true,
           _ => false,
         },
         _ => unreachable!("should only call object visitor for object body types"),
       });
 
     if matched.is_empty() {
+      let expected_kinds = expected_kinds(trail_choices);
       unmatched.into_iter().for_each(|choice| {
-        self.results.push(ShapeDiffResult::UnmatchedShape {
-          json_trail: json_trail.clone(),
-          shape_trail: choice.shape_trail(),
-        });
+        self.results.push(ShapeDiffResult::unmatched(
+          json_trail.clone(),
+          choice.shape_trail(),
+          expected_kinds.clone(),
+          Some(body.kind()),
+        ));
       });
     }
 
@@ -290,10 +332,9 @@ impl BodyObjectKeyVisitor<ShapeDiffResult> for DiffObjectKeyVisitor {
                   );
                   let json_trail = object_json_trail
                     .with_component(JsonTrailPathComponent::JsonObjectKey { key: key.clone() });
-                  let diff = ShapeDiffResult::UnmatchedShape {
-                    json_trail,
-                    shape_trail,
-                  };
+                  let expected_kinds = vec![(*field_core_shape_kind).clone()];
+                  let diff =
+                    ShapeDiffResult::unmatched(json_trail, shape_trail, expected_kinds, None);
                   //dbg!(&diff);
                   self.push(diff);
                 }
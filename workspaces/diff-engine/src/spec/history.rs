@@ -0,0 +1,124 @@
+use crate::events::{RfcEvent, SpecEvent};
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+/// A point in the history of a spec, marked by the end of a batch commit
+#[derive(Clone, Debug, PartialEq)]
+pub enum SpecHistoryPoint {
+  /// Right after the batch commit with this id ended
+  BatchCommit(String),
+  /// Right after the last batch commit that ended at or before this time
+  CreatedAt(DateTime<Utc>),
+}
+
+#[derive(Debug, Error)]
+pub enum SpecHistoryError {
+  #[error("no batch commit with id '{0}' was ended in the spec")]
+  UnknownBatchCommit(String),
+  #[error("no batch commit was ended in the spec at or before {0}")]
+  NoBatchCommitBefore(DateTime<Utc>),
+}
+
+/// The events of a spec up to a point in its history, so a `SpecProjection` can be built of the
+/// spec as it was at that point, like the version of it that shipped with an older release.
+pub fn events_until(
+  events: Vec<SpecEvent>,
+  history_point: &SpecHistoryPoint,
+) -> Result<Vec<SpecEvent>, SpecHistoryError> {
+  let mut batch_commits_ended =
+    events
+      .iter()
+      .enumerate()
+      .filter_map(|(index, event)| match event {
+        SpecEvent::RfcEvent(RfcEvent::BatchCommitEnded(ended)) => Some((index, ended)),
+        _ => None,
+      });
+
+  let end_index = match history_point {
+    SpecHistoryPoint::BatchCommit(batch_id) => batch_commits_ended
+      .find(|(_, ended)| ended.batch_id == *batch_id)
+      .map(|(index, _)| index)
+      .ok_or_else(|| SpecHistoryError::UnknownBatchCommit(batch_id.clone()))?,
+    SpecHistoryPoint::CreatedAt(time) => batch_commits_ended
+      .filter(|(_, ended)| {
+        // batch commits without a (valid) timestamp can't tell whether they were before
+        let created_at = ended
+          .event_context
+          .as_ref()
+          .and_then(|event_context| DateTime::parse_from_rfc3339(&event_context.created_at).ok());
+        matches!(created_at, Some(created_at) if created_at <= *time)
+      })
+      .map(|(index, _)| index)
+      .next_back()
+      .ok_or(SpecHistoryError::NoBatchCommitBefore(*time))?,
+  };
+
+  let mut events = events;
+  events.truncate(end_index + 1);
+  Ok(events)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::projections::SpecProjection;
+  use crate::queries::endpoint::EndpointQueries;
+  use serde_json::json;
+
+  fn spec_events() -> Vec<SpecEvent> {
+    serde_json::from_value(json!([
+      {"BatchCommitStarted": {"batchId": "batch_1","commitMessage": "Document todos","eventContext": {"clientId": "anonymous","clientSessionId": "session","clientCommandBatchId": "batch_1","createdAt": "2021-01-01T10:00:00Z"}}},
+      {"PathComponentAdded": {"pathId": "path_1","parentPathId": "root","name": "todos"}},
+      {"BatchCommitEnded": {"batchId": "batch_1","eventContext": {"clientId": "anonymous","clientSessionId": "session","clientCommandBatchId": "batch_1","createdAt": "2021-01-01T10:00:00Z"}}},
+      {"BatchCommitStarted": {"batchId": "batch_2","commitMessage": "Document users","eventContext": {"clientId": "anonymous","clientSessionId": "session","clientCommandBatchId": "batch_2","createdAt": "2021-02-01T10:00:00Z"}}},
+      {"PathComponentAdded": {"pathId": "path_2","parentPathId": "root","name": "users"}},
+      {"BatchCommitEnded": {"batchId": "batch_2","eventContext": {"clientId": "anonymous","clientSessionId": "session","clientCommandBatchId": "batch_2","createdAt": "2021-02-01T10:00:00Z"}}},
+    ]))
+    .expect("initial events should be valid spec events")
+  }
+
+  fn documents_path(events: Vec<SpecEvent>, path: &str) -> bool {
+    let spec_projection = SpecProjection::from(events);
+    let endpoint_queries = EndpointQueries::new(spec_projection.endpoint());
+    endpoint_queries.resolve_path(path).is_some()
+  }
+
+  #[test]
+  fn can_replay_events_until_batch_commit() {
+    let point = SpecHistoryPoint::BatchCommit(String::from("batch_1"));
+    let events = events_until(spec_events(), &point).unwrap();
+
+    assert_eq!(events.len(), 3);
+    assert!(documents_path(events.clone(), "/todos"));
+    assert!(!documents_path(events, "/users"));
+
+    let point = SpecHistoryPoint::BatchCommit(String::from("batch_3"));
+    assert!(matches!(
+      events_until(spec_events(), &point),
+      Err(SpecHistoryError::UnknownBatchCommit(_))
+    ));
+  }
+
+  #[test]
+  fn can_replay_events_until_time() {
+    let time = |time: &str| {
+      DateTime::parse_from_rfc3339(time)
+        .unwrap()
+        .with_timezone(&Utc)
+    };
+
+    let point = SpecHistoryPoint::CreatedAt(time("2021-01-15T00:00:00Z"));
+    let events = events_until(spec_events(), &point).unwrap();
+    assert_eq!(events.len(), 3);
+
+    let point = SpecHistoryPoint::CreatedAt(time("2021-02-01T10:00:00Z"));
+    let events = events_until(spec_events(), &point).unwrap();
+    assert!(documents_path(events, "/users"));
+
+    let point = SpecHistoryPoint::CreatedAt(time("2020-12-31T00:00:00Z"));
+    assert!(matches!(
+      events_until(spec_events(), &point),
+      Err(SpecHistoryError::NoBatchCommitBefore(_))
+    ));
+  }
+}
@@ -6,6 +6,10 @@ use crate::events::SpecEvent;
 use crate::projections::SpecProjection;
 use cqrs_core::Aggregate;
 
+mod history;
+
+pub use history::{events_until, SpecHistoryError, SpecHistoryPoint};
+
 /// Create an interface that allows commands to be applied to a spec as part of a single
 /// batch. Will produce events enclosed in `BatchCommitStarted` and `BatchCommitEnded`.
 pub fn append_batch(
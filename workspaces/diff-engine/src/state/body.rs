@@ -1,18 +1,181 @@
 use crate::shapehash;
+use crate::state::shape::StringFormat;
+use crate::state::xml::XmlElement;
+use serde::{Deserialize, Serialize};
 use serde_json::map::Map as JsonMap;
+use serde_json::Number as JsonNumber;
 use serde_json::Value as JsonValue;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
 
 #[derive(PartialEq, Clone, Debug, Hash, Eq)]
 pub enum BodyDescriptor {
   Object(ObjectDescriptor),
   Array(ItemsDescriptor),
+  String(Option<StringDescriptor>),
+  Number(Option<NumberDescriptor>),
+  Boolean,
+  Null,
+}
+
+// The kind of value a body descriptor describes, as reported when explaining diffs
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum BodyKind {
+  Object,
+  Array,
   String,
   Number,
+  Integer,
   Boolean,
   Null,
 }
 
+impl BodyKind {
+  pub fn name(&self) -> &'static str {
+    match self {
+      BodyKind::Object => "object",
+      BodyKind::Array => "array",
+      BodyKind::String => "string",
+      BodyKind::Number => "number",
+      BodyKind::Integer => "integer",
+      BodyKind::Boolean => "boolean",
+      BodyKind::Null => "null",
+    }
+  }
+}
+
+// Observations about a string value, only available when the value itself was captured
+// (as opposed to a shape hash, which only records that it was a string)
+#[derive(Clone, Debug)]
+pub struct StringDescriptor {
+  pub format: Option<StringFormat>,
+  // every value described, more than one when equally shaped array items were deduplicated
+  pub values: Vec<String>,
+}
+
+impl StringDescriptor {
+  fn merge_values(&mut self, other: StringDescriptor) {
+    self.values.extend(other.values);
+  }
+}
+
+// Values don't contribute to the identity of a string descriptor, so array items that only
+// differ by value are deduplicated into a single item
+impl PartialEq for StringDescriptor {
+  fn eq(&self, other: &Self) -> bool {
+    self.format == other.format
+  }
+}
+
+impl Eq for StringDescriptor {}
+
+impl Hash for StringDescriptor {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.format.hash(state);
+  }
+}
+
+impl From<String> for StringDescriptor {
+  fn from(value: String) -> Self {
+    Self {
+      format: StringFormat::detect(&value),
+      values: vec![value],
+    }
+  }
+}
+
+impl From<&str> for StringDescriptor {
+  fn from(value: &str) -> Self {
+    Self::from(String::from(value))
+  }
+}
+
+// Observations about a number value, only available when the value itself was captured
+#[derive(Clone, Debug)]
+pub struct NumberDescriptor {
+  pub is_integer: bool,
+  // range of every value described, wider when equally shaped array items were deduplicated
+  pub minimum: f64,
+  pub maximum: f64,
+}
+
+impl NumberDescriptor {
+  // describes a number parsed from text, such as a query parameter value
+  pub fn parse(value: &str) -> Option<Self> {
+    let number = value.parse::<f64>().ok()?;
+    Some(Self {
+      is_integer: value.parse::<i64>().is_ok() || value.parse::<u64>().is_ok(),
+      minimum: number,
+      maximum: number,
+    })
+  }
+
+  fn merge_values(&mut self, other: NumberDescriptor) {
+    self.minimum = self.minimum.min(other.minimum);
+    self.maximum = self.maximum.max(other.maximum);
+  }
+}
+
+// Like string values, number values don't contribute to the identity of a number descriptor
+impl PartialEq for NumberDescriptor {
+  fn eq(&self, other: &Self) -> bool {
+    self.is_integer == other.is_integer
+  }
+}
+
+impl Eq for NumberDescriptor {}
+
+impl Hash for NumberDescriptor {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.is_integer.hash(state);
+  }
+}
+
+impl From<&JsonNumber> for NumberDescriptor {
+  fn from(number: &JsonNumber) -> Self {
+    let value = number.as_f64().unwrap_or_default();
+    Self {
+      is_integer: number.is_i64() || number.is_u64(),
+      minimum: value,
+      maximum: value,
+    }
+  }
+}
+
+impl BodyDescriptor {
+  pub fn kind(&self) -> BodyKind {
+    match self {
+      BodyDescriptor::Object(_) => BodyKind::Object,
+      BodyDescriptor::Array(_) => BodyKind::Array,
+      BodyDescriptor::String(_) => BodyKind::String,
+      // only observed values can be told apart as integers
+      BodyDescriptor::Number(Some(number)) if number.is_integer => BodyKind::Integer,
+      BodyDescriptor::Number(_) => BodyKind::Number,
+      BodyDescriptor::Boolean => BodyKind::Boolean,
+      BodyDescriptor::Null => BodyKind::Null,
+    }
+  }
+
+  // merge the values of an equal descriptor into this one, keeping track of all values described
+  fn merge_values(&mut self, other: BodyDescriptor) {
+    match (self, other) {
+      (BodyDescriptor::Object(object), BodyDescriptor::Object(other_object)) => {
+        object.merge_values(other_object)
+      }
+      (BodyDescriptor::Array(items), BodyDescriptor::Array(other_items)) => {
+        items.merge_values(other_items)
+      }
+      (BodyDescriptor::String(Some(string)), BodyDescriptor::String(Some(other_string))) => {
+        string.merge_values(other_string)
+      }
+      (BodyDescriptor::Number(Some(number)), BodyDescriptor::Number(Some(other_number))) => {
+        number.merge_values(other_number)
+      }
+      _ => {}
+    }
+  }
+}
+
 #[derive(PartialEq, Clone, Debug, Hash, Eq)]
 pub struct FieldDescriptor(pub String, pub Box<BodyDescriptor>);
 
@@ -28,6 +191,14 @@ pub struct ObjectDescriptor {
 }
 
 impl ObjectDescriptor {
+  fn merge_values(&mut self, other: ObjectDescriptor) {
+    for (FieldDescriptor(_, body), FieldDescriptor(_, other_body)) in
+      self.fields.iter_mut().zip(other.fields)
+    {
+      body.merge_values(*other_body);
+    }
+  }
+
   pub fn keys(&self) -> impl Iterator<Item = &String> {
     self.fields.iter().map(|FieldDescriptor(key, body)| key)
   }
@@ -63,21 +234,22 @@ where
   T: Iterator<Item = BodyDescriptor>,
 {
   fn from(all_items: T) -> Self {
-    let mut unique_items = all_items
-      .enumerate()
-      .fold(HashMap::new(), |mut indexes_by_unique_items, (i, item)| {
-        {
-          let items = indexes_by_unique_items.entry(item).or_insert(vec![]);
-          items.push(i);
-        };
-
-        indexes_by_unique_items
-      })
-      .into_iter()
-      .map(|(item, indexes)| (item.clone(), indexes))
-      .collect::<Vec<_>>();
+    let mut positions_by_unique_item: HashMap<BodyDescriptor, usize> = HashMap::new();
+    let mut unique_items: Vec<(BodyDescriptor, Vec<usize>)> = vec![];
 
-    unique_items.sort_by_key(|(_, indexes)| *indexes.first().unwrap());
+    for (i, item) in all_items.enumerate() {
+      match positions_by_unique_item.get(&item) {
+        Some(position) => {
+          let (unique_item, indexes) = &mut unique_items[*position];
+          unique_item.merge_values(item);
+          indexes.push(i);
+        }
+        None => {
+          positions_by_unique_item.insert(item.clone(), unique_items.len());
+          unique_items.push((item, vec![i]));
+        }
+      }
+    }
 
     Self {
       unique_items: Box::new(unique_items),
@@ -98,6 +270,12 @@ impl ItemsDescriptor {
   pub fn unique_items_count(&self) -> usize {
     self.unique_items.len()
   }
+
+  fn merge_values(&mut self, other: ItemsDescriptor) {
+    for ((item, _), (other_item, _)) in self.unique_items.iter_mut().zip(*other.unique_items) {
+      item.merge_values(other_item);
+    }
+  }
 }
 
 impl From<shapehash::ShapeDescriptor> for BodyDescriptor {
@@ -125,8 +303,8 @@ impl From<shapehash::ShapeDescriptor> for BodyDescriptor {
       }
       shapehash::ShapeDescriptor_PrimitiveType::BOOLEAN => BodyDescriptor::Boolean,
       shapehash::ShapeDescriptor_PrimitiveType::NULL => BodyDescriptor::Null,
-      shapehash::ShapeDescriptor_PrimitiveType::NUMBER => BodyDescriptor::Number,
-      shapehash::ShapeDescriptor_PrimitiveType::STRING => BodyDescriptor::String,
+      shapehash::ShapeDescriptor_PrimitiveType::NUMBER => BodyDescriptor::Number(None),
+      shapehash::ShapeDescriptor_PrimitiveType::STRING => BodyDescriptor::String(None),
     }
   }
 }
@@ -150,20 +328,67 @@ impl From<JsonValue> for BodyDescriptor {
       }
       JsonValue::Bool(_) => BodyDescriptor::Boolean,
       JsonValue::Null => BodyDescriptor::Null,
-      JsonValue::Number(_) => BodyDescriptor::Number,
-      JsonValue::String(_) => BodyDescriptor::String,
+      JsonValue::Number(number) => BodyDescriptor::Number(Some(NumberDescriptor::from(&number))),
+      JsonValue::String(value) => BodyDescriptor::String(Some(StringDescriptor::from(value))),
     }
   }
 }
 
+// Elements are described as objects of their attributes (prefixed with `@`) and child elements,
+// where children that occur more than once are lists. Elements with only text are strings, while
+// text mixed with attributes or children is kept as a `#text` field.
+impl From<XmlElement> for BodyDescriptor {
+  fn from(element: XmlElement) -> Self {
+    let text = element.text.trim();
+    if element.attributes.is_empty() && element.children.is_empty() {
+      return BodyDescriptor::String(Some(StringDescriptor::from(text)));
+    }
+
+    let mut children_by_name: BTreeMap<String, Vec<BodyDescriptor>> = BTreeMap::new();
+    for child in element.children {
+      children_by_name
+        .entry(child.name.clone())
+        .or_default()
+        .push(BodyDescriptor::from(child));
+    }
+
+    let attributes = element
+      .attributes
+      .into_iter()
+      .filter(|(name, _)| name != "xmlns" && !name.starts_with("xmlns:"))
+      .map(|(name, value)| {
+        let value = BodyDescriptor::String(Some(StringDescriptor::from(value)));
+        (format!("@{}", name), value)
+      });
+    let children = children_by_name.into_iter().map(|(name, mut children)| {
+      let value = if children.len() > 1 {
+        BodyDescriptor::Array(ItemsDescriptor::from(children.into_iter()))
+      } else {
+        children.pop().unwrap()
+      };
+      (name, value)
+    });
+    let text = Some(text).filter(|text| !text.is_empty()).map(|text| {
+      let value = BodyDescriptor::String(Some(StringDescriptor::from(text)));
+      (String::from("#text"), value)
+    });
+
+    let fields = attributes
+      .chain(children)
+      .chain(text)
+      .collect::<BTreeMap<_, _>>();
+    BodyDescriptor::Object(ObjectDescriptor::from(fields.into_iter()))
+  }
+}
+
 impl From<String> for BodyDescriptor {
   fn from(string: String) -> Self {
-    BodyDescriptor::String
+    BodyDescriptor::String(None)
   }
 }
 
 impl From<&String> for BodyDescriptor {
   fn from(str: &String) -> Self {
-    BodyDescriptor::String
+    BodyDescriptor::String(None)
   }
 }
@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+// How specifically a documented media type matches an observed one, higher is more specific
+pub type MediaTypeSpecificity = u8;
+
+const ANY_TYPE_SPECIFICITY: MediaTypeSpecificity = 0;
+const ANY_SUBTYPE_SPECIFICITY: MediaTypeSpecificity = 1;
+const SUFFIX_SPECIFICITY: MediaTypeSpecificity = 2;
+const EXACT_SPECIFICITY: MediaTypeSpecificity = 3;
+
+/// A parsed media type, as found in a `Content-Type` header (`type/subtype; name=value`).
+/// Type, subtype and parameter names are case-insensitive, so they're kept in lowercase.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MediaType {
+  pub main_type: String,
+  pub subtype: String,
+  pub parameters: BTreeMap<String, String>,
+}
+
+impl MediaType {
+  pub fn parse(content_type: &str) -> Option<Self> {
+    let mut segments = content_type.split(';');
+    let essence = segments.next()?.trim();
+
+    let mut essence_parts = essence.splitn(2, '/');
+    let main_type = essence_parts.next()?.trim();
+    let subtype = essence_parts.next()?.trim();
+    if !is_token(main_type) || !is_token(subtype) {
+      return None;
+    }
+
+    let parameters = segments
+      .filter_map(|segment| {
+        let mut parameter_parts = segment.splitn(2, '=');
+        let name = parameter_parts.next()?.trim();
+        let value = parameter_parts.next()?.trim();
+        if !is_token(name) {
+          return None;
+        }
+        let value = value
+          .strip_prefix('"')
+          .and_then(|value| value.strip_suffix('"'))
+          .unwrap_or(value);
+        Some((name.to_lowercase(), String::from(value)))
+      })
+      .collect();
+
+    Some(Self {
+      main_type: main_type.to_lowercase(),
+      subtype: subtype.to_lowercase(),
+      parameters,
+    })
+  }
+
+  /// The media type without its parameters, which is how it's normalized for matching and reporting
+  pub fn essence(&self) -> String {
+    format!("{}/{}", self.main_type, self.subtype)
+  }
+
+  /// The structured syntax suffix of the subtype, like `json` for `application/vnd.api+json`
+  pub fn suffix(&self) -> Option<&str> {
+    let suffix_start = self.subtype.rfind('+')?;
+    Some(&self.subtype[suffix_start + 1..])
+  }
+
+  /// Whether the subtype is in the vendor tree, like `application/vnd.github.v3+json`
+  pub fn is_vendor(&self) -> bool {
+    self.subtype.starts_with("vnd.")
+  }
+
+  pub fn is_json(&self) -> bool {
+    self.subtype == "json" || self.suffix() == Some("json")
+  }
+
+  pub fn parameter(&self, name: &str) -> Option<&str> {
+    self
+      .parameters
+      .get(&name.to_lowercase())
+      .map(|value| value.as_str())
+  }
+
+  /// Match an observed media type against this (documented) one, which can use wildcards:
+  /// `*/*`, `type/*` and `type/*+suffix`, the latter matching vendor types by their suffix.
+  /// Parameters don't affect matching.
+  pub fn match_specificity(&self, observed: &MediaType) -> Option<MediaTypeSpecificity> {
+    if self.main_type == "*" && self.subtype == "*" {
+      return Some(ANY_TYPE_SPECIFICITY);
+    }
+    if self.main_type != observed.main_type {
+      return None;
+    }
+
+    if self.subtype == "*" {
+      Some(ANY_SUBTYPE_SPECIFICITY)
+    } else if let Some(suffix) = self.subtype.strip_prefix("*+") {
+      let suffix_matches = observed.suffix() == Some(suffix) || observed.subtype == suffix;
+      if suffix_matches {
+        Some(SUFFIX_SPECIFICITY)
+      } else {
+        None
+      }
+    } else if self.subtype == observed.subtype {
+      Some(EXACT_SPECIFICITY)
+    } else {
+      None
+    }
+  }
+}
+
+impl fmt::Display for MediaType {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.essence())
+  }
+}
+
+/// Match an observed content type against a documented one, falling back to comparing them
+/// verbatim when either isn't a valid media type
+pub fn match_content_types(
+  documented_content_type: &str,
+  observed_content_type: &str,
+) -> Option<MediaTypeSpecificity> {
+  match (
+    MediaType::parse(documented_content_type),
+    MediaType::parse(observed_content_type),
+  ) {
+    (Some(documented), Some(observed)) => documented.match_specificity(&observed),
+    _ if documented_content_type == observed_content_type => Some(EXACT_SPECIFICITY),
+    _ => None,
+  }
+}
+
+/// The content type as reported in diff results: the essence of its media type when valid,
+/// so content types that only differ by parameters or case are reported as one
+pub fn normalize_content_type(content_type: &str) -> String {
+  match MediaType::parse(content_type) {
+    Some(media_type) => media_type.essence(),
+    None => String::from(content_type),
+  }
+}
+
+// the characters allowed in types, subtypes and parameter names (RFC 7230 tokens)
+fn is_token(value: &str) -> bool {
+  !value.is_empty()
+    && value
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn can_parse_media_types() {
+    let media_type = MediaType::parse("Application/Vnd.API+JSON; Charset=\"UTF-8\"; version=2")
+      .expect("should parse a media type with parameters");
+    assert_eq!(media_type.essence(), "application/vnd.api+json");
+    assert_eq!(media_type.suffix(), Some("json"));
+    assert!(media_type.is_vendor());
+    assert!(media_type.is_json());
+    assert_eq!(media_type.parameter("charset"), Some("UTF-8"));
+    assert_eq!(media_type.parameter("version"), Some("2"));
+
+    assert!(MediaType::parse("application").is_none());
+    assert!(MediaType::parse("application/").is_none());
+    assert!(MediaType::parse("not a/media type").is_none());
+  }
+
+  #[test]
+  fn can_match_media_types() {
+    assert!(match_content_types("application/json", "application/json; charset=utf-8").is_some());
+    assert!(match_content_types("application/json", "APPLICATION/JSON").is_some());
+    assert!(match_content_types("application/json", "text/json").is_none());
+    assert!(match_content_types("application/json", "application/vnd.api+json").is_none());
+    assert!(match_content_types("application/*+json", "application/vnd.api+json").is_some());
+    assert!(match_content_types("application/*+json", "application/json").is_some());
+    assert!(match_content_types("application/*+json", "application/xml").is_none());
+    assert!(match_content_types("text/*", "text/plain").is_some());
+    assert!(match_content_types("*/*", "image/png").is_some());
+    assert!(match_content_types("not a media type", "not a media type").is_some());
+
+    let exact = match_content_types("application/json", "application/json");
+    let suffix = match_content_types("application/*+json", "application/json");
+    let subtype_wildcard = match_content_types("application/*", "application/json");
+    let wildcard = match_content_types("*/*", "application/json");
+    assert!(exact > suffix && suffix > subtype_wildcard && subtype_wildcard > wildcard);
+  }
+
+  #[test]
+  fn can_normalize_content_types() {
+    assert_eq!(
+      normalize_content_type("Application/JSON; charset=utf-8"),
+      "application/json"
+    );
+    assert_eq!(
+      normalize_content_type("not a media type"),
+      "not a media type"
+    );
+  }
+}
@@ -4,11 +4,17 @@ use std::collections::HashSet;
 
 pub mod body;
 pub mod endpoint;
+pub mod media_type;
 pub mod shape;
+pub mod xml;
 
 pub trait SpecIdGenerator {
   fn generate_id(&mut self, prefix: &str) -> String;
 
+  fn path(&mut self) -> String {
+    self.generate_id("path_")
+  }
+
   fn field(&mut self) -> String {
     self.generate_id("field_")
   }
@@ -21,6 +27,10 @@ pub trait SpecIdGenerator {
     self.generate_id("response_")
   }
 
+  fn request_parameter(&mut self) -> String {
+    self.generate_id("request_parameter_")
+  }
+
   fn shape(&mut self) -> String {
     self.generate_id("shape_")
   }
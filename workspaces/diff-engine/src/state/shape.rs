@@ -97,7 +97,7 @@ pub struct ShapeProvider {
 pub struct NoProvider {}
 ////////////////////////////////////////////////////////////////////////////////
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum ShapeKind {
   ObjectKind,
   ListKind,
@@ -106,6 +106,7 @@ pub enum ShapeKind {
   AnyKind,
   StringKind,
   NumberKind,
+  IntegerKind,
   BooleanKind,
   IdentifierKind,
   ReferenceKind,
@@ -145,6 +146,10 @@ impl ShapeKind {
         base_shape_id: "$number",
         name: "Number",
       },
+      Self::IntegerKind => ShapeKindDescriptor {
+        base_shape_id: "$integer",
+        name: "Integer",
+      },
       Self::BooleanKind => ShapeKindDescriptor {
         base_shape_id: "$boolean",
         name: "Boolean",
@@ -177,6 +182,9 @@ impl ShapeKind {
       Self::ListKind => Some(ShapeKindParameterDescriptor {
         shape_parameter_id: "$listItem",
       }),
+      Self::MapKind => Some(ShapeKindParameterDescriptor {
+        shape_parameter_id: "$mapValue",
+      }),
       Self::NullableKind => Some(ShapeKindParameterDescriptor {
         shape_parameter_id: "$nullableInner",
       }),
@@ -194,3 +202,72 @@ pub struct ShapeKindDescriptor {
   pub base_shape_id: &'static str,
   pub name: &'static str,
 }
+////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum StringFormat {
+  #[serde(rename = "uuid")]
+  Uuid,
+  #[serde(rename = "date-time")]
+  DateTime,
+  #[serde(rename = "email")]
+  Email,
+  #[serde(rename = "uri")]
+  Uri,
+}
+
+impl StringFormat {
+  pub fn detect(value: &str) -> Option<Self> {
+    if Self::Uuid.matches(value) {
+      Some(Self::Uuid)
+    } else if Self::DateTime.matches(value) {
+      Some(Self::DateTime)
+    } else if Self::Email.matches(value) {
+      Some(Self::Email)
+    } else if Self::Uri.matches(value) {
+      Some(Self::Uri)
+    } else {
+      None
+    }
+  }
+
+  pub fn matches(&self, value: &str) -> bool {
+    match self {
+      Self::Uuid => value.len() == 36 && uuid::Uuid::parse_str(value).is_ok(),
+      Self::DateTime => chrono::DateTime::parse_from_rfc3339(value).is_ok(),
+      Self::Email => {
+        let mut parts = value.splitn(2, '@');
+        let local = parts.next().unwrap_or_default();
+        let domain = parts.next().unwrap_or_default();
+
+        !local.is_empty()
+          && !domain.contains('@')
+          && domain.contains('.')
+          && !domain.starts_with('.')
+          && !domain.ends_with('.')
+          && !value.contains(char::is_whitespace)
+      }
+      Self::Uri => match value.find("://") {
+        Some(scheme_end) => {
+          let scheme = &value[..scheme_end];
+          let mut scheme_chars = scheme.chars();
+
+          matches!(scheme_chars.next(), Some(first) if first.is_ascii_alphabetic())
+            && scheme_chars.all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+            && value.len() > scheme_end + 3
+            && !value.contains(char::is_whitespace)
+        }
+        None => false,
+      },
+    }
+  }
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      Self::Uuid => "uuid",
+      Self::DateTime => "date-time",
+      Self::Email => "email",
+      Self::Uri => "uri",
+    }
+  }
+}
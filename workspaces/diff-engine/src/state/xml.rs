@@ -0,0 +1,236 @@
+// A minimal XML parser, reading documents into a tree of elements so XML bodies can be described
+// like JSON bodies. Only well-formedness as far as needed to build that tree is checked: DTDs are
+// skipped, namespaces aren't resolved and only predefined and character entities are expanded.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct XmlElement {
+  pub name: String,
+  pub attributes: Vec<(String, String)>,
+  pub children: Vec<XmlElement>,
+  pub text: String,
+}
+
+impl XmlElement {
+  /// Parse the root element of a document, `None` when the document isn't well-formed
+  pub fn parse(document: &str) -> Option<Self> {
+    let mut parser = Parser {
+      input: document,
+      position: 0,
+    };
+
+    parser.skip_misc()?;
+    let root = parser.element()?;
+    parser.skip_misc()?;
+
+    if parser.rest().is_empty() {
+      Some(root)
+    } else {
+      None
+    }
+  }
+}
+
+struct Parser<'a> {
+  input: &'a str,
+  position: usize,
+}
+
+impl<'a> Parser<'a> {
+  fn rest(&self) -> &'a str {
+    &self.input[self.position..]
+  }
+
+  fn skip_whitespace(&mut self) {
+    let rest = self.rest();
+    self.position += rest.len() - rest.trim_start().len();
+  }
+
+  fn skip_past(&mut self, terminator: &str) -> Option<()> {
+    let end = self.rest().find(terminator)?;
+    self.position += end + terminator.len();
+    Some(())
+  }
+
+  // skip the prolog, comments, processing instructions and doctype declarations around elements
+  fn skip_misc(&mut self) -> Option<()> {
+    loop {
+      self.skip_whitespace();
+      let rest = self.rest();
+      if rest.starts_with("<?") {
+        self.skip_past("?>")?;
+      } else if rest.starts_with("<!--") {
+        self.skip_past("-->")?;
+      } else if rest.starts_with("<!DOCTYPE") {
+        match (rest.find('['), rest.find('>')) {
+          (Some(subset_start), Some(end)) if subset_start < end => self.skip_past("]>")?,
+          _ => self.skip_past(">")?,
+        }
+      } else {
+        return Some(());
+      }
+    }
+  }
+
+  fn name(&mut self) -> Option<&'a str> {
+    let rest = self.rest();
+    let length = rest
+      .find(|c: char| c.is_whitespace() || "/>=".contains(c))
+      .unwrap_or(rest.len());
+    if length == 0 {
+      return None;
+    }
+    self.position += length;
+    Some(&rest[..length])
+  }
+
+  fn element(&mut self) -> Option<XmlElement> {
+    self.rest().strip_prefix('<')?;
+    self.position += 1;
+    let name = self.name()?;
+
+    let mut element = XmlElement {
+      name: String::from(name),
+      attributes: vec![],
+      children: vec![],
+      text: String::new(),
+    };
+
+    loop {
+      self.skip_whitespace();
+      let rest = self.rest();
+      if rest.starts_with("/>") {
+        self.position += 2;
+        return Some(element);
+      } else if rest.starts_with('>') {
+        self.position += 1;
+        break;
+      }
+
+      let attribute_name = self.name()?;
+      self.skip_whitespace();
+      self.rest().strip_prefix('=')?;
+      self.position += 1;
+      self.skip_whitespace();
+      let quote = self
+        .rest()
+        .chars()
+        .next()
+        .filter(|c| *c == '"' || *c == '\'')?;
+      self.position += 1;
+      let value_length = self.rest().find(quote)?;
+      let value = decode_entities(&self.rest()[..value_length])?;
+      self.position += value_length + 1;
+      element
+        .attributes
+        .push((String::from(attribute_name), value));
+    }
+
+    loop {
+      let rest = self.rest();
+      if rest.starts_with("</") {
+        self.position += 2;
+        if self.name()? != name {
+          return None;
+        }
+        self.skip_whitespace();
+        self.rest().strip_prefix('>')?;
+        self.position += 1;
+        return Some(element);
+      } else if rest.starts_with("<![CDATA[") {
+        let end = rest.find("]]>")?;
+        element.text.push_str(&rest["<![CDATA[".len()..end]);
+        self.position += end + 3;
+      } else if rest.starts_with("<!--") {
+        self.skip_past("-->")?;
+      } else if rest.starts_with("<?") {
+        self.skip_past("?>")?;
+      } else if rest.starts_with('<') {
+        element.children.push(self.element()?);
+      } else {
+        let length = rest.find('<')?;
+        element.text.push_str(&decode_entities(&rest[..length])?);
+        self.position += length;
+      }
+    }
+  }
+}
+
+fn decode_entities(text: &str) -> Option<String> {
+  let mut decoded = String::with_capacity(text.len());
+  let mut rest = text;
+  while let Some(start) = rest.find('&') {
+    decoded.push_str(&rest[..start]);
+    let end = rest[start..].find(';')? + start;
+    let entity = &rest[start + 1..end];
+    let character = match entity {
+      "lt" => '<',
+      "gt" => '>',
+      "amp" => '&',
+      "quot" => '"',
+      "apos" => '\'',
+      _ => {
+        let code = if let Some(hex) = entity.strip_prefix("#x") {
+          u32::from_str_radix(hex, 16).ok()?
+        } else {
+          entity.strip_prefix('#')?.parse::<u32>().ok()?
+        };
+        std::char::from_u32(code)?
+      }
+    };
+    decoded.push(character);
+    rest = &rest[end + 1..];
+  }
+  decoded.push_str(rest);
+  Some(decoded)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn can_parse_xml_documents() {
+    let document = r#"<?xml version="1.0" encoding="UTF-8"?>
+      <!DOCTYPE order [ <!ELEMENT order ANY> ]>
+      <!-- an order -->
+      <order id="42" status='open'>
+        <customer>Homer &amp; Marge</customer>
+        <item sku="donut"/>
+        <item sku="duff">two &#x26; counting</item>
+        <note><![CDATA[<fragile>]]></note>
+      </order>"#;
+
+    let order = XmlElement::parse(document).expect("should parse a well-formed document");
+    assert_eq!(order.name, "order");
+    assert_eq!(
+      order.attributes,
+      vec![
+        (String::from("id"), String::from("42")),
+        (String::from("status"), String::from("open"))
+      ]
+    );
+    let children = order
+      .children
+      .iter()
+      .map(|child| (child.name.as_str(), child.text.as_str()))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      children,
+      vec![
+        ("customer", "Homer & Marge"),
+        ("item", ""),
+        ("item", "two & counting"),
+        ("note", "<fragile>"),
+      ]
+    );
+  }
+
+  #[test]
+  fn rejects_malformed_documents() {
+    assert!(XmlElement::parse("<order><item></order>").is_none());
+    assert!(XmlElement::parse("<order>").is_none());
+    assert!(XmlElement::parse("<order/><order/>").is_none());
+    assert!(XmlElement::parse("not xml").is_none());
+    assert!(XmlElement::parse("<order>&unknown;</order>").is_none());
+  }
+}
@@ -1,6 +1,9 @@
 use futures::sink::SinkExt;
 use insta::assert_debug_snapshot;
-use optic_diff_engine::{diff_interaction, streams, HttpInteraction, SpecEvent, SpecProjection};
+use optic_diff_engine::{
+  diff_interaction, diff_interaction_with_config, streams, HttpInteraction, InteractionDiffConfig,
+  InteractionDiffResult, SpecEvent, SpecProjection,
+};
 use petgraph::dot::Dot;
 use serde_json::json;
 use tokio::io::{AsyncBufReadExt, BufReader};
@@ -289,3 +292,569 @@ async fn can_yield_unmatched_shape() {
   assert_debug_snapshot!(results);
   assert_debug_snapshot!("can_yield_unmatched_shape__fingerprints", fingerprints);
 }
+
+fn interaction_with_query(method: &str, path: &str, query: Option<&str>) -> HttpInteraction {
+  serde_json::from_value(json!({
+    "uuid": "5",
+    "request": {
+      "host": "localhost",
+      "method": method,
+      "path": path,
+      "query": { "asJsonString": null, "asText": query, "shapeHashV1Base64": null },
+      "headers": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null },
+      "body": {
+        "contentType": null,
+        "value": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null }
+      }
+    },
+    "response": {
+      "statusCode": 200,
+      "headers": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null },
+      "body": {
+        "contentType": null,
+        "value": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null }
+      }
+    },
+    "tags": []
+  }))
+  .expect("should be a valid interaction")
+}
+
+#[test]
+fn can_diff_against_renamed_and_removed_endpoints() {
+  let events: Vec<SpecEvent> = serde_json::from_value(
+    json!([
+      {"PathComponentAdded":{"pathId":"path_1","parentPathId":"root","name":"todos"}},
+      {"RequestAdded":{"requestId":"request_1","pathId":"path_1","httpMethod":"GET"}},
+      {"ResponseAddedByPathAndMethod":{"responseId":"response_1","httpStatusCode":200,"pathId":"path_1","httpMethod":"GET"}},
+      {"RequestAdded":{"requestId":"request_2","pathId":"path_1","httpMethod":"POST"}},
+      {"ResponseAddedByPathAndMethod":{"responseId":"response_2","httpStatusCode":200,"pathId":"path_1","httpMethod":"POST"}},
+      {"PathComponentAdded":{"pathId":"path_2","parentPathId":"root","name":"users"}},
+      {"PathParameterAdded":{"pathId":"path_3","parentPathId":"path_2","name":"userId"}},
+      {"RequestAdded":{"requestId":"request_3","pathId":"path_3","httpMethod":"GET"}},
+      {"ResponseAddedByPathAndMethod":{"responseId":"response_3","httpStatusCode":200,"pathId":"path_3","httpMethod":"GET"}},
+      {"PathComponentRenamed":{"pathId":"path_1","name":"tasks"}},
+      {"RequestRemoved":{"requestId":"request_2"}},
+      {"ResponseRemoved":{"responseId":"response_2"}},
+      {"PathComponentRemoved":{"pathId":"path_2"}},
+    ]),
+  ).expect("should be able to deserialize path and request events as spec events");
+
+  let spec_projection = SpecProjection::from(events);
+
+  let renamed_results =
+    diff_interaction(&spec_projection, interaction_with_query("GET", "/tasks", None));
+  assert_eq!(renamed_results.len(), 0);
+
+  let unmatched_interactions = vec![
+    interaction_with_query("GET", "/todos", None),
+    interaction_with_query("GET", "/users/1", None),
+  ];
+  for interaction in unmatched_interactions {
+    let results = diff_interaction(&spec_projection, interaction);
+    assert_eq!(results.len(), 1);
+    assert!(matches!(
+      results[0],
+      InteractionDiffResult::UnmatchedRequestUrl(_)
+    ));
+  }
+
+  // the path is still there, but the operation is undocumented
+  let removed_operation_results =
+    diff_interaction(&spec_projection, interaction_with_query("POST", "/tasks", None));
+  assert_eq!(removed_operation_results.len(), 2);
+  assert!(matches!(
+    removed_operation_results[0],
+    InteractionDiffResult::UnmatchedRequestBodyContentType(_)
+  ));
+  assert!(matches!(
+    removed_operation_results[1],
+    InteractionDiffResult::UnmatchedResponseBodyContentType(_)
+  ));
+}
+
+#[test]
+fn can_yield_query_parameter_diffs() {
+  let events: Vec<SpecEvent> = serde_json::from_value(
+    json!([
+      {"PathComponentAdded":{"pathId":"path_1","parentPathId":"root","name":"todos"}},
+      {"RequestAdded":{"requestId":"request_1","pathId":"path_1","httpMethod":"GET"}},
+      {"ResponseAddedByPathAndMethod":{"responseId":"response_1", "httpStatusCode":200,"pathId":"path_1","httpMethod":"GET"}},
+      {"ShapeAdded":{"shapeId":"status_shape","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"RequestParameterAddedByPathAndMethod":{"parameterId":"parameter_1","pathId":"path_1","httpMethod":"GET","parameterLocation":"query","name":"status"}},
+      {"RequestParameterShapeSet":{"parameterId":"parameter_1","parameterDescriptor":{"shapeId":"status_shape","isRemoved":false}}},
+      {"ShapeAdded":{"shapeId":"limit_inner_shape","baseShapeId":"$number","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeAdded":{"shapeId":"limit_shape","baseShapeId":"$optional","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeParameterShapeSet":{"shapeDescriptor":{"ProviderInShape":{"shapeId":"limit_shape","providerDescriptor":{"ShapeProvider":{"shapeId":"limit_inner_shape"}},"consumingParameterId":"$optionalInner"}}}},
+      {"RequestParameterAddedByPathAndMethod":{"parameterId":"parameter_2","pathId":"path_1","httpMethod":"GET","parameterLocation":"query","name":"limit"}},
+      {"RequestParameterShapeSet":{"parameterId":"parameter_2","parameterDescriptor":{"shapeId":"limit_shape","isRemoved":false}}},
+      {"ShapeAdded":{"shapeId":"tag_shape","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeAdded":{"shapeId":"tags_shape","baseShapeId":"$list","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeParameterShapeSet":{"shapeDescriptor":{"ProviderInShape":{"shapeId":"tags_shape","providerDescriptor":{"ShapeProvider":{"shapeId":"tag_shape"}},"consumingParameterId":"$listItem"}}}},
+      {"RequestParameterAddedByPathAndMethod":{"parameterId":"parameter_3","pathId":"path_1","httpMethod":"GET","parameterLocation":"query","name":"tags"}},
+      {"RequestParameterShapeSet":{"parameterId":"parameter_3","parameterDescriptor":{"shapeId":"tags_shape","isRemoved":false}}},
+    ]),
+  ).expect("should be able to deserialize request parameter events as spec events");
+
+  let spec_projection = SpecProjection::from(events);
+
+  let compliant_interaction =
+    interaction_with_query("GET", "/todos", Some("status=open&limit=10&tags=a&tags=b"));
+  let compliant_results = diff_interaction(&spec_projection, compliant_interaction);
+  assert_eq!(compliant_results.len(), 0);
+
+  let optional_omitted_interaction =
+    interaction_with_query("GET", "/todos", Some("status=open&tags=a"));
+  let optional_omitted_results = diff_interaction(&spec_projection, optional_omitted_interaction);
+  assert_eq!(optional_omitted_results.len(), 0);
+
+  let unmatched_interaction =
+    interaction_with_query("GET", "/todos", Some("limit=ten&tags=a&sort=desc"));
+  let results = diff_interaction(&spec_projection, unmatched_interaction);
+  let fingerprints = results
+    .iter()
+    .map(|result| result.fingerprint())
+    .collect::<Vec<_>>();
+
+  assert_debug_snapshot!("can_yield_query_parameter_diffs__results", results);
+  assert_debug_snapshot!(
+    "can_yield_query_parameter_diffs__fingerprints",
+    fingerprints
+  );
+  assert_eq!(results.len(), 3);
+}
+
+#[test]
+fn can_yield_query_parameter_diffs_for_query_string_shapes() {
+  let events: Vec<SpecEvent> = serde_json::from_value(
+    json!([
+      {"PathComponentAdded":{"pathId":"path_1","parentPathId":"root","name":"todos"}},
+      {"RequestAdded":{"requestId":"request_1","pathId":"path_1","httpMethod":"GET"}},
+      {"ResponseAddedByPathAndMethod":{"responseId":"response_1", "httpStatusCode":200,"pathId":"path_1","httpMethod":"GET"}},
+      {"ShapeAdded":{"shapeId":"query_shape","baseShapeId":"$object","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeAdded":{"shapeId":"done_shape","baseShapeId":"$boolean","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"FieldAdded":{"fieldId":"field_1","shapeId":"query_shape","name":"done","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_1","shapeId":"done_shape"}}}},
+      {"RequestParameterAddedByPathAndMethod":{"parameterId":"parameter_1","pathId":"path_1","httpMethod":"GET","parameterLocation":"query","name":"queryString"}},
+      {"RequestParameterShapeSet":{"parameterId":"parameter_1","parameterDescriptor":{"shapeId":"query_shape","isRemoved":false}}},
+    ]),
+  ).expect("should be able to deserialize request parameter events as spec events");
+
+  let spec_projection = SpecProjection::from(events);
+
+  let compliant_interaction = interaction_with_query("GET", "/todos", Some("done=true"));
+  let compliant_results = diff_interaction(&spec_projection, compliant_interaction);
+  assert_eq!(compliant_results.len(), 0);
+
+  let unmatched_interaction = interaction_with_query("GET", "/todos", Some("done=yes&page=2"));
+  let results = diff_interaction(&spec_projection, unmatched_interaction);
+
+  assert_debug_snapshot!(
+    "can_yield_query_parameter_diffs_for_query_string_shapes__results",
+    results
+  );
+  assert_eq!(results.len(), 2);
+}
+
+fn interaction_with_headers(
+  request_headers: serde_json::Value,
+  response_headers: serde_json::Value,
+) -> HttpInteraction {
+  serde_json::from_value(json!({
+    "uuid": "6",
+    "request": {
+      "host": "localhost",
+      "method": "GET",
+      "path": "/todos",
+      "query": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null },
+      "headers": { "asJsonString": request_headers.to_string(), "asText": null, "shapeHashV1Base64": null },
+      "body": {
+        "contentType": null,
+        "value": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null }
+      }
+    },
+    "response": {
+      "statusCode": 200,
+      "headers": { "asJsonString": response_headers.to_string(), "asText": null, "shapeHashV1Base64": null },
+      "body": {
+        "contentType": null,
+        "value": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null }
+      }
+    },
+    "tags": []
+  }))
+  .expect("should be a valid interaction")
+}
+
+#[test]
+fn can_yield_header_diffs() {
+  let events: Vec<SpecEvent> = serde_json::from_value(
+    json!([
+      {"PathComponentAdded":{"pathId":"path_1","parentPathId":"root","name":"todos"}},
+      {"RequestAdded":{"requestId":"request_1","pathId":"path_1","httpMethod":"GET"}},
+      {"ResponseAddedByPathAndMethod":{"responseId":"response_1", "httpStatusCode":200,"pathId":"path_1","httpMethod":"GET"}},
+      {"ShapeAdded":{"shapeId":"request_id_shape","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"RequestParameterAddedByPathAndMethod":{"parameterId":"header_1","pathId":"path_1","httpMethod":"GET","parameterLocation":"header","name":"X-Request-Id"}},
+      {"RequestParameterShapeSet":{"parameterId":"header_1","parameterDescriptor":{"shapeId":"request_id_shape","isRemoved":false}}},
+      {"ShapeAdded":{"shapeId":"page_inner_shape","baseShapeId":"$number","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeAdded":{"shapeId":"page_shape","baseShapeId":"$optional","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeParameterShapeSet":{"shapeDescriptor":{"ProviderInShape":{"shapeId":"page_shape","providerDescriptor":{"ShapeProvider":{"shapeId":"page_inner_shape"}},"consumingParameterId":"$optionalInner"}}}},
+      {"RequestParameterAddedByPathAndMethod":{"parameterId":"header_2","pathId":"path_1","httpMethod":"GET","parameterLocation":"header","name":"x-page"}},
+      {"RequestParameterShapeSet":{"parameterId":"header_2","parameterDescriptor":{"shapeId":"page_shape","isRemoved":false}}},
+      {"ShapeAdded":{"shapeId":"total_shape","baseShapeId":"$number","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"RequestParameterAddedByPathAndMethod":{"parameterId":"header_3","pathId":"path_1","httpMethod":"GET","parameterLocation":"response-header","name":"x-total-count"}},
+      {"RequestParameterShapeSet":{"parameterId":"header_3","parameterDescriptor":{"shapeId":"total_shape","isRemoved":false}}},
+    ]),
+  ).expect("should be able to deserialize request parameter events as spec events");
+
+  let spec_projection = SpecProjection::from(events);
+
+  let compliant_interaction = interaction_with_headers(
+    json!([
+      { "name": "x-request-id", "value": "abc" },
+      { "name": "Connection", "value": "keep-alive" },
+      { "name": "Host", "value": "localhost" },
+      { "name": "User-Agent", "value": "curl/7.64.1" }
+    ]),
+    json!({ "X-Total-Count": "12", "Transfer-Encoding": "chunked", "Date": "Sun, 18 Oct 2026 13:00:00 GMT" }),
+  );
+  let compliant_results = diff_interaction(&spec_projection, compliant_interaction);
+  assert_eq!(compliant_results.len(), 0);
+
+  let unmatched_interaction = interaction_with_headers(
+    json!({ "X-Page": "first", "X-Trace": "1" }),
+    json!({ "x-total-count": "many" }),
+  );
+  let results = diff_interaction(&spec_projection, unmatched_interaction);
+  let fingerprints = results
+    .iter()
+    .map(|result| result.fingerprint())
+    .collect::<Vec<_>>();
+
+  assert_debug_snapshot!("can_yield_header_diffs__results", results);
+  assert_debug_snapshot!("can_yield_header_diffs__fingerprints", fingerprints);
+  assert_eq!(results.len(), 4);
+
+  let differently_capitalized_interaction = interaction_with_headers(
+    json!({ "x-page": "first", "x-trace": "1" }),
+    json!({ "X-TOTAL-COUNT": "many" }),
+  );
+  let differently_capitalized_fingerprints =
+    diff_interaction(&spec_projection, differently_capitalized_interaction)
+      .iter()
+      .map(|result| result.fingerprint())
+      .collect::<Vec<_>>();
+  assert_eq!(differently_capitalized_fingerprints, fingerprints);
+
+  let config = InteractionDiffConfig::default().with_ignored_header("X-Trace");
+  let ignored_interaction = interaction_with_headers(
+    json!({ "X-Request-Id": "abc", "X-Trace": "1" }),
+    json!({ "x-total-count": "12" }),
+  );
+  let ignored_results =
+    diff_interaction_with_config(&spec_projection, ignored_interaction, &config);
+  assert_eq!(ignored_results.len(), 0);
+}
+
+#[test]
+fn ignores_undocumented_headers_of_operations_without_headers() {
+  let events: Vec<SpecEvent> = serde_json::from_value(
+    json!([
+      {"PathComponentAdded":{"pathId":"path_1","parentPathId":"root","name":"todos"}},
+      {"RequestAdded":{"requestId":"request_1","pathId":"path_1","httpMethod":"GET"}},
+      {"ResponseAddedByPathAndMethod":{"responseId":"response_1", "httpStatusCode":200,"pathId":"path_1","httpMethod":"GET"}},
+    ]),
+  ).expect("should be able to deserialize events as spec events");
+
+  let spec_projection = SpecProjection::from(events);
+
+  let interaction = interaction_with_headers(
+    json!({ "Host": "localhost", "User-Agent": "curl/7.64.1", "Accept": "*/*", "X-Trace": "1" }),
+    json!({ "Date": "Sun, 18 Oct 2026 13:00:00 GMT", "Server": "nginx", "X-Powered-By": "Express" }),
+  );
+  let results = diff_interaction(&spec_projection, interaction);
+  assert_eq!(results.len(), 0);
+}
+
+fn interaction_with_bodies(
+  request_content_type: &str,
+  request_body: serde_json::Value,
+  response_content_type: &str,
+  response_body: serde_json::Value,
+) -> HttpInteraction {
+  serde_json::from_value(json!({
+    "uuid": "7",
+    "request": {
+      "host": "localhost",
+      "method": "POST",
+      "path": "/todos",
+      "query": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null },
+      "headers": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null },
+      "body": {
+        "contentType": request_content_type,
+        "value": { "asJsonString": request_body.to_string(), "asText": null, "shapeHashV1Base64": null }
+      }
+    },
+    "response": {
+      "statusCode": 201,
+      "headers": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null },
+      "body": {
+        "contentType": response_content_type,
+        "value": { "asJsonString": response_body.to_string(), "asText": null, "shapeHashV1Base64": null }
+      }
+    },
+    "tags": []
+  }))
+  .expect("should be a valid interaction")
+}
+
+#[test]
+fn can_match_body_content_types_by_media_type() {
+  let events: Vec<SpecEvent> = serde_json::from_value(
+    json!([
+      {"PathComponentAdded":{"pathId":"path_1","parentPathId":"root","name":"todos"}},
+      {"RequestAdded":{"requestId":"request_1","pathId":"path_1","httpMethod":"POST"}},
+      {"ShapeAdded":{"shapeId":"todo_shape","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"RequestBodySet":{"requestId":"request_1","bodyDescriptor":{"httpContentType":"application/json","shapeId":"todo_shape","isRemoved":false}}},
+      {"ResponseAddedByPathAndMethod":{"responseId":"response_1","httpStatusCode":201,"pathId":"path_1","httpMethod":"POST"}},
+      {"ShapeAdded":{"shapeId":"created_shape","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ResponseBodySet":{"responseId":"response_1","bodyDescriptor":{"httpContentType":"application/*+json","shapeId":"created_shape","isRemoved":false}}},
+      {"ResponseAddedByPathAndMethod":{"responseId":"response_2","httpStatusCode":201,"pathId":"path_1","httpMethod":"POST"}},
+      {"ShapeAdded":{"shapeId":"any_shape","baseShapeId":"$number","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ResponseBodySet":{"responseId":"response_2","bodyDescriptor":{"httpContentType":"*/*","shapeId":"any_shape","isRemoved":false}}},
+    ]),
+  ).expect("should be able to deserialize body events as spec events");
+
+  let spec_projection = SpecProjection::from(events);
+
+  let compliant_interaction = interaction_with_bodies(
+    "Application/JSON; charset=utf-8",
+    json!("Buy milk"),
+    "application/vnd.todos.v2+json",
+    json!("created"),
+  );
+  let compliant_results = diff_interaction(&spec_projection, compliant_interaction);
+  assert_eq!(
+    compliant_results.len(),
+    0,
+    "bodies should only be matched by the most specific media type"
+  );
+
+  let unmatched_interaction = interaction_with_bodies(
+    "text/plain; charset=utf-8",
+    json!("Buy milk"),
+    "application/vnd.todos.v2+json; charset=utf-8",
+    json!(1),
+  );
+  let results = diff_interaction(&spec_projection, unmatched_interaction);
+  assert_debug_snapshot!(
+    "can_match_body_content_types_by_media_type__results",
+    results
+  );
+  assert_eq!(results.len(), 2);
+}
+
+#[test]
+fn can_diff_against_removed_shapes_still_in_use() {
+  // shapes removed while bodies and fields still refer to them, like specs written before
+  // removing shapes in use was rejected
+  let events: Vec<SpecEvent> = serde_json::from_value(
+    json!([
+      {"PathComponentAdded":{"pathId":"path_1","parentPathId":"root","name":"todos"}},
+      {"RequestAdded":{"requestId":"request_1","pathId":"path_1","httpMethod":"POST"}},
+      {"ShapeAdded":{"shapeId":"todo_shape","baseShapeId":"$object","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":"Todo"}},
+      {"ShapeAdded":{"shapeId":"title_shape","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"FieldAdded":{"fieldId":"title_field","shapeId":"todo_shape","name":"title","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"title_field","shapeId":"title_shape"}}}},
+      {"RequestBodySet":{"requestId":"request_1","bodyDescriptor":{"httpContentType":"application/json","shapeId":"todo_shape","isRemoved":false}}},
+      {"ResponseAddedByPathAndMethod":{"responseId":"response_1","httpStatusCode":201,"pathId":"path_1","httpMethod":"POST"}},
+      {"ShapeAdded":{"shapeId":"created_shape","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ResponseBodySet":{"responseId":"response_1","bodyDescriptor":{"httpContentType":"application/json","shapeId":"created_shape","isRemoved":false}}},
+      {"ShapeRemoved":{"shapeId":"title_shape"}},
+      {"ShapeRemoved":{"shapeId":"created_shape"}},
+    ]),
+  ).expect("should be able to deserialize body events as spec events");
+
+  let spec_projection = SpecProjection::from(events);
+
+  let compliant_results = diff_interaction(
+    &spec_projection,
+    interaction_with_bodies(
+      "application/json",
+      json!({ "title": "Buy milk" }),
+      "application/json",
+      json!("created"),
+    ),
+  );
+  assert_eq!(compliant_results.len(), 0);
+
+  let results = diff_interaction(
+    &spec_projection,
+    interaction_with_bodies(
+      "application/json",
+      json!({ "title": 1 }),
+      "application/json",
+      json!(1),
+    ),
+  );
+  let messages = results
+    .iter()
+    .map(|result| result.to_string())
+    .collect::<Vec<_>>();
+  assert_eq!(
+    messages,
+    vec![
+      "request body: `title`: unmatched shape, expected string, observed integer",
+      "response 201 body: unmatched shape, expected string, observed integer",
+    ]
+  );
+}
+
+#[test]
+fn can_diff_form_bodies() {
+  let events: Vec<SpecEvent> = serde_json::from_value(
+    json!([
+      {"PathComponentAdded":{"pathId":"path_1","parentPathId":"root","name":"signup"}},
+      {"RequestAdded":{"requestId":"request_1","pathId":"path_1","httpMethod":"POST"}},
+      {"ShapeAdded":{"shapeId":"form_shape","baseShapeId":"$object","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeAdded":{"shapeId":"name_shape","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"FieldAdded":{"fieldId":"field_1","shapeId":"form_shape","name":"name","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_1","shapeId":"name_shape"}}}},
+      {"ShapeAdded":{"shapeId":"email_shape","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"FieldAdded":{"fieldId":"field_2","shapeId":"form_shape","name":"email","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_2","shapeId":"email_shape"}}}},
+      {"RequestBodySet":{"requestId":"request_1","bodyDescriptor":{"httpContentType":"application/x-www-form-urlencoded","shapeId":"form_shape","isRemoved":false}}},
+    ]),
+  ).expect("should be able to deserialize body events as spec events");
+
+  let spec_projection = SpecProjection::from(events);
+
+  let interaction_with_form = |form: &str| -> HttpInteraction {
+    serde_json::from_value(json!({
+      "uuid": "8",
+      "request": {
+        "host": "localhost",
+        "method": "POST",
+        "path": "/signup",
+        "query": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null },
+        "headers": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null },
+        "body": {
+          "contentType": "application/x-www-form-urlencoded",
+          "value": { "asJsonString": null, "asText": form, "shapeHashV1Base64": null }
+        }
+      },
+      "response": {
+        "statusCode": 204,
+        "headers": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null },
+        "body": {
+          "contentType": null,
+          "value": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null }
+        }
+      },
+      "tags": []
+    }))
+    .expect("should be a valid interaction")
+  };
+
+  let compliant_results = diff_interaction(
+    &spec_projection,
+    interaction_with_form("name=Homer&email=homer%40example.com"),
+  );
+  assert!(compliant_results
+    .iter()
+    .all(|result| !matches!(result, InteractionDiffResult::UnmatchedRequestBodyShape(_))));
+
+  let results = diff_interaction(
+    &spec_projection,
+    interaction_with_form("name=Homer&nickname=Homie"),
+  );
+  let shape_results = results
+    .into_iter()
+    .filter(|result| matches!(result, InteractionDiffResult::UnmatchedRequestBodyShape(_)))
+    .collect::<Vec<_>>();
+  assert_debug_snapshot!("can_diff_form_bodies__results", shape_results);
+  assert_eq!(
+    shape_results.len(),
+    2,
+    "missing and undocumented form fields should be unmatched shapes"
+  );
+}
+
+#[test]
+fn can_diff_xml_bodies() {
+  let events: Vec<SpecEvent> = serde_json::from_value(
+    json!([
+      {"PathComponentAdded":{"pathId":"path_1","parentPathId":"root","name":"orders"}},
+      {"RequestAdded":{"requestId":"request_1","pathId":"path_1","httpMethod":"POST"}},
+      {"ShapeAdded":{"shapeId":"document_shape","baseShapeId":"$object","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeAdded":{"shapeId":"order_shape","baseShapeId":"$object","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"FieldAdded":{"fieldId":"field_1","shapeId":"document_shape","name":"order","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_1","shapeId":"order_shape"}}}},
+      {"ShapeAdded":{"shapeId":"id_shape","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"FieldAdded":{"fieldId":"field_2","shapeId":"order_shape","name":"@id","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_2","shapeId":"id_shape"}}}},
+      {"ShapeAdded":{"shapeId":"customer_shape","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"FieldAdded":{"fieldId":"field_3","shapeId":"order_shape","name":"customer","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_3","shapeId":"customer_shape"}}}},
+      {"RequestBodySet":{"requestId":"request_1","bodyDescriptor":{"httpContentType":"application/xml","shapeId":"document_shape","isRemoved":false}}},
+    ]),
+  ).expect("should be able to deserialize body events as spec events");
+
+  let spec_projection = SpecProjection::from(events);
+
+  let interaction_with_document = |document: &str| -> HttpInteraction {
+    serde_json::from_value(json!({
+      "uuid": "9",
+      "request": {
+        "host": "localhost",
+        "method": "POST",
+        "path": "/orders",
+        "query": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null },
+        "headers": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null },
+        "body": {
+          "contentType": "application/xml; charset=utf-8",
+          "value": { "asJsonString": null, "asText": document, "shapeHashV1Base64": null }
+        }
+      },
+      "response": {
+        "statusCode": 204,
+        "headers": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null },
+        "body": {
+          "contentType": null,
+          "value": { "asJsonString": null, "asText": null, "shapeHashV1Base64": null }
+        }
+      },
+      "tags": []
+    }))
+    .expect("should be a valid interaction")
+  };
+
+  let compliant_results = diff_interaction(
+    &spec_projection,
+    interaction_with_document(r#"<order id="42"><customer>Homer</customer></order>"#),
+  );
+  assert!(compliant_results
+    .iter()
+    .all(|result| !matches!(result, InteractionDiffResult::UnmatchedRequestBodyShape(_))));
+
+  let results = diff_interaction(
+    &spec_projection,
+    interaction_with_document(r#"<order><customer>Homer</customer><item/><item/></order>"#),
+  );
+  let shape_results = results
+    .into_iter()
+    .filter(|result| matches!(result, InteractionDiffResult::UnmatchedRequestBodyShape(_)))
+    .collect::<Vec<_>>();
+  assert_debug_snapshot!("can_diff_xml_bodies__results", shape_results);
+  assert_eq!(
+    shape_results.len(),
+    2,
+    "missing attributes and undocumented elements should be unmatched shapes"
+  );
+
+  let mut messages = shape_results
+    .iter()
+    .map(|result| result.to_string())
+    .collect::<Vec<_>>();
+  messages.sort();
+  assert_eq!(
+    messages,
+    vec![
+      "request body: `order.@id`: missing required field, expected string",
+      "request body: `order.item`: undocumented, observed array",
+    ]
+  );
+}
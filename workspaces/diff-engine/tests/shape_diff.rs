@@ -370,6 +370,71 @@ fn can_yield_unmatched_shape_for_missing_field() {
   );
 }
 
+#[test]
+fn can_handle_field_renames_and_removals() {
+  let events : Vec<SpecEvent> = serde_json::from_value(
+    json!([
+      {"ShapeAdded":{"shapeId":"object_1","baseShapeId":"$object","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+
+      {"ShapeAdded":{"shapeId":"string_shape_1","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"FieldAdded":{"fieldId":"field_1","shapeId":"object_1","name":"firstName","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_1","shapeId":"string_shape_1"}}}},
+
+      {"ShapeAdded":{"shapeId":"string_shape_2","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"FieldAdded":{"fieldId":"field_2","shapeId":"object_1","name":"lastName","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_2","shapeId":"string_shape_2"}}}},
+
+      {"ShapeAdded":{"shapeId":"number_shape_1","baseShapeId":"$number","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"FieldAdded":{"fieldId":"field_3","shapeId":"object_1","name":"age","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_3","shapeId":"number_shape_1"}}}},
+
+      {"FieldRenamed":{"fieldId":"field_2","name":"surname"}},
+      {"FieldRemoved":{"fieldId":"field_3"}},
+      {"ShapeRemoved":{"shapeId":"number_shape_1"}},
+    ])
+  ).expect("should be able to deserialize shape events as spec events");
+
+  let shape_projection = ShapeProjection::from(events);
+
+  let compliant_body = json!({
+    "firstName": "Homer",
+    "surname": "Simpson"
+  });
+  let shape_id = String::from("object_1");
+  let results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(compliant_body)),
+    &shape_id,
+  );
+  assert_eq!(
+    results.len(),
+    0,
+    "renamed fields should match and removed fields should no longer be required"
+  );
+
+  let object_body = json!({
+    "firstName": "Homer",
+    "lastName": "Simpson",
+    "age": 39
+  });
+  let results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(object_body)),
+    &shape_id,
+  );
+  let fingerprints = results
+    .iter()
+    .map(|result| result.fingerprint())
+    .collect::<Vec<_>>();
+
+  assert_debug_snapshot!(
+    "can_handle_field_renames_and_removals__results",
+    results
+  );
+  assert_eq!(results.len(), 3);
+  assert_debug_snapshot!(
+    "can_handle_field_renames_and_removals__fingerprints",
+    fingerprints
+  );
+}
+
 #[test]
 fn can_diff_nullable() {
   let events : Vec<SpecEvent> = serde_json::from_value(
@@ -549,3 +614,205 @@ fn can_handle_base_shape_changes() {
   assert_eq!(results.len(), 0);
   assert_debug_snapshot!("can_handle_base_shape_changes__fingerprints", fingerprints);
 }
+
+#[test]
+fn can_diff_map_values() {
+  let events : Vec<SpecEvent> = serde_json::from_value(
+    json!([
+      {"ShapeAdded":{"shapeId":"object_1","baseShapeId":"$object","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeAdded":{"shapeId":"string_shape_1","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"FieldAdded":{"fieldId":"field_1","shapeId":"object_1","name":"name","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_1","shapeId":"string_shape_1"}}}},
+      {"ShapeAdded":{"shapeId":"map_1","baseShapeId":"$map","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeParameterShapeSet":{"shapeDescriptor":{"ProviderInShape":{"shapeId":"map_1","providerDescriptor":{"ShapeProvider":{"shapeId":"object_1"}},"consumingParameterId":"$mapValue"}}}},
+    ])
+  ).expect("should be able to deserialize shape added events as spec events");
+
+  let shape_projection = ShapeProjection::from(events);
+  let shape_id = String::from("map_1");
+
+  let matching_body = json!({
+    "user_1": { "name": "Homer" },
+    "user_2": { "name": "Marge" },
+  });
+  let matching_results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(matching_body)),
+    &shape_id,
+  );
+  assert_eq!(
+    matching_results.len(),
+    0,
+    "keys of maps are not diffed as fields"
+  );
+
+  let body = json!({
+    "user_1": { "name": "Homer" },
+    "user_2": { "name": 3 },
+    "user_3": {},
+    "user_4": "Bart",
+  });
+  let results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(body)),
+    &shape_id,
+  );
+
+  assert_debug_snapshot!("can_diff_map_values__results", results);
+  assert_eq!(results.len(), 3);
+
+  let fingerprints_at_key = |key: &str| {
+    let body = json!({ key: { "name": 3, "age": 42 } });
+    diff_shape(
+      &shape_projection,
+      Some(BodyDescriptor::from(body)),
+      &shape_id,
+    )
+    .iter()
+    .map(|result| result.fingerprint())
+    .collect::<Vec<_>>()
+  };
+  assert_eq!(fingerprints_at_key("user_1").len(), 2);
+  assert_eq!(
+    fingerprints_at_key("user_1"),
+    fingerprints_at_key("user_2"),
+    "diffs of map values have the same fingerprint, whatever their key"
+  );
+
+  let list_body = json!([{ "name": "Homer" }]);
+  let list_results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(list_body)),
+    &shape_id,
+  );
+  assert_eq!(list_results.len(), 1, "arrays do not match maps");
+}
+
+#[test]
+fn can_diff_string_formats() {
+  let events : Vec<SpecEvent> = serde_json::from_value(
+    json!([
+      {"ShapeAdded":{"shapeId":"object_1","baseShapeId":"$object","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeAdded":{"shapeId":"date_time_shape_1","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"StringFormatSet":{"shapeId":"date_time_shape_1","format":"date-time"}},
+      {"FieldAdded":{"fieldId":"field_1","shapeId":"object_1","name":"createdAt","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_1","shapeId":"date_time_shape_1"}}}},
+      {"ShapeAdded":{"shapeId":"string_shape_1","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"FieldAdded":{"fieldId":"field_2","shapeId":"object_1","name":"name","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_2","shapeId":"string_shape_1"}}}},
+    ])
+  ).expect("should be able to deserialize shape added events as spec events");
+
+  let shape_projection = ShapeProjection::from(events);
+  let shape_id = String::from("object_1");
+
+  let matching_body = json!({
+    "createdAt": "2021-03-14T15:09:26.535Z",
+    "name": "2021-03-14T15:09:26.535Z",
+  });
+  let matching_results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(matching_body)),
+    &shape_id,
+  );
+  assert_eq!(
+    matching_results.len(),
+    0,
+    "strings without a documented format match any format"
+  );
+
+  let non_conforming_body = json!({
+    "createdAt": "not-a-date",
+    "name": "Homer",
+  });
+  let results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(non_conforming_body)),
+    &shape_id,
+  );
+
+  assert_debug_snapshot!("can_diff_string_formats__results", results);
+  assert_eq!(results.len(), 1);
+}
+
+#[test]
+fn can_diff_string_enums() {
+  let events : Vec<SpecEvent> = serde_json::from_value(
+    json!([
+      {"ShapeAdded":{"shapeId":"status_shape_1","baseShapeId":"$string","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"StringEnumSet":{"shapeId":"status_shape_1","values":["active","suspended"]}},
+      {"ShapeAdded":{"shapeId":"list_1","baseShapeId":"$list","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeParameterShapeSet":{"shapeDescriptor":{"ProviderInShape":{"shapeId":"list_1","providerDescriptor":{"ShapeProvider":{"shapeId":"status_shape_1"}},"consumingParameterId":"$listItem"}}}},
+    ])
+  ).expect("should be able to deserialize shape added events as spec events");
+
+  let shape_projection = ShapeProjection::from(events);
+  let shape_id = String::from("list_1");
+
+  let matching_body = json!(["active", "suspended", "active"]);
+  let matching_results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(matching_body)),
+    &shape_id,
+  );
+  assert_eq!(matching_results.len(), 0);
+
+  let new_member_body = json!(["active", "deleted"]);
+  let results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(new_member_body)),
+    &shape_id,
+  );
+
+  assert_debug_snapshot!("can_diff_string_enums__results", results);
+  assert_eq!(
+    results.len(),
+    1,
+    "new enum members should be unmatched shapes"
+  );
+}
+
+#[test]
+fn can_diff_integers() {
+  let events : Vec<SpecEvent> = serde_json::from_value(
+    json!([
+      {"ShapeAdded":{"shapeId":"object_1","baseShapeId":"$object","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"ShapeAdded":{"shapeId":"integer_shape_1","baseShapeId":"$integer","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"FieldAdded":{"fieldId":"field_1","shapeId":"object_1","name":"count","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_1","shapeId":"integer_shape_1"}}}},
+      {"ShapeAdded":{"shapeId":"number_shape_1","baseShapeId":"$number","parameters":{"DynamicParameterList":{"shapeParameterIds":[]}},"name":""}},
+      {"FieldAdded":{"fieldId":"field_2","shapeId":"object_1","name":"price","shapeDescriptor":{"FieldShapeFromShape":{"fieldId":"field_2","shapeId":"number_shape_1"}}}},
+    ])
+  ).expect("should be able to deserialize shape added events as spec events");
+
+  let shape_projection = ShapeProjection::from(events);
+  let shape_id = String::from("object_1");
+
+  let matching_body = json!({
+    "count": 3,
+    "price": 3,
+  });
+  let matching_results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(matching_body)),
+    &shape_id,
+  );
+  assert_eq!(
+    matching_results.len(),
+    0,
+    "integers match both integer and number shapes"
+  );
+
+  let float_body = json!({
+    "count": 3.5,
+    "price": 3.5,
+  });
+  let results = diff_shape(
+    &shape_projection,
+    Some(BodyDescriptor::from(float_body)),
+    &shape_id,
+  );
+
+  assert_debug_snapshot!("can_diff_integers__results", results);
+  assert_eq!(
+    results.len(),
+    1,
+    "floats should only be unmatched where integers are documented"
+  );
+}